@@ -0,0 +1,164 @@
+//! # Alpha
+//!
+//! The `alpha` module interprets the attributes type of the TGA 2.0
+//! extension area, which records what the alpha channel of an image holds,
+//! and converts 32 bit images between premultiplied and straight alpha.
+#[cfg(feature = "transforms")]
+use std::sync::Arc;
+#[cfg(feature = "transforms")]
+use crate::{Channel, ChannelIter, TgaError};
+use crate::{RawTgaImage, TgaImage};
+
+
+/// The position of the attributes type within the extension area.
+const ATTRIBUTES_TYPE_OFFSET: usize = 494;
+
+/// The `AttributesType` of an image records what its alpha channel holds.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AttributesType {
+    /// The image has no alpha data.
+    NoAlpha,
+    /// The alpha channel holds undefined data, which can be ignored.
+    UndefinedIgnore,
+    /// The alpha channel holds undefined data, which should be retained.
+    UndefinedRetain,
+    /// The alpha channel holds straight alpha: colours are stored as they
+    /// are, independent of their alpha.
+    Alpha,
+    /// The alpha channel holds premultiplied alpha: colours are stored
+    /// already multiplied by their alpha.
+    PremultipliedAlpha,
+}
+
+impl AttributesType {
+    /// Read an attributes type from its value in the extension area.
+    #[cfg(any(feature = "metadata", feature = "encode"))]
+    pub(crate) fn from_byte(byte: u8) -> Option<AttributesType> {
+        match byte {
+            0 => Some(AttributesType::NoAlpha),
+            1 => Some(AttributesType::UndefinedIgnore),
+            2 => Some(AttributesType::UndefinedRetain),
+            3 => Some(AttributesType::Alpha),
+            4 => Some(AttributesType::PremultipliedAlpha),
+            _ => None,
+        }
+    }
+
+    /// The value of the attributes type in the extension area.
+    #[cfg(any(feature = "encode", feature = "transforms"))]
+    pub(crate) fn to_byte(self) -> u8 {
+        match self {
+            AttributesType::NoAlpha => 0,
+            AttributesType::UndefinedIgnore => 1,
+            AttributesType::UndefinedRetain => 2,
+            AttributesType::Alpha => 3,
+            AttributesType::PremultipliedAlpha => 4,
+        }
+    }
+}
+
+impl RawTgaImage {
+    /// The attributes type recorded in the extension area of the image.
+    #[cfg(any(feature = "metadata", feature = "encode"))]
+    pub(crate) fn attributes_type(&self) -> Option<AttributesType> {
+        let position = self.extension_area_position()? + ATTRIBUTES_TYPE_OFFSET;
+
+        AttributesType::from_byte(self.extended_image_identification[position])
+    }
+
+    /// Record an attributes type in the extension area of the image, if it
+    /// has one.
+    #[cfg(feature = "transforms")]
+    fn set_attributes_type(&mut self, attributes_type: AttributesType) {
+        if let Some(offset) = self.extension_area_position() {
+            let extended_image_identification = Arc::make_mut(&mut self.extended_image_identification);
+            extended_image_identification[offset + ATTRIBUTES_TYPE_OFFSET] = attributes_type.to_byte();
+        }
+    }
+
+    /// Check that the colours of the image carry eight bits of alpha.
+    #[cfg(feature = "transforms")]
+    pub(crate) fn check_32_bit_colours(&self) -> Result<(), TgaError> {
+        let bits = if self.header.is_colour_mapped() {
+            self.header.colour_map_depth()
+        } else {
+            self.header.bits_per_pixel()
+        };
+        if bits != 32 {
+            return Err(TgaError::UnsupportedBitDepth(bits));
+        }
+
+        Ok(())
+    }
+}
+
+impl TgaImage {
+    /// The function `attributes_type` returns what the alpha channel of the
+    /// image holds, as recorded in the extension area of the file the image
+    /// was read from. It returns `None` when the file has no extension area,
+    /// or records a value the TGA specification does not define. The
+    /// encoder keeps premultiplied alpha recorded when the image is encoded
+    /// again with an alpha channel.
+    #[cfg(feature = "metadata")]
+    pub fn attributes_type(&self) -> Option<AttributesType> {
+        self.raw_tga_image().attributes_type()
+    }
+
+    /// The function `alphas` generates an iterator over the alpha of every
+    /// pixel of the image, in the order the pixels are stored, for masking
+    /// code that needs nothing else. For a colour mapped image, the alpha
+    /// comes from the colour map entry of each pixel. A 16 bit pixel has a
+    /// single attribute bit, which gives an alpha of 0 or 255, and pixels
+    /// without attribute bits are opaque. It is the same as
+    /// `channel(Channel::Alpha)`.
+    #[cfg(feature = "transforms")]
+    pub fn alphas(&self) -> ChannelIter<'_> {
+        self.channel(Channel::Alpha)
+    }
+
+    /// The function `premultiply_alpha` multiplies the colour channels of
+    /// every pixel of a 32 bit image by its alpha, rounding to the nearest
+    /// value. For a colour mapped image with 32 bit colour map entries, the
+    /// entries are converted instead. The extension area of the image, if it
+    /// has one, then records premultiplied alpha. Images with other depths
+    /// fail with `UnsupportedBitDepth`.
+    #[cfg(feature = "transforms")]
+    pub fn premultiply_alpha(&mut self) -> Result<(), TgaError> {
+        let image = self.raw_tga_image_mut();
+        image.check_32_bit_colours()?;
+        image.map_colours(|colour| {
+            let alpha = colour[3] as u32;
+            let premultiply = |channel: u8| ((channel as u32 * alpha + 127) / 255) as u8;
+
+            [premultiply(colour[0]), premultiply(colour[1]), premultiply(colour[2]), colour[3]]
+        });
+        image.set_attributes_type(AttributesType::PremultipliedAlpha);
+
+        Ok(())
+    }
+
+    /// The function `unpremultiply_alpha` divides the colour channels of
+    /// every pixel of a 32 bit image by its alpha, undoing `premultiply_alpha`
+    /// up to rounding. Fully transparent pixels have no colour left to
+    /// recover, so they become transparent black. For a colour mapped image
+    /// with 32 bit colour map entries, the entries are converted instead. The
+    /// extension area of the image, if it has one, then records straight
+    /// alpha. Images with other depths fail with `UnsupportedBitDepth`.
+    #[cfg(feature = "transforms")]
+    pub fn unpremultiply_alpha(&mut self) -> Result<(), TgaError> {
+        let image = self.raw_tga_image_mut();
+        image.check_32_bit_colours()?;
+        image.map_colours(|colour| {
+            let alpha = colour[3] as u32;
+            let unpremultiply = |channel: u8| match alpha {
+                0 => 0,
+                _ => u32::min(255, (channel as u32 * 255 + alpha / 2) / alpha) as u8,
+            };
+
+            [unpremultiply(colour[0]), unpremultiply(colour[1]), unpremultiply(colour[2]), colour[3]]
+        });
+        image.set_attributes_type(AttributesType::Alpha);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+//! # Anonymization
+//!
+//! The `anonymize` module strips identifying metadata from TGA files on disk
+//! before they are published, by overwriting the fields that hold it in
+//! place. The pixel data is never read or rewritten, so anonymizing a large
+//! file costs a few small reads and writes.
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use crate::{TgaError, TgaHeader, EXTENSION_AREA_LENGTH, RGB_CHANNEL_ORDER_TAG, TGA_FOOTER, TGA_HEADER_LENGTH};
+
+
+/// The ranges of the author name, author comments, job name, and job time
+/// fields within an extension area.
+const IDENTIFYING_FIELDS: [(usize, usize); 3] = [(2, 367), (379, 420), (420, 426)];
+
+/// The function `anonymize` overwrites the identifying metadata of a TGA file
+/// in place with zeros: the image identification, and the author name,
+/// author comments, job name, and job time of the extension area, when the
+/// footer of the file points at one. The image identification keeps its
+/// length, so nothing in the file moves, and everything else, including the
+/// pixel data, the software identification, and the date the file was
+/// saved, is left alone. A leading `RGB_CHANNEL_ORDER_TAG` is kept, since
+/// the pixels of the file cannot be decoded in the right channel order
+/// without it, and only the text after it is zeroed. Nothing is written past
+/// the end of a truncated file. Errors are labelled with the path of the
+/// file.
+pub fn anonymize<P: AsRef<Path>>(path: P) -> Result<(), TgaError> {
+    let path = path.as_ref();
+    let io_error = |err| TgaError::Io(Box::new(err));
+    let anonymize_file = || -> Result<(), TgaError> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path).map_err(io_error)?;
+        let mut buf = Vec::with_capacity(TGA_HEADER_LENGTH);
+        (&mut file).take(TGA_HEADER_LENGTH as u64).read_to_end(&mut buf).map_err(io_error)?;
+        let header = TgaHeader::parse_from_buffer(&buf)?;
+        let length = file.metadata().map_err(io_error)?.len() as usize;
+        let id_length = header.id_length().min(length.saturating_sub(TGA_HEADER_LENGTH));
+        let mut image_identification = vec![0; id_length];
+        file.read_exact(&mut image_identification).map_err(io_error)?;
+        let kept = if image_identification.starts_with(RGB_CHANNEL_ORDER_TAG) { RGB_CHANNEL_ORDER_TAG.len() } else { 0 };
+        file.seek(SeekFrom::Start((TGA_HEADER_LENGTH + kept) as u64)).map_err(io_error)?;
+        file.write_all(&vec![0; id_length - kept]).map_err(io_error)?;
+
+        if length < TGA_HEADER_LENGTH + TGA_FOOTER.len() {
+            return Ok(());
+        }
+        let footer_position = length - TGA_FOOTER.len();
+        let mut footer = [0; 26];
+        file.seek(SeekFrom::Start(footer_position as u64)).map_err(io_error)?;
+        file.read_exact(&mut footer).map_err(io_error)?;
+        let extension_area = u32::from_le_bytes([footer[0], footer[1], footer[2], footer[3]]) as usize;
+        let found = footer.ends_with(&TGA_FOOTER[8..])
+            && (extension_area >= TGA_HEADER_LENGTH)
+            && (extension_area + EXTENSION_AREA_LENGTH <= footer_position);
+        if !found {
+            return Ok(());
+        }
+
+        let mut size = [0; 2];
+        file.seek(SeekFrom::Start(extension_area as u64)).map_err(io_error)?;
+        file.read_exact(&mut size).map_err(io_error)?;
+        if (u16::from_le_bytes(size) as usize) < EXTENSION_AREA_LENGTH {
+            return Ok(());
+        }
+        for &(start, end) in IDENTIFYING_FIELDS.iter() {
+            file.seek(SeekFrom::Start((extension_area + start) as u64)).map_err(io_error)?;
+            file.write_all(&vec![0; end - start]).map_err(io_error)?;
+        }
+
+        Ok(())
+    };
+
+    anonymize_file().map_err(|err| err.named(path.display().to_string()))
+}
@@ -0,0 +1,92 @@
+//! # Texture Atlas Tiles
+//!
+//! The `atlas` module prepares images for packing into a texture atlas.
+//! Sampling a sprite in an atlas with bilinear filtering or mipmapping blends
+//! in the texels around it, so each sprite is premultiplied and given a
+//! border of padding copied from its own edges, so the texels it bleeds into
+//! are its own.
+use std::convert::TryFrom;
+use std::sync::Arc;
+use crate::{RawTgaImage, TgaError, TgaImage};
+
+
+/// A `PadMode` decides which pixels of an image fill the padding around it.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PadMode {
+    /// Each padding pixel repeats the nearest edge pixel of the image, which
+    /// suits sprites sampled with clamping.
+    Extend,
+    /// Each padding pixel repeats the pixel from the opposite edge of the
+    /// image, which suits textures sampled with wrapping, such as tiling
+    /// terrain.
+    Wrap,
+}
+
+impl PadMode {
+    /// The index of the pixel of the image a position `padding` pixels into
+    /// the padded image copies, along an image dimension of length `length`.
+    fn source_index(self, position: usize, padding: usize, length: usize) -> usize {
+        let offset = position as isize - padding as isize;
+        match self {
+            PadMode::Extend => offset.clamp(0, length as isize - 1) as usize,
+            PadMode::Wrap => offset.rem_euclid(length as isize) as usize,
+        }
+    }
+}
+
+impl RawTgaImage {
+    /// Surround the image with a border of `padding` pixels on every side,
+    /// filled as `mode` says. The stored pixels are copied as they are, so a
+    /// colour mapped image is padded with colour map indices.
+    fn padded(&self, padding: usize, mode: PadMode) -> Result<RawTgaImage, TgaError> {
+        let width = self.width() + 2 * padding;
+        let height = self.height() + 2 * padding;
+        let too_large = |length: usize| u16::try_from(length).is_err();
+        if too_large(width) || too_large(height) || ((padding > 0) && (self.image_data_length() == 0)) {
+            return Err(TgaError::InvalidDimensions(width as u32, height as u32));
+        }
+        let mut header = self.header;
+        header.width = (width as u16).to_le_bytes();
+        header.height = (height as u16).to_le_bytes();
+
+        let bytes_per_pixel = self.header.bytes_per_pixel();
+        let mut image_data = vec![0; width * height * bytes_per_pixel];
+        for (y, row) in image_data.chunks_mut((width * bytes_per_pixel).max(1)).enumerate() {
+            let source = self.row(mode.source_index(y, padding, self.height()));
+            for (x, pixel) in row.chunks_mut(bytes_per_pixel).enumerate() {
+                let start = mode.source_index(x, padding, self.width()) * bytes_per_pixel;
+                pixel.copy_from_slice(&source[start..(start + bytes_per_pixel)]);
+            }
+        }
+
+        let mut image = RawTgaImage::new(
+            header,
+            self.image_identification.clone(),
+            self.colour_map_data.clone(),
+            Arc::new(image_data),
+            self.extended_image_identification.clone(),
+        );
+        image.trailing_position = self.trailing_position;
+
+        Ok(image)
+    }
+}
+
+impl TgaImage {
+    /// The function `prepare_atlas_tile` returns a copy of the image ready to
+    /// be packed into a texture atlas: premultiplied when the image has 32
+    /// bit colours, as `premultiply_alpha` does, and surrounded by a border of
+    /// `padding` pixels on every side, filled from the edges of the image as
+    /// `mode` says. The copy keeps the type, orientation, and metadata of the
+    /// image. Padding that makes the image too large for a TGA header, or
+    /// padding an image without pixels, fails with `InvalidDimensions`.
+    pub fn prepare_atlas_tile(&self, padding: u32, mode: PadMode) -> Result<TgaImage, TgaError> {
+        let mut image = self.clone();
+        if image.raw_tga_image().check_32_bit_colours().is_ok() {
+            image.premultiply_alpha()?;
+        }
+        let padded = image.raw_tga_image().padded(padding as usize, mode)?;
+
+        Ok(image.with_raw_tga_image(padded))
+    }
+}
@@ -0,0 +1,79 @@
+//! # Batch Operations
+//!
+//! The `batch` module contains operations that work on every TGA file in a
+//! directory at once.
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use crate::{TgaError, TgaHeader, TGA_HEADER_LENGTH};
+
+
+/// Determine whether a path names a TGA file, judging by its extension.
+fn is_tga_file(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("tga"))
+}
+
+/// Determine whether two headers describe the same layout of the rest of the
+/// file, differing at most in their origin, and the orientation and attribute
+/// bits of their image descriptor.
+fn has_same_layout(header: &TgaHeader, new_header: &TgaHeader) -> bool {
+    let mut new_header = *new_header;
+    new_header.x_origin = header.x_origin;
+    new_header.y_origin = header.y_origin;
+    new_header.image_descriptor = (new_header.image_descriptor & 0xC0) | (header.image_descriptor & 0x3F);
+
+    new_header == *header
+}
+
+/// The function `rewrite_headers` patches the header of every TGA file in a
+/// directory in place. The function `f` is called with the header of each
+/// file, and when it returns a new header, only the 18 header bytes of that
+/// file are overwritten; the rest of the file is never read or rewritten.
+/// Files are visited in order of their paths, and subdirectories are not
+/// searched. The function returns the number of files it rewrote, and stops
+/// at the first file it fails to read or write, labelling the error with the
+/// path of that file.
+///
+/// Only the origin and the orientation and attribute bits of the image
+/// descriptor of a header can be changed, so a rewritten header always
+/// describes the same layout as the original one. A new header that changes
+/// anything else fails with `LayoutMismatch`, and the file is left alone.
+pub fn rewrite_headers<P, F>(dir: P, mut f: F) -> Result<usize, TgaError>
+    where P: AsRef<Path>, F: FnMut(TgaHeader) -> Option<TgaHeader>
+{
+    let io_error = |err| TgaError::Io(Box::new(err));
+    let mut paths = fs::read_dir(dir)
+        .map_err(io_error)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()
+        .map_err(io_error)?;
+    paths.retain(|path| is_tga_file(path));
+    paths.sort();
+
+    let mut rewritten = 0;
+    for path in paths.iter() {
+        let mut rewrite = || -> Result<bool, TgaError> {
+            let mut file = OpenOptions::new().read(true).write(true).open(path).map_err(io_error)?;
+            let mut buf = Vec::with_capacity(TGA_HEADER_LENGTH);
+            (&mut file).take(TGA_HEADER_LENGTH as u64).read_to_end(&mut buf).map_err(io_error)?;
+            let header = TgaHeader::parse_from_buffer(&buf)?;
+            match f(header) {
+                Some(new_header) if new_header != header => {
+                    if !has_same_layout(&header, &new_header) {
+                        return Err(TgaError::LayoutMismatch);
+                    }
+                    file.seek(SeekFrom::Start(0)).map_err(io_error)?;
+                    file.write_all(&new_header.to_bytes()).map_err(io_error)?;
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        };
+        if rewrite().map_err(|err| err.named(path.display().to_string()))? {
+            rewritten += 1;
+        }
+    }
+
+    Ok(rewritten)
+}
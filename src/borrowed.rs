@@ -0,0 +1,158 @@
+//! # Borrowed Images
+//!
+//! The `borrowed` module reads uncompressed TGA files in place. A
+//! `TgaImageRef` borrows the image identification, colour map, and pixels of
+//! a file straight out of the buffer holding it, so reading the header and
+//! pixels of even a very large file copies nothing. Run length encoded files
+//! have to be decompressed, so they are read into a `TgaImage` instead.
+use crate::{
+    check_supported_header, Interleaving, TgaError, TgaHeader, TgaImage, TGA_FOOTER, TGA_HEADER_LENGTH,
+};
+
+
+/// A `TgaImageRef` is an uncompressed TGA image borrowed from the buffer
+/// holding its file. Its sections are the bytes of the file exactly as they
+/// are stored: pixels are in the depth given by the header, and a file
+/// tagged with the red, green, blue channel order keeps it. Colour map
+/// indices are not checked against the colour map.
+#[derive(Clone, Debug)]
+pub struct TgaImageRef<'a> {
+    buf: &'a [u8],
+    header: TgaHeader,
+    image_identification: &'a [u8],
+    colour_map_data: &'a [u8],
+    image_data: &'a [u8],
+    extended_image_identification: &'a [u8],
+    /// The stored position of each row of an interleaved image, in order.
+    /// This is empty for images that are not interleaved.
+    stored_rows: Vec<usize>,
+}
+
+impl<'a> TgaImageRef<'a> {
+    /// The function `parse_from_buffer` reads an uncompressed TGA image from
+    /// the start of a buffer, borrowing its sections from the buffer. Run
+    /// length encoded files fail with `Not24BitRgb`, as do files of types
+    /// this crate does not support.
+    pub fn parse_from_buffer(buf: &'a [u8]) -> Result<TgaImageRef<'a>, TgaError> {
+        let header = TgaHeader::parse_from_buffer(buf)?;
+        check_supported_header(&header)?;
+        if header.is_run_length_encoded() {
+            return Err(TgaError::Not24BitRgb(header.data_type_code as usize));
+        }
+        if buf.len() < TGA_HEADER_LENGTH + header.id_length() {
+            return Err(TgaError::CorruptTgaHeader);
+        }
+
+        let slice = &buf[TGA_HEADER_LENGTH..];
+        let (image_identification, slice) = slice.split_at(header.id_length());
+        if slice.len() < header.colour_map_size() {
+            return Err(TgaError::IncompleteColourMap(slice.len(), header.colour_map_size()));
+        }
+        let (colour_map_data, slice) = slice.split_at(header.colour_map_size());
+        let image_size = if header.has_image_data() {
+            header.width() * header.height() * header.bytes_per_pixel()
+        } else {
+            0
+        };
+        if slice.len() < image_size {
+            return Err(TgaError::IncompleteImageData(slice.len(), image_size));
+        }
+        let (image_data, mut slice) = slice.split_at(image_size);
+        if slice.ends_with(&TGA_FOOTER) {
+            slice = &slice[0..(slice.len() - TGA_FOOTER.len())];
+        }
+
+        let interleaving = Interleaving::from_image_descriptor(header.image_descriptor);
+        let mut stored_rows = Vec::new();
+        if interleaving != Interleaving::NonInterleaved {
+            stored_rows = vec![0; header.height()];
+            for stored in 0..header.height() {
+                stored_rows[interleaving.row_index(stored, header.height())] = stored;
+            }
+        }
+
+        Ok(TgaImageRef {
+            buf,
+            header,
+            image_identification,
+            colour_map_data,
+            image_data,
+            extended_image_identification: slice,
+            stored_rows,
+        })
+    }
+
+    /// The function `header` produces a copy of the TGA header.
+    pub fn header(&self) -> TgaHeader {
+        self.header
+    }
+
+    /// Return the width of the image, in pixels. A file without image data
+    /// has a width of zero, whatever its header says.
+    pub fn width(&self) -> u32 {
+        if self.header.has_image_data() { self.header.width() as u32 } else { 0 }
+    }
+
+    /// Return the height of the image, in pixels. A file without image data
+    /// has a height of zero, whatever its header says.
+    pub fn height(&self) -> u32 {
+        if self.header.has_image_data() { self.header.height() as u32 } else { 0 }
+    }
+
+    /// Return the bit depth per pixel of the image.
+    pub fn bits_per_pixel(&self) -> usize {
+        self.header.bits_per_pixel()
+    }
+
+    /// The function `image_identification` returns the image identification
+    /// field of the file.
+    pub fn image_identification(&self) -> &'a [u8] {
+        self.image_identification
+    }
+
+    /// The function `colour_map_data` returns the colour map of the file, as
+    /// stored. It is empty for files without a colour map.
+    pub fn colour_map_data(&self) -> &'a [u8] {
+        self.colour_map_data
+    }
+
+    /// The function `image_data` returns the pixels of the file, in the order
+    /// the file stores them, including the scanlines of an interleaved file.
+    pub fn image_data(&self) -> &'a [u8] {
+        self.image_data
+    }
+
+    /// The function `extended_image_identification` returns the bytes of the
+    /// file between the image data and the TGA 2.0 footer, if it has one.
+    pub fn extended_image_identification(&self) -> &'a [u8] {
+        self.extended_image_identification
+    }
+
+    /// The function `row` returns the stored bytes of a single row, or `None`
+    /// when the image has no such row. Rows are numbered as a parsed
+    /// `TgaImage` numbers them, so the scanlines of an interleaved file are
+    /// put in order.
+    pub fn row(&self, y: u32) -> Option<&'a [u8]> {
+        if y >= self.height() {
+            return None;
+        }
+        let y = y as usize;
+        let stored = self.stored_rows.get(y).cloned().unwrap_or(y);
+        let row_length = self.width() as usize * self.header.bytes_per_pixel();
+
+        self.image_data.get((stored * row_length)..((stored + 1) * row_length))
+    }
+
+    /// The function `rows` generates an iterator over the rows of the image,
+    /// in the order `row` numbers them, borrowed from the buffer.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &'a [u8]> + ExactSizeIterator + '_ {
+        (0..self.height()).map(move |y| self.row(y).unwrap())
+    }
+
+    /// The function `to_image` parses the buffer into an owned `TgaImage`,
+    /// copying its sections, checking its colour map indices, and putting its
+    /// channels in blue, green, red order.
+    pub fn to_image(&self) -> Result<TgaImage, TgaError> {
+        TgaImage::parse_from_buffer(self.buf)
+    }
+}
@@ -0,0 +1,67 @@
+//! # Capabilities
+//!
+//! The `capabilities` module reports which parts of the TGA format the library
+//! supports, as configured by the cargo features of the current build, so an
+//! application can describe the formats it accepts without hard coding them.
+
+
+/// A `Capabilities` value describes what the current build of the library can
+/// read and write. The lists are in ascending order.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Capabilities {
+    /// The image type codes the parser accepts.
+    pub read_type_codes: Vec<u8>,
+    /// The image type codes the encoder can write. This is empty when the
+    /// `encode` feature is disabled.
+    pub write_type_codes: Vec<u8>,
+    /// The pixel depths of true colour images, in bits.
+    pub true_colour_depths: Vec<u8>,
+    /// The sizes of the colour map indices of colour mapped images, in bits.
+    pub colour_map_index_depths: Vec<u8>,
+    /// The depths of colour map entries, in bits.
+    pub colour_map_depths: Vec<u8>,
+    /// Whether run length encoded images can be read and written.
+    pub run_length_encoding: bool,
+    /// Whether 15 and 16 bit pixels and colour map entries are supported.
+    pub sixteen_bit: bool,
+    /// Whether TGA 2.0 extension areas can be written. The extension areas
+    /// of files being read are kept as they are, and with the `metadata`
+    /// feature the fields describing how the image is displayed, such as its
+    /// gamma, key colour, pixel aspect ratio, and attributes type, are read
+    /// from them.
+    pub extension_area: bool,
+    /// Whether interleaved scanlines, recorded in bits 6 and 7 of the image
+    /// descriptor, are reordered when reading.
+    pub interleave: bool,
+}
+
+/// The function `capabilities` reports what the current build of the library
+/// supports.
+pub fn capabilities() -> Capabilities {
+    let rle = cfg!(feature = "rle");
+    let encode = cfg!(feature = "encode");
+
+    let mut read_type_codes = vec![0, 1, 2];
+    if rle {
+        read_type_codes.extend_from_slice(&[9, 10]);
+    }
+    let mut write_type_codes = Vec::new();
+    if encode {
+        write_type_codes.extend_from_slice(&[1, 2, 3]);
+        if rle {
+            write_type_codes.extend_from_slice(&[9, 10, 11]);
+        }
+    }
+
+    Capabilities {
+        read_type_codes,
+        write_type_codes,
+        true_colour_depths: vec![15, 16, 24, 32],
+        colour_map_index_depths: vec![8, 16],
+        colour_map_depths: vec![15, 16, 24, 32],
+        run_length_encoding: rle,
+        sixteen_bit: true,
+        extension_area: encode,
+        interleave: true,
+    }
+}
@@ -0,0 +1,157 @@
+//! # Shared Colour Maps
+//!
+//! The `colour_maps` module shares colour maps between images. Sprite sheets
+//! and texture sets often hold hundreds of images with the same colour map,
+//! and a `ColourMapCache` keeps a single copy of each distinct colour map for
+//! all of them. Files that leave their colour map out, relying on a colour
+//! map shared outside of the file, can be parsed with that colour map given
+//! as a palette.
+#[cfg(feature = "metadata")]
+use std::collections::HashSet;
+#[cfg(feature = "metadata")]
+use std::sync::Arc;
+#[cfg(feature = "metadata")]
+use std::slice::ChunksExact;
+#[cfg(feature = "encode")]
+use crate::{Palette, TgaHeader, TGA_HEADER_LENGTH};
+#[cfg(feature = "metadata")]
+use crate::decode_pixel;
+use crate::{TgaError, TgaImage};
+
+
+/// A `ColourMapCache` interns colour maps, so images with identical colour
+/// maps share one copy of it. Colour maps are compared by their contents.
+/// Sharing is invisible to the images: an image that modifies its colour map
+/// gets its own copy first.
+#[cfg(feature = "metadata")]
+#[derive(Clone, Debug, Default)]
+pub struct ColourMapCache {
+    colour_maps: HashSet<Arc<Vec<u8>>>,
+}
+
+#[cfg(feature = "metadata")]
+impl ColourMapCache {
+    /// The function `new` creates an empty cache.
+    pub fn new() -> ColourMapCache {
+        ColourMapCache::default()
+    }
+
+    /// The function `len` returns the number of distinct colour maps in the cache.
+    pub fn len(&self) -> usize {
+        self.colour_maps.len()
+    }
+
+    /// The function `is_empty` determines whether the cache holds no colour maps.
+    pub fn is_empty(&self) -> bool {
+        self.colour_maps.is_empty()
+    }
+
+    /// The function `intern` replaces the colour map of an image with the
+    /// copy in the cache, adding it to the cache when it is new. Images
+    /// without a colour map are left alone.
+    pub fn intern(&mut self, image: &mut TgaImage) {
+        let colour_map_data = &mut image.raw_tga_image_mut().colour_map_data;
+        if colour_map_data.is_empty() {
+            return;
+        }
+
+        match self.colour_maps.get(colour_map_data.as_ref()) {
+            Some(shared) => *colour_map_data = shared.clone(),
+            None => {
+                self.colour_maps.insert(colour_map_data.clone());
+            }
+        }
+    }
+}
+
+/// A `PaletteEntries` is an iterator over the decoded entries of the colour
+/// map of an image, created by `TgaImage::palette`.
+#[cfg(feature = "metadata")]
+#[derive(Clone, Debug)]
+pub struct PaletteEntries<'a> {
+    entries: ChunksExact<'a, u8>,
+    depth: usize,
+    next_index: usize,
+}
+
+#[cfg(feature = "metadata")]
+impl<'a> Iterator for PaletteEntries<'a> {
+    type Item = (usize, [u8; 4]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.entries.next()?;
+        let index = self.next_index;
+        self.next_index += 1;
+
+        Some((index, decode_pixel(entry, self.depth)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+#[cfg(feature = "metadata")]
+impl<'a> ExactSizeIterator for PaletteEntries<'a> {}
+
+impl TgaImage {
+    /// The function `palette` generates an iterator over the entries of the
+    /// colour map of the image, decoded into blue, green, red, and alpha
+    /// channels whatever depth they are stored in. Each entry comes with the
+    /// index pixels use to refer to it, which counts from the colour map
+    /// origin given in the header. An image without a colour map has no
+    /// entries.
+    #[cfg(feature = "metadata")]
+    pub fn palette(&self) -> PaletteEntries<'_> {
+        let image = self.raw_tga_image();
+        let entry_bytes = usize::max(1, image.header.colour_map_entry_bytes());
+
+        PaletteEntries {
+            entries: image.colour_map_data.chunks_exact(entry_bytes),
+            depth: image.header.colour_map_depth(),
+            next_index: image.header.colour_map_origin(),
+        }
+    }
+
+    /// The function `parse_interned` parses an image from a buffer like
+    /// `parse_from_buffer`, and shares its colour map with the images parsed
+    /// before it through the same cache.
+    #[cfg(feature = "metadata")]
+    pub fn parse_interned(buf: &[u8], cache: &mut ColourMapCache) -> Result<TgaImage, TgaError> {
+        let mut image = TgaImage::parse_from_buffer(buf)?;
+        cache.intern(&mut image);
+
+        Ok(image)
+    }
+
+    /// The function `parse_with_palette` parses a colour mapped image whose
+    /// file leaves out its colour map, using the palette as its colour map.
+    /// Such files are type 1 or type 9 files with a colour map length of
+    /// zero. The image holds the palette as an ordinary colour map from then
+    /// on, so encoding it writes the colour map into the file. Files with a
+    /// colour map of their own, and other types of file, are parsed as
+    /// `parse_from_buffer` parses them.
+    #[cfg(feature = "encode")]
+    pub fn parse_with_palette(buf: &[u8], palette: &Palette) -> Result<TgaImage, TgaError> {
+        let mut header = TgaHeader::parse_from_buffer(buf)?;
+        if !header.is_colour_mapped() || (header.colour_map_length() != 0) {
+            return TgaImage::parse_from_buffer(buf);
+        }
+        if palette.origin() as usize + palette.len() > u16::MAX as usize {
+            return Err(TgaError::PaletteTooLarge(palette.len()));
+        }
+
+        header.color_map_type = 1;
+        header.colour_map_origin = palette.origin().to_le_bytes();
+        header.colour_map_length = (palette.len() as u16).to_le_bytes();
+        header.colour_map_depth = palette.depth().bits() as u8;
+        let colour_map_start = usize::min(TGA_HEADER_LENGTH + header.id_length(), buf.len());
+        let mut patched = Vec::with_capacity(buf.len() + header.colour_map_size());
+        patched.extend_from_slice(&header.to_bytes());
+        patched.extend_from_slice(&buf[TGA_HEADER_LENGTH..colour_map_start]);
+        patched.extend_from_slice(&palette.to_colour_map());
+        patched.extend_from_slice(&buf[colour_map_start..]);
+
+        TgaImage::parse_from_buffer(&patched)
+    }
+}
@@ -0,0 +1,211 @@
+//! # Constructing Images
+//!
+//! The `construct` module builds new images in memory out of pixels supplied
+//! by the caller, so procedural images and test data can be encoded without
+//! packing pixel buffers by hand. The images built are uncompressed 24 bit
+//! true colour images whose rows run from the top of the image to the bottom,
+//! 24 bits deep unless a `TgaImageBuilder` asks for another format, or the
+//! pixels come from a 32 bit capture buffer.
+use std::convert::TryFrom;
+use std::sync::Arc;
+use crate::encoder::top_down_header;
+#[cfg(feature = "rle")]
+use crate::RunLengthEncodedRgb;
+use crate::{PixelDepth, RawTgaImage, TgaError, TgaHeader, TgaImage, UncompressedRgb};
+
+
+impl TgaImage {
+    /// The function `from_fn` builds an image by calling a closure for every
+    /// pixel. The closure receives the column and row of the pixel, with
+    /// `(0, 0)` the top left corner of the image, and returns its colour as
+    /// blue, green, and red channels, the same order `pixels` yields them.
+    /// Pixels are visited one row at a time from the top of the image, and
+    /// from left to right within each row. Dimensions that do not fit in the
+    /// 16 bit fields of a TGA header are rejected with `InvalidDimensions`.
+    pub fn from_fn<F>(width: u32, height: u32, mut f: F) -> Result<TgaImage, TgaError>
+    where
+        F: FnMut(u32, u32) -> [u8; 3],
+    {
+        let header = top_down_header(2, width, height, 24)?;
+        let mut image_data = Vec::with_capacity(header.width() * header.height() * 3);
+        for y in 0..height {
+            for x in 0..width {
+                image_data.extend_from_slice(&f(x, y));
+            }
+        }
+
+        Ok(true_colour_image(header, image_data))
+    }
+
+    /// The function `new` builds an image out of its pixels, given as blue,
+    /// green, and red channels, the same order `pixels` yields them. The rows
+    /// of pixels run from the top of the image to the bottom, and there must
+    /// be exactly `width * height` pixels. Dimensions that do not fit in the
+    /// 16 bit fields of a TGA header are rejected with `InvalidDimensions`.
+    pub fn new(width: u32, height: u32, pixels: Vec<[u8; 3]>) -> Result<TgaImage, TgaError> {
+        let header = top_down_header(2, width, height, 24)?;
+        let pixel_count = header.width() * header.height();
+        if pixels.len() != pixel_count {
+            return Err(TgaError::IncompleteImageData(pixels.len(), pixel_count));
+        }
+        let image_data = pixels.iter().flat_map(|pixel| pixel.iter().cloned()).collect();
+
+        Ok(true_colour_image(header, image_data))
+    }
+
+    /// The function `from_bgra_with_stride` builds a 32 bit image out of a
+    /// buffer of blue, green, red, and alpha channels whose rows are
+    /// `stride` bytes apart, such as the screen captures GDI and DXGI
+    /// produce. Padding between rows is skipped, and the last row need not
+    /// be padded. A positive stride means the rows run from the top of the
+    /// image to the bottom, and a negative stride that they run from the
+    /// bottom to the top, as in a bottom up device independent bitmap. The
+    /// alpha channel is kept as given, so captures in a format without
+    /// alpha, whose fourth channel is undefined, should have it set first.
+    /// A stride shorter than a row of pixels is rejected with
+    /// `InvalidStride`, a buffer too short for the rows with
+    /// `IncompleteImageData`, and dimensions that do not fit in the 16 bit
+    /// fields of a TGA header with `InvalidDimensions`.
+    pub fn from_bgra_with_stride(width: u32, height: u32, stride: isize, pixels: &[u8]) -> Result<TgaImage, TgaError> {
+        let mut header = top_down_header(2, width, height, 32)?;
+        header.image_descriptor |= PixelDepth::Bits32.attribute_bits();
+        let row_length = header.width() * 4;
+        let row_distance = stride.unsigned_abs();
+        if row_distance < row_length {
+            return Err(TgaError::InvalidStride(stride));
+        }
+        let data_length = match header.height() {
+            0 => 0,
+            height => (height - 1) * row_distance + row_length,
+        };
+        if pixels.len() < data_length {
+            return Err(TgaError::IncompleteImageData(pixels.len(), data_length));
+        }
+
+        let mut image_data = Vec::with_capacity(header.height() * row_length);
+        for y in 0..header.height() {
+            let row = if stride < 0 { header.height() - 1 - y } else { y };
+            let start = row * row_distance;
+            image_data.extend_from_slice(&pixels[start..(start + row_length)]);
+        }
+
+        Ok(true_colour_image(header, image_data))
+    }
+}
+
+/// Wrap the pixel data of a new true colour image into an image.
+fn true_colour_image(header: TgaHeader, image_data: Vec<u8>) -> TgaImage {
+    let inner = RawTgaImage::new(header, Arc::default(), Arc::default(), Arc::new(image_data), Arc::default());
+
+    TgaImage::Type02(UncompressedRgb { inner })
+}
+
+/// A `TgaImageBuilder` describes a new true colour image one property at a
+/// time, and builds it out of a buffer of pixels with `build`. Properties
+/// left unset keep their defaults: an empty 24 bit image at the origin,
+/// without an image identification, and uncompressed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TgaImageBuilder {
+    width: u32,
+    height: u32,
+    pixel_format: PixelDepth,
+    origin: (u16, u16),
+    id_string: Vec<u8>,
+    #[cfg(feature = "rle")]
+    rle: bool,
+}
+
+impl Default for TgaImageBuilder {
+    fn default() -> TgaImageBuilder {
+        TgaImageBuilder {
+            width: 0,
+            height: 0,
+            pixel_format: PixelDepth::Bits24,
+            origin: (0, 0),
+            id_string: Vec::new(),
+            #[cfg(feature = "rle")]
+            rle: false,
+        }
+    }
+}
+
+impl TgaImageBuilder {
+    /// Start describing a new image.
+    pub fn new() -> TgaImageBuilder {
+        TgaImageBuilder::default()
+    }
+
+    /// Set the width of the image, in pixels.
+    pub fn width(mut self, width: u32) -> TgaImageBuilder {
+        self.width = width;
+        self
+    }
+
+    /// Set the height of the image, in pixels.
+    pub fn height(mut self, height: u32) -> TgaImageBuilder {
+        self.height = height;
+        self
+    }
+
+    /// Set the depth of the pixels. The image descriptor records the alpha
+    /// bits of the depth.
+    pub fn pixel_format(mut self, pixel_format: PixelDepth) -> TgaImageBuilder {
+        self.pixel_format = pixel_format;
+        self
+    }
+
+    /// Set the position of the lower left corner of the image on a display.
+    pub fn origin(mut self, x_origin: u16, y_origin: u16) -> TgaImageBuilder {
+        self.origin = (x_origin, y_origin);
+        self
+    }
+
+    /// Set the image identification. The field holds at most 255 bytes.
+    pub fn id_string(mut self, id_string: &str) -> TgaImageBuilder {
+        self.id_string = id_string.as_bytes().to_vec();
+        self
+    }
+
+    /// Set whether the image is run length encoded, making it a type 10
+    /// image instead of a type 2 image.
+    #[cfg(feature = "rle")]
+    pub fn rle(mut self, rle: bool) -> TgaImageBuilder {
+        self.rle = rle;
+        self
+    }
+
+    /// Build the image out of its pixels, stored at the depth of the pixel
+    /// format with their channels in blue, green, red, and alpha order. The
+    /// rows of pixels run from the top of the image to the bottom, and the
+    /// buffer must hold exactly one pixel for every position in the image.
+    /// Dimensions that do not fit in the 16 bit fields of a TGA header are
+    /// rejected with `InvalidDimensions`, and an image identification longer
+    /// than 255 bytes with `ImageIdentificationTooLong`.
+    pub fn build(&self, pixels: &[u8]) -> Result<TgaImage, TgaError> {
+        let mut header = top_down_header(2, self.width, self.height, self.pixel_format.bits() as u8)?;
+        header.id_length = u8::try_from(self.id_string.len())
+            .map_err(|_| TgaError::ImageIdentificationTooLong(self.id_string.len()))?;
+        header.set_x_origin(self.origin.0);
+        header.set_y_origin(self.origin.1);
+        header.image_descriptor |= self.pixel_format.attribute_bits();
+        #[cfg(feature = "rle")]
+        {
+            if self.rle {
+                header.data_type_code = 10;
+            }
+        }
+        let data_length = header.width() * header.height() * header.bytes_per_pixel();
+        if pixels.len() != data_length {
+            return Err(TgaError::IncompleteImageData(pixels.len(), data_length));
+        }
+
+        let inner = RawTgaImage::new(
+            header, Arc::new(self.id_string.clone()), Arc::default(), Arc::new(pixels.to_vec()), Arc::default()
+        );
+        match header.data_type_code {
+            #[cfg(feature = "rle")]
+            10 => Ok(TgaImage::Type10(RunLengthEncodedRgb { inner })),
+            _ => Ok(TgaImage::Type02(UncompressedRgb { inner })),
+        }
+    }
+}
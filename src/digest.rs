@@ -0,0 +1,124 @@
+//! # Digests
+//!
+//! The `digest` module computes checksums of TGA files while they are read,
+//! so services ingesting large files can fingerprint them without reading
+//! them a second time. The checksum of the file is computed on the bytes as
+//! they come out of the reader, and the hash of the content on the colours
+//! the file decodes to.
+#[cfg(feature = "metadata")]
+use std::io;
+#[cfg(feature = "metadata")]
+use crate::{RawTgaImage, TgaError, TgaImage};
+
+
+/// The lookup table of the CRC-32 used by zlib, PNG, and gzip, with one entry
+/// per byte value, built at compile time.
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+/// Build the lookup table of the CRC-32.
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+
+    table
+}
+
+/// A running CRC-32 checksum.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    /// Start a checksum of no bytes.
+    pub(crate) fn new() -> Crc32 {
+        Crc32 { crc: 0xFFFF_FFFF }
+    }
+
+    /// Add bytes to the checksum.
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes.iter() {
+            self.crc = CRC32_TABLE[((self.crc ^ byte as u32) & 0xFF) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    /// The checksum of every byte added.
+    pub(crate) fn finish(self) -> u32 {
+        !self.crc
+    }
+}
+
+/// A reader that checksums the bytes read through it.
+#[cfg(feature = "metadata")]
+struct Crc32Reader<'a, R> {
+    inner: &'a mut R,
+    crc: Crc32,
+}
+
+#[cfg(feature = "metadata")]
+impl<'a, R: io::Read> io::Read for Crc32Reader<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes_read = self.inner.read(buf)?;
+        self.crc.update(&buf[..bytes_read]);
+
+        Ok(bytes_read)
+    }
+}
+
+/// A `Digest` fingerprints a TGA file and the image it holds.
+#[cfg(feature = "metadata")]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Digest {
+    /// The CRC-32 of the raw bytes of the file, as zlib and PNG compute it.
+    pub file_crc32: u32,
+    /// A 64 bit FNV-1a hash of the dimensions of the image and its colours in
+    /// display order. Files that store the same image differently, such as
+    /// run length encoded or from another corner, hash the same.
+    pub content_hash: u64,
+}
+
+/// Hash the dimensions and colours of an image with FNV-1a.
+#[cfg(feature = "metadata")]
+fn content_hash(image: &RawTgaImage) -> u64 {
+    let mut hash = 0xCBF2_9CE4_8422_2325_u64;
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes.iter() {
+            hash = (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3);
+        }
+    };
+    feed(&(image.width() as u32).to_le_bytes());
+    feed(&(image.height() as u32).to_le_bytes());
+    for colour in image.colours_in_display_order().iter() {
+        feed(colour);
+    }
+
+    hash
+}
+
+#[cfg(feature = "metadata")]
+impl TgaImage {
+    /// The function `parse_from_file_with_digest` reads and decodes a TGA file
+    /// like `parse_from_file`, checksumming the bytes of the file as they are
+    /// read, and returns the image together with its digest. The file is
+    /// read exactly once.
+    pub fn parse_from_file_with_digest<F: io::Read>(f: &mut F) -> Result<(TgaImage, Digest), TgaError> {
+        let mut reader = Crc32Reader { inner: f, crc: Crc32::new() };
+        let image = TgaImage::parse_from_file(&mut reader)?;
+        let digest = Digest {
+            file_crc32: reader.crc.finish(),
+            content_hash: content_hash(image.raw_tga_image()),
+        };
+
+        Ok((image, digest))
+    }
+}
@@ -0,0 +1,168 @@
+//! # Embedded Images
+//!
+//! The `embedded` module checks TGA files embedded in a program at compile
+//! time. The `tga_image!` macro embeds a file with `include_bytes!` and
+//! validates its header and pixel data during constant evaluation, so a
+//! malformed asset fails the build instead of the program.
+use crate::{TgaError, TgaImage, TGA_HEADER_LENGTH};
+
+
+/// An `EmbeddedTga` is a TGA file embedded in the program, whose header and
+/// pixel data have been checked. Its dimensions are known at compile time.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct EmbeddedTga {
+    bytes: &'static [u8],
+    width: u32,
+    height: u32,
+    data_type_code: u8,
+    bits_per_pixel: u8,
+}
+
+/// Read a little endian 16 bit field of a header.
+const fn read_u16(bytes: &[u8], offset: usize) -> usize {
+    (bytes[offset] as usize) | ((bytes[offset + 1] as usize) << 8)
+}
+
+/// Check that run length encoded packets starting at `offset` decode to
+/// `image_size` bytes of pixel data without running past the end of the file.
+const fn check_packets(bytes: &[u8], mut offset: usize, image_size: usize, bytes_per_pixel: usize) -> bool {
+    let mut image_data_found = 0;
+    while image_data_found < image_size {
+        if offset >= bytes.len() {
+            return false;
+        }
+        let packet_header = bytes[offset];
+        let packet_length = (packet_header & 0x7F) as usize + 1;
+        image_data_found += bytes_per_pixel * packet_length;
+        offset += if packet_header & 0x80 != 0 {
+            bytes_per_pixel + 1
+        } else {
+            bytes_per_pixel * packet_length + 1
+        };
+    }
+
+    (image_data_found == image_size) && (offset <= bytes.len())
+}
+
+impl EmbeddedTga {
+    /// The function `from_bytes` checks the bytes of a TGA file and records
+    /// its dimensions. It applies the same checks as the parser: the image
+    /// type must be supported, the pixel and colour map depths valid, and the
+    /// file long enough to hold all of its pixel data.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the file is malformed. Evaluated in a constant,
+    /// as the `tga_image!` macro does, the panic becomes a compile error.
+    pub const fn from_bytes(bytes: &'static [u8]) -> EmbeddedTga {
+        if bytes.len() < TGA_HEADER_LENGTH {
+            panic!("malformed TGA asset: the file is shorter than a TGA header");
+        }
+
+        let id_length = bytes[0] as usize;
+        let color_map_type = bytes[1];
+        let data_type_code = bytes[2];
+        let colour_map_length = read_u16(bytes, 5);
+        let colour_map_depth = bytes[7];
+        let width = read_u16(bytes, 12);
+        let height = read_u16(bytes, 14);
+        let bits_per_pixel = bytes[16];
+
+        let is_colour_mapped = match data_type_code {
+            1 => true,
+            2 => false,
+            9 if cfg!(feature = "rle") => true,
+            10 if cfg!(feature = "rle") => false,
+            _ => panic!("malformed TGA asset: unsupported image type"),
+        };
+        let colour_map_bytes = if is_colour_mapped {
+            if (color_map_type != 1) || (colour_map_length == 0) {
+                panic!("malformed TGA asset: colour mapped image without a colour map");
+            }
+            if !matches!(bits_per_pixel, 8 | 16) {
+                panic!("malformed TGA asset: unsupported colour map index size");
+            }
+            if !matches!(colour_map_depth, 15 | 16 | 24 | 32) {
+                panic!("malformed TGA asset: unsupported colour map depth");
+            }
+            colour_map_length * (colour_map_depth as usize).div_ceil(8)
+        } else {
+            if !matches!(bits_per_pixel, 15 | 16 | 24 | 32) {
+                panic!("malformed TGA asset: unsupported pixel depth");
+            }
+            0
+        };
+
+        let data_offset = TGA_HEADER_LENGTH + id_length + colour_map_bytes;
+        let bytes_per_pixel = (bits_per_pixel as usize).div_ceil(8);
+        let image_size = width * height * bytes_per_pixel;
+        let complete = if data_type_code & 0x08 != 0 {
+            check_packets(bytes, data_offset, image_size, bytes_per_pixel)
+        } else {
+            data_offset + image_size <= bytes.len()
+        };
+        if !complete {
+            panic!("malformed TGA asset: the pixel data is incomplete");
+        }
+
+        EmbeddedTga {
+            bytes,
+            width: width as u32,
+            height: height as u32,
+            data_type_code,
+            bits_per_pixel,
+        }
+    }
+
+    /// The function `width` returns the width of the embedded image, in pixels.
+    #[inline]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The function `height` returns the height of the embedded image, in pixels.
+    #[inline]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Return the data type code of the embedded image.
+    #[inline]
+    pub const fn data_type_code(&self) -> usize {
+        self.data_type_code as usize
+    }
+
+    /// Return the bit depth per pixel of the embedded image.
+    #[inline]
+    pub const fn bits_per_pixel(&self) -> usize {
+        self.bits_per_pixel as usize
+    }
+
+    /// The function `bytes` returns the embedded file.
+    #[inline]
+    pub const fn bytes(&self) -> &'static [u8] {
+        self.bytes
+    }
+
+    /// The function `decode` parses the embedded file into an image.
+    pub fn decode(&self) -> Result<TgaImage, TgaError> {
+        TgaImage::parse_from_buffer(self.bytes)
+    }
+}
+
+/// The macro `tga_image!` embeds a TGA file in the program and checks it at
+/// compile time, producing an `EmbeddedTga`. The path is resolved relative to
+/// the file invoking the macro, as with `include_bytes!`. A malformed file is
+/// a compile error.
+///
+/// ```ignore
+/// const LOGO: tga::EmbeddedTga = tga::tga_image!("assets/logo.tga");
+/// const _: () = assert!(LOGO.width() == 64);
+/// ```
+#[macro_export]
+macro_rules! tga_image {
+    ($path:expr) => {{
+        const IMAGE: $crate::EmbeddedTga = $crate::EmbeddedTga::from_bytes(include_bytes!($path));
+        IMAGE
+    }};
+}
@@ -0,0 +1,735 @@
+//! # Encoder
+//!
+//! The `encoder` module serializes images back into TGA files, either into a
+//! buffer in memory or directly into a writer. The `EncoderOptions` type
+//! controls the flavour of TGA file the encoder produces.
+//!
+//! Encoding is deterministic. The same image encoded with the same options
+//! always produces the same bytes, on every platform and every thread, so
+//! encoder output is safe to use as a cache key. Nothing but the image and
+//! the options goes into the file; in particular, an extension area only
+//! records a timestamp when the options give one. Images built in memory, by
+//! `TgaImage::new`, `TgaImage::from_fn`, `TgaImageBuilder`,
+//! `encode_grayscale`, or `encode_colour_mapped`, start from canonical header
+//! fields: no image identification, an origin of `(0, 0)`, and an image
+//! descriptor recording a top left orientation and the alpha bits of the
+//! pixel depth. A TGA 2.0 footer has offsets of zero unless the options add
+//! an extension area or developer area, or the image keeps one from the file
+//! it was read from.
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use crate::extension::{write_trailer, DeveloperTag, ExtensionArea, PostageStamp};
+#[cfg(feature = "rle")]
+use crate::rle::encode_rle_scanline;
+#[cfg(feature = "rle")]
+use crate::{RunLengthEncodedColorMapped, RunLengthEncodedRgb};
+use crate::{
+    encode_pixel, swap_red_and_blue, AttributesType, ChannelOrder, Palette, RawTgaImage, TgaError, TgaHeader, TgaImage, UncompressedColorMapped, UncompressedRgb,
+    RGB_CHANNEL_ORDER_TAG, TGA_FOOTER, TGA_HEADER_LENGTH,
+};
+
+
+/// The maximum length of the image identification field, in bytes.
+const MAX_IMAGE_IDENTIFICATION_LENGTH: usize = 255;
+
+/// The corner of the display at which the first pixel of the image data is
+/// placed. This is recorded in bits 4 and 5 of the image descriptor.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Orientation {
+    /// Scanlines run bottom to top, and pixels run left to right. This is the
+    /// orientation the TGA specification assumes by default.
+    #[default]
+    BottomLeft,
+    /// Scanlines run bottom to top, and pixels run right to left.
+    BottomRight,
+    /// Scanlines run top to bottom, and pixels run left to right.
+    TopLeft,
+    /// Scanlines run top to bottom, and pixels run right to left.
+    TopRight,
+}
+
+impl Orientation {
+    /// Read the orientation from an image descriptor byte.
+    pub fn from_image_descriptor(image_descriptor: u8) -> Orientation {
+        match (image_descriptor & 0x20 != 0, image_descriptor & 0x10 != 0) {
+            (false, false) => Orientation::BottomLeft,
+            (false, true) => Orientation::BottomRight,
+            (true, false) => Orientation::TopLeft,
+            (true, true) => Orientation::TopRight,
+        }
+    }
+
+    /// The bits of the image descriptor byte that record the orientation.
+    pub fn image_descriptor_bits(self) -> u8 {
+        match self {
+            Orientation::BottomLeft => 0x00,
+            Orientation::BottomRight => 0x10,
+            Orientation::TopLeft => 0x20,
+            Orientation::TopRight => 0x30,
+        }
+    }
+
+    /// Determine whether scanlines run top to bottom.
+    pub fn is_top_to_bottom(self) -> bool {
+        self.image_descriptor_bits() & 0x20 != 0
+    }
+
+    /// Determine whether pixels run right to left.
+    pub fn is_right_to_left(self) -> bool {
+        self.image_descriptor_bits() & 0x10 != 0
+    }
+}
+
+/// The version of the TGA format the encoder writes.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum TgaFormat {
+    /// The original TGA 1.0 format. The file ends with the pixel data, without
+    /// the data stored after it or the footer, for consumers that reject
+    /// anything past the pixel data.
+    Original,
+    /// The TGA 2.0 format. The data stored after the pixel data is kept, and
+    /// the file ends with a footer identifying it as a TGA 2.0 file.
+    #[default]
+    New,
+}
+
+/// The depth of the true colour pixels the encoder writes.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PixelDepth {
+    /// Five bits each of red, green, and blue.
+    Bits15,
+    /// Five bits each of red, green, and blue, and one bit of alpha.
+    Bits16,
+    /// Eight bits each of blue, green, and red.
+    Bits24,
+    /// Eight bits each of blue, green, red, and alpha.
+    Bits32,
+}
+
+impl PixelDepth {
+    /// The number of bits each pixel occupies.
+    pub fn bits(self) -> usize {
+        match self {
+            PixelDepth::Bits15 => 15,
+            PixelDepth::Bits16 => 16,
+            PixelDepth::Bits24 => 24,
+            PixelDepth::Bits32 => 32,
+        }
+    }
+
+    /// The number of alpha bits per pixel, as recorded in the attribute bits
+    /// of the image descriptor.
+    pub fn attribute_bits(self) -> u8 {
+        match self {
+            PixelDepth::Bits15 | PixelDepth::Bits24 => 0,
+            PixelDepth::Bits16 => 1,
+            PixelDepth::Bits32 => 8,
+        }
+    }
+}
+
+/// The `EncoderOptions` type controls the flavour of TGA file the encoder
+/// produces. The default options reproduce the image as it was read, and
+/// produce a file that follows the TGA specification. Every field left as
+/// `None` carries the corresponding property of the image over unchanged.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct EncoderOptions {
+    /// Whether the pixel data is run length encoded. `Some(true)` writes a
+    /// type 9 or type 10 file, and `Some(false)` writes a type 1 or type 2
+    /// file.
+    #[cfg(feature = "rle")]
+    pub rle: Option<bool>,
+    /// The version of the TGA format to write. The default is TGA 2.0.
+    pub format: TgaFormat,
+    /// The extension area to write after the pixel data, recording the
+    /// author, software, and time of the image. Only TGA 2.0 files have an
+    /// extension area.
+    pub extension_area: Option<ExtensionArea>,
+    /// Application data to store in the developer area after the pixel data,
+    /// listed in a developer directory. The developer tags of the file the
+    /// image was read from are written too, after being moved to offsets of
+    /// their own, unless a tag here has the same tag number. Only TGA 2.0
+    /// files have a developer area.
+    pub developer_tags: Vec<DeveloperTag>,
+    /// Whether to store a postage stamp, a thumbnail of at most 64 by 64
+    /// pixels that file browsers can show without decoding the whole image.
+    /// The postage stamp is recorded in the extension area, so an empty
+    /// extension area is written when `extension_area` is `None`. Only TGA
+    /// 2.0 files have a postage stamp.
+    pub postage_stamp: bool,
+    /// The position of the lower left corner of the image on a display.
+    pub origin: Option<(u16, u16)>,
+    /// The corner of the display the image data starts from. When this
+    /// differs from the orientation of the image, the pixels are reordered
+    /// so the image still displays the same way.
+    pub orientation: Option<Orientation>,
+    /// The number of attribute bits per pixel, recorded in the lower four
+    /// bits of the image descriptor. Only the lower four bits are used.
+    pub attribute_bits: Option<u8>,
+    /// The depth to write the pixels in. Colour mapped images are expanded
+    /// into true colour images. The image descriptor records the alpha bits
+    /// of the new depth, so 32 bit pixels carry 8 attribute bits, unless
+    /// `attribute_bits` gives another count.
+    pub pixel_depth: Option<PixelDepth>,
+    /// Whether to apply an ordered dither when converting pixels to 15 or 16
+    /// bits, so smooth gradients do not band. Pixels written at other depths
+    /// are never dithered.
+    pub dither: bool,
+    /// The image identification. The field holds at most 255 bytes, so a
+    /// longer identification is truncated.
+    pub image_identification: Option<Vec<u8>>,
+    /// The order the colour channels of each pixel are written in. Only 24 and
+    /// 32 bit pixels and colour map entries are affected. Files written in
+    /// red, green, blue order have `RGB_CHANNEL_ORDER_TAG` prepended to their
+    /// image identification, and the parser uses the tag to restore the
+    /// standard order when reading them back.
+    pub channel_order_in_file: ChannelOrder,
+}
+
+/// The 4x4 Bayer matrix used for ordered dithering, with thresholds from 0 to 15.
+const BAYER_MATRIX: [[u8; 4]; 4] = [
+    [0, 8, 2, 10],
+    [12, 4, 14, 6],
+    [3, 11, 1, 9],
+    [15, 7, 13, 5],
+];
+
+/// Add the ordered dither threshold for the pixel at column `x` of row `y` to
+/// the colour channels of a pixel about to be reduced to five bits per channel.
+/// The thresholds spread over the eight values each five bit level covers, so
+/// on average the colours round to the nearest level instead of truncating.
+fn dither_pixel(colour: [u8; 4], x: usize, y: usize) -> [u8; 4] {
+    let threshold = BAYER_MATRIX[y % 4][x % 4] / 2;
+    let dither = |value: u8| value.saturating_add(threshold);
+
+    [dither(colour[0]), dither(colour[1]), dither(colour[2]), colour[3]]
+}
+
+impl EncoderOptions {
+    /// Construct the default encoder options.
+    pub fn new() -> EncoderOptions {
+        EncoderOptions::default()
+    }
+
+    /// The image identification written to the file: the identification from
+    /// the options, or else the one given, tagged with the channel order and
+    /// truncated to fit the header.
+    pub(crate) fn image_identification_in_file(&self, image_identification: &[u8]) -> Vec<u8> {
+        let mut image_identification = match self.image_identification {
+            Some(ref image_identification) => image_identification.clone(),
+            None => image_identification.to_vec(),
+        };
+        if self.channel_order_in_file == ChannelOrder::Rgb {
+            image_identification.splice(0..0, RGB_CHANNEL_ORDER_TAG.iter().cloned());
+        }
+        image_identification.truncate(MAX_IMAGE_IDENTIFICATION_LENGTH);
+
+        image_identification
+    }
+}
+
+impl Default for EncoderOptions {
+    fn default() -> EncoderOptions {
+        EncoderOptions {
+            #[cfg(feature = "rle")]
+            rle: None,
+            format: TgaFormat::New,
+            extension_area: None,
+            developer_tags: Vec::new(),
+            postage_stamp: false,
+            origin: None,
+            orientation: None,
+            attribute_bits: None,
+            pixel_depth: None,
+            dither: false,
+            image_identification: None,
+            channel_order_in_file: ChannelOrder::Bgr,
+        }
+    }
+}
+
+impl RawTgaImage {
+    /// The function `encode_to_vec` serializes the image into the bytes of a
+    /// TGA file, using the default encoder options.
+    pub(crate) fn encode_to_vec(&self) -> Vec<u8> {
+        self.encode_with_options(&EncoderOptions::default())
+    }
+
+    /// The function `encode_to_writer` writes the image as a TGA file into
+    /// a writer, using the default encoder options.
+    pub(crate) fn encode_to_writer<W: io::Write>(&self, writer: &mut W) -> Result<(), TgaError> {
+        self.write_with_options(&EncoderOptions::default(), writer)
+            .map_err(|err| TgaError::Io(Box::new(err)))
+    }
+
+    /// Serialize the image into a buffer in memory.
+    pub(crate) fn encode_with_options(&self, options: &EncoderOptions) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(
+            TGA_HEADER_LENGTH
+                + self.image_identification.len()
+                + self.colour_map_data.len()
+                + self.image_data_length_bytes()
+                + self.extended_image_identification.len()
+                + TGA_FOOTER.len()
+        );
+        self.write_with_options(options, &mut buf).expect("writing to a Vec cannot fail");
+
+        buf
+    }
+
+    /// Write the image as controlled by the encoder options. When the output is
+    /// run length encoded, the pixel data is compressed one scanline at a time,
+    /// so at most one compressed scanline is ever held in memory.
+    pub(crate) fn write_with_options<W: io::Write>(
+        &self, options: &EncoderOptions, writer: &mut W
+    ) -> io::Result<()> {
+        let writer = &mut CountingWriter { writer, count: 0 };
+        let mut header = self.header;
+        #[cfg(feature = "rle")]
+        match options.rle {
+            Some(true) if header.has_image_data() => header.data_type_code |= 0x08,
+            Some(false) => header.data_type_code &= !0x08,
+            _ => {}
+        }
+        if let Some((x_origin, y_origin)) = options.origin {
+            header.set_x_origin(x_origin);
+            header.set_y_origin(y_origin);
+        }
+
+        // Expand colour mapped images into true colour ones, and convert true
+        // colour pixels to the requested depth.
+        let target_depth = options.pixel_depth.filter(|depth| {
+            self.header.has_image_data()
+                && (self.header.is_colour_mapped() || (depth.bits() != self.header.bits_per_pixel()))
+        });
+        let dither = options.dither && target_depth.is_some_and(|depth| depth.bits() <= 16);
+        if let Some(depth) = target_depth {
+            header.color_map_type = 0;
+            header.data_type_code = 2 | (header.data_type_code & 0x08);
+            header.colour_map_origin = [0, 0];
+            header.colour_map_length = [0, 0];
+            header.colour_map_depth = 0;
+            header.bits_per_pixel = depth.bits() as u8;
+            header.image_descriptor = (header.image_descriptor & 0xF0) | depth.attribute_bits();
+        }
+        if let Some(attribute_bits) = options.attribute_bits {
+            header.image_descriptor = (header.image_descriptor & 0xF0) | (attribute_bits & 0x0F);
+        }
+
+        let source = Orientation::from_image_descriptor(self.header.image_descriptor);
+        let target = options.orientation.unwrap_or(source);
+        header.image_descriptor = (header.image_descriptor & !0x30) | target.image_descriptor_bits();
+        let flip_rows = source.is_top_to_bottom() != target.is_top_to_bottom();
+        let flip_columns = source.is_right_to_left() != target.is_right_to_left();
+
+        let swap_channels = options.channel_order_in_file == ChannelOrder::Rgb;
+        let image_identification = options.image_identification_in_file(&self.image_identification);
+        header.id_length = image_identification.len() as u8;
+
+        let mut colour_map_data = match target_depth {
+            Some(_) => Vec::new(),
+            None => self.colour_map_data.to_vec(),
+        };
+        if swap_channels {
+            swap_red_and_blue(&mut colour_map_data, header.colour_map_depth());
+        }
+        let swap_pixels = swap_channels && !header.is_colour_mapped();
+
+        writer.write_all(&header.to_bytes())?;
+        writer.write_all(&image_identification)?;
+        writer.write_all(&colour_map_data)?;
+
+        let decoder = self.pixel_decoder();
+        let source_bytes_per_pixel = self.header.bytes_per_pixel();
+        let bytes_per_pixel = header.bytes_per_pixel();
+        let mut postage_stamp = if options.postage_stamp && (options.format == TgaFormat::New) && (self.image_data_length() > 0) {
+            Some(PostageStamp::new(self.width(), self.height(), bytes_per_pixel))
+        } else {
+            None
+        };
+        if self.width() > 0 {
+            let rows: Box<dyn Iterator<Item = &[u8]>> = if flip_rows {
+                Box::new(self.rows().rev())
+            } else {
+                Box::new(self.rows())
+            };
+            let mut scratch = Vec::with_capacity(self.width() * bytes_per_pixel);
+            #[cfg(feature = "rle")]
+            let mut packets = Vec::new();
+            for (y, scanline) in rows.enumerate() {
+                let scanline = if swap_pixels || flip_columns || target_depth.is_some() {
+                    scratch.clear();
+                    let mut push = |pixel: &[u8]| match target_depth {
+                        Some(depth) => {
+                            let start = scratch.len();
+                            let mut colour = decoder.decode(pixel);
+                            if dither {
+                                colour = dither_pixel(colour, start / bytes_per_pixel, y);
+                            }
+                            scratch.resize(start + bytes_per_pixel, 0);
+                            encode_pixel(colour, depth.bits(), &mut scratch[start..]);
+                        }
+                        None => scratch.extend_from_slice(pixel),
+                    };
+                    if flip_columns {
+                        scanline.chunks(source_bytes_per_pixel).rev().for_each(&mut push);
+                    } else {
+                        scanline.chunks(source_bytes_per_pixel).for_each(&mut push);
+                    }
+                    if swap_pixels {
+                        swap_red_and_blue(&mut scratch, header.bits_per_pixel());
+                    }
+                    &scratch[..]
+                } else {
+                    scanline
+                };
+                if let Some(ref mut postage_stamp) = postage_stamp {
+                    postage_stamp.push_scanline(y, scanline);
+                }
+
+                #[cfg(feature = "rle")]
+                {
+                    if header.is_run_length_encoded() {
+                        packets.clear();
+                        encode_rle_scanline(scanline, bytes_per_pixel, &mut packets);
+                        writer.write_all(&packets)?;
+                        continue;
+                    }
+                }
+                writer.write_all(scanline)?;
+            }
+        }
+        if options.format == TgaFormat::New {
+            let alpha = header.image_descriptor & 0x0F != 0;
+            let position = writer.count;
+            self.write_trailing_areas(writer, position, alpha, options, postage_stamp.as_ref())?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the areas following the pixel data of a TGA 2.0 file, starting
+    /// at the offset `position` in the file: the trailing bytes kept from the
+    /// source file, and the developer area, extension area, and footer, with
+    /// offsets of their own. The argument `alpha` records whether the pixels
+    /// written have an alpha channel.
+    pub(crate) fn write_trailing_areas<W: io::Write>(
+        &self,
+        writer: &mut W,
+        position: usize,
+        alpha: bool,
+        options: &EncoderOptions,
+        postage_stamp: Option<&PostageStamp>,
+    ) -> io::Result<()> {
+        // The areas carried over from the source file have offsets into
+        // that file, so they are written again with offsets of their own.
+        // Developer tags the options do not replace are kept.
+        let trailing = &self.extended_image_identification[..self.trailing_data_length()];
+        let mut developer_tags = self.developer_tags();
+        developer_tags.retain(|developer_tag| {
+            options.developer_tags.iter().all(|replacement| replacement.tag != developer_tag.tag)
+        });
+        developer_tags.extend(options.developer_tags.iter().cloned());
+        // The key colour and pixel aspect ratio describe how the image is
+        // displayed rather than where it came from, so they are kept
+        // unless the options replace them. Premultiplied alpha is kept
+        // too, as long as the pixels still have an alpha channel.
+        let requested = options.extension_area.as_ref();
+        let key_colour = requested
+            .and_then(|extension_area| extension_area.key_colour)
+            .or_else(|| self.key_colour());
+        let pixel_aspect_ratio = requested
+            .and_then(|extension_area| extension_area.pixel_aspect_ratio)
+            .or_else(|| self.pixel_aspect_ratio());
+        let attributes_type = requested
+            .and_then(|extension_area| extension_area.attributes_type)
+            .or_else(|| self.attributes_type().filter(|&attributes_type| {
+                alpha && (attributes_type == AttributesType::PremultipliedAlpha)
+            }));
+        let extension_area = match (requested, key_colour, pixel_aspect_ratio, attributes_type) {
+            (None, None, None, None) => None,
+            (extension_area, key_colour, pixel_aspect_ratio, attributes_type) => Some(ExtensionArea {
+                key_colour,
+                pixel_aspect_ratio,
+                attributes_type,
+                ..extension_area.cloned().unwrap_or_default()
+            }),
+        };
+
+        write_trailer(
+            writer,
+            position,
+            trailing,
+            extension_area.as_ref(),
+            &developer_tags,
+            postage_stamp,
+            alpha,
+        )
+    }
+}
+
+/// A `CountingWriter` counts the bytes written through it, so the encoder
+/// knows the file offsets of the areas it writes.
+pub(crate) struct CountingWriter<'a, W: io::Write> {
+    pub(crate) writer: &'a mut W,
+    pub(crate) count: usize,
+}
+
+impl<'a, W: io::Write> io::Write for CountingWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.count += written;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Build the header of a new image whose rows run from the top of the image
+/// to the bottom, checking that its dimensions fit in the header.
+pub(crate) fn top_down_header(
+    data_type_code: u8, width: u32, height: u32, bits_per_pixel: u8
+) -> Result<TgaHeader, TgaError> {
+    let (header_width, header_height) = match (u16::try_from(width), u16::try_from(height)) {
+        (Ok(header_width), Ok(header_height)) => (header_width, header_height),
+        _ => return Err(TgaError::InvalidDimensions(width, height)),
+    };
+
+    Ok(TgaHeader {
+        id_length: 0,
+        color_map_type: 0,
+        data_type_code,
+        colour_map_origin: [0, 0],
+        colour_map_length: [0, 0],
+        colour_map_depth: 0,
+        x_origin: [0, 0],
+        y_origin: [0, 0],
+        width: header_width.to_le_bytes(),
+        height: header_height.to_le_bytes(),
+        bits_per_pixel,
+        image_descriptor: Orientation::TopLeft.image_descriptor_bits(),
+    })
+}
+
+/// Build an image out of a buffer of eight bit grey levels, whose rows run
+/// from the top of the image to the bottom.
+fn grayscale_image(width: u32, height: u32, data: &[u8]) -> Result<RawTgaImage, TgaError> {
+    let header = top_down_header(3, width, height, 8)?;
+    let pixel_count = header.width() * header.height();
+    if data.len() != pixel_count {
+        return Err(TgaError::IncompleteImageData(data.len(), pixel_count));
+    }
+
+    Ok(RawTgaImage::new(header, Arc::default(), Arc::default(), Arc::new(data.to_vec()), Arc::default()))
+}
+
+/// Build a colour mapped image out of a buffer of eight bit colour map indices,
+/// whose rows run from the top of the image to the bottom, and a palette.
+fn colour_mapped_image(
+    width: u32, height: u32, indices: &[u8], palette: &Palette
+) -> Result<RawTgaImage, TgaError> {
+    let mut header = top_down_header(1, width, height, 8)?;
+    let pixel_count = header.width() * header.height();
+    if indices.len() != pixel_count {
+        return Err(TgaError::IncompleteImageData(indices.len(), pixel_count));
+    }
+    if palette.origin() as usize + palette.len() > u16::MAX as usize {
+        return Err(TgaError::PaletteTooLarge(palette.len()));
+    }
+    header.color_map_type = 1;
+    header.colour_map_origin = palette.origin().to_le_bytes();
+    header.colour_map_length = (palette.len() as u16).to_le_bytes();
+    header.colour_map_depth = palette.depth().bits() as u8;
+    header.image_descriptor |= palette.depth().attribute_bits();
+
+    let image = RawTgaImage::new(
+        header, Arc::default(), Arc::new(palette.to_colour_map()), Arc::new(indices.to_vec()), Arc::default()
+    );
+    image.validate_colour_map_indices()?;
+
+    Ok(image)
+}
+
+/// The function `encode_grayscale` serializes a buffer of eight bit grey
+/// levels into a grayscale TGA file (type 3, or type 11 when run length
+/// encoded), as controlled by the encoder options. The rows of the buffer run
+/// from the top of the image to the bottom, and the buffer must hold exactly
+/// `width * height` bytes. Dimensions that do not fit in the 16 bit fields of
+/// a TGA header are rejected with `InvalidDimensions`.
+///
+/// Grayscale files are write only: the library does not decode types 3 and
+/// 11, so parsing the file this function returns fails with `Not24BitRgb`.
+/// Setting the `pixel_depth` of the options converts the grey levels to true
+/// colour pixels, which gives a file the library can read back.
+pub fn encode_grayscale(
+    width: u32, height: u32, data: &[u8], options: &EncoderOptions
+) -> Result<Vec<u8>, TgaError> {
+    let image = grayscale_image(width, height, data)?;
+
+    Ok(image.encode_with_options(options))
+}
+
+/// The function `encode_grayscale_to_writer` writes a buffer of eight bit grey
+/// levels as a grayscale TGA file directly into a writer. It is the streaming
+/// counterpart of `encode_grayscale`, and its grayscale files are write only
+/// in the same way.
+pub fn encode_grayscale_to_writer<W: io::Write>(
+    width: u32, height: u32, data: &[u8], options: &EncoderOptions, writer: &mut W
+) -> Result<(), TgaError> {
+    let image = grayscale_image(width, height, data)?;
+
+    image.write_with_options(options, writer).map_err(|err| TgaError::Io(Box::new(err)))
+}
+
+/// The function `encode_colour_mapped` serializes a buffer of eight bit colour
+/// map indices and the palette they refer to into a colour mapped TGA file
+/// (type 1, or type 9 when run length encoded), as controlled by the encoder
+/// options. The rows of the buffer run from the top of the image to the
+/// bottom, the buffer must hold exactly `width * height` bytes, and every
+/// index must refer to a colour of the palette. Dimensions that do not fit in
+/// the 16 bit fields of a TGA header are rejected with `InvalidDimensions`.
+pub fn encode_colour_mapped(
+    width: u32, height: u32, indices: &[u8], palette: &Palette, options: &EncoderOptions
+) -> Result<Vec<u8>, TgaError> {
+    let image = colour_mapped_image(width, height, indices, palette)?;
+
+    Ok(image.encode_with_options(options))
+}
+
+/// The function `encode_colour_mapped_to_writer` writes a buffer of eight bit
+/// colour map indices and their palette as a colour mapped TGA file directly
+/// into a writer. It is the streaming counterpart of `encode_colour_mapped`.
+pub fn encode_colour_mapped_to_writer<W: io::Write>(
+    width: u32, height: u32, indices: &[u8], palette: &Palette, options: &EncoderOptions, writer: &mut W
+) -> Result<(), TgaError> {
+    let image = colour_mapped_image(width, height, indices, palette)?;
+
+    image.write_with_options(options, writer).map_err(|err| TgaError::Io(Box::new(err)))
+}
+
+impl TgaImage {
+    /// The function `encode_to_vec` serializes the image into the bytes of a
+    /// TGA file. Uncompressed images are written out uncompressed, and run
+    /// length encoded images are compressed again.
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        self.encode_to_vec_with_options(&EncoderOptions::default())
+    }
+
+    /// The function `encode_to_vec_with_options` serializes the image into
+    /// the bytes of a TGA file, as controlled by the encoder options.
+    pub fn encode_to_vec_with_options(&self, options: &EncoderOptions) -> Vec<u8> {
+        self.raw_tga_image().encode_with_options(options)
+    }
+
+    /// The function `encode_to_writer` writes the image as a TGA file
+    /// directly into a writer, without building the file in memory first.
+    /// The header, image identification, colour map, pixel data, and footer
+    /// are written in order, and run length encoded pixel data is compressed
+    /// one scanline at a time.
+    pub fn encode_to_writer<W: io::Write>(&self, writer: &mut W) -> Result<(), TgaError> {
+        self.encode_to_writer_with_options(writer, &EncoderOptions::default())
+    }
+
+    /// The function `encode_to_writer_with_options` writes the image as a TGA
+    /// file directly into a writer, as controlled by the encoder options.
+    pub fn encode_to_writer_with_options<W: io::Write>(
+        &self, writer: &mut W, options: &EncoderOptions
+    ) -> Result<(), TgaError> {
+        self.raw_tga_image().write_with_options(options, writer).map_err(|err| TgaError::Io(Box::new(err)))
+    }
+
+    /// The function `save` writes the image as a TGA file at the given path,
+    /// creating the file if it does not exist and truncating it if it does.
+    /// The output is buffered and flushed before the function returns.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), TgaError> {
+        let file = File::create(path).map_err(|err| TgaError::Io(Box::new(err)))?;
+        let mut writer = io::BufWriter::new(file);
+        self.encode_to_writer(&mut writer)?;
+
+        io::Write::flush(&mut writer).map_err(|err| TgaError::Io(Box::new(err)))
+    }
+
+    /// The function `encode_rle` serializes the image as a run length encoded
+    /// TGA file (type 10, or type 9 for a colour mapped image). The header,
+    /// image identification, colour map, and extended image identification are
+    /// carried over from the image unchanged, and the pixel data is packed into
+    /// a mix of raw and run length packets.
+    #[cfg(feature = "rle")]
+    pub fn encode_rle(&self) -> Vec<u8> {
+        let options = EncoderOptions { rle: Some(true), ..EncoderOptions::default() };
+        self.raw_tga_image().encode_with_options(&options)
+    }
+}
+
+impl UncompressedRgb {
+    /// The function `encode_to_vec` serializes the image into the bytes of a
+    /// TGA file.
+    #[inline]
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        self.inner.encode_to_vec()
+    }
+
+    /// The function `encode_to_writer` writes the image as a TGA file
+    /// directly into a writer, without building the file in memory first.
+    #[inline]
+    pub fn encode_to_writer<W: io::Write>(&self, writer: &mut W) -> Result<(), TgaError> {
+        self.inner.encode_to_writer(writer)
+    }
+}
+
+#[cfg(feature = "rle")]
+impl RunLengthEncodedRgb {
+    /// The function `encode_to_vec` serializes the image into the bytes of a
+    /// TGA file.
+    #[inline]
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        self.inner.encode_to_vec()
+    }
+
+    /// The function `encode_to_writer` writes the image as a TGA file
+    /// directly into a writer, without building the file in memory first.
+    #[inline]
+    pub fn encode_to_writer<W: io::Write>(&self, writer: &mut W) -> Result<(), TgaError> {
+        self.inner.encode_to_writer(writer)
+    }
+}
+
+impl UncompressedColorMapped {
+    /// The function `encode_to_vec` serializes the image into the bytes of a
+    /// TGA file.
+    #[inline]
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        self.inner.encode_to_vec()
+    }
+
+    /// The function `encode_to_writer` writes the image as a TGA file
+    /// directly into a writer, without building the file in memory first.
+    #[inline]
+    pub fn encode_to_writer<W: io::Write>(&self, writer: &mut W) -> Result<(), TgaError> {
+        self.inner.encode_to_writer(writer)
+    }
+}
+
+#[cfg(feature = "rle")]
+impl RunLengthEncodedColorMapped {
+    /// The function `encode_to_vec` serializes the image into the bytes of a
+    /// TGA file.
+    #[inline]
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        self.inner.encode_to_vec()
+    }
+
+    /// The function `encode_to_writer` writes the image as a TGA file
+    /// directly into a writer, without building the file in memory first.
+    #[inline]
+    pub fn encode_to_writer<W: io::Write>(&self, writer: &mut W) -> Result<(), TgaError> {
+        self.inner.encode_to_writer(writer)
+    }
+}
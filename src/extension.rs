@@ -0,0 +1,270 @@
+//! # Extension Areas
+//!
+//! The `extension` module writes the TGA 2.0 extension area, which records
+//! who made an image, when, and with what software, and the developer area,
+//! which holds tagged data of an application's own. Both follow the pixel
+//! data, and the footer at the end of the file records where they start.
+use std::convert::TryFrom;
+use std::io;
+use crate::{AttributesType, Timestamp, EXTENSION_AREA_LENGTH, TGA_FOOTER};
+
+/// The length of the author name, job name, and software identification
+/// fields, not counting their terminating null byte.
+const NAME_LENGTH: usize = 40;
+
+/// The number of lines of author comments, and the length of each line, not
+/// counting its terminating null byte.
+const COMMENT_LINES: usize = 4;
+const COMMENT_LINE_LENGTH: usize = 80;
+
+/// The largest width and height of a postage stamp, in pixels.
+const MAX_POSTAGE_STAMP_SIZE: usize = 64;
+
+/// An `ExtensionArea` holds the fields of a TGA 2.0 extension area that
+/// describe where an image came from. Text fields are truncated to the space
+/// the extension area has for them: 40 bytes for names, and four lines of 80
+/// bytes for the author comments.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ExtensionArea {
+    /// The name of the person who created the image.
+    pub author_name: String,
+    /// Comments about the image. Each line of the comments is stored on a
+    /// line of its own, and lines past the fourth are dropped.
+    pub author_comments: String,
+    /// The date and time the image was saved.
+    pub timestamp: Option<Timestamp>,
+    /// The name or identifier of the job the image belongs to.
+    pub job_name: String,
+    /// The name of the software that created the image.
+    pub software_id: String,
+    /// The version of the software that created the image: the version
+    /// number multiplied by 100, and a version letter such as `b'b'` for a
+    /// beta release. Version 4.17b is `(417, b'b')`.
+    pub software_version: Option<(u16, u8)>,
+    /// The background colour of the areas of the display the image does not
+    /// cover, given as blue, green, red, and alpha channels. When this is
+    /// `None`, the encoder keeps the key colour recorded in the file the
+    /// image was read from, if any.
+    pub key_colour: Option<[u8; 4]>,
+    /// The ratio of the width of a pixel to its height, as a numerator and
+    /// denominator, for images whose pixels are not square. When this is
+    /// `None`, the encoder keeps the ratio recorded in the file the image was
+    /// read from, if any.
+    pub pixel_aspect_ratio: Option<(u16, u16)>,
+    /// What the alpha channel of the image holds. When this is `None`, the
+    /// encoder records straight alpha for images with an alpha channel, or
+    /// premultiplied alpha when the file the image was read from recorded
+    /// it, and no alpha for the rest.
+    pub attributes_type: Option<AttributesType>,
+}
+
+/// A `DeveloperTag` is a block of application data stored in the developer
+/// area of a TGA 2.0 file, identified by a tag number. Tags 0 to 32767 are
+/// free for applications to use, and the rest are reserved by the TGA
+/// specification.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DeveloperTag {
+    /// The tag number identifying the data.
+    pub tag: u16,
+    /// The data itself.
+    pub data: Vec<u8>,
+}
+
+/// Copy a text field into the extension area, truncating it to fit and
+/// leaving room for the terminating null byte.
+fn write_text(field: &mut [u8], text: &str, length: usize) {
+    let bytes = text.as_bytes();
+    let length = usize::min(bytes.len(), length);
+    field[0..length].copy_from_slice(&bytes[0..length]);
+}
+
+/// Write a little endian 16 bit value.
+fn write_u16(buf: &mut [u8], position: usize, value: u16) {
+    buf[position..(position + 2)].copy_from_slice(&value.to_le_bytes());
+}
+
+impl ExtensionArea {
+    /// The function `set_author_comment_lines` sets the author comments from
+    /// the four lines of the extension area, such as those `author_comments`
+    /// reads from a file. Line breaks within a line are replaced by spaces,
+    /// and each line is truncated to 80 bytes when it is written.
+    pub fn set_author_comment_lines<S: AsRef<str>>(&mut self, lines: &[S; COMMENT_LINES]) {
+        let lines: Vec<String> = lines.iter().map(|line| line.as_ref().replace(['\r', '\n'], " ")).collect();
+        self.author_comments = lines.join("\n");
+    }
+
+    /// Serialize the extension area into its 495 byte on-disk representation.
+    /// The argument `alpha` records whether the image has an alpha channel,
+    /// and `postage_stamp_offset` the position of the postage stamp in the
+    /// file, or zero when there is none. The fields the options do not set,
+    /// such as the gamma, are left as zero, meaning unspecified.
+    fn to_bytes(&self, alpha: bool, postage_stamp_offset: [u8; 4]) -> Vec<u8> {
+        let mut buf = vec![0; EXTENSION_AREA_LENGTH];
+        write_u16(&mut buf, 0, EXTENSION_AREA_LENGTH as u16);
+        write_text(&mut buf[2..43], &self.author_name, NAME_LENGTH);
+        let comments = &mut buf[43..(43 + COMMENT_LINES * (COMMENT_LINE_LENGTH + 1))];
+        for (line, text) in comments.chunks_mut(COMMENT_LINE_LENGTH + 1).zip(self.author_comments.lines()) {
+            write_text(line, text, COMMENT_LINE_LENGTH);
+        }
+        if let Some(timestamp) = self.timestamp {
+            let fields = [
+                timestamp.month, timestamp.day, timestamp.year, timestamp.hour, timestamp.minute, timestamp.second
+            ];
+            for (i, &value) in fields.iter().enumerate() {
+                write_u16(&mut buf, 367 + 2 * i, value);
+            }
+        }
+        write_text(&mut buf[379..420], &self.job_name, NAME_LENGTH);
+        write_text(&mut buf[426..467], &self.software_id, NAME_LENGTH);
+        let (version_number, version_letter) = self.software_version.unwrap_or((0, b' '));
+        write_u16(&mut buf, 467, version_number);
+        buf[469] = version_letter;
+        if let Some(key_colour) = self.key_colour {
+            buf[470..474].copy_from_slice(&key_colour);
+        }
+        if let Some((numerator, denominator)) = self.pixel_aspect_ratio {
+            write_u16(&mut buf, 474, numerator);
+            write_u16(&mut buf, 476, denominator);
+        }
+        buf[486..490].copy_from_slice(&postage_stamp_offset);
+        let attributes_type = match self.attributes_type {
+            Some(attributes_type) => attributes_type,
+            None if alpha => AttributesType::Alpha,
+            None => AttributesType::NoAlpha,
+        };
+        buf[494] = attributes_type.to_byte();
+
+        buf
+    }
+}
+
+/// A `PostageStamp` builds the postage stamp of an image, a thumbnail of at
+/// most 64 by 64 pixels stored uncompressed in the same pixel format as the
+/// image, from the scanlines of the image as they are written. Each pixel of
+/// the postage stamp is the nearest pixel of the image, so no new colours
+/// are made up, and colour mapped images keep their colour map indices.
+#[derive(Debug)]
+pub(crate) struct PostageStamp {
+    height: usize,
+    stamp_width: usize,
+    stamp_height: usize,
+    bytes_per_pixel: usize,
+    rows_sampled: usize,
+    data: Vec<u8>,
+}
+
+impl PostageStamp {
+    /// Prepare the postage stamp of an image of the given dimensions, scaled
+    /// down to fit, keeping its aspect ratio. Images no larger than a postage
+    /// stamp are kept at their own size.
+    pub(crate) fn new(width: usize, height: usize, bytes_per_pixel: usize) -> PostageStamp {
+        let largest = usize::max(width, height);
+        let (stamp_width, stamp_height) = if largest <= MAX_POSTAGE_STAMP_SIZE {
+            (width, height)
+        } else {
+            (
+                usize::max(1, width * MAX_POSTAGE_STAMP_SIZE / largest),
+                usize::max(1, height * MAX_POSTAGE_STAMP_SIZE / largest),
+            )
+        };
+        let mut data = Vec::with_capacity(2 + stamp_width * stamp_height * bytes_per_pixel);
+        data.push(stamp_width as u8);
+        data.push(stamp_height as u8);
+
+        PostageStamp { height, stamp_width, stamp_height, bytes_per_pixel, rows_sampled: 0, data }
+    }
+
+    /// Sample the scanline at position `y` in the file, in the pixel format
+    /// it is written in. Scanlines must be pushed in the order they are
+    /// written.
+    pub(crate) fn push_scanline(&mut self, y: usize, scanline: &[u8]) {
+        let width = scanline.len() / self.bytes_per_pixel;
+        while (self.rows_sampled < self.stamp_height) && (self.rows_sampled * self.height / self.stamp_height == y) {
+            for column in 0..self.stamp_width {
+                let x = column * width / self.stamp_width;
+                let pixel = &scanline[(x * self.bytes_per_pixel)..((x + 1) * self.bytes_per_pixel)];
+                self.data.extend_from_slice(pixel);
+            }
+            self.rows_sampled += 1;
+        }
+    }
+
+    /// The postage stamp as stored in the file: its width and height, one
+    /// byte each, followed by its pixels.
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Convert a position in a file into a footer offset, which is 32 bits wide.
+fn file_offset(position: usize) -> io::Result<[u8; 4]> {
+    u32::try_from(position)
+        .map(u32::to_le_bytes)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "the file is too large for a TGA 2.0 footer"))
+}
+
+/// Write the data of the developer tags followed by the developer directory
+/// listing them, returning the number of bytes written. The argument
+/// `position` is the offset in the file the developer area starts at.
+fn write_developer_area<W: io::Write>(
+    writer: &mut W, position: usize, developer_tags: &[DeveloperTag]
+) -> io::Result<usize> {
+    let tag_count = u16::try_from(developer_tags.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "too many tags for a developer directory"))?;
+    let mut directory = tag_count.to_le_bytes().to_vec();
+    let mut data_position = position;
+    for developer_tag in developer_tags.iter() {
+        let size = u32::try_from(developer_tag.data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "developer tag data is too large"))?;
+        directory.extend_from_slice(&developer_tag.tag.to_le_bytes());
+        directory.extend_from_slice(&file_offset(data_position)?);
+        directory.extend_from_slice(&size.to_le_bytes());
+        writer.write_all(&developer_tag.data)?;
+        data_position += developer_tag.data.len();
+    }
+    writer.write_all(&directory)?;
+
+    Ok(data_position - position + directory.len())
+}
+
+/// Write everything that follows the pixel data of a TGA 2.0 file: the
+/// trailing bytes kept from the source file, the developer area, postage
+/// stamp, and extension area if there are any, and the footer pointing at
+/// them. The argument `position` is the number of bytes of the file written
+/// before the trailing bytes. The extension area records where the postage
+/// stamp is, so a postage stamp without an extension area gets an empty one.
+pub(crate) fn write_trailer<W: io::Write>(
+    writer: &mut W,
+    position: usize,
+    trailing: &[u8],
+    extension_area: Option<&ExtensionArea>,
+    developer_tags: &[DeveloperTag],
+    postage_stamp: Option<&PostageStamp>,
+    alpha: bool,
+) -> io::Result<()> {
+    writer.write_all(trailing)?;
+    let mut position = position + trailing.len();
+    let mut footer = TGA_FOOTER;
+    if !developer_tags.is_empty() {
+        let data_length: usize = developer_tags.iter().map(|developer_tag| developer_tag.data.len()).sum();
+        footer[4..8].copy_from_slice(&file_offset(position + data_length)?);
+        position += write_developer_area(writer, position, developer_tags)?;
+    }
+    let mut postage_stamp_offset = [0; 4];
+    if let Some(postage_stamp) = postage_stamp {
+        postage_stamp_offset = file_offset(position)?;
+        writer.write_all(postage_stamp.as_bytes())?;
+        position += postage_stamp.as_bytes().len();
+    }
+    let empty_extension_area = ExtensionArea::default();
+    let extension_area = match extension_area {
+        None if postage_stamp.is_some() => Some(&empty_extension_area),
+        extension_area => extension_area,
+    };
+    if let Some(extension_area) = extension_area {
+        footer[0..4].copy_from_slice(&file_offset(position)?);
+        writer.write_all(&extension_area.to_bytes(alpha, postage_stamp_offset))?;
+    }
+
+    writer.write_all(&footer)
+}
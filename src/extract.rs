@@ -0,0 +1,101 @@
+//! # Extracting Regions
+//!
+//! The `extract` module cuts rectangular regions out of uncompressed TGA
+//! files on disk without decoding them. Only the scanlines the region covers
+//! are read, and only the bytes of the region within them, so cutting a small
+//! piece out of a giant scan costs as much as the piece.
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use crate::{Interleaving, TgaError, TgaHeader, TGA_HEADER_LENGTH};
+
+
+/// A `Region` is a rectangular region of an image. Coordinates follow the
+/// order the pixels are stored in, the same order `pixels` yields them, so
+/// `(0, 0)` is the first stored pixel.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Region {
+    /// The column of the first pixel of the region.
+    pub x: u32,
+    /// The row of the first pixel of the region.
+    pub y: u32,
+    /// The width of the region, in pixels.
+    pub width: u32,
+    /// The height of the region, in pixels.
+    pub height: u32,
+}
+
+/// The function `extract_region_to_file` copies a rectangular region of an
+/// uncompressed TGA file into a new TGA file, seeking to each scanline of the
+/// region and reading only its pixels. The new file keeps the image type,
+/// pixel depth, orientation, image identification, and colour map of the
+/// source, and has no extension area or developer area. Interleaved
+/// scanlines are put in order, and the new file is not interleaved. Run
+/// length encoded sources fail with `Not24BitRgb`, and regions that do not lie
+/// within the image with `RegionOutOfBounds`. Errors are labelled with the
+/// path of the file they came from.
+pub fn extract_region_to_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    src_path: P, region: Region, dst_path: Q) -> Result<(), TgaError>
+{
+    let src_path = src_path.as_ref();
+    let dst_path = dst_path.as_ref();
+    let io_error = |err| TgaError::Io(Box::new(err));
+    let read_region = || -> Result<(TgaHeader, Vec<u8>, Vec<u8>), TgaError> {
+        let mut file = File::open(src_path).map_err(io_error)?;
+        let mut buf = Vec::with_capacity(TGA_HEADER_LENGTH);
+        (&mut file).take(TGA_HEADER_LENGTH as u64).read_to_end(&mut buf).map_err(io_error)?;
+        let header = TgaHeader::parse_from_buffer(&buf)?;
+        if (header.data_type_code != 1) && (header.data_type_code != 2) {
+            return Err(TgaError::Not24BitRgb(header.data_type_code as usize));
+        }
+        let fits = |start: u32, length: u32, limit: usize| {
+            start.checked_add(length).is_some_and(|end| end as usize <= limit)
+        };
+        if !fits(region.x, region.width, header.width()) || !fits(region.y, region.height, header.height()) {
+            return Err(TgaError::RegionOutOfBounds(region.x, region.y, region.width, region.height));
+        }
+
+        let data_offset = TGA_HEADER_LENGTH + header.id_length() + header.colour_map_size();
+        let bytes_per_pixel = header.bytes_per_pixel();
+        let row_length = header.width() * bytes_per_pixel;
+        let data_length = header.height() * row_length;
+        let found = (file.metadata().map_err(io_error)?.len() as usize).saturating_sub(data_offset);
+        if found < data_length {
+            return Err(TgaError::IncompleteImageData(found, data_length));
+        }
+        let mut prelude = vec![0; data_offset - TGA_HEADER_LENGTH];
+        file.read_exact(&mut prelude).map_err(io_error)?;
+
+        let height = header.height();
+        let interleaving = Interleaving::from_image_descriptor(header.image_descriptor);
+        let mut stored_rows = vec![0; height];
+        for stored in 0..height {
+            stored_rows[interleaving.row_index(stored, height)] = stored;
+        }
+        let start = region.x as usize * bytes_per_pixel;
+        let length = region.width as usize * bytes_per_pixel;
+        let mut image_data = vec![0; region.height as usize * length];
+        let region_rows = stored_rows[(region.y as usize)..].iter();
+        for (row, &stored) in image_data.chunks_exact_mut(length.max(1)).zip(region_rows) {
+            let position = data_offset + stored * row_length + start;
+            file.seek(SeekFrom::Start(position as u64)).map_err(io_error)?;
+            file.read_exact(row).map_err(io_error)?;
+        }
+
+        Ok((header, prelude, image_data))
+    };
+    let (mut header, prelude, image_data) = read_region().map_err(|err| err.named(src_path.display().to_string()))?;
+
+    header.width = (region.width as u16).to_le_bytes();
+    header.height = (region.height as u16).to_le_bytes();
+    header.image_descriptor &= !0xC0;
+    let write_region = || -> Result<(), TgaError> {
+        let mut file = File::create(dst_path).map_err(io_error)?;
+        file.write_all(&header.to_bytes()).map_err(io_error)?;
+        file.write_all(&prelude).map_err(io_error)?;
+
+        file.write_all(&image_data).map_err(io_error)
+    };
+
+    write_region().map_err(|err| err.named(dst_path.display().to_string()))
+}
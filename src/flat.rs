@@ -0,0 +1,111 @@
+//! # Flat Samples
+//!
+//! The `flat` module describes the pixel data of an image as a flat buffer of
+//! samples together with its layout, so interop code can hand the buffer to
+//! a GPU API or another image library without guessing at the channel order,
+//! the row stride, or which way up the rows are. TGA files store colours in
+//! blue, green, red order, which most other libraries do not.
+use crate::{TgaError, TgaImage};
+
+
+/// A `SampleFormat` says how the bytes of each pixel are laid out.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SampleFormat {
+    /// Fifteen bit colours stored in a little endian 16 bit word, five bits
+    /// each of blue, green, and red, starting from the lowest bit. The top
+    /// bit is unused.
+    Bgr555,
+    /// Sixteen bit colours laid out as `Bgr555`, with the top bit holding an
+    /// alpha bit.
+    Bgra5551,
+    /// Three bytes per pixel, in blue, green, and red order.
+    Bgr8,
+    /// Four bytes per pixel, in blue, green, red, and alpha order.
+    Bgra8,
+    /// One byte per pixel, holding an index into the colour map.
+    Index8,
+    /// A little endian 16 bit index into the colour map per pixel.
+    Index16,
+}
+
+impl SampleFormat {
+    /// The function `channel_count` returns the number of channels each pixel
+    /// holds. Colour map indices count as a single channel.
+    pub fn channel_count(self) -> usize {
+        match self {
+            SampleFormat::Bgr555 | SampleFormat::Bgr8 => 3,
+            SampleFormat::Bgra5551 | SampleFormat::Bgra8 => 4,
+            SampleFormat::Index8 | SampleFormat::Index16 => 1,
+        }
+    }
+
+    /// The function `bytes_per_pixel` returns the number of bytes each pixel
+    /// occupies.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            SampleFormat::Index8 => 1,
+            SampleFormat::Bgr555 | SampleFormat::Bgra5551 | SampleFormat::Index16 => 2,
+            SampleFormat::Bgr8 => 3,
+            SampleFormat::Bgra8 => 4,
+        }
+    }
+}
+
+/// A `FlatSamples` is the pixel data of an image borrowed as a flat buffer,
+/// together with its layout. Row `y` starts `y * row_stride` bytes into
+/// `samples` and holds `width` pixels of `format`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct FlatSamples<'a> {
+    /// The bytes of the pixels. The buffer ends with the last pixel of the
+    /// last row, so it may be shorter than `height * row_stride`.
+    pub samples: &'a [u8],
+    /// The width of the image, in pixels.
+    pub width: u32,
+    /// The height of the image, in pixels.
+    pub height: u32,
+    /// The distance from the start of one row to the start of the next, in
+    /// bytes. It is larger than a row when the image is a view into a larger
+    /// image.
+    pub row_stride: usize,
+    /// The layout of each pixel.
+    pub format: SampleFormat,
+    /// Whether the first row is the top row of the image. Otherwise it is the
+    /// bottom row, the usual order for TGA files.
+    pub top_to_bottom: bool,
+    /// Whether the pixels of each row run from right to left.
+    pub right_to_left: bool,
+}
+
+impl TgaImage {
+    /// The function `as_flat_samples` borrows the pixel data of the image as
+    /// a flat buffer together with its layout, without copying it, even for
+    /// a view. Images whose pixels have no `SampleFormat`, such as an image
+    /// without pixel data, fail with `UnsupportedBitDepth`.
+    pub fn as_flat_samples(&self) -> Result<FlatSamples<'_>, TgaError> {
+        let image = self.raw_tga_image();
+        let header = image.header;
+        let format = match (header.is_colour_mapped(), header.bits_per_pixel()) {
+            (false, 15) => SampleFormat::Bgr555,
+            (false, 16) => SampleFormat::Bgra5551,
+            (false, 24) => SampleFormat::Bgr8,
+            (false, 32) => SampleFormat::Bgra8,
+            (true, 8) => SampleFormat::Index8,
+            (true, 16) => SampleFormat::Index16,
+            (_, bits) => return Err(TgaError::UnsupportedBitDepth(bits)),
+        };
+        let length = match image.height() {
+            0 => 0,
+            height => (height - 1) * image.data_stride + image.row_length(),
+        };
+
+        Ok(FlatSamples {
+            samples: &image.image_data[image.data_offset..(image.data_offset + length)],
+            width: self.width(),
+            height: self.height(),
+            row_stride: image.data_stride,
+            format,
+            top_to_bottom: header.image_descriptor & 0x20 != 0,
+            right_to_left: header.image_descriptor & 0x10 != 0,
+        })
+    }
+}
@@ -0,0 +1,103 @@
+//! # Gamma
+//!
+//! The `gamma` module reads the gamma recorded in the TGA 2.0 extension area
+//! of a file, and converts the pixels of images carrying one into linear
+//! light, so renderers can bring older assets into a linear workflow without
+//! guessing how they were encoded.
+#[cfg(feature = "transforms")]
+use std::sync::Arc;
+#[cfg(feature = "transforms")]
+use crate::transforms::{adjustment_table, srgb_to_linear, Brightness, Contrast, Gamma};
+use crate::{RawTgaImage, TgaImage};
+
+
+/// The position of the gamma numerator and denominator within the extension
+/// area.
+const GAMMA_OFFSET: usize = 478;
+
+impl RawTgaImage {
+    /// The gamma recorded in the extension area of the image, as a numerator
+    /// and denominator.
+    fn gamma(&self) -> Option<(u16, u16)> {
+        self.extension_area_ratio(GAMMA_OFFSET)
+    }
+
+    /// Record a gamma in the extension area of the image, if it has one.
+    #[cfg(feature = "transforms")]
+    fn set_gamma(&mut self, numerator: u16, denominator: u16) {
+        if let Some(offset) = self.extension_area_position() {
+            let position = offset + GAMMA_OFFSET;
+            let extended_image_identification = Arc::make_mut(&mut self.extended_image_identification);
+            extended_image_identification[position..(position + 2)].copy_from_slice(&numerator.to_le_bytes());
+            extended_image_identification[(position + 2)..(position + 4)].copy_from_slice(&denominator.to_le_bytes());
+        }
+    }
+}
+
+impl TgaImage {
+    /// The function `gamma` returns the gamma the extension area of the image
+    /// records, as a numerator and denominator, so a gamma of 2.2 is
+    /// `(22, 10)`. It returns `None` when the file the image was read from
+    /// has no extension area, or leaves the gamma unspecified with a zero
+    /// denominator. A zero numerator, a gamma of zero, is treated the same.
+    #[cfg(feature = "metadata")]
+    pub fn gamma(&self) -> Option<(u16, u16)> {
+        self.raw_tga_image().gamma()
+    }
+
+    /// The function `apply_gamma` converts the colours of the image into
+    /// linear light using the gamma its extension area records, raising every
+    /// colour channel to the power of the gamma. The alpha channel is left
+    /// unchanged. The extension area then records a gamma of 1.0, so applying
+    /// it again does nothing. The function returns whether the image recorded
+    /// a gamma to apply.
+    #[cfg(feature = "transforms")]
+    pub fn apply_gamma(&mut self) -> bool {
+        let (numerator, denominator) = match self.raw_tga_image().gamma() {
+            Some(gamma) => gamma,
+            None => return false,
+        };
+        let gamma = Gamma(denominator as f32 / numerator as f32);
+        let table = adjustment_table(Brightness(0.0), Contrast(1.0), gamma, false);
+        let image = self.raw_tga_image_mut();
+        image.map_colours(|colour| {
+            [table[colour[0] as usize], table[colour[1] as usize], table[colour[2] as usize], colour[3]]
+        });
+        image.set_gamma(1, 1);
+
+        true
+    }
+
+    /// The function `to_linear_f32` converts the pixels of the image into
+    /// floats in linear light, four per pixel in red, green, blue, and alpha
+    /// order, in the order the pixels are stored, the same order `pixels`
+    /// yields them. The colour channels are decoded with the gamma the
+    /// extension area of the image records, or taken to be sRGB encoded when
+    /// it records none. The alpha channel is only scaled to the range
+    /// `[0, 1]`.
+    #[cfg(feature = "transforms")]
+    pub fn to_linear_f32(&self) -> Vec<f32> {
+        let image = self.raw_tga_image();
+        let decoder = image.pixel_decoder();
+        let gamma = image.gamma().map(|(numerator, denominator)| numerator as f32 / denominator as f32);
+        let mut linear = [0.0; 256];
+        for (value, entry) in linear.iter_mut().enumerate() {
+            let channel = value as f32 / 255.0;
+            *entry = match gamma {
+                Some(gamma) => channel.powf(gamma),
+                None => srgb_to_linear(channel),
+            };
+        }
+
+        let mut result = Vec::with_capacity(4 * image.width() * image.height());
+        for pixel in image.rows().flat_map(|row| row.chunks(decoder.bytes_per_pixel())) {
+            let colour = decoder.decode(pixel);
+            result.extend_from_slice(&[
+                linear[colour[2] as usize], linear[colour[1] as usize], linear[colour[0] as usize],
+                colour[3] as f32 / 255.0,
+            ]);
+        }
+
+        result
+    }
+}
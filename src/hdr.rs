@@ -0,0 +1,40 @@
+//! # Half Precision Export
+//!
+//! The `hdr` module converts decoded images into half precision floating
+//! point pixels in linear light, the layout of the `RGBA16F` textures that
+//! high dynamic range renderers sample from, so an image can be uploaded
+//! without converting it to wider floats on the way.
+use half::f16;
+use crate::transforms::srgb_to_linear;
+use crate::TgaImage;
+
+
+impl TgaImage {
+    /// The function `to_f16_vec` converts the pixels of the image into half
+    /// precision floats, four per pixel in red, green, blue, and alpha order,
+    /// in the order the pixels are stored, the same order `pixels` yields
+    /// them. The colour channels are taken to be sRGB encoded and are
+    /// converted to linear light. The alpha channel is already linear, so it
+    /// is only scaled to the range `[0, 1]`.
+    pub fn to_f16_vec(&self) -> Vec<f16> {
+        let image = self.raw_tga_image();
+        let decoder = image.pixel_decoder();
+        let mut linear = [f16::ZERO; 256];
+        let mut alpha = [f16::ZERO; 256];
+        for value in 0..256 {
+            linear[value] = f16::from_f32(srgb_to_linear(value as f32 / 255.0));
+            alpha[value] = f16::from_f32(value as f32 / 255.0);
+        }
+
+        let mut result = Vec::with_capacity(4 * image.width() * image.height());
+        for pixel in image.rows().flat_map(|row| row.chunks(decoder.bytes_per_pixel())) {
+            let colour = decoder.decode(pixel);
+            result.extend_from_slice(&[
+                linear[colour[2] as usize], linear[colour[1] as usize], linear[colour[0] as usize],
+                alpha[colour[3] as usize],
+            ]);
+        }
+
+        result
+    }
+}
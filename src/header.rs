@@ -0,0 +1,147 @@
+//! # Header Construction
+//!
+//! The `header` module builds TGA headers field by field. The fields of a
+//! header depend on each other: the image type decides whether a colour map
+//! is allowed and which pixel depths make sense, and the pixel depth bounds
+//! the number of attribute bits. A `TgaHeaderBuilder` checks all of them
+//! before handing out a header, so a header it builds is always consistent.
+use crate::{TgaError, TgaHeader};
+
+
+/// A `TgaHeaderBuilder` collects the fields of a TGA header and checks that
+/// they agree with each other when the header is built. Fields left unset are
+/// zero, except for the image type, which is given up front.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TgaHeaderBuilder {
+    header: TgaHeader,
+}
+
+impl TgaHeaderBuilder {
+    /// Start building the header of an image of the given type: 0 for no
+    /// image data, 1 for colour mapped, 2 for true colour, 3 for grayscale,
+    /// and 9, 10, and 11 for their run length encoded counterparts.
+    pub fn new(data_type_code: u8) -> TgaHeaderBuilder {
+        let header = TgaHeader {
+            id_length: 0,
+            color_map_type: 0,
+            data_type_code,
+            colour_map_origin: [0, 0],
+            colour_map_length: [0, 0],
+            colour_map_depth: 0,
+            x_origin: [0, 0],
+            y_origin: [0, 0],
+            width: [0, 0],
+            height: [0, 0],
+            bits_per_pixel: 0,
+            image_descriptor: 0,
+        };
+
+        TgaHeaderBuilder { header }
+    }
+
+    /// Set the length of the image identification that follows the header.
+    pub fn id_length(mut self, id_length: u8) -> TgaHeaderBuilder {
+        self.header.id_length = id_length;
+        self
+    }
+
+    /// Describe the colour map: the index of its first entry, the number of
+    /// entries, and the number of bits in each entry. Only colour mapped
+    /// images have a colour map.
+    pub fn colour_map(mut self, origin: u16, length: u16, depth: u8) -> TgaHeaderBuilder {
+        self.header.color_map_type = 1;
+        self.header.colour_map_origin = origin.to_le_bytes();
+        self.header.colour_map_length = length.to_le_bytes();
+        self.header.colour_map_depth = depth;
+        self
+    }
+
+    /// Set the position of the lower left corner of the image on a display.
+    pub fn origin(mut self, x_origin: u16, y_origin: u16) -> TgaHeaderBuilder {
+        self.header.set_x_origin(x_origin);
+        self.header.set_y_origin(y_origin);
+        self
+    }
+
+    /// Set the width of the image, in pixels.
+    pub fn width(mut self, width: u16) -> TgaHeaderBuilder {
+        self.header.width = width.to_le_bytes();
+        self
+    }
+
+    /// Set the height of the image, in pixels.
+    pub fn height(mut self, height: u16) -> TgaHeaderBuilder {
+        self.header.height = height.to_le_bytes();
+        self
+    }
+
+    /// Set the number of bits per pixel. For colour mapped images, this is
+    /// the size of a colour map index.
+    pub fn bits_per_pixel(mut self, bits_per_pixel: u8) -> TgaHeaderBuilder {
+        self.header.bits_per_pixel = bits_per_pixel;
+        self
+    }
+
+    /// Set the image descriptor byte, which holds the number of attribute
+    /// bits per pixel in bits 0 to 3, and the orientation in bits 4 and 5.
+    pub fn image_descriptor(mut self, image_descriptor: u8) -> TgaHeaderBuilder {
+        self.header.image_descriptor = image_descriptor;
+        self
+    }
+
+    /// Build the header, checking that its fields are consistent. A colour
+    /// map is required for colour mapped images and rejected for every other
+    /// type, with `InconsistentHeader`. Pixel depths the image type does not
+    /// allow, and colour map entries of unsupported depths, are rejected with
+    /// `UnsupportedBitDepth`. The image descriptor may not claim more
+    /// attribute bits than the pixels have room for, and its reserved bits
+    /// must be clear.
+    pub fn build(&self) -> Result<TgaHeader, TgaError> {
+        let header = self.header;
+        let colour_mapped = match header.data_type_code {
+            1 | 9 => true,
+            0 | 2 | 3 | 10 | 11 => false,
+            _ => return Err(TgaError::InconsistentHeader("unknown image type")),
+        };
+        if colour_mapped {
+            if (header.color_map_type != 1) || (header.colour_map_length() == 0) {
+                return Err(TgaError::InconsistentHeader("colour mapped images need a colour map"));
+            }
+            match header.colour_map_depth {
+                15 | 16 | 24 | 32 => {}
+                bits => return Err(TgaError::UnsupportedBitDepth(bits as usize)),
+            }
+        } else if header.color_map_type != 0 {
+            return Err(TgaError::InconsistentHeader("only colour mapped images have a colour map"));
+        }
+
+        let attribute_bits = match (header.data_type_code & !0x08, header.bits_per_pixel) {
+            (0, 0) => 0,
+            (1, 8) | (1, 16) => 0,
+            (2, 15) | (2, 24) => 0,
+            (2, 16) => 1,
+            (2, 32) => 8,
+            (3, 8) => 0,
+            (3, 16) => 8,
+            (_, bits) => return Err(TgaError::UnsupportedBitDepth(bits as usize)),
+        };
+        let attribute_bits = if colour_mapped {
+            // The alpha bits of a colour mapped image live in its colour map.
+            match header.colour_map_depth {
+                16 => 1,
+                32 => 8,
+                _ => 0,
+            }
+        } else {
+            attribute_bits
+        };
+        if header.image_descriptor & 0x0F > attribute_bits {
+            return Err(TgaError::InconsistentHeader("more attribute bits than the pixels hold"));
+        }
+        if header.image_descriptor & 0xC0 != 0 {
+            return Err(TgaError::InconsistentHeader("reserved image descriptor bits are set"));
+        }
+
+        Ok(header)
+    }
+}
@@ -0,0 +1,94 @@
+//! # Interleaving
+//!
+//! The `interleave` module puts the scanlines of interleaved files back in
+//! order. Some early capture hardware wrote the even scanlines of a frame
+//! before the odd ones, or every fourth scanline in four passes, and recorded
+//! this in bits 6 and 7 of the image descriptor. Interleaving reorders whole
+//! scanlines, so it is undone after any run length encoded pixel data has
+//! been decoded, whether or not packets cross scanlines.
+use std::sync::Arc;
+use crate::{RawTgaImage, TgaImage};
+
+
+/// The `Interleaving` of a file records the order its scanlines are stored in.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Interleaving {
+    /// Scanlines are stored one after the other.
+    #[default]
+    NonInterleaved,
+    /// The even scanlines are stored first, followed by the odd ones.
+    TwoWay,
+    /// Every fourth scanline is stored in each of four passes, starting from
+    /// the first, second, third, and fourth scanlines in turn.
+    FourWay,
+}
+
+impl Interleaving {
+    /// Read the interleaving recorded in bits 6 and 7 of an image descriptor.
+    /// The fourth value of the bits is reserved, and is read as not
+    /// interleaved.
+    pub(crate) fn from_image_descriptor(image_descriptor: u8) -> Interleaving {
+        match image_descriptor & 0xC0 {
+            0x40 => Interleaving::TwoWay,
+            0x80 => Interleaving::FourWay,
+            _ => Interleaving::NonInterleaved,
+        }
+    }
+
+    /// The number of passes the scanlines are stored in.
+    fn passes(self) -> usize {
+        match self {
+            Interleaving::NonInterleaved => 1,
+            Interleaving::TwoWay => 2,
+            Interleaving::FourWay => 4,
+        }
+    }
+
+    /// The position of the scanline stored at `stored` once the scanlines of
+    /// an image `height` scanlines tall are put in order.
+    pub(crate) fn row_index(self, stored: usize, height: usize) -> usize {
+        let passes = self.passes();
+        let mut first = 0;
+        for pass in 0..passes {
+            let rows_in_pass = height.saturating_sub(pass).div_ceil(passes);
+            if stored < first + rows_in_pass {
+                return pass + (stored - first) * passes;
+            }
+            first += rows_in_pass;
+        }
+
+        stored
+    }
+}
+
+impl RawTgaImage {
+    /// Put the scanlines of a freshly parsed image in order, and clear the
+    /// interleaving bits of its header, remembering what they recorded.
+    pub(crate) fn deinterleave(&mut self) {
+        let interleaving = Interleaving::from_image_descriptor(self.header.image_descriptor);
+        if interleaving == Interleaving::NonInterleaved {
+            return;
+        }
+
+        let row_length = self.row_length();
+        let height = self.height();
+        let mut image_data = vec![0; height * row_length];
+        for (stored, row) in self.rows().enumerate() {
+            let y = interleaving.row_index(stored, height);
+            image_data[(y * row_length)..((y + 1) * row_length)].copy_from_slice(row);
+        }
+        self.image_data = Arc::new(image_data);
+        self.header.image_descriptor &= !0xC0;
+        self.interleaving = interleaving;
+    }
+}
+
+impl TgaImage {
+    /// The function `interleaving` returns the order the scanlines were
+    /// stored in by the file the image was read from. The scanlines are put
+    /// in order when the file is parsed, so the image itself, its header, and
+    /// the files it is encoded into are never interleaved.
+    pub fn interleaving(&self) -> Interleaving {
+        self.raw_tga_image().interleaving
+    }
+}
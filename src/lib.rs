@@ -4,17 +4,198 @@
 //! TARGA format images. The TGA format is capable of representing multiple 
 //! types of bitmapped images including  black and white, indexed colour, RGB
 //! colour, and various compressed representations. The minimal implementation
-//! is a 24 bit unmapped RGB colour image. This library presently implements 24
-//! bit unmapped uncompressed RBG images only.
+//! is a 24 bit unmapped RGB colour image. This library presently implements
+//! unmapped RGB images of 15, 16, 24, and 32 bits per pixel, and colour mapped
+//! images, both uncompressed and run length encoded. Files without image data
+//! (type 0) are read as images without pixels.
+//!
+//! Decoding is deterministic. The same input bytes always decode to the same
+//! pixel data, and a malformed file always fails with the same error, namely
+//! the first defect found reading the file from front to back. This holds on
+//! every thread, so decoder output is safe to use as a cache key.
+#[cfg(feature = "decode24")]
+use std::convert::TryFrom;
 use std::error;
 use std::fmt;
+#[cfg(feature = "decode24")]
+use std::fs::File;
 use std::io;
-use std::rc::Rc;
-
+#[cfg(feature = "decode24")]
+use std::ops::{Deref, Index, IndexMut, Range};
+#[cfg(feature = "decode24")]
+use std::path::Path;
+#[cfg(feature = "decode24")]
+use std::cell::OnceCell;
+#[cfg(feature = "decode24")]
+use std::sync::Arc;
+#[cfg(feature = "decode24")]
+use std::slice;
+#[cfg(feature = "decode24")]
+use std::slice::{ChunksExact, ChunksExactMut, SliceIndex};
+#[cfg(feature = "decode24")]
+use std::vec;
+
+#[cfg(any(feature = "metadata", feature = "encode", feature = "transforms"))]
+mod alpha;
+#[cfg(feature = "transforms")]
+mod atlas;
+#[cfg(feature = "metadata")]
+mod anonymize;
+#[cfg(feature = "metadata")]
+pub mod batch;
+#[cfg(feature = "in-place")]
+mod borrowed;
+#[cfg(feature = "decode24")]
+mod capabilities;
+#[cfg(any(feature = "metadata", feature = "encode"))]
+mod colour_maps;
+#[cfg(feature = "encode")]
+mod construct;
+#[cfg(any(feature = "metadata", feature = "encode"))]
+mod digest;
+#[cfg(feature = "macros")]
+mod embedded;
+#[cfg(feature = "encode")]
+mod encoder;
+#[cfg(feature = "encode")]
+mod extension;
+#[cfg(feature = "in-place")]
+mod extract;
+#[cfg(feature = "transforms")]
+mod flat;
+#[cfg(any(feature = "metadata", feature = "transforms"))]
+mod gamma;
+mod header;
+#[cfg(feature = "decode24")]
+mod interleave;
+#[cfg(feature = "half")]
+mod hdr;
+pub mod lowlevel;
+#[cfg(feature = "encode")]
+mod palette;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "in-place")]
+mod patch;
+#[cfg(feature = "transforms")]
+mod planar;
+#[cfg(feature = "transforms")]
+mod pipeline;
+#[cfg(feature = "transforms")]
+mod present;
+#[cfg(feature = "encode")]
+mod provenance;
+#[cfg(all(feature = "encode", feature = "transforms"))]
+mod pyramid;
+#[cfg(feature = "encode")]
+mod quantize;
+#[cfg(feature = "metadata")]
+mod metadata;
+#[cfg(feature = "rle")]
+mod rle;
+#[cfg(feature = "in-place")]
+mod rows;
+#[cfg(feature = "transforms")]
+mod spans;
+#[cfg(feature = "transforms")]
+mod stats;
+#[cfg(feature = "encode")]
+mod stream;
+#[cfg(any(feature = "metadata", feature = "encode"))]
+mod timestamp;
+#[cfg(feature = "rle")]
+mod transcode;
+#[cfg(any(feature = "metadata", feature = "encode", feature = "transforms"))]
+mod trailer;
+#[cfg(feature = "transforms")]
+mod transforms;
+#[cfg(feature = "encode")]
+mod upgrade;
+#[cfg(feature = "transforms")]
+mod view;
+#[cfg(feature = "transforms")]
+mod windows;
+
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
+#[cfg(any(feature = "metadata", feature = "encode", feature = "transforms"))]
+pub use alpha::AttributesType;
+#[cfg(feature = "transforms")]
+pub use atlas::PadMode;
+#[cfg(feature = "metadata")]
+pub use anonymize::anonymize;
+#[cfg(feature = "in-place")]
+pub use borrowed::TgaImageRef;
+#[cfg(feature = "decode24")]
+pub use capabilities::{capabilities, Capabilities};
+#[cfg(feature = "metadata")]
+pub use colour_maps::{ColourMapCache, PaletteEntries};
+#[cfg(feature = "metadata")]
+pub use digest::Digest;
+#[cfg(feature = "transforms")]
+pub use flat::{FlatSamples, SampleFormat};
+#[cfg(feature = "macros")]
+pub use embedded::EmbeddedTga;
+#[cfg(feature = "encode")]
+pub use construct::TgaImageBuilder;
+#[cfg(feature = "encode")]
+pub use encoder::{
+    encode_colour_mapped, encode_colour_mapped_to_writer, encode_grayscale, encode_grayscale_to_writer,
+    EncoderOptions, Orientation, PixelDepth, TgaFormat,
+};
+#[cfg(feature = "encode")]
+pub use extension::{DeveloperTag, ExtensionArea};
+#[cfg(feature = "encode")]
+pub use palette::Palette;
+#[cfg(feature = "transforms")]
+pub use pipeline::Pipeline;
+#[cfg(feature = "encode")]
+pub use provenance::{ProvenanceInfo, PROVENANCE_TAG};
+#[cfg(all(feature = "encode", feature = "transforms"))]
+pub use pyramid::{Pyramid, PyramidLevel, PyramidTile};
+#[cfg(feature = "encode")]
+pub use quantize::{QuantizationReport, Quantizer};
+#[cfg(feature = "rle")]
+pub use rle::{RunLengthEncodedColorMapped, RunLengthEncodedRgb};
+#[cfg(feature = "in-place")]
+pub use extract::{extract_region_to_file, Region};
+pub use header::TgaHeaderBuilder;
+#[cfg(feature = "decode24")]
+pub use interleave::Interleaving;
+#[cfg(feature = "in-place")]
+pub use patch::DirtyRect;
+#[cfg(feature = "transforms")]
+pub use planar::{Channel, ChannelIter, Planes};
+#[cfg(feature = "in-place")]
+pub use rows::{decode_into, decode_rows_with};
+#[cfg(feature = "transforms")]
+pub use spans::{Span, Spans};
+#[cfg(feature = "transforms")]
+pub use stats::{ChannelStats, ImageStats};
+#[cfg(any(feature = "metadata", feature = "encode"))]
+pub use timestamp::Timestamp;
+#[cfg(feature = "encode")]
+pub use stream::ScanlineWriter;
+#[cfg(feature = "rle")]
+pub use transcode::transcode;
+#[cfg(feature = "transforms")]
+pub use transforms::{Brightness, ColourTransform, Contrast, Gamma};
+#[cfg(feature = "encode")]
+pub use upgrade::upgrade_to_v2;
+#[cfg(feature = "transforms")]
+pub use view::Tiles;
+#[cfg(feature = "transforms")]
+pub use windows::{BorderPolicy, Window, Windows};
 
 /// The length of a TGA Header is always 18 bytes.
 pub const TGA_HEADER_LENGTH: usize = 18;
 
+/// The length of a TGA 2.0 extension area.
+#[cfg(any(feature = "metadata", feature = "encode", feature = "transforms"))]
+const EXTENSION_AREA_LENGTH: usize = 495;
+
+#[cfg(feature = "decode24")]
 const TGA_FOOTER: [u8; 26] = [
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
     0x54, 0x52, 0x55, 0x45, 0x56, 0x49, 0x53, 0x49,
@@ -22,6 +203,23 @@ const TGA_FOOTER: [u8; 26] = [
     0x2E, 0x00
 ];
 
+/// The image identification written at the front of the image identification
+/// field of files whose pixels are stored in red, green, blue order. The
+/// TGA specification stores pixels in blue, green, red order, so this marks
+/// files that only a consumer expecting the nonstandard order can read.
+pub const RGB_CHANNEL_ORDER_TAG: &[u8] = b"tga:channel-order=rgb;";
+
+/// The order of the colour channels of each pixel as stored in a file.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum ChannelOrder {
+    /// Blue, green, then red, as the TGA specification requires.
+    #[default]
+    Bgr,
+    /// Red, green, then blue. Files written this way are not valid TGA
+    /// files, and exist only for consumers that read TGA files incorrectly.
+    Rgb,
+}
+
 /// A `TgaHeader` type is a structure containing all the infomation about
 /// a TGA file.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -58,6 +256,7 @@ pub struct TgaHeader {
     image_descriptor: u8,
 }
 
+#[cfg_attr(not(feature = "decode24"), allow(dead_code))]
 impl TgaHeader {
     /// Parse a TGA header from a buffer. We assume that the header to be parsed
     /// starts at the beginning of the buffer. If this is not the case, the
@@ -88,6 +287,26 @@ impl TgaHeader {
         Err(TgaError::IncompleteTgaHeader(buf.len(), TGA_HEADER_LENGTH))
     }
 
+    /// Serialize a TGA header into its 18 byte on-disk representation. This is
+    /// the inverse of `parse_from_buffer`.
+    #[inline]
+    fn to_bytes(self) -> [u8; TGA_HEADER_LENGTH] {
+        [
+            self.id_length,
+            self.color_map_type,
+            self.data_type_code,
+            self.colour_map_origin[0], self.colour_map_origin[1],
+            self.colour_map_length[0], self.colour_map_length[1],
+            self.colour_map_depth,
+            self.x_origin[0], self.x_origin[1],
+            self.y_origin[0], self.y_origin[1],
+            self.width[0], self.width[1],
+            self.height[0], self.height[1],
+            self.bits_per_pixel,
+            self.image_descriptor,
+        ]
+    }
+
     /// The width of a TGA image, in pixels.
     #[inline]
     fn width(&self) -> usize {
@@ -97,7 +316,7 @@ impl TgaHeader {
     /// The height of a TGA image, in pixels.
     #[inline]
     fn height(&self) -> usize {
-        ((((self.height[1] as u16) << 8) as u16) | (self.height[0] as u16)) as usize
+        (((self.height[1] as u16) << 8) | (self.height[0] as u16)) as usize
     }
 
     /// The bit depth for each pixel. By default this will be 24 bits as the most
@@ -107,9 +326,38 @@ impl TgaHeader {
         self.bits_per_pixel as usize
     }
 
+    /// The number of bytes each pixel occupies in the image data. Fifteen bit
+    /// pixels are stored in two bytes, just like sixteen bit pixels.
     #[inline]
     fn bytes_per_pixel(&self) -> usize {
-        (self.bits_per_pixel / 8) as usize
+        (self.bits_per_pixel as usize).div_ceil(8)
+    }
+
+    /// Determine whether the pixel data is run length encoded. Run length
+    /// encoded data type codes are the uncompressed ones with bit 3 set.
+    #[inline]
+    fn is_run_length_encoded(&self) -> bool {
+        self.data_type_code & 0x08 != 0
+    }
+
+    /// Determine whether the file holds any pixel data. Type 0 files hold no
+    /// image data at all, whatever dimensions their headers give.
+    #[inline]
+    fn has_image_data(&self) -> bool {
+        self.data_type_code != 0
+    }
+
+    /// Determine whether the pixels are indices into a colour map.
+    #[inline]
+    fn is_colour_mapped(&self) -> bool {
+        self.data_type_code & 0x07 == 1
+    }
+
+    /// The index of the first colour map entry. Pixel values in a colour mapped
+    /// image are offset by this amount.
+    #[inline]
+    fn colour_map_origin(&self) -> usize {
+        (((self.colour_map_origin[1] as u16) << 8) | (self.colour_map_origin[0] as u16)) as usize
     }
 
     #[inline]
@@ -122,23 +370,68 @@ impl TgaHeader {
         self.colour_map_depth as usize
     }
 
+    /// The number of bytes each colour map entry occupies. From the TGA
+    /// specification, the colour map depth will be one of 15, 16, 24, or 32
+    /// bits, and fifteen bit entries are stored in two bytes.
     #[inline]
-    fn colour_map_size(&self) -> usize {
-        let colour_map_length = ((self.colour_map_length[1] as u16) << 8) 
-                              | self.colour_map_length[0] as u16;
-
-        // From the TGA specification, the color map depth will be one of
-        // 16, 24, or 32 bits; it is always a multiple of 8. Therefore
-        // we can always safely divide by 8.
-        let colour_map_depth_bytes = (self.colour_map_depth / 8) as u16;
+    fn colour_map_entry_bytes(&self) -> usize {
+        self.colour_map_depth().div_ceil(8)
+    }
 
-        (colour_map_length * colour_map_depth_bytes) as usize
+    #[inline]
+    fn colour_map_size(&self) -> usize {
+        self.colour_map_length() * self.colour_map_entry_bytes()
     }
 
     #[inline]
     fn id_length(&self) -> usize {
         self.id_length as usize
     }
+
+    /// The function `x_origin` returns the horizontal coordinate of the lower
+    /// left corner of the image on a display.
+    #[inline]
+    pub fn x_origin(&self) -> u16 {
+        u16::from_le_bytes(self.x_origin)
+    }
+
+    /// The function `y_origin` returns the vertical coordinate of the lower
+    /// left corner of the image on a display.
+    #[inline]
+    pub fn y_origin(&self) -> u16 {
+        u16::from_le_bytes(self.y_origin)
+    }
+
+    /// The function `image_descriptor` returns the image descriptor byte. Bits
+    /// 0 through 3 give the number of attribute bits per pixel, bit 4 is set
+    /// when pixels are stored right to left, and bit 5 is set when scanlines
+    /// are stored top to bottom. Bits 6 and 7 record interleaved scanlines,
+    /// and are cleared in the headers of parsed images, whose scanlines are
+    /// put in order.
+    #[inline]
+    pub fn image_descriptor(&self) -> u8 {
+        self.image_descriptor
+    }
+
+    /// The function `set_x_origin` sets the horizontal coordinate of the lower
+    /// left corner of the image on a display.
+    #[inline]
+    pub fn set_x_origin(&mut self, x_origin: u16) {
+        self.x_origin = x_origin.to_le_bytes();
+    }
+
+    /// The function `set_y_origin` sets the vertical coordinate of the lower
+    /// left corner of the image on a display.
+    #[inline]
+    pub fn set_y_origin(&mut self, y_origin: u16) {
+        self.y_origin = y_origin.to_le_bytes();
+    }
+
+    /// The function `set_image_descriptor` sets the image descriptor byte.
+    #[inline]
+    pub fn set_image_descriptor(&mut self, image_descriptor: u8) {
+        self.image_descriptor = image_descriptor;
+    }
 }
 
 #[derive(Debug)]
@@ -152,6 +445,112 @@ pub enum TgaError {
     IncompleteIdString(usize, usize),
     IncompleteColourMap(usize, usize),
     IncompleteImageData(usize, usize),
+    Io(Box<io::Error>),
+    UnsupportedBitDepth(usize),
+    InvalidColourMapIndex(usize),
+    InvalidDimensions(u32, u32),
+    RegionOutOfBounds(u32, u32, u32, u32),
+    PaletteTooLarge(usize),
+    TooManyColours(usize),
+    UnexpectedScanlineCount(usize, usize),
+    Named(String, Box<TgaError>),
+    ImageIdentificationTooLong(usize),
+    LayoutMismatch,
+    InconsistentHeader(&'static str),
+    InvalidProvenance(&'static str),
+    InvalidStride(isize),
+    InvalidAreaOffset(&'static str, usize, &'static str),
+    DimensionMismatch(u32, u32),
+    BufferTooSmall(usize, usize),
+}
+
+impl TgaError {
+    /// The function `named` attaches a source label, such as a file path, to
+    /// the error, so its `Display` output identifies where the error came from.
+    pub fn named<S: Into<String>>(self, source: S) -> TgaError {
+        TgaError::Named(source.into(), Box::new(self))
+    }
+
+    /// The function `error_code` returns a number identifying the kind of the
+    /// error, for programs that handle errors without parsing their `Display`
+    /// output, such as services written in other languages. Codes never
+    /// change meaning between releases, and new kinds of error get new codes.
+    /// A named error has the code of the error it names.
+    pub fn error_code(&self) -> u32 {
+        match *self {
+            TgaError::CorruptTgaHeader => 1,
+            TgaError::Not24BitRgb(_) => 2,
+            TgaError::CorruptIdString(_) => 3,
+            TgaError::CorruptColourMap(_) => 4,
+            TgaError::CorruptImageData(_) => 5,
+            TgaError::IncompleteTgaHeader(_, _) => 6,
+            TgaError::IncompleteIdString(_, _) => 7,
+            TgaError::IncompleteColourMap(_, _) => 8,
+            TgaError::IncompleteImageData(_, _) => 9,
+            TgaError::Io(_) => 10,
+            TgaError::UnsupportedBitDepth(_) => 11,
+            TgaError::InvalidColourMapIndex(_) => 12,
+            TgaError::InvalidDimensions(_, _) => 13,
+            TgaError::RegionOutOfBounds(_, _, _, _) => 14,
+            TgaError::PaletteTooLarge(_) => 15,
+            TgaError::TooManyColours(_) => 16,
+            TgaError::UnexpectedScanlineCount(_, _) => 17,
+            TgaError::ImageIdentificationTooLong(_) => 18,
+            TgaError::LayoutMismatch => 19,
+            TgaError::InconsistentHeader(_) => 20,
+            TgaError::InvalidProvenance(_) => 21,
+            TgaError::InvalidStride(_) => 22,
+            TgaError::InvalidAreaOffset(_, _, _) => 23,
+            TgaError::DimensionMismatch(_, _) => 24,
+            TgaError::BufferTooSmall(_, _) => 25,
+            TgaError::Named(_, ref err) => err.error_code(),
+        }
+    }
+
+    /// The function `error_id` returns a string identifying the kind of the
+    /// error, in lower case words separated by underscores, such as
+    /// `"incomplete_image_data"`. Like the codes of `error_code`, the strings
+    /// are stable between releases, and a named error has the string of the
+    /// error it names.
+    pub fn error_id(&self) -> &'static str {
+        match *self {
+            TgaError::CorruptTgaHeader => "corrupt_tga_header",
+            TgaError::Not24BitRgb(_) => "not_24_bit_rgb",
+            TgaError::CorruptIdString(_) => "corrupt_id_string",
+            TgaError::CorruptColourMap(_) => "corrupt_colour_map",
+            TgaError::CorruptImageData(_) => "corrupt_image_data",
+            TgaError::IncompleteTgaHeader(_, _) => "incomplete_tga_header",
+            TgaError::IncompleteIdString(_, _) => "incomplete_id_string",
+            TgaError::IncompleteColourMap(_, _) => "incomplete_colour_map",
+            TgaError::IncompleteImageData(_, _) => "incomplete_image_data",
+            TgaError::Io(_) => "io",
+            TgaError::UnsupportedBitDepth(_) => "unsupported_bit_depth",
+            TgaError::InvalidColourMapIndex(_) => "invalid_colour_map_index",
+            TgaError::InvalidDimensions(_, _) => "invalid_dimensions",
+            TgaError::RegionOutOfBounds(_, _, _, _) => "region_out_of_bounds",
+            TgaError::PaletteTooLarge(_) => "palette_too_large",
+            TgaError::TooManyColours(_) => "too_many_colours",
+            TgaError::UnexpectedScanlineCount(_, _) => "unexpected_scanline_count",
+            TgaError::ImageIdentificationTooLong(_) => "image_identification_too_long",
+            TgaError::LayoutMismatch => "layout_mismatch",
+            TgaError::InconsistentHeader(_) => "inconsistent_header",
+            TgaError::InvalidProvenance(_) => "invalid_provenance",
+            TgaError::InvalidStride(_) => "invalid_stride",
+            TgaError::InvalidAreaOffset(_, _, _) => "invalid_area_offset",
+            TgaError::DimensionMismatch(_, _) => "dimension_mismatch",
+            TgaError::BufferTooSmall(_, _) => "buffer_too_small",
+            TgaError::Named(_, ref err) => err.error_id(),
+        }
+    }
+
+    /// The function `source_label` returns the source label attached to the
+    /// error, if there is one.
+    pub fn source_label(&self) -> Option<&str> {
+        match *self {
+            TgaError::Named(ref source, _) => Some(source),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for TgaError {
@@ -184,6 +583,57 @@ impl fmt::Display for TgaError {
             TgaError::IncompleteImageData(have, need) => {
                 write!(f, "IncompleteImageData(have={}, need={})", have, need)
             }
+            TgaError::Io(ref err) => {
+                write!(f, "Io({})", err)
+            }
+            TgaError::UnsupportedBitDepth(bits) => {
+                write!(f, "UnsupportedBitDepth(got_bits={})", bits)
+            }
+            TgaError::InvalidColourMapIndex(index) => {
+                write!(f, "InvalidColourMapIndex(got_index={})", index)
+            }
+            TgaError::InvalidDimensions(width, height) => {
+                write!(f, "InvalidDimensions(width={}, height={})", width, height)
+            }
+            TgaError::RegionOutOfBounds(x, y, width, height) => {
+                write!(f, "RegionOutOfBounds(x={}, y={}, width={}, height={})", x, y, width, height)
+            }
+            TgaError::PaletteTooLarge(length) => {
+                write!(f, "PaletteTooLarge(got_length={})", length)
+            }
+            TgaError::TooManyColours(colours) => {
+                write!(f, "TooManyColours(got_colours={})", colours)
+            }
+            TgaError::UnexpectedScanlineCount(got, expected) => {
+                write!(f, "UnexpectedScanlineCount(got={}, expected={})", got, expected)
+            }
+            TgaError::Named(ref source, ref err) => {
+                write!(f, "Named(source={}, error={})", source, err)
+            }
+            TgaError::ImageIdentificationTooLong(length) => {
+                write!(f, "ImageIdentificationTooLong(got_length={})", length)
+            }
+            TgaError::LayoutMismatch => {
+                write!(f, "LayoutMismatch")
+            }
+            TgaError::InconsistentHeader(reason) => {
+                write!(f, "InconsistentHeader(reason={})", reason)
+            }
+            TgaError::InvalidProvenance(reason) => {
+                write!(f, "InvalidProvenance(reason={})", reason)
+            }
+            TgaError::InvalidStride(stride) => {
+                write!(f, "InvalidStride(stride={})", stride)
+            }
+            TgaError::InvalidAreaOffset(area, offset, reason) => {
+                write!(f, "InvalidAreaOffset(area={}, offset={}, reason={})", area, offset, reason)
+            }
+            TgaError::DimensionMismatch(width, height) => {
+                write!(f, "DimensionMismatch(width={}, height={})", width, height)
+            }
+            TgaError::BufferTooSmall(have, need) => {
+                write!(f, "BufferTooSmall(have={}, need={})", have, need)
+            }
         }
     }
 }
@@ -218,10 +668,61 @@ impl error::Error for TgaError {
             TgaError::IncompleteImageData(_,_) => {
                 "The number of pixels in the TGA image does not equal what was reported in the header."
             }
+            TgaError::Io(_) => {
+                "An I/O error occurred while reading or writing the TGA image."
+            }
+            TgaError::UnsupportedBitDepth(_) => {
+                "The pixel or colour map depth is not supported for this type of TGA image."
+            }
+            TgaError::InvalidColourMapIndex(_) => {
+                "A pixel refers to an entry outside of the colour map."
+            }
+            TgaError::InvalidDimensions(_,_) => {
+                "The image dimensions do not fit in a TGA header."
+            }
+            TgaError::RegionOutOfBounds(_,_,_,_) => {
+                "The region does not lie within the image."
+            }
+            TgaError::PaletteTooLarge(_) => {
+                "The palette has more entries than a colour map can hold."
+            }
+            TgaError::TooManyColours(_) => {
+                "The image has more distinct colours than a palette can hold."
+            }
+            TgaError::UnexpectedScanlineCount(_,_) => {
+                "The number of scanlines written does not match the height of the image."
+            }
+            TgaError::Named(_,_) => {
+                "The TGA image from the named source could not be read."
+            }
+            TgaError::ImageIdentificationTooLong(_) => {
+                "The image identification is longer than the 255 bytes its field can hold."
+            }
+            TgaError::LayoutMismatch => {
+                "The file does not store its pixels the way the image does."
+            }
+            TgaError::InconsistentHeader(_) => {
+                "The fields of the TGA header contradict each other."
+            }
+            TgaError::InvalidProvenance(_) => {
+                "The provenance record of the image is malformed, or does not match its pixels."
+            }
+            TgaError::InvalidStride(_) => {
+                "The stride of the pixel buffer is shorter than a row of pixels."
+            }
+            TgaError::InvalidAreaOffset(_, _, _) => {
+                "An offset in the footer or extension area of the file does not point at the area it should."
+            }
+            TgaError::DimensionMismatch(_, _) => {
+                "The images do not have the same dimensions."
+            }
+            TgaError::BufferTooSmall(_, _) => {
+                "The buffer provided is too small to hold the pixel data of the image."
+            }
         }
     }
 
-    fn cause(&self) -> Option<&error::Error> {
+    fn cause(&self) -> Option<&dyn error::Error> {
         match *self {
             TgaError::CorruptTgaHeader => None,
             TgaError::Not24BitRgb(_) => None,
@@ -232,57 +733,219 @@ impl error::Error for TgaError {
             TgaError::IncompleteIdString(_,_) => None,
             TgaError::IncompleteColourMap(_,_) => None,
             TgaError::IncompleteImageData(_,_) => None,
+            TgaError::Io(ref err) => Some(err),
+            TgaError::UnsupportedBitDepth(_) => None,
+            TgaError::InvalidColourMapIndex(_) => None,
+            TgaError::InvalidDimensions(_,_) => None,
+            TgaError::RegionOutOfBounds(_,_,_,_) => None,
+            TgaError::PaletteTooLarge(_) => None,
+            TgaError::TooManyColours(_) => None,
+            TgaError::UnexpectedScanlineCount(_,_) => None,
+            TgaError::Named(_, ref err) => Some(err),
+            TgaError::ImageIdentificationTooLong(_) => None,
+            TgaError::LayoutMismatch => None,
+            TgaError::InconsistentHeader(_) => None,
+            TgaError::InvalidProvenance(_) => None,
+            TgaError::InvalidStride(_) => None,
+            TgaError::InvalidAreaOffset(_, _, _) => None,
+            TgaError::DimensionMismatch(_, _) => None,
+            TgaError::BufferTooSmall(_, _) => None,
         }
     }
 }
 
 /// A `RawTgaImage` is a structure containing the underlying raw TGA image data.
-#[derive(PartialEq, Eq, Debug)]
+/// The buffers are shared between clones of an image and the views cropped from
+/// it, and are only copied when one of the images sharing them is modified.
+#[cfg(feature = "decode24")]
+#[derive(Clone, Debug)]
 struct RawTgaImage {
     /// The TGA header.
     header: TgaHeader,
     /// The image identification data. This is typically omitted, but it can
     /// up to 255 character long. If more data is needed, it can be placed
     /// after the image data.
-    image_identification: Rc<Vec<u8>>,
+    image_identification: Arc<Vec<u8>>,
     /// The colour map data, as specified by the colour map specification.
-    colour_map_data: Rc<Vec<u8>>,
-    /// The raw pixels themselves.
-    image_data: Rc<Vec<u8>>,
+    colour_map_data: Arc<Vec<u8>>,
+    /// The raw pixels themselves. Run length encoded pixel data is decoded
+    /// when the image is parsed, so this always holds one uncompressed
+    /// pixel after another, in the bit depth given by the header.
+    image_data: Arc<Vec<u8>>,
     /// The extended image identification data. This field is the spillover from
     /// the image identification field if the image identification data is too
     /// long to fit into the image indentification field.
-    extended_image_identification: Rc<Vec<u8>>,
+    extended_image_identification: Arc<Vec<u8>>,
+    /// The position in the source file of the extended image identification
+    /// data, which the offsets in a TGA 2.0 footer at the end of it count
+    /// from. This is `None` for images that were not parsed from a file.
+    trailing_position: Option<usize>,
+    /// The order the scanlines were stored in by the file the image was
+    /// parsed from. They are put in order when the image is parsed.
+    interleaving: Interleaving,
+    /// The offset of the first pixel of the image within `image_data`, in
+    /// bytes. This is zero unless the image is a view into a larger image.
+    data_offset: usize,
+    /// The distance from the start of one row of pixels to the start of the
+    /// next within `image_data`, in bytes.
+    data_stride: usize,
+    /// A contiguous copy of the pixels of a view whose rows are not adjacent
+    /// in `image_data`, built the first time the image data is requested.
+    contiguous_data: OnceCell<Vec<u8>>,
 }
 
+#[cfg(feature = "decode24")]
+impl PartialEq for RawTgaImage {
+    fn eq(&self, other: &RawTgaImage) -> bool {
+        (self.header == other.header)
+            && (self.image_identification == other.image_identification)
+            && (self.colour_map_data == other.colour_map_data)
+            && (self.extended_image_identification == other.extended_image_identification)
+            && self.rows().eq(other.rows())
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl Eq for RawTgaImage {}
+
+#[cfg(feature = "decode24")]
 impl RawTgaImage {
     /// Construct a new TGA image.
     fn new(
-        header: TgaHeader, 
-        image_identification: Rc<Vec<u8>>, 
-        colour_map_data: Rc<Vec<u8>>, 
-        image_data: Rc<Vec<u8>>,
-        extended_image_identification: Rc<Vec<u8>>
+        header: TgaHeader,
+        image_identification: Arc<Vec<u8>>,
+        colour_map_data: Arc<Vec<u8>>,
+        image_data: Arc<Vec<u8>>,
+        extended_image_identification: Arc<Vec<u8>>
     ) -> RawTgaImage {
+        let data_stride = header.width() * header.bytes_per_pixel();
+
         RawTgaImage {
-            header: header, 
-            image_identification: image_identification, 
-            colour_map_data: colour_map_data,
-            image_data: image_data,
-            extended_image_identification: extended_image_identification,
+            header,
+            image_identification,
+            colour_map_data,
+            image_data,
+            extended_image_identification,
+            trailing_position: None,
+            interleaving: Interleaving::NonInterleaved,
+            data_offset: 0,
+            data_stride,
+            contiguous_data: OnceCell::new(),
         }
     }
 
-    /// The function `width` returns the width of a TGA image, in pixels.
+    /// Parse everything following the TGA header from a buffer. We assume
+    /// that the header has already been parsed and validated by the caller, and
+    /// that the buffer starts at the beginning of the header. Run length encoded
+    /// image data is decompressed as it is read.
+    fn parse_from_buffer(buf: &[u8], header: TgaHeader) -> Result<RawTgaImage, TgaError> {
+        if buf.len() < header.id_length() + TGA_HEADER_LENGTH {
+            return Err(TgaError::CorruptTgaHeader);
+        }
+
+        // Parse the image identification.
+        let slice = &buf[TGA_HEADER_LENGTH..buf.len()];
+        let image_identification = Arc::new(
+            slice[0..header.id_length()].to_vec()
+        );
+
+        // Parse the colour map data.
+        let slice = &slice[header.id_length()..slice.len()];
+        if slice.len() < header.colour_map_size() {
+            return Err(TgaError::IncompleteColourMap(
+                slice.len(), header.colour_map_size()
+            ));
+        }
+
+        let colour_map_data = Arc::new(
+            slice[0..header.colour_map_size()].to_vec()
+        );
+
+        // Parse the image data.
+        let slice = &slice[header.colour_map_size()..slice.len()];
+        let image_size = if header.has_image_data() {
+            header.width() * header.height() * header.bytes_per_pixel()
+        } else {
+            0
+        };
+        let (image_data, image_data_end) = if header.is_run_length_encoded() {
+            #[cfg(feature = "rle")]
+            { rle::decode_rle(slice, image_size, header.bytes_per_pixel())? }
+            #[cfg(not(feature = "rle"))]
+            { return Err(TgaError::Not24BitRgb(header.data_type_code as usize)); }
+        } else {
+            if slice.len() < image_size {
+                return Err(TgaError::IncompleteImageData(slice.len(), image_size));
+            }
+
+            (slice[0..image_size].to_vec(), image_size)
+        };
+
+        // Parse the extended image identification information from the end
+        // of the image data field.
+        let mut slice = &slice[image_data_end..slice.len()];
+
+        // Check whether the end of the remaining bytes is a TGA image footer.
+        if slice.ends_with(&TGA_FOOTER) {
+            slice = &slice[0..(slice.len() - TGA_FOOTER.len())];
+        }
+        let trailing_position = buf.len() - slice.len();
+
+        let extended_image_identification = Arc::new(slice.to_vec());
+
+        let mut image = RawTgaImage::new(
+            header, image_identification, colour_map_data, Arc::new(image_data), extended_image_identification
+        );
+        image.trailing_position = Some(trailing_position);
+        image.deinterleave();
+        image.normalize_channel_order();
+
+        Ok(image)
+    }
+
+    /// Remove the channel order tag from a freshly parsed image, restoring its
+    /// pixels to the standard blue, green, red order.
+    pub(crate) fn normalize_channel_order(&mut self) {
+        if !self.image_identification.starts_with(RGB_CHANNEL_ORDER_TAG) {
+            return;
+        }
+
+        let image_identification = self.image_identification[RGB_CHANNEL_ORDER_TAG.len()..].to_vec();
+        self.header.id_length = image_identification.len() as u8;
+        self.image_identification = Arc::new(image_identification);
+        if self.header.is_colour_mapped() {
+            let bits = self.header.colour_map_depth();
+            let colour_map_data: &mut Vec<u8> = Arc::make_mut(&mut self.colour_map_data);
+            swap_red_and_blue(colour_map_data, bits);
+        } else {
+            let bits = self.header.bits_per_pixel();
+            swap_red_and_blue(self.image_data_mut(), bits);
+        }
+    }
+
+    /// Check that every pixel of a colour mapped image refers to an entry
+    /// that is actually present in the colour map.
+    fn validate_colour_map_indices(&self) -> Result<(), TgaError> {
+        let index_bytes = self.header.bytes_per_pixel();
+        for bytes in self.rows().flat_map(|row| row.chunks(index_bytes)) {
+            check_colour_map_index(&self.header, bytes)?;
+        }
+
+        Ok(())
+    }
+
+
+    /// The function `width` returns the width of a TGA image, in pixels. An
+    /// image without image data has no pixels, so its width is zero.
     #[inline]
     fn width(&self) -> usize {
-        self.header.width()
+        if self.header.has_image_data() { self.header.width() } else { 0 }
     }
 
     /// Return the height of a TGA image, in pixels.
     #[inline]
     fn height(&self) -> usize {
-        self.header.height()
+        if self.header.has_image_data() { self.header.height() } else { 0 }
     }
 
     /// Return the bit depth per pixel in a TGA Image.
@@ -292,7 +955,7 @@ impl RawTgaImage {
     }
 
     /// Compute the colour map type. The colour map type is either `0` or `1`.
-    /// A `0` indicates that there is no colour map; a `1` indicates that a 
+    /// A `0` indicates that there is no colour map; a `1` indicates that a
     /// colour map is included.
     #[inline]
     fn color_map_type(&self) -> usize {
@@ -310,26 +973,71 @@ impl RawTgaImage {
         self.header
     }
 
+    /// The function `pixel_decoder` returns the decoder that turns the stored
+    /// pixels of this image into colours.
+    #[inline]
+    fn pixel_decoder(&self) -> PixelDecoder<'_> {
+        PixelDecoder {
+            header: self.header,
+            colour_map: self.colour_map_data.as_slice(),
+        }
+    }
+
     /// The function `pixels` generates an iterator over the pixels of the image.
-    /// It sweeps through the TGA image going from left to right in each row, and 
+    /// It sweeps through the TGA image going from left to right in each row, and
     /// going from bottom to top. The first pixel returned is the bottom left corner;
-    /// the last pixel returned is the top right corner. 
+    /// the last pixel returned is the top right corner.
     #[inline]
-    fn pixels(&self) -> PixelIter {
+    fn pixels(&self) -> PixelIter<'_> {
         PixelIter {
-            inner: self.image_data.as_slice(),
-            current: [0; 3],
-            index: 0,
+            image: self,
+            front: 0,
+            back: self.image_data_length(),
+        }
+    }
+
+    /// The function `colour` decodes the blue, green, red, and alpha channels
+    /// of the pixel with the given index, counting in the order the pixels
+    /// are stored.
+    #[inline]
+    fn colour(&self, index: usize) -> [u8; 4] {
+        let decoder = self.pixel_decoder();
+        let bytes_per_pixel = decoder.bytes_per_pixel();
+        let (row, column) = (index / self.width(), index % self.width());
+        let start = self.data_offset + row * self.data_stride + column * bytes_per_pixel;
+
+        decoder.decode(&self.image_data[start..(start + bytes_per_pixel)])
+    }
+
+    /// The function `pixel` decodes the pixel with the given index, counting
+    /// in the order the pixels are stored.
+    #[inline]
+    fn pixel(&self, index: usize) -> [u8; 3] {
+        let colour = self.colour(index);
+
+        [colour[0], colour[1], colour[2]]
+    }
+
+    #[inline]
+    fn scanlines(&self) -> ScanlineIter<'_> {
+        ScanlineIter {
+            image: self,
+            rows: 0..self.height(),
+            bottom_up: false,
+            right_to_left: false,
         }
     }
 
+    /// The function `scanlines_top_down` generates an iterator over the rows
+    /// of the image in display order, from the top row to the bottom row and
+    /// from left to right within each row.
     #[inline]
-    fn scanlines(&self) -> ScanlineIter {
+    fn scanlines_top_down(&self) -> ScanlineIter<'_> {
         ScanlineIter {
-            inner: self.image_data.as_slice(),
-            height: self.height(), 
-            width:  self.width(),
-            row: 0,
+            image: self,
+            rows: 0..self.height(),
+            bottom_up: self.header.image_descriptor & 0x20 == 0,
+            right_to_left: self.header.image_descriptor & 0x10 != 0,
         }
     }
 
@@ -340,196 +1048,736 @@ impl RawTgaImage {
     /// ```
     #[inline]
     fn image_data_length(&self) -> usize {
-        self.image_data.len() / 3
+        self.width() * self.height()
     }
 
-    /// The function `image_data_length_bytes` computes the size of the 
+    /// The function `image_data_length_bytes` computes the size of the
     /// image data, in the number of bytes. For an unmapped RGB image, this will
     /// simply be `3 * image_data_length()`, since each RGB pixel is 3 bytes long.
     #[inline]
     fn image_data_length_bytes(&self) -> usize {
-        self.image_data.len()
+        self.height() * self.row_length()
+    }
+
+    /// The length of a single row of pixels, in bytes.
+    #[inline]
+    fn row_length(&self) -> usize {
+        self.width() * self.header.bytes_per_pixel()
+    }
+
+    /// Determine whether the rows of the image are adjacent in the underlying
+    /// buffer, so the image data can be borrowed as one slice.
+    #[inline]
+    fn is_contiguous(&self) -> bool {
+        (self.data_stride == self.row_length()) || (self.height() <= 1)
+    }
+
+    /// The function `rows` returns an iterator over the rows of stored pixels,
+    /// borrowed directly from the underlying buffer.
+    fn rows(&self) -> impl DoubleEndedIterator<Item = &[u8]> + '_ {
+        (0..self.height()).map(move |row| self.row(row))
     }
 
-    /// The function `image_identification` returns a slice into the 
-    /// image identification field. This is a free-form field that immediately
-    /// follows the header.
+    /// The function `row` returns the stored pixels of a single row, borrowed
+    /// directly from the underlying buffer.
     #[inline]
-    fn image_identification(&self) -> &[u8] {
-        &self.image_identification
+    fn row(&self, row: usize) -> &[u8] {
+        let start = self.data_offset + row * self.data_stride;
+        &self.image_data[start..(start + self.row_length())]
+    }
+
+    /// The function `colours_in_display_order` decodes the colours of the
+    /// image in display order, from the top left corner of the image to the
+    /// bottom right, one row at a time, whichever corner the pixels are
+    /// stored from.
+    #[cfg(any(feature = "metadata", feature = "encode", feature = "transforms", feature = "test-util"))]
+    fn colours_in_display_order(&self) -> Vec<[u8; 4]> {
+        let top_to_bottom = self.header.image_descriptor & 0x20 != 0;
+        let right_to_left = self.header.image_descriptor & 0x10 != 0;
+        let decoder = self.pixel_decoder();
+        let mut colours = Vec::with_capacity(self.width() * self.height());
+        for y in 0..self.height() {
+            let row = if top_to_bottom { y } else { self.height() - 1 - y };
+            let start = colours.len();
+            colours.extend(self.row(row).chunks(decoder.bytes_per_pixel()).map(|pixel| decoder.decode(pixel)));
+            if right_to_left {
+                colours[start..].reverse();
+            }
+        }
+
+        colours
     }
 
+    /// The function `image_data` returns the stored pixels as one slice. The
+    /// pixels of a view whose rows are not adjacent in the shared buffer are
+    /// copied into a contiguous buffer the first time they are requested.
     #[inline]
     fn image_data(&self) -> &[u8] {
-        &self.image_data
+        if self.is_contiguous() {
+            &self.image_data[self.data_offset..(self.data_offset + self.image_data_length_bytes())]
+        } else {
+            self.contiguous_data.get_or_init(|| self.rows().flat_map(|row| row.iter().cloned()).collect())
+        }
+    }
+
+    /// The function `shared_image_data` returns the stored pixels as a shared
+    /// buffer, without copying them when the image covers the whole buffer.
+    #[cfg(feature = "encode")]
+    fn shared_image_data(&self) -> Arc<Vec<u8>> {
+        if (self.data_offset == 0) && (self.image_data_length_bytes() == self.image_data.len()) {
+            self.image_data.clone()
+        } else {
+            Arc::new(self.image_data().to_vec())
+        }
+    }
+
+    /// The function `image_data_mut` gives mutable access to the stored pixels.
+    /// An image sharing its pixels with other images gets its own copy of them
+    /// first, so the other images are left unchanged.
+    fn image_data_mut(&mut self) -> &mut [u8] {
+        let length = self.image_data_length_bytes();
+        if (self.data_offset != 0) || (length != self.image_data.len()) || !self.is_contiguous() {
+            self.image_data = Arc::new(self.image_data().to_vec());
+            self.data_offset = 0;
+            self.data_stride = self.row_length();
+        }
+        self.contiguous_data = OnceCell::new();
+
+        Arc::make_mut(&mut self.image_data).as_mut_slice()
+    }
+
+}
+
+/// Swap the red and blue channels of every pixel in a buffer of 24 or 32 bit
+/// pixels. Buffers of any other depth are left unchanged.
+#[cfg(feature = "decode24")]
+fn swap_red_and_blue(data: &mut [u8], bits: usize) {
+    if (bits == 24) || (bits == 32) {
+        for pixel in data.chunks_mut(bits / 8) {
+            pixel.swap(0, 2);
+        }
+    }
+}
+
+/// Decode a single pixel stored with the given number of bits into its blue,
+/// green, red, and alpha channels, in that order. The same decoding applies
+/// to true colour pixel data and to the entries of a colour map, so both
+/// go through this function.
+///
+/// Sixteen bit pixels pack five bits each of red, green, and blue, with the
+/// top bit holding the attribute (alpha) bit. Fifteen bit pixels have no
+/// attribute bit and are always opaque. Eight bit pixels are grey levels.
+#[inline]
+#[cfg(feature = "decode24")]
+fn decode_pixel(bytes: &[u8], bits: usize) -> [u8; 4] {
+    match bits {
+        15 | 16 => {
+            let value = (bytes[0] as u16) | ((bytes[1] as u16) << 8);
+            // Scale each five bit channel to eight bits, filling the low bits
+            // with the high bits so that full intensity maps to 255.
+            let expand = |channel: u16| ((channel << 3) | (channel >> 2)) as u8;
+            let alpha = if (bits == 16) && (value & 0x8000 == 0) { 0 } else { 255 };
+
+            [
+                expand(value & 0x1F),
+                expand((value >> 5) & 0x1F),
+                expand((value >> 10) & 0x1F),
+                alpha,
+            ]
+        }
+        8 => [bytes[0], bytes[0], bytes[0], 255],
+        24 => [bytes[0], bytes[1], bytes[2], 255],
+        _ => [bytes[0], bytes[1], bytes[2], bytes[3]],
+    }
+}
+
+/// Encode a colour given as blue, green, red, and alpha channels into a pixel
+/// stored with the given number of bits. This is the inverse of `decode_pixel`,
+/// except that sixteen bit pixels keep only five bits of each colour channel
+/// and a single attribute bit, which is set for alpha values of 128 or more.
+#[inline]
+#[cfg(any(feature = "encode", feature = "transforms", feature = "rayon"))]
+fn encode_pixel(colour: [u8; 4], bits: usize, bytes: &mut [u8]) {
+    match bits {
+        15 | 16 => {
+            let mut value = ((colour[2] as u16 >> 3) << 10)
+                | ((colour[1] as u16 >> 3) << 5)
+                | (colour[0] as u16 >> 3);
+            if (bits == 16) && (colour[3] >= 128) {
+                value |= 0x8000;
+            }
+            bytes[0] = (value & 0xFF) as u8;
+            bytes[1] = (value >> 8) as u8;
+        }
+        24 => bytes[0..3].copy_from_slice(&colour[0..3]),
+        _ => bytes[0..4].copy_from_slice(&colour),
+    }
+}
+
+/// Reinterpret the bytes of a slice as a slice of pixels of `N` bytes each,
+/// without copying them. Bytes left over after the last whole pixel are left
+/// out.
+#[cfg(feature = "decode24")]
+fn as_pixel_slice<const N: usize>(data: &[u8]) -> &[[u8; N]] {
+    // SAFETY: `[u8; N]` has the same alignment as `u8` and no invalid bit
+    // patterns, so every run of `N` bytes is a valid pixel, and `align_to`
+    // never needs a prefix to align the pixels.
+    let (prefix, pixels, _) = unsafe { data.align_to::<[u8; N]>() };
+    debug_assert!(prefix.is_empty());
+
+    pixels
+}
+
+/// Decode a little endian colour map index of one or two bytes.
+#[inline]
+#[cfg(feature = "decode24")]
+fn decode_colour_map_index(bytes: &[u8]) -> usize {
+    match bytes.len() {
+        1 => bytes[0] as usize,
+        _ => (bytes[0] as usize) | ((bytes[1] as usize) << 8),
+    }
+}
+
+/// Check that a stored pixel of a colour mapped image refers to an entry that
+/// is actually present in the colour map.
+#[cfg(feature = "decode24")]
+fn check_colour_map_index(header: &TgaHeader, bytes: &[u8]) -> Result<(), TgaError> {
+    let origin = header.colour_map_origin();
+    let length = header.colour_map_length();
+    let index = decode_colour_map_index(bytes);
+    if (index < origin) || (index >= origin + length) {
+        return Err(TgaError::InvalidColourMapIndex(index));
+    }
+
+    Ok(())
+}
+
+/// A `PixelDecoder` turns the stored pixels of an image into colours. For a
+/// true colour image this decodes the pixel directly, and for a colour mapped
+/// image it looks the pixel up in the colour map.
+#[cfg(feature = "decode24")]
+#[derive(Copy, Clone, Debug)]
+struct PixelDecoder<'a> {
+    header: TgaHeader,
+    colour_map: &'a [u8],
+}
+
+#[cfg(feature = "decode24")]
+impl<'a> PixelDecoder<'a> {
+    /// The number of bytes each stored pixel occupies in the image data.
+    #[inline]
+    fn bytes_per_pixel(&self) -> usize {
+        self.header.bytes_per_pixel()
     }
 
-    /// The function `extended_image_identification` returns a slice to the 
-    /// extended image identification data. This is the data that follows after
-    /// the image data that is too large for the image identification field.
+    /// Decode a stored pixel into its blue, green, red, and alpha channels.
+    /// Parsing and `set_pixel` reject colour map indices outside the colour
+    /// map, but `image_data_mut` and indexing can still write them. Such an
+    /// index decodes to transparent black.
     #[inline]
-    fn extended_image_identification(&self) -> &[u8] {
-        &self.extended_image_identification
+    fn decode(&self, bytes: &[u8]) -> [u8; 4] {
+        if self.header.is_colour_mapped() {
+            let entry_bytes = self.header.colour_map_entry_bytes();
+            let entry = decode_colour_map_index(bytes)
+                .checked_sub(self.header.colour_map_origin())
+                .and_then(|offset| self.colour_map.get((offset * entry_bytes)..((offset + 1) * entry_bytes)));
+
+            match entry {
+                Some(entry) => decode_pixel(entry, self.header.colour_map_depth()),
+                None => [0, 0, 0, 0],
+            }
+        } else {
+            decode_pixel(bytes, self.header.bits_per_pixel())
+        }
     }
 }
 
+#[cfg(feature = "decode24")]
 pub struct PixelIter<'a> {
-    inner: &'a [u8],
-    current: [u8; 3],
-    index: usize,
+    image: &'a RawTgaImage,
+    /// The index of the next pixel from the front, in stored order.
+    front: usize,
+    /// One past the index of the next pixel from the back.
+    back: usize,
 }
 
+#[cfg(feature = "decode24")]
 impl<'a> Iterator for PixelIter<'a> {
     type Item = [u8; 3];
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.inner.len() {
-            self.current[0] = self.inner[self.index];
-            self.current[1] = self.inner[self.index + 1];
-            self.current[2] = self.inner[self.index + 2];
-            self.index += 3;
-            
-            return Some(self.current);
+        if self.front < self.back {
+            let pixel = self.image.pixel(self.front);
+            self.front += 1;
+
+            return Some(pixel);
         }
 
         None
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let length = self.back - self.front;
+
+        (length, Some(length))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.front = usize::min(self.back, self.front.saturating_add(n));
+
+        self.next()
+    }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
-pub struct Scanline(Vec<[u8; 3]>);
+#[cfg(feature = "decode24")]
+impl<'a> DoubleEndedIterator for PixelIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.back -= 1;
 
-impl PartialEq<[u8]> for Scanline {
-    fn eq(&self, rhs: &[u8]) -> bool {
-        if rhs.len() == 3 * self.0.len() {
-            let pixels = &self.0;
-            let rhs_pixels = rhs.chunks(3);
-            return pixels.iter().zip(rhs_pixels).all(
-                |(pixel, rhs_pixel)| { pixel == rhs_pixel }
-            );
+            return Some(self.image.pixel(self.back));
         }
 
-        false
+        None
     }
 }
 
-pub struct ScanlineIter<'a> {
-    inner: &'a [u8],
-    height: usize,
-    width: usize,
-    row: usize,
+#[cfg(feature = "decode24")]
+impl<'a> ExactSizeIterator for PixelIter<'a> {}
+
+/// A `PixelRefs` is an iterator over references to the pixels of a 24 bit
+/// true colour image, created by `TgaImage::pixel_refs`.
+#[cfg(feature = "decode24")]
+#[derive(Clone, Debug)]
+pub struct PixelRefs<'a> {
+    chunks: ChunksExact<'a, u8>,
 }
 
-impl<'a> Iterator for ScanlineIter<'a> {
-    type Item = Scanline;
+#[cfg(feature = "decode24")]
+impl<'a> Iterator for PixelRefs<'a> {
+    type Item = &'a [u8; 3];
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.row < self.height {
-            let mut scanline = vec![[0; 3]; self.width];
-            for col in 0..self.width {
-                let pixel = [
-                    self.inner[self.row * (3 * self.width) + (3*col)], 
-                    self.inner[self.row * (3 * self.width) + (3*col) + 1], 
-                    self.inner[self.row * (3 * self.width) + (3*col) + 2],
-                ];
-                scanline[col] = pixel;
-            }
-            self.row += 1;
+        self.chunks.next().map(|pixel| <&[u8; 3]>::try_from(pixel).unwrap())
+    }
 
-            return Some(Scanline(scanline));
-        }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
 
-        None
+#[cfg(feature = "decode24")]
+impl<'a> DoubleEndedIterator for PixelRefs<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chunks.next_back().map(|pixel| <&[u8; 3]>::try_from(pixel).unwrap())
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
-pub struct UncompressedRgb {
-    inner: RawTgaImage,
+#[cfg(feature = "decode24")]
+impl<'a> ExactSizeIterator for PixelRefs<'a> {}
+
+/// An `IntoPixels` is an iterator over the pixels of an image that owns
+/// the image, created by `TgaImage::into_pixels`.
+#[cfg(feature = "decode24")]
+pub struct IntoPixels {
+    image: RawTgaImage,
+    /// The index of the next pixel from the front, in stored order.
+    front: usize,
+    /// One past the index of the next pixel from the back.
+    back: usize,
 }
 
-impl UncompressedRgb {
-    /// Parse an unmapped uncompressed TGA image from a buffer in memory. 
-    /// We assume that the image to be parsed starts at the beginning of the buffer. 
-    /// In order to parse correctly, the bytes of the buffer must conform to the TGA 
-    /// image format.
-    pub fn parse_from_buffer(buf: &[u8]) -> Result<Self, TgaError> {
-        let header = TgaHeader::parse_from_buffer(buf)?;
+#[cfg(feature = "decode24")]
+impl Iterator for IntoPixels {
+    type Item = [u8; 3];
 
-        // Check that we were passed the correct TGA header.
-        if header.data_type_code != 2 {
-            return Err(TgaError::Not24BitRgb(header.data_type_code as usize));
-        }
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            let pixel = self.image.pixel(self.front);
+            self.front += 1;
 
-        // Targa 24 images can also be embedded in Targa 32 images, but we do
-        // not implement that (yet).
-        if header.bits_per_pixel != 24 {
-            return Err(TgaError::Not24BitRgb(header.data_type_code as usize));
+            return Some(pixel);
         }
 
-        if buf.len() < header.id_length() + TGA_HEADER_LENGTH {
-            return Err(TgaError::CorruptTgaHeader);
-        }
+        None
+    }
 
-        // Parse the image identification.
-        let slice = &buf[TGA_HEADER_LENGTH..buf.len()];
-        let image_identification = Rc::new(
-            slice[0..header.id_length()].iter().map(|&x| x).collect::<Vec<u8>>()
-        );
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let length = self.back - self.front;
 
-        // Parse the colour map data.
-        let slice = &slice[header.id_length()..slice.len()];
-        if slice.len() < header.colour_map_size() {
-            return Err(TgaError::IncompleteColourMap(
-                slice.len(), header.colour_map_size()
-            ));
+        (length, Some(length))
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl DoubleEndedIterator for IntoPixels {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.back -= 1;
+
+            return Some(self.image.pixel(self.back));
         }
 
-        let colour_map_data = Rc::new(
-            slice[0..header.colour_map_size()].iter().map(|&x| x).collect::<Vec<u8>>()
+        None
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl ExactSizeIterator for IntoPixels {}
+
+#[cfg(feature = "decode24")]
+impl TgaImage {
+    /// The range of the stored bytes of the pixel at a column and row within
+    /// the image data.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the pixel lies outside the image.
+    fn pixel_range(&self, x: usize, y: usize) -> Range<usize> {
+        let (width, height) = (self.width() as usize, self.height() as usize);
+        assert!(
+            (x < width) && (y < height),
+            "pixel ({}, {}) is outside an image of {} by {} pixels", x, y, width, height
         );
+        let bytes_per_pixel = self.raw_tga_image().header.bytes_per_pixel();
+        let start = (y * width + x) * bytes_per_pixel;
 
-        // Parse the image data.
-        let slice = &slice[header.colour_map_size()..slice.len()];
-        let image_size = header.width() * header.height() * header.bytes_per_pixel();
-        if slice.len() < image_size {
-            return Err(TgaError::IncompleteImageData(slice.len(), image_size));
+        start..(start + bytes_per_pixel)
+    }
+}
+
+/// Indexing an image by a column and row gives the stored bytes of the pixel
+/// there, as `set_pixel` writes them. Coordinates follow the order the pixels
+/// are stored in, so `image[(0, 0)]` is the first stored pixel. Indexing a
+/// pixel outside the image panics.
+#[cfg(feature = "decode24")]
+impl Index<(usize, usize)> for TgaImage {
+    type Output = [u8];
+
+    fn index(&self, (x, y): (usize, usize)) -> &[u8] {
+        let range = self.pixel_range(x, y);
+
+        &self.image_data()[range]
+    }
+}
+
+/// Indexing an image mutably gives the stored bytes of a pixel to overwrite.
+/// Like `image_data_mut`, it leaves images sharing the pixels unchanged, and
+/// does not check the colour map indices written through it, so a pixel
+/// given an index outside the colour map reads as transparent black.
+#[cfg(feature = "decode24")]
+impl IndexMut<(usize, usize)> for TgaImage {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut [u8] {
+        let range = self.pixel_range(x, y);
+
+        &mut self.image_data_mut()[range]
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl IntoIterator for TgaImage {
+    type Item = [u8; 3];
+    type IntoIter = IntoPixels;
+
+    fn into_iter(self) -> IntoPixels {
+        self.into_pixels()
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl<'a> IntoIterator for &'a TgaImage {
+    type Item = [u8; 3];
+    type IntoIter = PixelIter<'a>;
+
+    fn into_iter(self) -> PixelIter<'a> {
+        self.pixels()
+    }
+}
+
+/// A `Rows` is an iterator over the rows of an image, borrowed from the
+/// image, created by `TgaImage::rows`.
+#[cfg(feature = "decode24")]
+pub struct Rows<'a> {
+    image: &'a RawTgaImage,
+    rows: Range<usize>,
+}
+
+#[cfg(feature = "decode24")]
+impl<'a> Iterator for Rows<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let image = self.image;
+
+        self.rows.next().map(|row| image.row(row))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl<'a> DoubleEndedIterator for Rows<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let image = self.image;
+
+        self.rows.next_back().map(|row| image.row(row))
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl<'a> ExactSizeIterator for Rows<'a> {}
+
+/// An `EnumeratePixels` is an iterator over the pixels of an image together
+/// with their coordinates on the display, created by
+/// `TgaImage::enumerate_pixels`.
+#[cfg(feature = "decode24")]
+pub struct EnumeratePixels<'a> {
+    pixels: PixelIter<'a>,
+    width: u32,
+    height: u32,
+    top_to_bottom: bool,
+    right_to_left: bool,
+    index: u32,
+}
+
+#[cfg(feature = "decode24")]
+impl<'a> Iterator for EnumeratePixels<'a> {
+    type Item = (u32, u32, [u8; 3]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pixel = self.pixels.next()?;
+        let (column, row) = (self.index % self.width, self.index / self.width);
+        let x = if self.right_to_left { self.width - 1 - column } else { column };
+        let y = if self.top_to_bottom { row } else { self.height - 1 - row };
+        self.index += 1;
+
+        Some((x, y, pixel))
+    }
+}
+
+/// A `PixelIterMut` is an iterator over the stored bytes of each pixel of an
+/// image, created by `TgaImage::pixels_mut`.
+#[cfg(feature = "decode24")]
+#[derive(Debug)]
+pub struct PixelIterMut<'a> {
+    chunks: ChunksExactMut<'a, u8>,
+}
+
+#[cfg(feature = "decode24")]
+impl<'a> Iterator for PixelIterMut<'a> {
+    type Item = &'a mut [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl<'a> ExactSizeIterator for PixelIterMut<'a> {}
+
+/// A `Scanline` is a single row of decoded pixels, produced by
+/// `ScanlineIter`. It dereferences to a slice of pixels, so it can be
+/// indexed, measured, and iterated like one.
+#[cfg(feature = "decode24")]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Scanline(Vec<[u8; 3]>);
+
+#[cfg(feature = "decode24")]
+impl Deref for Scanline {
+    type Target = [[u8; 3]];
+
+    fn deref(&self) -> &[[u8; 3]] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl<I: SliceIndex<[[u8; 3]]>> Index<I> for Scanline {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.0[index]
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl IntoIterator for Scanline {
+    type Item = [u8; 3];
+    type IntoIter = vec::IntoIter<[u8; 3]>;
+
+    fn into_iter(self) -> vec::IntoIter<[u8; 3]> {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl<'a> IntoIterator for &'a Scanline {
+    type Item = &'a [u8; 3];
+    type IntoIter = slice::Iter<'a, [u8; 3]>;
+
+    fn into_iter(self) -> slice::Iter<'a, [u8; 3]> {
+        self.0.iter()
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl PartialEq<[u8]> for Scanline {
+    fn eq(&self, rhs: &[u8]) -> bool {
+        if rhs.len() == 3 * self.0.len() {
+            let pixels = &self.0;
+            let rhs_pixels = rhs.chunks(3);
+            return pixels.iter().zip(rhs_pixels).all(
+                |(pixel, rhs_pixel)| { pixel == rhs_pixel }
+            );
         }
 
-        let image_data = Rc::new(
-            slice[0..image_size].iter().map(|&x| x).collect::<Vec<u8>>()
-        );
+        false
+    }
+}
 
-        // Parse the extended image identification information from the end
-        // of the image data field.
-        let mut slice = &slice[image_size..slice.len()];
-        
-        // Check whether the end of the remaining bytes is a TGA image footer.
-        if slice[(slice.len() - 26)..slice.len()] == TGA_FOOTER {
-            slice = &slice[0..(slice.len() - 26)];
+#[cfg(feature = "decode24")]
+pub struct ScanlineIter<'a> {
+    image: &'a RawTgaImage,
+    rows: Range<usize>,
+    /// Whether to take the stored rows from the last one back.
+    bottom_up: bool,
+    /// Whether to reverse the pixels of each row.
+    right_to_left: bool,
+}
+
+#[cfg(feature = "decode24")]
+impl<'a> Iterator for ScanlineIter<'a> {
+    type Item = Scanline;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = if self.bottom_up { self.rows.next_back()? } else { self.rows.next()? };
+        let decoder = self.image.pixel_decoder();
+        let mut scanline = self.image.row(row)
+            .chunks_exact(usize::max(1, decoder.bytes_per_pixel()))
+            .map(|bytes| {
+                let pixel = decoder.decode(bytes);
+                [pixel[0], pixel[1], pixel[2]]
+            })
+            .collect::<Vec<[u8; 3]>>();
+        if self.right_to_left {
+            scanline.reverse();
         }
 
-        let extended_image_identification = Rc::new(
-            slice.iter().map(|&x| x).collect::<Vec<u8>>()
-        );
+        Some(Scanline(scanline))
+    }
 
-        let inner = RawTgaImage::new(
-            header, image_identification, colour_map_data, image_data, extended_image_identification
-        );
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl<'a> ExactSizeIterator for ScanlineIter<'a> {}
+
+/// Check the pixel depth of a true colour image header. True colour images
+/// store 15, 16, 24, or 32 bits per pixel, and any colour map they carry
+/// must have entries of a depth `check_colour_map_depth` accepts.
+#[cfg(feature = "decode24")]
+fn check_true_colour_header(header: &TgaHeader) -> Result<(), TgaError> {
+    match header.bits_per_pixel {
+        15 | 16 | 24 | 32 => check_colour_map_depth(header),
+        bits => Err(TgaError::UnsupportedBitDepth(bits as usize)),
+    }
+}
+
+/// Check the depth of the colour map entries of a header. Any image may
+/// carry a colour map, and its entries must be 15, 16, 24, or 32 bits wide
+/// to be decoded. A header without colour map entries passes whatever
+/// depth it gives.
+#[cfg(feature = "decode24")]
+fn check_colour_map_depth(header: &TgaHeader) -> Result<(), TgaError> {
+    if header.colour_map_length() == 0 {
+        return Ok(());
+    }
+
+    match header.colour_map_depth {
+        15 | 16 | 24 | 32 => Ok(()),
+        bits => Err(TgaError::UnsupportedBitDepth(bits as usize)),
+    }
+}
+
+/// Check the colour map specification of a colour mapped image header. A
+/// colour mapped image must include a colour map, its pixels are 8 or 16 bit
+/// indices, and the colour map entries are 15, 16, 24, or 32 bits wide
+/// independently of the size of the indices.
+#[cfg(feature = "decode24")]
+fn check_colour_mapped_header(header: &TgaHeader) -> Result<(), TgaError> {
+    if (header.color_map_type != 1) || (header.colour_map_length() == 0) {
+        return Err(TgaError::CorruptTgaHeader);
+    }
+
+    match header.bits_per_pixel {
+        8 | 16 => check_colour_map_depth(header),
+        bits => Err(TgaError::UnsupportedBitDepth(bits as usize)),
+    }
+}
 
-        Ok(UncompressedRgb { inner: inner })
+/// Check that a header describes an image the library can decode: an image
+/// without image data, or a colour mapped or true colour image, run length
+/// encoded only with the `rle` feature.
+#[cfg(feature = "decode24")]
+fn check_supported_header(header: &TgaHeader) -> Result<(), TgaError> {
+    match header.data_type_code {
+        0 => check_colour_map_depth(header),
+        1 => check_colour_mapped_header(header),
+        2 => check_true_colour_header(header),
+        #[cfg(feature = "rle")]
+        9 => check_colour_mapped_header(header),
+        #[cfg(feature = "rle")]
+        10 => check_true_colour_header(header),
+        _ => Err(TgaError::Not24BitRgb(header.data_type_code as usize)),
+    }
+}
+
+/// An `UncompressedRgb` image is an unmapped true colour image whose pixel
+/// data is stored uncompressed (type 2).
+#[cfg(feature = "decode24")]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UncompressedRgb {
+    inner: RawTgaImage,
+}
+
+#[cfg(feature = "decode24")]
+impl UncompressedRgb {
+    /// Parse an unmapped uncompressed TGA image from a buffer in memory. 
+    /// We assume that the image to be parsed starts at the beginning of the buffer. 
+    /// In order to parse correctly, the bytes of the buffer must conform to the TGA 
+    /// image format.
+    pub fn parse_from_buffer(buf: &[u8]) -> Result<UncompressedRgb, TgaError> {
+        let header = TgaHeader::parse_from_buffer(buf)?;
+
+        // Check that we were passed the correct TGA header.
+        if header.data_type_code != 2 {
+            return Err(TgaError::Not24BitRgb(header.data_type_code as usize));
+        }
+
+        check_true_colour_header(&header)?;
+        let inner = RawTgaImage::parse_from_buffer(buf, header)?;
+
+        Ok(UncompressedRgb { inner })
     }
 
     /// The function `width` returns the width of a TGA image, in pixels.
     #[inline]
-    pub fn width(&self) -> usize {
-        self.inner.width()
+    pub fn width(&self) -> u32 {
+        self.inner.width() as u32
     }
 
     /// Return the height of a TGA image, in pixels.
     #[inline]
-    pub fn height(&self) -> usize {
-        self.inner.height()
+    pub fn height(&self) -> u32 {
+        self.inner.height() as u32
     }
 
     /// Return the bit depth per pixel in a TGA Image.
@@ -539,7 +1787,7 @@ impl UncompressedRgb {
     }
 
     /// Compute the colour map type. The colour map type is either `0` or `1`.
-    /// A `0` indicates that there is no colour map; a `1` indicates that a 
+    /// A `0` indicates that there is no colour map; a `1` indicates that a
     /// colour map is included.
     #[inline]
     pub fn color_map_type(&self) -> usize {
@@ -558,16 +1806,16 @@ impl UncompressedRgb {
     }
 
     /// The function `pixels` generates an iterator over the pixels of the image.
-    /// It sweeps through the TGA image going from left to right in each row, and 
+    /// It sweeps through the TGA image going from left to right in each row, and
     /// going from bottom to top. The first pixel returned is the bottom left corner;
-    /// the last pixel returned is the top right corner. 
+    /// the last pixel returned is the top right corner.
     #[inline]
-    pub fn pixels(&self) -> PixelIter {
+    pub fn pixels(&self) -> PixelIter<'_> {
         self.inner.pixels()
     }
 
     #[inline]
-    pub fn scanlines(&self) -> ScanlineIter {
+    pub fn scanlines(&self) -> ScanlineIter<'_> {
         self.inner.scanlines()
     }
 
@@ -581,181 +1829,61 @@ impl UncompressedRgb {
         self.inner.image_data_length()
     }
 
-    /// The function `image_data_length_bytes` computes the size of the 
-    /// image data, in the number of bytes. For an unmapped RGB image, this will
-    /// simply be `3 * image_data_length()`, since each RGB pixel is 3 bytes long.
-    #[inline]
-    pub fn image_data_length_bytes(&self) -> usize {
-        self.inner.image_data_length_bytes()
-    }
-
+    /// The function `image_data` returns a slice of the uncompressed pixel data,
+    /// stored exactly as the header describes it.
     #[inline]
-    fn image_data(&self) -> &[u8] {
+    pub fn image_data(&self) -> &[u8] {
         self.inner.image_data()
     }
 
-    /// The function `image_identification` returns a slice into the 
-    /// image identification field. This is a free-form field that immediately
-    /// follows the header.
-    #[inline]
-    pub fn image_identification(&self) -> &[u8] {
-        self.inner.image_identification()
-    }
-
-    /// The function `extended_image_identification` returns a slice to the 
-    /// extended image identification data. This is the data that follows after
-    /// the image data that is too large for the image identification field.
+    /// The function `image_data_length_bytes` computes the size of the
+    /// image data, in the number of bytes. For an unmapped RGB image, this will
+    /// simply be `3 * image_data_length()`, since each RGB pixel is 3 bytes long.
     #[inline]
-    pub fn extended_image_identification(&self) -> &[u8] {
-        self.inner.extended_image_identification()
+    pub fn image_data_length_bytes(&self) -> usize {
+        self.inner.image_data_length_bytes()
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
-pub struct RunLengthEncodedRgb {
+/// An `UncompressedColorMapped` image is a colour mapped image whose pixel
+/// data is stored uncompressed (type 1). Each pixel is an index into the
+/// colour map, and the colour map entries may have a different bit depth
+/// than the pixels themselves.
+#[cfg(feature = "decode24")]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UncompressedColorMapped {
     inner: RawTgaImage,
 }
 
-impl RunLengthEncodedRgb {
-    pub fn parse_from_buffer(buf: &[u8]) -> Result<RunLengthEncodedRgb, TgaError> {
+#[cfg(feature = "decode24")]
+impl UncompressedColorMapped {
+    /// Parse a colour mapped uncompressed TGA image from a buffer in memory.
+    /// Every pixel must refer to an entry of the colour map.
+    pub fn parse_from_buffer(buf: &[u8]) -> Result<UncompressedColorMapped, TgaError> {
         let header = TgaHeader::parse_from_buffer(buf)?;
-        
-        // Determine whether we support the image format. We presently
-        // support 24 bit unmapped RGB images only. They can either be 
-        // uncompressed (type code 2) or run length encoded (type code 10).
-        if header.data_type_code != 10 {
-            return Err(TgaError::Not24BitRgb(header.data_type_code as usize));
-        }
 
-        // Targa 24 images can also be embedded in Targa 32 images, but we do
-        // not implement that.
-        if header.bits_per_pixel != 24 {
+        // Check that we were passed the correct TGA header.
+        if header.data_type_code != 1 {
             return Err(TgaError::Not24BitRgb(header.data_type_code as usize));
         }
 
-        if buf.len() < header.id_length() + TGA_HEADER_LENGTH {
-            return Err(TgaError::CorruptTgaHeader);
-        }
-
-        // Parse the image identification.
-        let slice = &buf[TGA_HEADER_LENGTH..buf.len()];
-        let image_identification = Rc::new(
-            slice[0..header.id_length()].iter().map(|&x| x).collect::<Vec<u8>>()
-        );
-
-        // Parse the colour map data.
-        let slice = &slice[header.id_length()..slice.len()];
-        if slice.len() < header.colour_map_size() {
-            return Err(
-                TgaError::IncompleteColourMap(slice.len(), header.colour_map_size()
-            ));
-        }
-
-        let colour_map_data = Rc::new(
-            slice[0..header.colour_map_size()].iter().map(|&x| x).collect::<Vec<u8>>()
-        );
-
-        // Parse the image data.
-        let slice = &slice[header.colour_map_size()..slice.len()];
-        let image_size = header.width() * header.height() * header.bytes_per_pixel();
-
-        // Search the buffer for all the data packets. Here we count
-        // the number of bytes of image data we have available, and compare it
-        // against the size of the image that the TGA image header claims it is.
-        // Simultaneously, we find where the end of the image data is.
-        let mut slice_i = 0;
-        let mut image_data_found = 0;
-        while (slice_i < slice.len()) && (image_data_found < image_size) {
-            let packet_header = slice[slice_i];
-            // A run length encoded packet never represents a run of zero.
-            // Hence, we add 1 to get the true run length.
-            let packet_length = (packet_header & 0x7F) as usize + 1;
-            if packet_header & 0x80 != 0 {
-                // We have a run length packet.
-                image_data_found += 3 * packet_length;
-                slice_i += 4;
-            } else {
-                // We have a raw packet.
-                image_data_found += 3 * packet_length;
-                slice_i += 3 * packet_length + 1;
-            }
-        }
-
-        if (image_data_found != image_size) || (slice_i > slice.len()) {
-            // Either not enough image data was found, or too much was found.
-            // Either way, the image data is corrupt.
-            return Err(
-                TgaError::IncompleteImageData(image_data_found, image_size)
-            );
-        }
-
-        // The slice of the buffer that's the actual image data we
-        // searched through above.
-        let image_slice = &slice[0..slice_i];
-        slice_i = 0;
-        let mut image_data = vec![0; image_size];
-        let mut i = 0;
-        while i < image_size {
-            let packet_header = image_slice[slice_i];
-            // A run length encoded packet never represents a run of zero.
-            // Hence, we add 1 to get the true run length.
-            let packet_length = (packet_header & 0x7F) as usize + 1;
-            if packet_header & 0x80 != 0 {
-                // We have a run length packet.
-                for _ in 0..packet_length {
-                    image_data[i + 0] = image_slice[slice_i + 1];
-                    image_data[i + 1] = image_slice[slice_i + 2];
-                    image_data[i + 2] = image_slice[slice_i + 3];
-                    i += 3;
-                }
-                // Jump to the next packet.
-                slice_i += 4;
-            } else {
-                // We have a raw packet.
-                for _ in 0..packet_length {
-                    image_data[i + 0] = image_slice[slice_i + 1];
-                    image_data[i + 1] = image_slice[slice_i + 2];
-                    image_data[i + 2] = image_slice[slice_i + 3];
-
-                    i += 3;
-                    // Jump to the next element in the raw packet.
-                    slice_i += 3;
-                }
-                // Jump to the next packet.
-                slice_i += 1;
-            }
-        }
-
-        // Parse the extended image identification information from the end
-        // of the image data field.
-        let mut slice = &slice[slice_i..slice.len()];
-        
-        // Check whether the end of the remaining bytes is a TGA image footer.
-        if slice[(slice.len() - 26)..slice.len()] == TGA_FOOTER {
-            slice = &slice[0..(slice.len() - 26)];
-        }
+        check_colour_mapped_header(&header)?;
+        let inner = RawTgaImage::parse_from_buffer(buf, header)?;
+        inner.validate_colour_map_indices()?;
 
-        let extended_image_identification = Rc::new(
-            slice.iter().map(|&x| x).collect::<Vec<u8>>()
-        );
-
-        let inner = RawTgaImage::new(
-            header, image_identification, colour_map_data, Rc::new(image_data), extended_image_identification
-        );
-
-        Ok(RunLengthEncodedRgb { inner: inner })
+        Ok(UncompressedColorMapped { inner })
     }
 
     /// The function `width` returns the width of a TGA image, in pixels.
     #[inline]
-    pub fn width(&self) -> usize {
-        self.inner.width()
+    pub fn width(&self) -> u32 {
+        self.inner.width() as u32
     }
 
     /// Return the height of a TGA image, in pixels.
     #[inline]
-    pub fn height(&self) -> usize {
-        self.inner.height()
+    pub fn height(&self) -> u32 {
+        self.inner.height() as u32
     }
 
     /// Return the bit depth per pixel in a TGA Image.
@@ -765,7 +1893,7 @@ impl RunLengthEncodedRgb {
     }
 
     /// Compute the colour map type. The colour map type is either `0` or `1`.
-    /// A `0` indicates that there is no colour map; a `1` indicates that a 
+    /// A `0` indicates that there is no colour map; a `1` indicates that a
     /// colour map is included.
     #[inline]
     pub fn color_map_type(&self) -> usize {
@@ -784,16 +1912,16 @@ impl RunLengthEncodedRgb {
     }
 
     /// The function `pixels` generates an iterator over the pixels of the image.
-    /// It sweeps through the TGA image going from left to right in each row, and 
+    /// It sweeps through the TGA image going from left to right in each row, and
     /// going from bottom to top. The first pixel returned is the bottom left corner;
-    /// the last pixel returned is the top right corner. 
+    /// the last pixel returned is the top right corner.
     #[inline]
-    pub fn pixels(&self) -> PixelIter {
+    pub fn pixels(&self) -> PixelIter<'_> {
         self.inner.pixels()
     }
 
     #[inline]
-    pub fn scanlines(&self) -> ScanlineIter {
+    pub fn scanlines(&self) -> ScanlineIter<'_> {
         self.inner.scanlines()
     }
 
@@ -807,57 +1935,115 @@ impl RunLengthEncodedRgb {
         self.inner.image_data_length()
     }
 
+    /// The function `image_data` returns a slice of the uncompressed pixel data,
+    /// stored exactly as the header describes it.
     #[inline]
-    fn image_data(&self) -> &[u8] {
+    pub fn image_data(&self) -> &[u8] {
         self.inner.image_data()
     }
 
-    /// The function `image_data_length_bytes` computes the size of the 
+    /// The function `image_data_length_bytes` computes the size of the
     /// image data, in the number of bytes. For an unmapped RGB image, this will
     /// simply be `3 * image_data_length()`, since each RGB pixel is 3 bytes long.
     #[inline]
     pub fn image_data_length_bytes(&self) -> usize {
         self.inner.image_data_length_bytes()
     }
+}
+
+/// An `EmptyImage` is a file without image data (type 0). Such files serve
+/// as placeholders that carry only a header and metadata. The header may
+/// still give dimensions, but the image itself has no pixels.
+#[cfg(feature = "decode24")]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct EmptyImage {
+    inner: RawTgaImage,
+}
+
+#[cfg(feature = "decode24")]
+impl EmptyImage {
+    /// Parse a TGA file without image data from a buffer in memory. Everything
+    /// after the image identification and colour map is kept as the extended
+    /// image identification.
+    pub fn parse_from_buffer(buf: &[u8]) -> Result<EmptyImage, TgaError> {
+        let header = TgaHeader::parse_from_buffer(buf)?;
+
+        // Check that we were passed the correct TGA header.
+        if header.data_type_code != 0 {
+            return Err(TgaError::Not24BitRgb(header.data_type_code as usize));
+        }
+        check_colour_map_depth(&header)?;
+
+        let inner = RawTgaImage::parse_from_buffer(buf, header)?;
+
+        Ok(EmptyImage { inner })
+    }
 
-    /// The function `image_identification` returns a slice into the 
-    /// image identification field. This is a free-form field that immediately
-    /// follows the header.
+    /// The function `width` returns the width of the image, in pixels, which
+    /// is always zero. The header keeps the width it was given.
     #[inline]
-    pub fn image_identification(&self) -> &[u8] {
-        self.inner.image_identification()
+    pub fn width(&self) -> u32 {
+        self.inner.width() as u32
     }
 
-    /// The function `extended_image_identification` returns a slice to the 
-    /// extended image identification data. This is the data that follows after
-    /// the image data that is too large for the image identification field.
+    /// Return the height of the image, in pixels, which is always zero.
     #[inline]
-    pub fn extended_image_identification(&self) -> &[u8] {
-        self.inner.extended_image_identification()
+    pub fn height(&self) -> u32 {
+        self.inner.height() as u32
+    }
+
+    #[inline]
+    pub fn data_type_code(&self) -> usize {
+        self.inner.data_type_code()
     }
-}
 
+    /// The function `header` produces a copy of the TGA header.
+    #[inline]
+    pub fn header(&self) -> TgaHeader {
+        self.inner.header()
+    }
+}
 
-/// A `TgaImage` is a structure containing a TGA image. This data type 
-/// can represent either 24 bit uncompressed RGB images, or 24 bit
-/// run-length encoded RGB images.
-#[derive(PartialEq, Eq, Debug)]
+/// A `TgaImage` is a structure containing a TGA image. This data type
+/// can represent true colour images of 15, 16, 24, or 32 bits per pixel, and
+/// colour mapped images, each either uncompressed or run length encoded, as
+/// well as files without image data.
+#[cfg(feature = "decode24")]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum TgaImage {
+    Empty(EmptyImage),
+    Type01(UncompressedColorMapped),
     Type02(UncompressedRgb),
+    #[cfg(feature = "rle")]
+    Type09(RunLengthEncodedColorMapped),
+    #[cfg(feature = "rle")]
     Type10(RunLengthEncodedRgb),
 }
 
+#[cfg(feature = "decode24")]
 impl TgaImage {
     pub fn parse_from_buffer(buf: &[u8]) -> Result<TgaImage, TgaError> {
         let header = TgaHeader::parse_from_buffer(buf)?;
 
         // Determine whether we support the image format. We presently
-        // support 24 bit unmapped RGB images only. They can either be 
-        // uncompressed (type code 2) or run length encoded (type code 10).
+        // support colour mapped images (type codes 1 and 9) and unmapped RGB
+        // images (type codes 2 and 10), either uncompressed or run length encoded,
+        // and files without image data (type code 0).
         match header.data_type_code {
-            2 => UncompressedRgb::parse_from_buffer(buf).map(|image| { 
+            0 => EmptyImage::parse_from_buffer(buf).map(|image| {
+                TgaImage::Empty(image)
+            }),
+            1 => UncompressedColorMapped::parse_from_buffer(buf).map(|image| {
+                TgaImage::Type01(image)
+            }),
+            2 => UncompressedRgb::parse_from_buffer(buf).map(|image| {
                 TgaImage::Type02(image)
             }),
+            #[cfg(feature = "rle")]
+            9 => RunLengthEncodedColorMapped::parse_from_buffer(buf).map(|image| {
+                TgaImage::Type09(image)
+            }),
+            #[cfg(feature = "rle")]
             10 => RunLengthEncodedRgb::parse_from_buffer(buf).map(|image| {
                 TgaImage::Type10(image)
             }),
@@ -865,76 +2051,186 @@ impl TgaImage {
         }
     }
 
+    /// The function `parse_named` parses an image from a buffer like
+    /// `parse_from_buffer`, labelling any error with the name of its source,
+    /// such as the path the buffer was read from.
+    pub fn parse_named(buf: &[u8], source: &str) -> Result<TgaImage, TgaError> {
+        Self::parse_from_buffer(buf).map_err(|err| err.named(source))
+    }
+
     pub fn parse_from_file<F: io::Read>(f: &mut F) -> Result<TgaImage, TgaError> {
         let mut buf = Vec::new();
-        f.read_to_end(&mut buf).unwrap();
+        f.read_to_end(&mut buf).map_err(|err| TgaError::Io(Box::new(err)))?;
         Self::parse_from_buffer(&buf)
     }
 
+    /// The function `open` reads and decodes the TGA file at the given path.
+    /// Failing to open or read the file is reported as an I/O error.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<TgaImage, TgaError> {
+        let mut file = File::open(path).map_err(|err| TgaError::Io(Box::new(err)))?;
+        Self::parse_from_file(&mut file)
+    }
+
     /// The function `width` returns the width of a TGA image, in pixels.
-    pub fn width(&self) -> usize {
-        match self {
-            &TgaImage::Type02(ref image) => image.width(),
-            &TgaImage::Type10(ref image) => image.width()
-        }
+    /// Dimensions are reported as `u32` throughout the library, although a
+    /// TGA header stores them in 16 bits.
+    pub fn width(&self) -> u32 {
+        self.raw_tga_image().width() as u32
     }
 
     /// Return the height of a TGA image, in pixels.
-    pub fn height(&self) -> usize {
-        match self {
-            &TgaImage::Type02(ref image) => image.height(),
-            &TgaImage::Type10(ref image) => image.height()
-        }
+    pub fn height(&self) -> u32 {
+        self.raw_tga_image().height() as u32
     }
 
     /// Return the bit depth per pixel in a TGA Image.
     pub fn bits_per_pixel(&self) -> usize {
-        match self {
-            &TgaImage::Type02(ref image) => image.bits_per_pixel(),
-            &TgaImage::Type10(ref image) => image.bits_per_pixel()
-        }
+        self.raw_tga_image().bits_per_pixel()
     }
 
     /// Compute the colour map type. The colour map type is either `0` or `1`.
     /// A `0` indicates that there is no colour map; a `1` indicates that a 
     /// colour map is included.
     pub fn color_map_type(&self) -> usize {
-        match self {
-            &TgaImage::Type02(ref image) => image.color_map_type(),
-            &TgaImage::Type10(ref image) => image.color_map_type()
-        }
+        self.raw_tga_image().color_map_type()
     }
 
     pub fn data_type_code(&self) -> usize {
-        match self {
-            &TgaImage::Type02(ref image) => image.data_type_code(),
-            &TgaImage::Type10(ref image) => image.data_type_code()
-        }
+        self.raw_tga_image().data_type_code()
     }
 
     /// The function `header` produces a copy of the TGA header.
     pub fn header(&self) -> TgaHeader {
-        match self {
-            &TgaImage::Type02(ref image) => image.header(),
-            &TgaImage::Type10(ref image) => image.header()
-        }
+        self.raw_tga_image().header()
     }
 
     /// The function `pixels` generates an iterator over the pixels of the image.
     /// It sweeps through the TGA image going from left to right in each row, and 
     /// going from bottom to top. The first pixel returned is the bottom left corner;
     /// the last pixel returned is the top right corner. 
-    pub fn pixels(&self) -> PixelIter {
-        match self {
-            &TgaImage::Type02(ref image) => image.pixels(),
-            &TgaImage::Type10(ref image) => image.pixels()
+    pub fn pixels(&self) -> PixelIter<'_> {
+        self.raw_tga_image().pixels()
+    }
+
+    /// The function `scanlines` generates an iterator over the rows of the
+    /// image as decoded pixels, in the order they are stored. Each scanline
+    /// is collected into its own vector; `rows` borrows the stored bytes of
+    /// each row instead.
+    pub fn scanlines(&self) -> ScanlineIter<'_> {
+        self.raw_tga_image().scanlines()
+    }
+
+    /// The function `scanlines_top_down` generates an iterator over the rows
+    /// of the image as decoded pixels in display order: the top row first,
+    /// with the pixels of each row from left to right, whichever corner the
+    /// pixels are stored from.
+    pub fn scanlines_top_down(&self) -> ScanlineIter<'_> {
+        self.raw_tga_image().scanlines_top_down()
+    }
+
+    /// The function `pixel_refs` generates an iterator over references to the
+    /// pixels of a 24 bit true colour image, borrowed straight from its image
+    /// data rather than decoded and copied as `pixels` does. Pixels come in
+    /// the same order as `pixels` yields them. Colour mapped images fail with
+    /// `Not24BitRgb`, and true colour images of other depths with
+    /// `UnsupportedBitDepth`, since their stored bytes are not pixels of this
+    /// form.
+    pub fn pixel_refs(&self) -> Result<PixelRefs<'_>, TgaError> {
+        Ok(PixelRefs { chunks: self.true_colour_image_data(24)?.chunks_exact(3) })
+    }
+
+    /// The function `as_bgr_pixels` returns the image data of a 24 bit true
+    /// colour image as a slice of pixels, each in blue, green, and red order,
+    /// without copying it. It fails the same way as `pixel_refs`.
+    pub fn as_bgr_pixels(&self) -> Result<&[[u8; 3]], TgaError> {
+        Ok(as_pixel_slice(self.true_colour_image_data(24)?))
+    }
+
+    /// The function `as_bgra_pixels` returns the image data of a 32 bit true
+    /// colour image as a slice of pixels, each in blue, green, red, and alpha
+    /// order, without copying it. Colour mapped images fail with
+    /// `Not24BitRgb`, and true colour images of other depths with
+    /// `UnsupportedBitDepth`.
+    pub fn as_bgra_pixels(&self) -> Result<&[[u8; 4]], TgaError> {
+        Ok(as_pixel_slice(self.true_colour_image_data(32)?))
+    }
+
+    /// The image data of a true colour image with pixels of the given depth.
+    /// An image without pixels passes whatever its depth.
+    fn true_colour_image_data(&self, bits: usize) -> Result<&[u8], TgaError> {
+        let header = self.raw_tga_image().header;
+        if header.is_colour_mapped() {
+            return Err(TgaError::Not24BitRgb(header.data_type_code as usize));
+        }
+        if (header.bits_per_pixel() != bits) && (self.image_data_length() > 0) {
+            return Err(TgaError::UnsupportedBitDepth(header.bits_per_pixel()));
+        }
+
+        Ok(self.image_data())
+    }
+
+    /// The function `into_pixels` consumes the image and generates an
+    /// iterator over its pixels, in the same order as `pixels`. The iterator
+    /// owns the pixel data, so it can outlive any borrow of the image.
+    pub fn into_pixels(self) -> IntoPixels {
+        let image = match self {
+            TgaImage::Empty(image) => image.inner,
+            TgaImage::Type01(image) => image.inner,
+            TgaImage::Type02(image) => image.inner,
+            #[cfg(feature = "rle")]
+            TgaImage::Type09(image) => image.inner,
+            #[cfg(feature = "rle")]
+            TgaImage::Type10(image) => image.inner,
+        };
+        let back = image.image_data_length();
+
+        IntoPixels { image, front: 0, back }
+    }
+
+    /// The function `rows` generates an iterator over the rows of the image,
+    /// in the order they are stored, as slices of the stored bytes borrowed
+    /// directly from the image, so iterating allocates nothing. Each row
+    /// holds `width` pixels in the bit depth given by the header, as
+    /// `image_data` stores them, so a colour mapped image yields colour map
+    /// indices.
+    pub fn rows(&self) -> Rows<'_> {
+        let image = self.raw_tga_image();
+
+        Rows { image, rows: 0..image.height() }
+    }
+
+    /// The function `row` returns the stored bytes of a single row of the
+    /// image, borrowed directly from the image, or `None` when the image has
+    /// no such row. Rows are numbered in the order they are stored, the same
+    /// order `rows` yields them: row zero is the top row of the image when
+    /// the image descriptor records rows stored from top to bottom, and the
+    /// bottom row otherwise. Within a row, pixels run from left to right
+    /// unless the image descriptor records them stored from right to left.
+    pub fn row(&self, y: u32) -> Option<&[u8]> {
+        let image = self.raw_tga_image();
+        if y as usize >= image.height() {
+            return None;
         }
+
+        Some(image.row(y as usize))
     }
 
-    pub fn scanlines(&self) -> ScanlineIter {
-        match self {
-            &TgaImage::Type02(ref image) => image.scanlines(),
-            &TgaImage::Type10(ref image) => image.scanlines()
+    /// The function `enumerate_pixels` generates an iterator over the pixels
+    /// of the image together with their coordinates on the display, with
+    /// `(0, 0)` the top left corner of the image, as `from_fn` numbers them.
+    /// Pixels come in the order `pixels` yields them, and the coordinates
+    /// follow the orientation recorded in the image descriptor, so a bottom
+    /// to top image starts at the bottom row.
+    pub fn enumerate_pixels(&self) -> EnumeratePixels<'_> {
+        let image_descriptor = self.raw_tga_image().header.image_descriptor;
+
+        EnumeratePixels {
+            pixels: self.pixels(),
+            width: self.width(),
+            height: self.height(),
+            top_to_bottom: image_descriptor & 0x20 != 0,
+            right_to_left: image_descriptor & 0x10 != 0,
+            index: 0,
         }
     }
 
@@ -944,117 +2240,220 @@ impl TgaImage {
     /// self.image_data_length() == self.width() * self.height()
     /// ```
     pub fn image_data_length(&self) -> usize {
-        match self {
-            &TgaImage::Type02(ref image) => image.image_data_length(),
-            &TgaImage::Type10(ref image) => image.image_data_length()
-        }
+        self.raw_tga_image().image_data_length()
     }
 
     /// The function `image_data_length_bytes` computes the size of the 
     /// image data, in the number of bytes. For an unmapped RGB image, this will
     /// simply be `3 * image_data_length()`, since each RGB pixel is 3 bytes long.
     pub fn image_data_length_bytes(&self) -> usize {
-        match self {
-            &TgaImage::Type02(ref image) => image.image_data_length_bytes(),
-            &TgaImage::Type10(ref image) => image.image_data_length_bytes()
-        }
+        self.raw_tga_image().image_data_length_bytes()
     }
 
+    /// The function `image_data` returns a slice of the uncompressed pixel data,
+    /// stored exactly as the header describes it.
     #[inline]
     pub fn image_data(&self) -> &[u8] {
-        match self {
-            &TgaImage::Type02(ref image) => image.image_data(),
-            &TgaImage::Type10(ref image) => image.image_data()
+        self.raw_tga_image().image_data()
+    }
+
+    /// The function `pixels_mut` generates an iterator over the stored bytes
+    /// of each pixel of the image, in the bit depth given by the header, so
+    /// the pixels of a colour mapped image are colour map indices. Pixels are
+    /// visited in the order they are stored, the same order `pixels` yields
+    /// them. Like `image_data_mut`, it leaves images sharing the pixels
+    /// unchanged.
+    pub fn pixels_mut(&mut self) -> PixelIterMut<'_> {
+        let bytes_per_pixel = usize::max(1, self.raw_tga_image().header.bytes_per_pixel());
+
+        PixelIterMut { chunks: self.image_data_mut().chunks_exact_mut(bytes_per_pixel) }
+    }
+
+    /// The function `image_data_mut` gives mutable access to the uncompressed
+    /// pixel data, stored exactly as `image_data` returns it. An image
+    /// sharing its pixels with other images, such as a view, gets its own
+    /// copy of them first, so the other images are left unchanged. Colour map
+    /// indices written this way are not checked, and a pixel whose index is
+    /// outside the colour map reads as transparent black; `set_pixel` checks
+    /// them.
+    #[inline]
+    pub fn image_data_mut(&mut self) -> &mut [u8] {
+        self.raw_tga_image_mut().image_data_mut()
+    }
+
+    /// The function `set_pixel` overwrites the stored bytes of a single pixel,
+    /// in the bit depth given by the header, so a colour mapped image takes a
+    /// colour map index. Coordinates follow the order the pixels are stored
+    /// in, the same order `pixels` yields them, so `(0, 0)` is the first
+    /// stored pixel. Coordinates outside the image fail with
+    /// `RegionOutOfBounds`, a pixel of the wrong length with
+    /// `IncompleteImageData`, and a colour map index outside the colour map
+    /// with `InvalidColourMapIndex`. Like `image_data_mut`, it leaves images
+    /// sharing the pixels unchanged.
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: &[u8]) -> Result<(), TgaError> {
+        if (x >= self.width()) || (y >= self.height()) {
+            return Err(TgaError::RegionOutOfBounds(x, y, 1, 1));
+        }
+        let bytes_per_pixel = self.raw_tga_image().header.bytes_per_pixel();
+        if pixel.len() != bytes_per_pixel {
+            return Err(TgaError::IncompleteImageData(pixel.len(), bytes_per_pixel));
+        }
+        if self.raw_tga_image().header.is_colour_mapped() {
+            check_colour_map_index(&self.raw_tga_image().header, pixel)?;
+        }
+        let start = (y as usize * self.width() as usize + x as usize) * bytes_per_pixel;
+        self.image_data_mut()[start..(start + bytes_per_pixel)].copy_from_slice(pixel);
+
+        Ok(())
+    }
+
+    /// The function `stored_coordinates` translates the coordinates of a
+    /// pixel on the display, with `(0, 0)` the top left corner, into the
+    /// coordinates it is stored at, following the orientation recorded in the
+    /// image descriptor. The translation is its own inverse.
+    fn stored_coordinates(&self, x: u32, y: u32) -> (u32, u32) {
+        let image_descriptor = self.raw_tga_image().header.image_descriptor;
+        let stored_x = if image_descriptor & 0x10 != 0 { self.width() - 1 - x } else { x };
+        let stored_y = if image_descriptor & 0x20 != 0 { y } else { self.height() - 1 - y };
+
+        (stored_x, stored_y)
+    }
+
+    /// The function `pixel_at` returns the colour of the pixel at a position
+    /// on the display, with `(0, 0)` the top left corner of the image, as
+    /// `from_fn` and `enumerate_pixels` number them, whichever corner the
+    /// pixels are stored from. It returns `None` outside the image.
+    pub fn pixel_at(&self, x: u32, y: u32) -> Option<[u8; 3]> {
+        if (x >= self.width()) || (y >= self.height()) {
+            return None;
         }
+        let (stored_x, stored_y) = self.stored_coordinates(x, y);
+
+        Some(self.raw_tga_image().pixel(stored_y as usize * self.width() as usize + stored_x as usize))
     }
 
-    /// The function `image_identification` returns a slice into the 
-    /// image identification field. This is a free-form field that immediately
-    /// follows the header.
-    pub fn image_identification(&self) -> &[u8] {
-        match self {
-            &TgaImage::Type02(ref image) => image.image_identification(),
-            &TgaImage::Type10(ref image) => image.image_identification()
+    /// The function `set_pixel_at` overwrites the stored bytes of the pixel
+    /// at a position on the display, with `(0, 0)` the top left corner of the
+    /// image, whichever corner the pixels are stored from. It is otherwise the
+    /// same as `set_pixel`, and fails the same way.
+    pub fn set_pixel_at(&mut self, x: u32, y: u32, pixel: &[u8]) -> Result<(), TgaError> {
+        if (x >= self.width()) || (y >= self.height()) {
+            return Err(TgaError::RegionOutOfBounds(x, y, 1, 1));
         }
+        let (stored_x, stored_y) = self.stored_coordinates(x, y);
+
+        self.set_pixel(stored_x, stored_y, pixel)
     }
 
-    /// The function `extended_image_identification` returns a slice to the 
-    /// extended image identification data. This is the data that follows after
-    /// the image data that is too large for the image identification field.
-    pub fn extended_image_identification(&self) -> &[u8] {
-        match self {
-            &TgaImage::Type02(ref image) => image.extended_image_identification(),
-            &TgaImage::Type10(ref image) => image.extended_image_identification()
+    pub(crate) fn raw_tga_image(&self) -> &RawTgaImage {
+        match *self {
+            TgaImage::Empty(ref image) => &image.inner,
+            TgaImage::Type01(ref image) => &image.inner,
+            TgaImage::Type02(ref image) => &image.inner,
+            #[cfg(feature = "rle")]
+            TgaImage::Type09(ref image) => &image.inner,
+            #[cfg(feature = "rle")]
+            TgaImage::Type10(ref image) => &image.inner,
         }
     }
 
-    fn raw_tga_image(&self) -> &RawTgaImage {
-        match self {
-            &TgaImage::Type02(ref image) => &image.inner,
-            &TgaImage::Type10(ref image) => &image.inner
+    pub(crate) fn raw_tga_image_mut(&mut self) -> &mut RawTgaImage {
+        match *self {
+            TgaImage::Empty(ref mut image) => &mut image.inner,
+            TgaImage::Type01(ref mut image) => &mut image.inner,
+            TgaImage::Type02(ref mut image) => &mut image.inner,
+            #[cfg(feature = "rle")]
+            TgaImage::Type09(ref mut image) => &mut image.inner,
+            #[cfg(feature = "rle")]
+            TgaImage::Type10(ref mut image) => &mut image.inner,
         }
     }
 }
 
 
+/// A `TgaReader` reads an image back out as the bytes of a TGA file, sharing
+/// the buffers of the image instead of serializing it up front.
+#[cfg(feature = "encode")]
 pub struct TgaReader {
-    buffer: [Rc<Vec<u8>>; 6],
-    bytes_read_from_buffer: [usize; 6],
+    buffer: [Arc<Vec<u8>>; 5],
+    bytes_read_from_buffer: [usize; 5],
     index: usize,
     total_bytes_read: usize,
 }
 
-impl TgaReader{
+#[cfg(feature = "encode")]
+impl TgaReader {
+    /// The function `new` creates a reader over an image written as an
+    /// uncompressed TGA file. A run length encoded image is written with its
+    /// pixel data decompressed, under the header of the matching uncompressed
+    /// image type (type 1 or type 2), so the bytes always form a valid file.
     pub fn new(image: &TgaImage) -> TgaReader {
-        let header = image.header();
-        let header_array = Rc::new(vec![
-            header.id_length,
-            header.color_map_type,
-            header.data_type_code,
-            header.colour_map_origin[0], header.colour_map_origin[1],
-            header.colour_map_length[0], header.colour_map_length[1],
-            header.colour_map_depth,
-            header.x_origin[0], header.x_origin[1],
-            header.y_origin[0], header.y_origin[1],
-            header.width[0], header.width[1],
-            header.height[0], header.height[1],
-            header.bits_per_pixel,
-            header.image_descriptor,
-        ]);
+        let inner = image.raw_tga_image();
+        let mut header = inner.header;
+        header.data_type_code &= !0x08;
+
+        TgaReader::from_parts(header, inner, inner.shared_image_data())
+    }
 
+    /// The function `compressed` creates a reader over an image written as a
+    /// run length encoded TGA file (type 9 or type 10). The pixel data is
+    /// packed one scanline at a time, the same way the encoder packs it.
+    #[cfg(feature = "rle")]
+    pub fn compressed(image: &TgaImage) -> TgaReader {
         let inner = image.raw_tga_image();
-        let footer = Rc::new(TGA_FOOTER.to_vec());
+        let mut header = inner.header;
+        if header.has_image_data() {
+            header.data_type_code |= 0x08;
+        }
+        let mut packets = Vec::new();
+        for scanline in inner.rows() {
+            rle::encode_rle_scanline(scanline, header.bytes_per_pixel(), &mut packets);
+        }
+
+        TgaReader::from_parts(header, inner, Arc::new(packets))
+    }
+
+    /// Assemble a reader from a header, the other parts of an image, and the
+    /// pixel data to write. The areas following the pixel data are written
+    /// again the way the encoder writes them, since the offsets of the footer
+    /// kept from the source file point into that file. A file too large for
+    /// the offsets of a footer keeps the trailing bytes without one.
+    fn from_parts(header: TgaHeader, inner: &RawTgaImage, image_data: Arc<Vec<u8>>) -> TgaReader {
+        let position = TGA_HEADER_LENGTH + inner.image_identification.len()
+            + inner.colour_map_data.len() + image_data.len();
+        let alpha = header.image_descriptor & 0x0F != 0;
+        let mut trailer = Vec::new();
+        if inner.write_trailing_areas(&mut trailer, position, alpha, &EncoderOptions::default(), None).is_err() {
+            trailer = inner.extended_image_identification[..inner.trailing_data_length()].to_vec();
+        }
 
         TgaReader {
             buffer: [
-                header_array, 
+                Arc::new(header.to_bytes().to_vec()),
                 inner.image_identification.clone(),
                 inner.colour_map_data.clone(),
-                inner.image_data.clone(),
-                inner.extended_image_identification.clone(),
-                footer,
+                image_data,
+                Arc::new(trailer),
             ],
-            bytes_read_from_buffer: [0; 6],
+            bytes_read_from_buffer: [0; 5],
             index: 0,
             total_bytes_read: 0,
         }
     }
 }
 
+#[cfg(feature = "encode")]
 impl io::Read for TgaReader {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut bytes_written = 0;
         while (self.index < self.buffer.len()) && (bytes_written < buf.len()) {
             let diff = self.buffer[self.index].len() - self.bytes_read_from_buffer[self.index];
             let bytes_read_from_buffer = self.bytes_read_from_buffer[self.index];
-            let bytes_to_be_written = match diff <= buf.len() {
-                true => diff,
-                false => buf.len(),
-            };
+            let bytes_to_be_written = usize::min(diff, buf.len() - bytes_written);
 
-            for i in 0..bytes_to_be_written {
-                buf[bytes_written + i] = self.buffer[self.index][bytes_read_from_buffer + i];
-            }
+            buf[bytes_written..(bytes_written + bytes_to_be_written)].copy_from_slice(
+                &self.buffer[self.index][bytes_read_from_buffer..(bytes_read_from_buffer + bytes_to_be_written)]
+            );
 
             bytes_written += bytes_to_be_written;
             self.bytes_read_from_buffer[self.index] += bytes_to_be_written;
@@ -1068,3 +2467,149 @@ impl io::Read for TgaReader {
         Ok(bytes_written)
     }
 }
+
+
+/// A `TgaWriter` assembles an image from the bytes of a TGA file written into
+/// it, so a file can be copied into it with `io::copy`. The file is validated
+/// as it arrives: the header as soon as it is complete, and then the pixel
+/// data packet by packet, including the colour map indices of a colour mapped
+/// image. A write whose bytes fail validation returns an `InvalidData` error
+/// and none of its bytes are kept, so the writer is left as it was before the
+/// write. The bytes following the pixel data, such as an extension area, are
+/// only checked by `finish`.
+#[cfg(feature = "decode24")]
+#[derive(Clone, Debug, Default)]
+pub struct TgaWriter {
+    buffer: Vec<u8>,
+    header: Option<TgaHeader>,
+    /// The position in the buffer of the first byte of pixel data that has
+    /// not been validated yet.
+    validated: usize,
+    /// The number of pixels validated so far.
+    pixels: usize,
+}
+
+#[cfg(feature = "decode24")]
+impl TgaWriter {
+    /// The function `new` creates a writer with no bytes written to it.
+    pub fn new() -> TgaWriter {
+        TgaWriter::default()
+    }
+
+    /// The function `bytes_written` returns the number of bytes written so far.
+    pub fn bytes_written(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// The function `finish` parses the bytes written into an image. It fails
+    /// in the same way as `TgaImage::parse_from_buffer` on the same bytes.
+    pub fn finish(self) -> Result<TgaImage, TgaError> {
+        TgaImage::parse_from_buffer(&self.buffer)
+    }
+
+    /// Validate as much of the buffer as has arrived.
+    fn validate(&mut self) -> Result<(), TgaError> {
+        let header = match self.header {
+            Some(header) => header,
+            None if self.buffer.len() < TGA_HEADER_LENGTH => return Ok(()),
+            None => {
+                let header = TgaHeader::parse_from_buffer(&self.buffer)?;
+                check_supported_header(&header)?;
+                self.header = Some(header);
+                self.validated = TGA_HEADER_LENGTH + header.id_length() + header.colour_map_size();
+                header
+            }
+        };
+
+        #[cfg(feature = "rle")]
+        {
+            if header.is_run_length_encoded() {
+                return self.validate_packets(header);
+            }
+        }
+        self.validate_pixels(header)
+    }
+
+    /// The number of pixels of the image being written.
+    fn pixel_count(header: &TgaHeader) -> usize {
+        if header.has_image_data() { header.width() * header.height() } else { 0 }
+    }
+
+    /// Check the stored pixels that arrived since the last validation.
+    fn check_pixels(header: &TgaHeader, pixels: &[u8]) -> Result<(), TgaError> {
+        if header.is_colour_mapped() {
+            for bytes in pixels.chunks_exact(header.bytes_per_pixel()) {
+                check_colour_map_index(header, bytes)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate the complete pixels of uncompressed pixel data.
+    fn validate_pixels(&mut self, header: TgaHeader) -> Result<(), TgaError> {
+        let bytes_per_pixel = header.bytes_per_pixel();
+        if (bytes_per_pixel == 0) || (self.validated >= self.buffer.len()) {
+            return Ok(());
+        }
+        let available = (self.buffer.len() - self.validated) / bytes_per_pixel;
+        let count = usize::min(available, Self::pixel_count(&header) - self.pixels);
+        let end = self.validated + count * bytes_per_pixel;
+        Self::check_pixels(&header, &self.buffer[self.validated..end])?;
+        self.validated = end;
+        self.pixels += count;
+
+        Ok(())
+    }
+
+    /// Validate the complete packets of run length encoded pixel data.
+    #[cfg(feature = "rle")]
+    fn validate_packets(&mut self, header: TgaHeader) -> Result<(), TgaError> {
+        let bytes_per_pixel = header.bytes_per_pixel();
+        let pixel_count = Self::pixel_count(&header);
+        while (self.pixels < pixel_count) && (self.validated < self.buffer.len()) {
+            // A packet cut short by the end of the buffer is validated once
+            // the rest of it arrives.
+            let (packet, packet_size) = match lowlevel::parse_packet(&self.buffer[self.validated..], bytes_per_pixel) {
+                Ok(parsed) => parsed,
+                Err(_) => break,
+            };
+            let count = packet.pixel_count(bytes_per_pixel);
+            if self.pixels + count > pixel_count {
+                return Err(TgaError::IncompleteImageData(
+                    (self.pixels + count) * bytes_per_pixel, pixel_count * bytes_per_pixel
+                ));
+            }
+            match packet {
+                lowlevel::Packet::Run { pixel, .. } => Self::check_pixels(&header, pixel)?,
+                lowlevel::Packet::Raw { pixels } => Self::check_pixels(&header, pixels)?,
+            }
+            self.validated += packet_size;
+            self.pixels += count;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "decode24")]
+impl io::Write for TgaWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let (length, header, validated, pixels) = (self.buffer.len(), self.header, self.validated, self.pixels);
+        self.buffer.extend_from_slice(buf);
+        if let Err(err) = self.validate() {
+            self.buffer.truncate(length);
+            self.header = header;
+            self.validated = validated;
+            self.pixels = pixels;
+
+            return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+//! # Low Level Primitives
+//!
+//! The `lowlevel` module exposes the building blocks of the TGA format as pure
+//! functions over slices, for tools that need to work with TGA files without
+//! constructing full image objects. Header serialization is always available,
+//! and the run length encoding primitives need the `rle` feature.
+use crate::{TgaError, TgaHeader, TGA_HEADER_LENGTH};
+
+
+/// The function `parse_header` parses a TGA header from the start of a buffer.
+pub fn parse_header(buf: &[u8]) -> Result<TgaHeader, TgaError> {
+    TgaHeader::parse_from_buffer(buf)
+}
+
+/// The function `write_header` serializes a TGA header into its 18 byte
+/// on-disk representation. This is the inverse of `parse_header`.
+pub fn write_header(header: TgaHeader) -> [u8; TGA_HEADER_LENGTH] {
+    header.to_bytes()
+}
+
+/// A single packet of run length encoded pixel data.
+#[cfg(feature = "rle")]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Packet<'a> {
+    /// A run of `length` copies of a single pixel.
+    Run { length: usize, pixel: &'a [u8] },
+    /// A sequence of pixels stored as they are.
+    Raw { pixels: &'a [u8] },
+}
+
+#[cfg(feature = "rle")]
+impl<'a> Packet<'a> {
+    /// The number of pixels the packet represents.
+    pub fn pixel_count(&self, bytes_per_pixel: usize) -> usize {
+        match *self {
+            Packet::Run { length, .. } => length,
+            Packet::Raw { pixels } => pixels.len() / bytes_per_pixel,
+        }
+    }
+}
+
+/// The function `parse_packet` parses a single run length encoded packet from
+/// the start of a buffer. It returns the packet together with the number of
+/// bytes of the buffer the packet occupies.
+#[cfg(feature = "rle")]
+pub fn parse_packet(buf: &[u8], bytes_per_pixel: usize) -> Result<(Packet<'_>, usize), TgaError> {
+    let packet_header = match buf.first() {
+        Some(&packet_header) => packet_header,
+        None => return Err(TgaError::IncompleteImageData(0, 1)),
+    };
+    // A run length encoded packet never represents a run of zero.
+    // Hence, we add 1 to get the true run length.
+    let length = (packet_header & 0x7F) as usize + 1;
+    let packet_size = if packet_header & 0x80 != 0 {
+        1 + bytes_per_pixel
+    } else {
+        1 + bytes_per_pixel * length
+    };
+    if buf.len() < packet_size {
+        return Err(TgaError::IncompleteImageData(buf.len(), packet_size));
+    }
+
+    let packet = if packet_header & 0x80 != 0 {
+        Packet::Run { length, pixel: &buf[1..packet_size] }
+    } else {
+        Packet::Raw { pixels: &buf[1..packet_size] }
+    };
+
+    Ok((packet, packet_size))
+}
+
+/// The function `decode_rle` decompresses run length encoded pixel data from
+/// the start of a buffer until `image_size` bytes of pixel data have been
+/// produced. It returns the pixel data together with the number of bytes of
+/// the buffer the packets occupied.
+#[cfg(feature = "rle")]
+pub fn decode_rle(
+    buf: &[u8], image_size: usize, bytes_per_pixel: usize
+) -> Result<(Vec<u8>, usize), TgaError> {
+    crate::rle::decode_rle(buf, image_size, bytes_per_pixel)
+}
+
+/// The function `encode_rle_scanline` run length encodes a single scanline of
+/// pixel data, appending the packets to `buf`.
+#[cfg(feature = "rle")]
+pub fn encode_rle_scanline(scanline: &[u8], bytes_per_pixel: usize, buf: &mut Vec<u8>) {
+    crate::rle::encode_rle_scanline(scanline, bytes_per_pixel, buf)
+}
+
+/// A problem found in a packet of run length encoded pixel data.
+#[cfg(feature = "rle")]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PacketProblem {
+    /// The packet starts in scanline `row` and runs `overrun` pixels into the
+    /// scanlines after it. The TGA 2.0 specification asks encoders to end
+    /// packets at scanline boundaries, but this crate decodes such packets.
+    OverrunsScanline { row: usize, overrun: usize },
+    /// The packet runs `excess` pixels past the last pixel of the image.
+    ExceedsImage { excess: usize },
+    /// The packet needs `needed` bytes but only `found` are left in the buffer.
+    Truncated { needed: usize, found: usize },
+    /// The buffer ends on a packet boundary `missing` pixels short of the image.
+    MissingPixels { missing: usize },
+    /// There are `length` bytes left after the packet completing the image.
+    TrailingBytes { length: usize },
+}
+
+/// A `PacketIssue` locates a problem in a stream of run length encoded
+/// packets.
+#[cfg(feature = "rle")]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PacketIssue {
+    /// The byte offset within the buffer of the packet the problem belongs to.
+    pub offset: usize,
+    /// The index, in stored order, of the first pixel of the packet.
+    pub pixel: usize,
+    /// The problem found.
+    pub problem: PacketProblem,
+}
+
+/// The function `validate_rle_stream` walks the run length encoded packets of
+/// the pixel data at the start of a buffer and reports every problem it finds
+/// with them, rather than stopping at the first as decoding does. The image
+/// dimensions and pixel depth are taken from `header`. The buffer should end
+/// where the pixel data ends, so any extension area, developer area, or footer
+/// should be cut off first, or it is reported as trailing bytes. An empty
+/// result means the stream decodes to exactly the image with every packet
+/// inside a single scanline. Problems after the image is complete are
+/// reported with the pixel index one past the last pixel of the image.
+#[cfg(feature = "rle")]
+pub fn validate_rle_stream(buf: &[u8], header: TgaHeader) -> Vec<PacketIssue> {
+    let bytes_per_pixel = header.bytes_per_pixel();
+    let width = header.width();
+    let pixel_count = width * header.height();
+    let mut issues = Vec::new();
+    if bytes_per_pixel == 0 {
+        return issues;
+    }
+
+    let mut offset = 0;
+    let mut pixel = 0;
+    while pixel < pixel_count {
+        let packet_header = match buf.get(offset) {
+            Some(&packet_header) => packet_header,
+            None => {
+                let problem = PacketProblem::MissingPixels { missing: pixel_count - pixel };
+                issues.push(PacketIssue { offset, pixel, problem });
+                return issues;
+            }
+        };
+        let length = (packet_header & 0x7F) as usize + 1;
+        let packet_size = if packet_header & 0x80 != 0 {
+            1 + bytes_per_pixel
+        } else {
+            1 + bytes_per_pixel * length
+        };
+        let found = buf.len() - offset;
+        if found < packet_size {
+            let problem = PacketProblem::Truncated { needed: packet_size, found };
+            issues.push(PacketIssue { offset, pixel, problem });
+            return issues;
+        }
+
+        let end = pixel + length;
+        let row_end = (pixel / width + 1) * width;
+        if end > row_end && row_end < pixel_count {
+            let overrun = end.min(pixel_count) - row_end;
+            let problem = PacketProblem::OverrunsScanline { row: pixel / width, overrun };
+            issues.push(PacketIssue { offset, pixel, problem });
+        }
+        if end > pixel_count {
+            let problem = PacketProblem::ExceedsImage { excess: end - pixel_count };
+            issues.push(PacketIssue { offset, pixel, problem });
+        }
+        offset += packet_size;
+        pixel = end;
+    }
+
+    if offset < buf.len() {
+        let problem = PacketProblem::TrailingBytes { length: buf.len() - offset };
+        issues.push(PacketIssue { offset, pixel: pixel_count, problem });
+    }
+
+    issues
+}
@@ -0,0 +1,278 @@
+//! # Metadata
+//!
+//! The `metadata` module gives access to the parts of a TGA file that describe
+//! the image rather than make it up, such as the image identification, and
+//! the author comments, timestamp, and software identification of the
+//! extension area.
+#[cfg(feature = "rle")]
+use crate::{RunLengthEncodedColorMapped, RunLengthEncodedRgb};
+use std::sync::Arc;
+use crate::{EmptyImage, RawTgaImage, TgaError, TgaImage, Timestamp, UncompressedColorMapped, UncompressedRgb};
+
+
+/// The maximum length of the image identification field, in bytes.
+const MAX_IMAGE_IDENTIFICATION_LENGTH: usize = 255;
+
+/// The position of the author comments within the extension area, and the
+/// number and length of their lines, counting the terminating null bytes.
+const AUTHOR_COMMENTS_OFFSET: usize = 43;
+const AUTHOR_COMMENT_LINES: usize = 4;
+const AUTHOR_COMMENT_LINE_LENGTH: usize = 81;
+
+/// The position of the timestamp within the extension area.
+const TIMESTAMP_OFFSET: usize = 367;
+
+/// The positions of the software identification, counting its terminating
+/// null byte, and the software version within the extension area.
+const SOFTWARE_ID_OFFSET: usize = 426;
+const SOFTWARE_ID_LENGTH: usize = 41;
+const SOFTWARE_VERSION_OFFSET: usize = 467;
+
+/// Read a text field of the extension area, which ends at its first null
+/// byte, replacing bytes that are not UTF-8.
+fn read_text(field: &[u8]) -> String {
+    let length = field.iter().position(|&byte| byte == 0).unwrap_or(field.len());
+
+    String::from_utf8_lossy(&field[0..length]).into_owned()
+}
+
+impl RawTgaImage {
+    /// The function `image_identification` returns a slice into the
+    /// image identification field. This is a free-form field that immediately
+    /// follows the header.
+    #[inline]
+    fn image_identification(&self) -> &[u8] {
+        &self.image_identification
+    }
+
+    /// The function `extended_image_identification` returns a slice to the
+    /// extended image identification data. This is the data that follows after
+    /// the image data that is too large for the image identification field.
+    #[inline]
+    fn extended_image_identification(&self) -> &[u8] {
+        &self.extended_image_identification
+    }
+
+    /// The lines of author comments recorded in the extension area of the
+    /// image.
+    fn author_comments(&self) -> Option<[String; AUTHOR_COMMENT_LINES]> {
+        let position = self.extension_area_position()? + AUTHOR_COMMENTS_OFFSET;
+        let line = |i: usize| {
+            let start = position + i * AUTHOR_COMMENT_LINE_LENGTH;
+            read_text(&self.extended_image_identification[start..(start + AUTHOR_COMMENT_LINE_LENGTH)])
+        };
+
+        Some([line(0), line(1), line(2), line(3)])
+    }
+
+    /// The timestamp recorded in the extension area of the image. A
+    /// timestamp of all zeros means the time is not recorded.
+    fn timestamp(&self) -> Option<Timestamp> {
+        let position = self.extension_area_position()? + TIMESTAMP_OFFSET;
+        let bytes = &self.extended_image_identification[position..(position + 12)];
+        let field = |i: usize| u16::from_le_bytes([bytes[2 * i], bytes[2 * i + 1]]);
+        if bytes.iter().all(|&byte| byte == 0) {
+            return None;
+        }
+
+        Some(Timestamp {
+            month: field(0),
+            day: field(1),
+            year: field(2),
+            hour: field(3),
+            minute: field(4),
+            second: field(5),
+        })
+    }
+
+    /// The software identification recorded in the extension area of the
+    /// image.
+    fn software_id(&self) -> Option<String> {
+        let position = self.extension_area_position()? + SOFTWARE_ID_OFFSET;
+        let software_id = read_text(&self.extended_image_identification[position..(position + SOFTWARE_ID_LENGTH)]);
+        if software_id.is_empty() {
+            return None;
+        }
+
+        Some(software_id)
+    }
+
+    /// The software version recorded in the extension area of the image. A
+    /// version number of zero means the version is not recorded.
+    fn software_version(&self) -> Option<(u16, u8)> {
+        let position = self.extension_area_position()? + SOFTWARE_VERSION_OFFSET;
+        let bytes = &self.extended_image_identification[position..(position + 3)];
+        let version_number = u16::from_le_bytes([bytes[0], bytes[1]]);
+        if version_number == 0 {
+            return None;
+        }
+
+        Some((version_number, bytes[2]))
+    }
+
+    /// Replace the image identification field, keeping the header in step.
+    fn set_image_identification(&mut self, image_identification: &[u8]) -> Result<(), TgaError> {
+        if image_identification.len() > MAX_IMAGE_IDENTIFICATION_LENGTH {
+            return Err(TgaError::ImageIdentificationTooLong(image_identification.len()));
+        }
+
+        self.header.id_length = image_identification.len() as u8;
+        self.image_identification = Arc::new(image_identification.to_vec());
+
+        Ok(())
+    }
+}
+
+impl EmptyImage {
+    /// The function `image_identification` returns a slice into the
+    /// image identification field. This is a free-form field that immediately
+    /// follows the header.
+    #[inline]
+    pub fn image_identification(&self) -> &[u8] {
+        self.inner.image_identification()
+    }
+
+    /// The function `extended_image_identification` returns a slice to the
+    /// extended image identification data. This is everything that follows
+    /// the image identification and colour map, since there is no image data.
+    #[inline]
+    pub fn extended_image_identification(&self) -> &[u8] {
+        self.inner.extended_image_identification()
+    }
+}
+
+impl UncompressedRgb {
+    /// The function `image_identification` returns a slice into the
+    /// image identification field. This is a free-form field that immediately
+    /// follows the header.
+    #[inline]
+    pub fn image_identification(&self) -> &[u8] {
+        self.inner.image_identification()
+    }
+
+    /// The function `extended_image_identification` returns a slice to the
+    /// extended image identification data. This is the data that follows after
+    /// the image data that is too large for the image identification field.
+    #[inline]
+    pub fn extended_image_identification(&self) -> &[u8] {
+        self.inner.extended_image_identification()
+    }
+}
+
+#[cfg(feature = "rle")]
+impl RunLengthEncodedRgb {
+    /// The function `image_identification` returns a slice into the
+    /// image identification field. This is a free-form field that immediately
+    /// follows the header.
+    #[inline]
+    pub fn image_identification(&self) -> &[u8] {
+        self.inner.image_identification()
+    }
+
+    /// The function `extended_image_identification` returns a slice to the
+    /// extended image identification data. This is the data that follows after
+    /// the image data that is too large for the image identification field.
+    #[inline]
+    pub fn extended_image_identification(&self) -> &[u8] {
+        self.inner.extended_image_identification()
+    }
+}
+
+impl UncompressedColorMapped {
+    /// The function `image_identification` returns a slice into the
+    /// image identification field. This is a free-form field that immediately
+    /// follows the header.
+    #[inline]
+    pub fn image_identification(&self) -> &[u8] {
+        self.inner.image_identification()
+    }
+
+    /// The function `extended_image_identification` returns a slice to the
+    /// extended image identification data. This is the data that follows after
+    /// the image data that is too large for the image identification field.
+    #[inline]
+    pub fn extended_image_identification(&self) -> &[u8] {
+        self.inner.extended_image_identification()
+    }
+}
+
+#[cfg(feature = "rle")]
+impl RunLengthEncodedColorMapped {
+    /// The function `image_identification` returns a slice into the
+    /// image identification field. This is a free-form field that immediately
+    /// follows the header.
+    #[inline]
+    pub fn image_identification(&self) -> &[u8] {
+        self.inner.image_identification()
+    }
+
+    /// The function `extended_image_identification` returns a slice to the
+    /// extended image identification data. This is the data that follows after
+    /// the image data that is too large for the image identification field.
+    #[inline]
+    pub fn extended_image_identification(&self) -> &[u8] {
+        self.inner.extended_image_identification()
+    }
+}
+
+impl TgaImage {
+    /// The function `image_identification` returns a slice into the 
+    /// image identification field. This is a free-form field that immediately
+    /// follows the header.
+    pub fn image_identification(&self) -> &[u8] {
+        self.raw_tga_image().image_identification()
+    }
+
+    /// The function `extended_image_identification` returns every byte of the
+    /// file the image was read from that follows the image data, unclassified:
+    /// the developer area, the extension area, the footer, and any unknown
+    /// bytes. The functions `developer_area`, `extension_area`, and
+    /// `unknown_trailing_bytes` return each of these on its own.
+    pub fn extended_image_identification(&self) -> &[u8] {
+        self.raw_tga_image().extended_image_identification()
+    }
+
+    /// The function `set_image_identification` replaces the image
+    /// identification field, which is written into files encoded from the
+    /// image, such as a watermark or a build identifier. The field holds at
+    /// most 255 bytes, so a longer identification is rejected.
+    pub fn set_image_identification(&mut self, image_identification: &[u8]) -> Result<(), TgaError> {
+        self.raw_tga_image_mut().set_image_identification(image_identification)
+    }
+
+    /// The function `author_comments` returns the four lines of author
+    /// comments recorded in the extension area of the file the image was read
+    /// from, with bytes that are not UTF-8 replaced. Lines the file leaves
+    /// blank are empty strings. It returns `None` when the file has no
+    /// extension area.
+    pub fn author_comments(&self) -> Option<[String; 4]> {
+        self.raw_tga_image().author_comments()
+    }
+
+    /// The function `timestamp` returns the date and time the file the image
+    /// was read from was saved, as recorded in its extension area. The fields
+    /// are returned as the file records them, without checking that they
+    /// form a valid date; with the `time` feature, converting the timestamp
+    /// into a `time::PrimitiveDateTime` checks them. It returns `None` when
+    /// the file has no extension area or leaves the timestamp blank.
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        self.raw_tga_image().timestamp()
+    }
+
+    /// The function `software_id` returns the name of the software that
+    /// saved the file the image was read from, as recorded in its extension
+    /// area, with bytes that are not UTF-8 replaced. It returns `None` when
+    /// the file has no extension area or leaves the name blank.
+    pub fn software_id(&self) -> Option<String> {
+        self.raw_tga_image().software_id()
+    }
+
+    /// The function `software_version` returns the version of the software
+    /// that saved the file the image was read from, as recorded in its
+    /// extension area: the version number multiplied by 100, and a version
+    /// letter, so version 4.17b is `(417, b'b')`. It returns `None` when the
+    /// file has no extension area or records a version number of zero.
+    pub fn software_version(&self) -> Option<(u16, u8)> {
+        self.raw_tga_image().software_version()
+    }
+}
@@ -0,0 +1,78 @@
+//! # Palettes
+//!
+//! The `palette` module contains the `Palette` type, which holds the colours
+//! of a colour map independently of any image, for writing colour mapped
+//! images.
+use crate::{encode_pixel, PixelDepth};
+
+
+/// A `Palette` is a list of colours for a colour mapped image, together with
+/// the depth its entries are stored in and the index of its first entry.
+/// Colours are given as blue, green, red, and alpha channels, in the order
+/// TGA files store them.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Palette {
+    colours: Vec<[u8; 4]>,
+    depth: PixelDepth,
+    origin: u16,
+}
+
+impl Palette {
+    /// Construct a palette from its colours and the depth to store them in.
+    /// The first colour has index zero.
+    pub fn new(colours: Vec<[u8; 4]>, depth: PixelDepth) -> Palette {
+        Palette { colours, depth, origin: 0 }
+    }
+
+    /// Construct a palette of opaque 24 bit colours from blue, green, and red
+    /// channels.
+    pub fn from_bgr(colours: &[[u8; 3]]) -> Palette {
+        let colours = colours.iter().map(|colour| [colour[0], colour[1], colour[2], 255]).collect();
+
+        Palette::new(colours, PixelDepth::Bits24)
+    }
+
+    /// Change the index of the first colour of the palette. Pixels refer to the
+    /// colour at position `i` of the palette with the index `origin + i`.
+    pub fn with_origin(mut self, origin: u16) -> Palette {
+        self.origin = origin;
+        self
+    }
+
+    /// The colours of the palette, as blue, green, red, and alpha channels.
+    pub fn colours(&self) -> &[[u8; 4]] {
+        &self.colours
+    }
+
+    /// The depth the colours are stored in.
+    pub fn depth(&self) -> PixelDepth {
+        self.depth
+    }
+
+    /// The index of the first colour of the palette.
+    pub fn origin(&self) -> u16 {
+        self.origin
+    }
+
+    /// The number of colours in the palette.
+    pub fn len(&self) -> usize {
+        self.colours.len()
+    }
+
+    /// Determine whether the palette has no colours.
+    pub fn is_empty(&self) -> bool {
+        self.colours.is_empty()
+    }
+
+    /// Serialize the colours into colour map entries.
+    pub(crate) fn to_colour_map(&self) -> Vec<u8> {
+        let bits = self.depth.bits();
+        let entry_bytes = bits.div_ceil(8);
+        let mut colour_map = vec![0; self.colours.len() * entry_bytes];
+        for (&colour, entry) in self.colours.iter().zip(colour_map.chunks_mut(entry_bytes)) {
+            encode_pixel(colour, bits, entry);
+        }
+
+        colour_map
+    }
+}
@@ -0,0 +1,43 @@
+//! # Parallel Transforms
+//!
+//! The `parallel` module spreads per pixel work over the threads of the
+//! `rayon` thread pool. The pixel data is split into scanlines, and each
+//! thread converts whole scanlines, so no two threads ever touch the same
+//! bytes.
+use rayon::prelude::*;
+use std::sync::Arc;
+use crate::{decode_pixel, encode_pixel, TgaImage};
+
+
+impl TgaImage {
+    /// The function `par_map_pixels_in_place` applies a function to the
+    /// colour of every pixel of the image, converting the scanlines in
+    /// parallel. The colour is given as blue, green, red, and alpha channels,
+    /// as `apply_colour_transform` gives it, and the result is stored back in
+    /// the depth of the image. For a colour mapped image, the function is
+    /// applied to each colour map entry instead, which is too little work to
+    /// be worth splitting up.
+    pub fn par_map_pixels_in_place<F>(&mut self, f: F) where F: Fn([u8; 4]) -> [u8; 4] + Sync + Send {
+        let image = self.raw_tga_image_mut();
+        if image.header.is_colour_mapped() {
+            let bits = image.header.colour_map_depth();
+            let bytes_per_pixel = bits.div_ceil(8);
+            for bytes in Arc::make_mut(&mut image.colour_map_data).chunks_mut(bytes_per_pixel) {
+                encode_pixel(f(decode_pixel(bytes, bits)), bits, bytes);
+            }
+            return;
+        }
+
+        let bits = image.header.bits_per_pixel();
+        let bytes_per_pixel = image.header.bytes_per_pixel();
+        let row_length = image.row_length();
+        if row_length == 0 {
+            return;
+        }
+        image.image_data_mut().par_chunks_mut(row_length).for_each(|scanline| {
+            for bytes in scanline.chunks_mut(bytes_per_pixel) {
+                encode_pixel(f(decode_pixel(bytes, bits)), bits, bytes);
+            }
+        });
+    }
+}
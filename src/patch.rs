@@ -0,0 +1,87 @@
+//! # Patching Files
+//!
+//! The `patch` module writes local edits to an image back into the TGA file
+//! the image was read from, rewriting only the pixels inside the regions that
+//! changed. Touching up a few pixels of a large uncompressed file then costs
+//! as much as the touch up, not as much as the file.
+use crate::{TgaError, TgaHeader, TgaImage, TGA_HEADER_LENGTH};
+
+
+/// A `DirtyRect` is a rectangular region of an image whose pixels have been
+/// modified. Coordinates follow the order the pixels are stored in, the same
+/// order `pixels` yields them, so `(0, 0)` is the first stored pixel.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DirtyRect {
+    /// The column of the first pixel of the region.
+    pub x: u32,
+    /// The row of the first pixel of the region.
+    pub y: u32,
+    /// The width of the region, in pixels.
+    pub width: u32,
+    /// The height of the region, in pixels.
+    pub height: u32,
+}
+
+impl TgaImage {
+    /// The function `patch_in_place` copies the pixels inside the dirty
+    /// rectangles of the image into the pixel data of an uncompressed TGA
+    /// file, leaving every other byte of the file alone. The file must store
+    /// its pixels the way the image does: uncompressed, with the same
+    /// dimensions, pixel depth, and orientation, or the function fails with
+    /// `LayoutMismatch`. Only pixels are patched, so changes to the colour
+    /// map or the metadata of the image are not written. Rectangles that do
+    /// not lie within the image are rejected with `RegionOutOfBounds` before
+    /// anything is written.
+    pub fn patch_in_place(&self, buf: &mut [u8], dirty_rects: &[DirtyRect]) -> Result<(), TgaError> {
+        let image = self.raw_tga_image();
+        let header = TgaHeader::parse_from_buffer(buf)?;
+        let same_layout = header.has_image_data()
+            && !header.is_run_length_encoded()
+            && (header.is_colour_mapped() == image.header.is_colour_mapped())
+            && (header.width() == image.width())
+            && (header.height() == image.height())
+            && (header.bits_per_pixel() == image.header.bits_per_pixel())
+            && (header.image_descriptor & 0x30 == image.header.image_descriptor & 0x30);
+        if !same_layout {
+            return Err(TgaError::LayoutMismatch);
+        }
+
+        let data_offset = TGA_HEADER_LENGTH + header.id_length() + header.colour_map_size();
+        let data_length = image.image_data_length_bytes();
+        let found = buf.len().saturating_sub(data_offset);
+        if found < data_length {
+            return Err(TgaError::IncompleteImageData(found, data_length));
+        }
+        let fits = |start: u32, length: u32, limit: usize| {
+            start.checked_add(length).is_some_and(|end| end as usize <= limit)
+        };
+        for rect in dirty_rects.iter() {
+            if !fits(rect.x, rect.width, image.width()) || !fits(rect.y, rect.height, image.height()) {
+                return Err(TgaError::RegionOutOfBounds(rect.x, rect.y, rect.width, rect.height));
+            }
+        }
+
+        let bytes_per_pixel = image.header.bytes_per_pixel();
+        let row_length = image.row_length();
+        for rect in dirty_rects.iter() {
+            let start = rect.x as usize * bytes_per_pixel;
+            let end = (rect.x + rect.width) as usize * bytes_per_pixel;
+            for y in (rect.y as usize)..((rect.y + rect.height) as usize) {
+                let position = data_offset + y * row_length;
+                buf[(position + start)..(position + end)].copy_from_slice(&image.row(y)[start..end]);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The function `encode_patched` returns a copy of an uncompressed TGA
+    /// file with the pixels inside the dirty rectangles of the image written
+    /// into it, as `patch_in_place` writes them.
+    pub fn encode_patched(&self, original: &[u8], dirty_rects: &[DirtyRect]) -> Result<Vec<u8>, TgaError> {
+        let mut buf = original.to_vec();
+        self.patch_in_place(&mut buf, dirty_rects)?;
+
+        Ok(buf)
+    }
+}
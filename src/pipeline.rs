@@ -0,0 +1,246 @@
+//! # Pipelines
+//!
+//! The `pipeline` module chains image operations together and runs them in a
+//! single pass over the pixel data. The geometric operations of a pipeline
+//! only decide which source pixel lands where, and the colour operations only
+//! change the colour of a pixel, so every output pixel can be produced by
+//! reading one source pixel and applying the colour operations to it once.
+use std::convert::TryFrom;
+use std::rc::Rc;
+use std::sync::Arc;
+use crate::transforms::adjustment_table;
+use crate::{
+    decode_pixel, encode_pixel, Brightness, ChannelOrder, ColourTransform, Contrast, Gamma, RawTgaImage, TgaError,
+    TgaImage,
+};
+
+
+/// An operation that moves pixels, applied along one axis at a time.
+#[derive(Copy, Clone, Debug)]
+enum GeometricOperation {
+    FlipHorizontal,
+    FlipVertical,
+    Resize(u32, u32),
+}
+
+/// An operation that changes the colour of each pixel independently.
+#[derive(Clone, Debug)]
+enum ColourOperation {
+    Swizzle(ChannelOrder),
+    Adjust(Box<[u8; 256]>),
+    Transform(Rc<dyn ColourTransform>),
+}
+
+impl ColourOperation {
+    /// Apply the operation to a colour given as blue, green, red, and alpha.
+    fn apply(&self, colour: [u8; 4]) -> [u8; 4] {
+        match *self {
+            ColourOperation::Swizzle(ChannelOrder::Bgr) => colour,
+            ColourOperation::Swizzle(ChannelOrder::Rgb) => [colour[2], colour[1], colour[0], colour[3]],
+            ColourOperation::Adjust(ref table) => {
+                [table[colour[0] as usize], table[colour[1] as usize], table[colour[2] as usize], colour[3]]
+            }
+            ColourOperation::Transform(ref transform) => transform.transform(colour),
+        }
+    }
+}
+
+/// A `Pipeline` is a sequence of operations on an image, created by
+/// `TgaImage::pipeline`. Nothing happens until `collect` runs the whole
+/// sequence in a single pass, producing a new image without any intermediate
+/// images in between.
+#[derive(Clone, Debug)]
+pub struct Pipeline<'a> {
+    image: &'a TgaImage,
+    geometric_operations: Vec<GeometricOperation>,
+    colour_operations: Vec<ColourOperation>,
+}
+
+/// Map each output position along one axis to the source position it is read
+/// from, by taking every output position back through the operations. There
+/// is no map when a resize has to sample pixels from an empty axis.
+fn axis_map(length: u32, operations: &[Option<GeometricOperation>], horizontal: bool) -> Option<Vec<usize>> {
+    let mut lengths = vec![length];
+    for operation in operations.iter() {
+        let current = *lengths.last().unwrap();
+        let next = match *operation {
+            Some(GeometricOperation::Resize(width, height)) => if horizontal { width } else { height },
+            _ => current,
+        };
+        if (current == 0) && (next > 0) {
+            return None;
+        }
+        lengths.push(next);
+    }
+
+    let output_length = *lengths.last().unwrap();
+    let map = (0..output_length as u64).map(|position| {
+        let mut position = position;
+        for (operation, &input_length) in operations.iter().zip(lengths.iter()).rev() {
+            let input_length = input_length as u64;
+            position = match *operation {
+                Some(GeometricOperation::Resize(width, height)) => {
+                    let output_length = if horizontal { width } else { height } as u64;
+                    // Sample the source pixel under the centre of the output pixel.
+                    ((2 * position + 1) * input_length) / (2 * output_length)
+                }
+                Some(_) => input_length - 1 - position,
+                None => position,
+            };
+        }
+
+        position as usize
+    });
+
+    Some(map.collect())
+}
+
+impl<'a> Pipeline<'a> {
+    /// Flip the image so its left and right sides swap.
+    pub fn flip_horizontal(mut self) -> Pipeline<'a> {
+        self.geometric_operations.push(GeometricOperation::FlipHorizontal);
+        self
+    }
+
+    /// Flip the image so its first and last rows swap.
+    pub fn flip_vertical(mut self) -> Pipeline<'a> {
+        self.geometric_operations.push(GeometricOperation::FlipVertical);
+        self
+    }
+
+    /// Resize the image to `width` by `height` pixels, sampling the nearest
+    /// source pixel for each output pixel.
+    pub fn resize(mut self, width: u32, height: u32) -> Pipeline<'a> {
+        self.geometric_operations.push(GeometricOperation::Resize(width, height));
+        self
+    }
+
+    /// Reorder the colour channels of every pixel. The `Rgb` order swaps the
+    /// red and blue channels, and the `Bgr` order leaves them alone.
+    pub fn swizzle(mut self, order: ChannelOrder) -> Pipeline<'a> {
+        self.colour_operations.push(ColourOperation::Swizzle(order));
+        self
+    }
+
+    /// Apply a brightness, contrast, and gamma adjustment, as `TgaImage::adjust` does.
+    pub fn adjust(mut self, brightness: Brightness, contrast: Contrast, gamma: Gamma) -> Pipeline<'a> {
+        let table = adjustment_table(brightness, contrast, gamma, false);
+        self.colour_operations.push(ColourOperation::Adjust(Box::new(table)));
+        self
+    }
+
+    /// Convert every colour with a colour transform supplied by the caller,
+    /// such as a conversion from the colour space of the source image into
+    /// the colour space of the output.
+    pub fn colour_transform<T: ColourTransform + 'static>(mut self, transform: T) -> Pipeline<'a> {
+        self.colour_operations.push(ColourOperation::Transform(Rc::new(transform)));
+        self
+    }
+
+    /// Apply the colour operations of the pipeline to a colour.
+    fn apply_colour_operations(&self, colour: [u8; 4]) -> [u8; 4] {
+        self.colour_operations.iter().fold(colour, |colour, operation| operation.apply(colour))
+    }
+
+    /// The function `collect` runs the pipeline and returns the new image. The
+    /// image keeps the type, orientation, and metadata of the source image. For
+    /// a colour mapped image, the colour operations change the colour map, and
+    /// the geometric operations move the colour map indices. The function
+    /// fails when the output dimensions do not fit in a TGA header, or when a
+    /// pixel has to be sampled from an image without pixels.
+    pub fn collect(self) -> Result<TgaImage, TgaError> {
+        let source = self.image.raw_tga_image();
+        let horizontal = self.geometric_operations.iter()
+            .map(|&operation| match operation {
+                GeometricOperation::FlipVertical => None,
+                operation => Some(operation),
+            })
+            .collect::<Vec<_>>();
+        let vertical = self.geometric_operations.iter()
+            .map(|&operation| match operation {
+                GeometricOperation::FlipHorizontal => None,
+                operation => Some(operation),
+            })
+            .collect::<Vec<_>>();
+        let output_length = |operations: &[Option<GeometricOperation>], length: usize, horizontal: bool| {
+            operations.iter().fold(length as u32, |length, operation| match *operation {
+                Some(GeometricOperation::Resize(width, height)) => if horizontal { width } else { height },
+                _ => length,
+            })
+        };
+        let output_width = output_length(&horizontal, source.width(), true);
+        let output_height = output_length(&vertical, source.height(), false);
+        let (header_width, header_height) = match (u16::try_from(output_width), u16::try_from(output_height)) {
+            (Ok(header_width), Ok(header_height)) => (header_width, header_height),
+            _ => return Err(TgaError::InvalidDimensions(output_width, output_height)),
+        };
+        let x_map = axis_map(source.width() as u32, &horizontal, true);
+        let y_map = axis_map(source.height() as u32, &vertical, false);
+        let (x_map, y_map) = match (x_map, y_map) {
+            (Some(x_map), Some(y_map)) => (x_map, y_map),
+            _ => return Err(TgaError::InvalidDimensions(output_width, output_height)),
+        };
+        let (width, height) = (x_map.len(), y_map.len());
+
+        let mut header = source.header;
+        header.width = header_width.to_le_bytes();
+        header.height = header_height.to_le_bytes();
+
+        // Colour mapped images keep their indices, so their colour operations
+        // apply to the colour map instead of the pixels.
+        let colour_mapped = header.is_colour_mapped();
+        let mut colour_map_data = source.colour_map_data.clone();
+        if colour_mapped && !self.colour_operations.is_empty() {
+            let bits = header.colour_map_depth();
+            let colour_map = Arc::make_mut(&mut colour_map_data);
+            for entry in colour_map.chunks_mut(bits.div_ceil(8)) {
+                encode_pixel(self.apply_colour_operations(decode_pixel(entry, bits)), bits, entry);
+            }
+        }
+        let recolour_pixels = !colour_mapped && !self.colour_operations.is_empty();
+
+        let bits = header.bits_per_pixel();
+        let bytes_per_pixel = header.bytes_per_pixel();
+        let rows = source.rows().collect::<Vec<&[u8]>>();
+        let mut image_data = vec![0; width * height * bytes_per_pixel];
+        if width > 0 {
+            for (row, &y) in image_data.chunks_mut(width * bytes_per_pixel).zip(y_map.iter()) {
+                let source_row = rows[y];
+                for (pixel, &x) in row.chunks_mut(bytes_per_pixel).zip(x_map.iter()) {
+                    let source_pixel = &source_row[(x * bytes_per_pixel)..((x + 1) * bytes_per_pixel)];
+                    if recolour_pixels {
+                        encode_pixel(self.apply_colour_operations(decode_pixel(source_pixel, bits)), bits, pixel);
+                    } else {
+                        pixel.copy_from_slice(source_pixel);
+                    }
+                }
+            }
+        }
+
+        let mut inner = RawTgaImage::new(
+            header,
+            source.image_identification.clone(),
+            colour_map_data,
+            Arc::new(image_data),
+            source.extended_image_identification.clone(),
+        );
+        inner.trailing_position = source.trailing_position;
+
+        Ok(self.image.with_raw_tga_image(inner))
+    }
+}
+
+impl TgaImage {
+    /// The function `pipeline` starts a pipeline of operations on the image.
+    /// The operations run when the pipeline is collected, in a single pass
+    /// over the pixel data. Geometric operations work on the pixels in the
+    /// order they are stored, so `flip_vertical` swaps the first and last
+    /// stored rows.
+    pub fn pipeline(&self) -> Pipeline<'_> {
+        Pipeline {
+            image: self,
+            geometric_operations: Vec::new(),
+            colour_operations: Vec::new(),
+        }
+    }
+}
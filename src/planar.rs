@@ -0,0 +1,150 @@
+//! # Planar Layout
+//!
+//! The `planar` module converts images between the interleaved layout TGA
+//! files store, where the channels of each pixel sit next to each other, and
+//! a planar layout with one buffer per channel, which video encoders and
+//! machine learning pipelines expect.
+use std::ops::Range;
+#[cfg(feature = "encode")]
+use crate::{PixelDepth, TgaError, TgaImageBuilder};
+use crate::{RawTgaImage, TgaImage};
+
+
+/// The `Planes` of an image hold each colour channel in a buffer of its own,
+/// one byte per pixel, with the pixels in the same order in every buffer.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Planes {
+    /// The red channel.
+    pub r: Vec<u8>,
+    /// The green channel.
+    pub g: Vec<u8>,
+    /// The blue channel.
+    pub b: Vec<u8>,
+    /// The alpha channel, for images with one.
+    pub a: Option<Vec<u8>>,
+}
+
+/// A `Channel` is one colour channel of the pixels of an image.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Channel {
+    /// The red channel.
+    Red,
+    /// The green channel.
+    Green,
+    /// The blue channel.
+    Blue,
+    /// The alpha channel. Pixels without alpha are opaque, so images without
+    /// attribute bits have an alpha channel of 255 throughout.
+    Alpha,
+}
+
+impl Channel {
+    /// The position of the channel within a decoded colour, whose channels
+    /// are in blue, green, red, and alpha order.
+    fn index(self) -> usize {
+        match self {
+            Channel::Blue => 0,
+            Channel::Green => 1,
+            Channel::Red => 2,
+            Channel::Alpha => 3,
+        }
+    }
+}
+
+/// A `ChannelIter` is an iterator over a single channel of the pixels of an
+/// image, created by `TgaImage::channel`.
+pub struct ChannelIter<'a> {
+    image: &'a RawTgaImage,
+    pixels: Range<usize>,
+    index: usize,
+}
+
+impl<'a> Iterator for ChannelIter<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        self.pixels.next().map(|pixel| self.image.colour(pixel)[self.index])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pixels.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for ChannelIter<'a> {
+    fn next_back(&mut self) -> Option<u8> {
+        self.pixels.next_back().map(|pixel| self.image.colour(pixel)[self.index])
+    }
+}
+
+impl<'a> ExactSizeIterator for ChannelIter<'a> {}
+
+impl TgaImage {
+    /// The function `channel` generates an iterator over a single channel of
+    /// the colours of the image, in the order the pixels are stored, the same
+    /// order `pixels` yields them. It gives the same bytes as the matching
+    /// plane of `to_planar`, without building the other planes.
+    pub fn channel(&self, channel: Channel) -> ChannelIter<'_> {
+        let image = self.raw_tga_image();
+
+        ChannelIter { image, pixels: 0..image.image_data_length(), index: channel.index() }
+    }
+
+    /// The function `to_planar` splits the colours of the image into planes,
+    /// in the order the pixels are stored, the same order `pixels` yields
+    /// them. The image has an alpha plane when its image descriptor records
+    /// attribute bits. The planes are filled in a single pass over the
+    /// pixels, whatever their depth.
+    pub fn to_planar(&self) -> Planes {
+        let image = self.raw_tga_image();
+        let decoder = image.pixel_decoder();
+        let pixel_count = image.width() * image.height();
+        let alpha = image.header.image_descriptor & 0x0F != 0;
+        let mut planes = Planes {
+            r: Vec::with_capacity(pixel_count),
+            g: Vec::with_capacity(pixel_count),
+            b: Vec::with_capacity(pixel_count),
+            a: if alpha { Some(Vec::with_capacity(pixel_count)) } else { None },
+        };
+        for pixel in image.rows().flat_map(|row| row.chunks(decoder.bytes_per_pixel())) {
+            let colour = decoder.decode(pixel);
+            planes.b.push(colour[0]);
+            planes.g.push(colour[1]);
+            planes.r.push(colour[2]);
+            if let Some(ref mut a) = planes.a {
+                a.push(colour[3]);
+            }
+        }
+
+        planes
+    }
+
+    /// The function `from_planar` interleaves planes into a true colour
+    /// image, 32 bits deep when there is an alpha plane and 24 bits deep
+    /// otherwise. The rows of the planes run from the top of the image to the
+    /// bottom, and each plane must hold exactly `width * height` bytes.
+    #[cfg(feature = "encode")]
+    pub fn from_planar(width: u32, height: u32, planes: &Planes) -> Result<TgaImage, TgaError> {
+        let pixel_count = width as usize * height as usize;
+        let alpha = planes.a.as_deref();
+        for plane in [&planes.r[..], &planes.g[..], &planes.b[..]].iter().chain(alpha.iter()) {
+            if plane.len() != pixel_count {
+                return Err(TgaError::IncompleteImageData(plane.len(), pixel_count));
+            }
+        }
+
+        let (depth, bytes_per_pixel) = match alpha {
+            Some(_) => (PixelDepth::Bits32, 4),
+            None => (PixelDepth::Bits24, 3),
+        };
+        let mut pixels = Vec::with_capacity(pixel_count * bytes_per_pixel);
+        for i in 0..pixel_count {
+            pixels.extend_from_slice(&[planes.b[i], planes.g[i], planes.r[i]]);
+            if let Some(a) = alpha {
+                pixels.push(a[i]);
+            }
+        }
+
+        TgaImageBuilder::new().width(width).height(height).pixel_format(depth).build(&pixels)
+    }
+}
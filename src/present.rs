@@ -0,0 +1,58 @@
+//! # Presentation
+//!
+//! The `present` module copies decoded images into the 32 bit pixel buffers
+//! that windowing libraries display, such as those of `minifb` or
+//! `softbuffer`.
+use crate::TgaImage;
+
+
+impl TgaImage {
+    /// The function `present_into` converts the image to `0xAARRGGBB` pixels
+    /// and copies it into a framebuffer of `fb_width` by `fb_height` pixels,
+    /// stored row by row from the top left corner. The top left corner of the
+    /// image as displayed lands at `offset` in the framebuffer, and the parts
+    /// of the image outside the framebuffer are clipped. The orientation in
+    /// the image descriptor is honoured, so the image appears upright.
+    /// Pixels replace the framebuffer contents; there is no blending.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the framebuffer holds fewer than
+    /// `fb_width * fb_height` pixels.
+    pub fn present_into(&self, framebuffer: &mut [u32], fb_width: u32, fb_height: u32, offset: (i32, i32)) {
+        let fb_width = fb_width as usize;
+        let fb_height = fb_height as usize;
+        assert!(
+            framebuffer.len() >= fb_width * fb_height,
+            "the framebuffer holds fewer than fb_width * fb_height pixels"
+        );
+
+        let image = self.raw_tga_image();
+        let decoder = image.pixel_decoder();
+        let bytes_per_pixel = decoder.bytes_per_pixel();
+        let width = image.width() as i64;
+        let height = image.height() as i64;
+        let top_to_bottom = image.header.image_descriptor & 0x20 != 0;
+        let right_to_left = image.header.image_descriptor & 0x10 != 0;
+        let (x_offset, y_offset) = (offset.0 as i64, offset.1 as i64);
+        let in_range = |value: i64, limit: usize| (value >= 0) && (value < limit as i64);
+
+        for (row, scanline) in image.rows().enumerate() {
+            let row = row as i64;
+            let y = y_offset + if top_to_bottom { row } else { height - 1 - row };
+            if !in_range(y, fb_height) {
+                continue;
+            }
+            let start = y as usize * fb_width;
+            let fb_row = &mut framebuffer[start..(start + fb_width)];
+            for (column, pixel) in scanline.chunks(bytes_per_pixel).enumerate() {
+                let column = column as i64;
+                let x = x_offset + if right_to_left { width - 1 - column } else { column };
+                if in_range(x, fb_width) {
+                    let [blue, green, red, alpha] = decoder.decode(pixel);
+                    fb_row[x as usize] = u32::from_be_bytes([alpha, red, green, blue]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! # Provenance
+//!
+//! The `provenance` module records where an image came from in the developer
+//! area of a TGA 2.0 file: the tool that produced it, the commit the tool was
+//! built from, and when it ran. The record carries a checksum of the colours
+//! of the image, so an asset can be traced back to the pipeline run that made
+//! it, and checked to still hold the pixels that run produced.
+use crate::digest::Crc32;
+use crate::{DeveloperTag, EncoderOptions, RawTgaImage, TgaError, TgaImage, Timestamp};
+
+
+/// The developer tag number a provenance record is stored under.
+pub const PROVENANCE_TAG: u16 = 0x5052;
+
+/// The first line of a provenance record, naming the version of its layout.
+const RECORD_VERSION: &str = "tga-provenance/1";
+
+/// A `ProvenanceInfo` describes the pipeline run that produced an image.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ProvenanceInfo {
+    /// The name of the tool that produced the image.
+    pub tool: String,
+    /// The commit hash of the tool, or of the pipeline it ran in.
+    pub commit_hash: String,
+    /// The time the image was produced, in UTC.
+    pub timestamp: Timestamp,
+}
+
+/// Compute the CRC-32 checksum of the colours of an image in display order,
+/// so the checksum does not depend on how the file stores them.
+fn colour_checksum(image: &RawTgaImage) -> u32 {
+    let mut crc = Crc32::new();
+    for colour in image.colours_in_display_order().iter() {
+        crc.update(colour);
+    }
+
+    crc.finish()
+}
+
+/// Keep a field of a record on its own line.
+fn single_line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+/// Parse a timestamp written as `YYYY-MM-DDTHH:MM:SSZ`.
+fn parse_timestamp(text: &str) -> Option<Timestamp> {
+    let bytes = text.as_bytes();
+    let separators = [(4, b'-'), (7, b'-'), (10, b'T'), (13, b':'), (16, b':'), (19, b'Z')];
+    if (bytes.len() != 20) || separators.iter().any(|&(position, separator)| bytes[position] != separator) {
+        return None;
+    }
+    let field = |start: usize, end: usize| text[start..end].parse::<u16>().ok();
+
+    Some(Timestamp {
+        year: field(0, 4)?,
+        month: field(5, 7)?,
+        day: field(8, 10)?,
+        hour: field(11, 13)?,
+        minute: field(14, 16)?,
+        second: field(17, 19)?,
+    })
+}
+
+impl ProvenanceInfo {
+    /// Serialize the provenance into a record of one `key=value` line per
+    /// field, ending with the checksum of the colours of the image.
+    fn to_record(&self, checksum: u32) -> Vec<u8> {
+        let timestamp = self.timestamp;
+        format!(
+            "{}\ntool={}\ncommit={}\ntimestamp={:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z\npixels={:08x}\n",
+            RECORD_VERSION, single_line(&self.tool), single_line(&self.commit_hash),
+            timestamp.year, timestamp.month, timestamp.day, timestamp.hour, timestamp.minute, timestamp.second,
+            checksum
+        ).into_bytes()
+    }
+
+    /// Parse a record written by `to_record`, returning the provenance and
+    /// the checksum it records.
+    fn from_record(record: &[u8]) -> Option<(ProvenanceInfo, u32)> {
+        let record = std::str::from_utf8(record).ok()?;
+        let mut lines = record.lines();
+        if lines.next()? != RECORD_VERSION {
+            return None;
+        }
+        let mut field = |key: &str| lines.next()?.strip_prefix(key)?.strip_prefix('=').map(str::to_string);
+        let tool = field("tool")?;
+        let commit_hash = field("commit")?;
+        let timestamp = parse_timestamp(&field("timestamp")?)?;
+        let checksum = u32::from_str_radix(&field("pixels")?, 16).ok()?;
+
+        Some((ProvenanceInfo { tool, commit_hash, timestamp }, checksum))
+    }
+}
+
+impl EncoderOptions {
+    /// The function `embed_provenance` adds a provenance record for an image
+    /// to the developer tags the options write, replacing any record added
+    /// before. The record checksums the colours of the image, so it only
+    /// verifies against files whose pixels have the same colours: options
+    /// that lose colour, such as writing 16 bit pixels, produce a file that
+    /// fails verification. Line breaks in the fields are replaced by spaces.
+    pub fn embed_provenance(&mut self, image: &TgaImage, info: ProvenanceInfo) {
+        let checksum = colour_checksum(image.raw_tga_image());
+        self.developer_tags.retain(|developer_tag| developer_tag.tag != PROVENANCE_TAG);
+        self.developer_tags.push(DeveloperTag { tag: PROVENANCE_TAG, data: info.to_record(checksum) });
+    }
+}
+
+impl TgaImage {
+    /// The function `read_provenance` reads the provenance record from the
+    /// developer area of the file the image was parsed from, and checks it
+    /// against the colours of the image. It returns `None` when the file has
+    /// no record, and fails with `InvalidProvenance` when the record cannot
+    /// be read, or when the colours of the image no longer match it.
+    pub fn read_provenance(&self) -> Result<Option<ProvenanceInfo>, TgaError> {
+        let image = self.raw_tga_image();
+        let record = match image.developer_tag(PROVENANCE_TAG) {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+        let (info, checksum) = ProvenanceInfo::from_record(record)
+            .ok_or(TgaError::InvalidProvenance("malformed provenance record"))?;
+        if checksum != colour_checksum(image) {
+            return Err(TgaError::InvalidProvenance("the pixels do not match the provenance record"));
+        }
+
+        Ok(Some(info))
+    }
+}
@@ -0,0 +1,199 @@
+//! # Image Pyramids
+//!
+//! The `pyramid` module splits an image into tiles at several resolutions, so
+//! map and chart viewers can stream the parts of a very large image they show
+//! at the zoom level they show it at, without ever decoding the whole image.
+//! Each level of a pyramid halves the dimensions of the level before it, and
+//! a manifest describes where every tile of every level belongs.
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use crate::{encode_pixel, RawTgaImage, TgaError, TgaImage};
+
+
+/// A `PyramidTile` is one tile of one level of a pyramid. Coordinates follow
+/// the order the pixels are stored in, as they do for `TgaImage::tiles`.
+#[derive(Clone, Debug)]
+pub struct PyramidTile {
+    /// The column of the tile within the grid of tiles of its level.
+    pub column: u32,
+    /// The row of the tile within the grid of tiles of its level.
+    pub row: u32,
+    /// The position of the first pixel of the tile within its level.
+    pub x: u32,
+    /// The position of the first row of the tile within its level.
+    pub y: u32,
+    /// The pixels of the tile.
+    pub image: TgaImage,
+}
+
+/// A `PyramidLevel` is the image at one resolution, split into tiles.
+#[derive(Clone, Debug)]
+pub struct PyramidLevel {
+    /// The width of the whole level, in pixels.
+    pub width: u32,
+    /// The height of the whole level, in pixels.
+    pub height: u32,
+    /// The tiles of the level, row by row.
+    pub tiles: Vec<PyramidTile>,
+}
+
+/// A `Pyramid` holds an image at successively halved resolutions, each split
+/// into tiles of the same size. It is created by `TgaImage::build_pyramid`.
+#[derive(Clone, Debug)]
+pub struct Pyramid {
+    /// The width and height of a tile, in pixels. Tiles along the right and
+    /// last edges of a level are smaller when the level is not a multiple of
+    /// the tile size.
+    pub tile_size: u32,
+    /// The levels of the pyramid, from the full resolution image down.
+    pub levels: Vec<PyramidLevel>,
+}
+
+/// The path of a tile within the directory a pyramid is saved to.
+fn tile_path(level: usize, tile: &PyramidTile) -> String {
+    format!("{}/{}_{}.tga", level, tile.column, tile.row)
+}
+
+impl Pyramid {
+    /// The function `manifest` describes the layout of the pyramid as JSON:
+    /// the tile size, and for each level its dimensions and the position,
+    /// dimensions, and file path of each of its tiles, as `save` writes them.
+    pub fn manifest(&self) -> String {
+        let mut manifest = String::new();
+        write!(manifest, "{{\"tile_size\":{},\"levels\":[", self.tile_size).unwrap();
+        for (level_index, level) in self.levels.iter().enumerate() {
+            if level_index > 0 {
+                manifest.push(',');
+            }
+            write!(
+                manifest,
+                "{{\"level\":{},\"width\":{},\"height\":{},\"tiles\":[",
+                level_index, level.width, level.height
+            ).unwrap();
+            for (tile_index, tile) in level.tiles.iter().enumerate() {
+                if tile_index > 0 {
+                    manifest.push(',');
+                }
+                write!(
+                    manifest,
+                    "{{\"column\":{},\"row\":{},\"x\":{},\"y\":{},\"width\":{},\"height\":{},\"path\":\"{}\"}}",
+                    tile.column, tile.row, tile.x, tile.y, tile.image.width(), tile.image.height(),
+                    tile_path(level_index, tile)
+                ).unwrap();
+            }
+            manifest.push_str("]}");
+        }
+        manifest.push_str("]}");
+
+        manifest
+    }
+
+    /// The function `save` writes the pyramid into a directory: the tiles of
+    /// each level into a subdirectory named after the level, as
+    /// `<column>_<row>.tga`, and the manifest as `manifest.json`. Directories
+    /// are created as needed, and existing files are overwritten.
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> Result<(), TgaError> {
+        let io_error = |err| TgaError::Io(Box::new(err));
+        let dir = dir.as_ref();
+        for (level_index, level) in self.levels.iter().enumerate() {
+            fs::create_dir_all(dir.join(level_index.to_string())).map_err(io_error)?;
+            for tile in level.tiles.iter() {
+                tile.image.save(dir.join(tile_path(level_index, tile)))?;
+            }
+        }
+
+        fs::write(dir.join("manifest.json"), self.manifest()).map_err(io_error)
+    }
+}
+
+impl RawTgaImage {
+    /// Halve the dimensions of the image, rounding up. Each pixel of a true
+    /// colour image becomes the average of the block of up to four pixels it
+    /// replaces. Colour map indices cannot be averaged, so a colour mapped
+    /// image keeps the first pixel of each block instead.
+    fn half_size(&self) -> RawTgaImage {
+        let width = self.width().div_ceil(2);
+        let height = self.height().div_ceil(2);
+        let mut header = self.header;
+        header.width = (width as u16).to_le_bytes();
+        header.height = (height as u16).to_le_bytes();
+
+        let bits = self.header.bits_per_pixel();
+        let bytes_per_pixel = self.header.bytes_per_pixel();
+        let decoder = self.pixel_decoder();
+        let colour_mapped = self.header.is_colour_mapped();
+        let mut image_data = vec![0; width * height * bytes_per_pixel];
+        for (y, row) in image_data.chunks_mut(width * bytes_per_pixel).enumerate() {
+            let rows = [self.row(2 * y), self.row(usize::min(2 * y + 1, self.height() - 1))];
+            for (x, pixel) in row.chunks_mut(bytes_per_pixel).enumerate() {
+                let columns = [2 * x, usize::min(2 * x + 1, self.width() - 1)];
+                let source = |row: usize, column: usize| {
+                    &rows[row][(columns[column] * bytes_per_pixel)..((columns[column] + 1) * bytes_per_pixel)]
+                };
+                if colour_mapped {
+                    pixel.copy_from_slice(source(0, 0));
+                    continue;
+                }
+
+                let mut sums = [0u32; 4];
+                for (row, column) in [(0, 0), (0, 1), (1, 0), (1, 1)].iter() {
+                    let colour = decoder.decode(source(*row, *column));
+                    for (sum, &channel) in sums.iter_mut().zip(colour.iter()) {
+                        *sum += channel as u32;
+                    }
+                }
+                let colour = [
+                    ((sums[0] + 2) / 4) as u8, ((sums[1] + 2) / 4) as u8,
+                    ((sums[2] + 2) / 4) as u8, ((sums[3] + 2) / 4) as u8,
+                ];
+                encode_pixel(colour, bits, pixel);
+            }
+        }
+
+        let mut image = RawTgaImage::new(
+            header,
+            self.image_identification.clone(),
+            self.colour_map_data.clone(),
+            Arc::new(image_data),
+            self.extended_image_identification.clone(),
+        );
+        image.trailing_position = self.trailing_position;
+
+        image
+    }
+}
+
+impl TgaImage {
+    /// The function `build_pyramid` builds a pyramid of up to `levels` levels
+    /// out of the image, with tiles of `tile_size` by `tile_size` pixels. The
+    /// first level is the image itself, and every following level halves the
+    /// dimensions of the one before it, rounding up. The pyramid stops early
+    /// once a level is a single pixel, or has no pixels at all. The tiles
+    /// keep the type, orientation, and metadata of the image.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the tile size is zero.
+    pub fn build_pyramid(&self, levels: u32, tile_size: u32) -> Pyramid {
+        assert!(tile_size > 0, "the tile size must be nonzero");
+
+        let mut pyramid = Pyramid { tile_size, levels: Vec::new() };
+        let mut image = self.clone();
+        for level in 0..levels {
+            if level > 0 {
+                if image.image_data_length() <= 1 {
+                    break;
+                }
+                image = image.with_raw_tga_image(image.raw_tga_image().half_size());
+            }
+            let tiles = image.tiles(tile_size, tile_size)
+                .map(|(x, y, tile)| PyramidTile { column: x / tile_size, row: y / tile_size, x, y, image: tile })
+                .collect();
+            pyramid.levels.push(PyramidLevel { width: image.width(), height: image.height(), tiles });
+        }
+
+        pyramid
+    }
+}
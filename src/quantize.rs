@@ -0,0 +1,227 @@
+//! # Quantization
+//!
+//! The `quantize` module turns true colour images into colour mapped ones,
+//! generating the palette from the colours of the image.
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::{EncoderOptions, Palette, PixelDepth, RawTgaImage, TgaError, TgaImage};
+
+
+/// The largest palette that eight bit colour map indices can address.
+const MAX_PALETTE_LENGTH: usize = 256;
+
+/// The method used to choose the colours of a generated palette.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Quantizer {
+    /// Use the colours of the image exactly. Images with more than 256
+    /// distinct colours are rejected.
+    Exact,
+    /// Use the colours of the image exactly when there are at most 256 of
+    /// them, and otherwise reduce them to 256 colours with the median cut
+    /// algorithm.
+    MedianCut,
+}
+
+/// A `QuantizationReport` measures how closely the palette generated for a
+/// colour mapped image reproduces the original colours. Errors are Euclidean
+/// distances between colours in blue, green, red, and alpha space, with each
+/// channel ranging from 0 to 255. An exact palette has no error.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct QuantizationReport {
+    /// The largest error of any pixel.
+    pub max_error: f64,
+    /// The mean error over all of the pixels.
+    pub mean_error: f64,
+    /// The number of colours in the generated palette.
+    pub colours_used: usize,
+}
+
+impl QuantizationReport {
+    /// Measure the error of replacing each colour with its palette colour.
+    fn new(colours: &[([u8; 4], usize)], indices: &[u8], palette: &[[u8; 4]]) -> QuantizationReport {
+        let mut max_error = 0.0;
+        let mut total_error = 0.0;
+        let mut pixel_count = 0;
+        for (&(colour, count), &index) in colours.iter().zip(indices.iter()) {
+            let replacement = palette[index as usize];
+            let error = (0..4)
+                .map(|channel| (colour[channel] as f64 - replacement[channel] as f64).powi(2))
+                .sum::<f64>()
+                .sqrt();
+            max_error = f64::max(max_error, error);
+            total_error += error * count as f64;
+            pixel_count += count;
+        }
+        let mean_error = if pixel_count > 0 { total_error / pixel_count as f64 } else { 0.0 };
+
+        QuantizationReport { max_error, mean_error, colours_used: palette.len() }
+    }
+}
+
+/// The distinct colours of an image in the order they first appear, together
+/// with the number of pixels of each colour.
+fn distinct_colours(image: &RawTgaImage) -> Vec<([u8; 4], usize)> {
+    let decoder = image.pixel_decoder();
+    let bytes_per_pixel = decoder.bytes_per_pixel();
+    let mut positions = HashMap::new();
+    let mut colours = Vec::new();
+    for pixel in image.rows().flat_map(|row| row.chunks(bytes_per_pixel)) {
+        let colour = decoder.decode(pixel);
+        let position = *positions.entry(colour).or_insert_with(|| {
+            colours.push((colour, 0));
+            colours.len() - 1
+        });
+        colours[position].1 += 1;
+    }
+
+    colours
+}
+
+/// The range of values a channel takes over a set of colours.
+fn channel_range(colours: &[([u8; 4], usize)], channel: usize) -> u8 {
+    let min = colours.iter().map(|&(colour, _)| colour[channel]).min().unwrap_or(0);
+    let max = colours.iter().map(|&(colour, _)| colour[channel]).max().unwrap_or(0);
+
+    max - min
+}
+
+/// Reduce a set of colours to at most `max_colours` colours with the median cut
+/// algorithm. The colours are repeatedly split along the channel with the
+/// widest range at the median pixel, and each final set of colours is replaced
+/// by its average weighted by pixel count. The function returns the palette
+/// and, for each input colour, the index of the palette colour replacing it.
+fn median_cut(colours: &[([u8; 4], usize)], max_colours: usize) -> (Vec<[u8; 4]>, Vec<u8>) {
+    let mut boxes = vec![colours.iter().cloned().enumerate().collect::<Vec<_>>()];
+    while boxes.len() < max_colours {
+        // Split the box whose colours spread furthest along any channel.
+        let widest = boxes.iter().enumerate()
+            .filter(|&(_, colours)| colours.len() > 1)
+            .map(|(i, colours)| {
+                let colours = colours.iter().map(|&(_, colour)| colour).collect::<Vec<_>>();
+                let (channel, range) = (0..4).map(|channel| (channel, channel_range(&colours, channel)))
+                    .max_by_key(|&(channel, range)| (range, usize::MAX - channel))
+                    .unwrap();
+                (i, channel, range)
+            })
+            .max_by_key(|&(i, _, range)| (range, usize::MAX - i));
+        let (i, channel) = match widest {
+            Some((i, channel, range)) if range > 0 => (i, channel),
+            _ => break,
+        };
+
+        let mut colours = boxes.swap_remove(i);
+        colours.sort_by_key(|&(position, (colour, _))| (colour[channel], position));
+        let total = colours.iter().map(|&(_, (_, count))| count).sum::<usize>();
+        let mut seen = 0;
+        let mut split = 1;
+        for (j, &(_, (_, count))) in colours.iter().enumerate() {
+            seen += count;
+            if 2 * seen >= total {
+                split = j + 1;
+                break;
+            }
+        }
+        let split = usize::min(usize::max(split, 1), colours.len() - 1);
+        let upper = colours.split_off(split);
+        boxes.push(colours);
+        boxes.push(upper);
+    }
+
+    // Order the palette by the first appearance of its colours in the image.
+    boxes.sort_by_key(|colours| colours.iter().map(|&(position, _)| position).min());
+    let mut palette = Vec::with_capacity(boxes.len());
+    let mut indices = vec![0; colours.len()];
+    for (index, colours) in boxes.iter().enumerate() {
+        let total = colours.iter().map(|&(_, (_, count))| count).sum::<usize>();
+        let mut average = [0; 4];
+        for (channel, value) in average.iter_mut().enumerate() {
+            let sum = colours.iter().map(|&(_, (colour, count))| colour[channel] as usize * count).sum::<usize>();
+            *value = ((sum + total / 2) / total) as u8;
+        }
+        palette.push(average);
+        for &(position, _) in colours.iter() {
+            indices[position] = index as u8;
+        }
+    }
+
+    (palette, indices)
+}
+
+impl RawTgaImage {
+    /// Convert the image into a colour mapped image with a generated palette.
+    /// The image keeps its orientation, identification, and compression.
+    fn quantize(&self, quantizer: Quantizer) -> Result<(RawTgaImage, QuantizationReport), TgaError> {
+        // A file without image data has no colours to build a palette from.
+        if !self.header.has_image_data() {
+            return Err(TgaError::Not24BitRgb(self.data_type_code()));
+        }
+
+        let colours = distinct_colours(self);
+        let (palette, colour_indices) = if colours.len() <= MAX_PALETTE_LENGTH {
+            let palette = colours.iter().map(|&(colour, _)| colour).collect::<Vec<_>>();
+            (palette, (0..colours.len()).map(|index| index as u8).collect::<Vec<_>>())
+        } else {
+            match quantizer {
+                Quantizer::Exact => return Err(TgaError::TooManyColours(colours.len())),
+                Quantizer::MedianCut => median_cut(&colours, MAX_PALETTE_LENGTH),
+            }
+        };
+        let report = QuantizationReport::new(&colours, &colour_indices, &palette);
+        let depth = if palette.iter().all(|colour| colour[3] == 255) {
+            PixelDepth::Bits24
+        } else {
+            PixelDepth::Bits32
+        };
+        let palette = Palette::new(palette, depth);
+
+        let lookup = colours.iter().map(|&(colour, _)| colour).zip(colour_indices).collect::<HashMap<_, _>>();
+        let decoder = self.pixel_decoder();
+        let indices = self.rows()
+            .flat_map(|row| row.chunks(decoder.bytes_per_pixel()))
+            .map(|pixel| lookup[&decoder.decode(pixel)])
+            .collect::<Vec<u8>>();
+
+        let mut header = self.header;
+        header.color_map_type = 1;
+        header.data_type_code = 1 | (header.data_type_code & 0x08);
+        header.colour_map_origin = [0, 0];
+        header.colour_map_length = (palette.len() as u16).to_le_bytes();
+        header.colour_map_depth = depth.bits() as u8;
+        header.bits_per_pixel = 8;
+        header.image_descriptor = (header.image_descriptor & 0xF0) | depth.attribute_bits();
+
+        let mut image = RawTgaImage::new(
+            header,
+            self.image_identification.clone(),
+            Arc::new(palette.to_colour_map()),
+            Arc::new(indices),
+            self.extended_image_identification.clone(),
+        );
+        image.trailing_position = self.trailing_position;
+
+        Ok((image, report))
+    }
+}
+
+impl TgaImage {
+    /// The function `encode_indexed` serializes the image as a colour mapped
+    /// TGA file with a palette generated from the colours of the image. The
+    /// file is type 1, or type 9 when the image is run length encoded or the
+    /// encoder options ask for run length encoding. The palette holds opaque
+    /// 24 bit colours, or 32 bit colours when the image has transparent pixels.
+    pub fn encode_indexed(&self, quantizer: Quantizer, options: &EncoderOptions) -> Result<Vec<u8>, TgaError> {
+        self.encode_indexed_with_report(quantizer, options).map(|(encoded, _)| encoded)
+    }
+
+    /// The function `encode_indexed_with_report` serializes the image like
+    /// `encode_indexed`, and also reports how closely the generated palette
+    /// reproduces the colours of the image, so a conversion that loses too
+    /// much quality can be rejected.
+    pub fn encode_indexed_with_report(
+        &self, quantizer: Quantizer, options: &EncoderOptions
+    ) -> Result<(Vec<u8>, QuantizationReport), TgaError> {
+        let (image, report) = self.raw_tga_image().quantize(quantizer)?;
+
+        Ok((image.encode_with_options(options), report))
+    }
+}
@@ -0,0 +1,402 @@
+//! # Run Length Encoding
+//!
+//! The `rle` module contains the run length encoded image types (types 9 and
+//! 10), together with the routines that compress and decompress their pixel
+//! data.
+use std::collections::VecDeque;
+use crate::{
+    check_colour_mapped_header, check_true_colour_header, PixelIter, RawTgaImage, ScanlineIter,
+    TgaError, TgaHeader, TgaImage,
+};
+
+
+/// The maximum number of pixels a single run length encoded packet can represent.
+const RLE_MAX_PACKET_LENGTH: usize = 128;
+
+/// Decode run length encoded image data from the start of a buffer. The
+/// function returns the decoded image data together with the number of bytes
+/// of the buffer the packets occupied. The packets are decoded in a single
+/// pass, checking each one against the end of the buffer and the size of the
+/// image the header claims before it is decoded.
+pub(crate) fn decode_rle(
+    slice: &[u8], image_size: usize, bytes_per_pixel: usize
+) -> Result<(Vec<u8>, usize), TgaError> {
+    // The header is not trusted yet, so reserve no more than the packets in
+    // the buffer could possibly expand to.
+    let expandable_size = (slice.len() / (bytes_per_pixel + 1))
+        .saturating_mul(RLE_MAX_PACKET_LENGTH * bytes_per_pixel);
+    let mut image_data = Vec::with_capacity(usize::min(image_size, expandable_size));
+    let mut slice_i = 0;
+    while image_data.len() < image_size {
+        let packet_header = match slice.get(slice_i) {
+            Some(&packet_header) => packet_header,
+            // The packets ran out before the image was complete.
+            None => return Err(TgaError::IncompleteImageData(image_data.len(), image_size)),
+        };
+        // A run length encoded packet never represents a run of zero.
+        // Hence, we add 1 to get the true run length.
+        let packet_length = (packet_header & 0x7F) as usize + 1;
+        let run_length_packet = packet_header & 0x80 != 0;
+        let packet_size = if run_length_packet {
+            bytes_per_pixel + 1
+        } else {
+            bytes_per_pixel * packet_length + 1
+        };
+        // Either the packet is cut short by the end of the buffer, or it
+        // holds more pixels than the image has left. Either way, the image
+        // data is corrupt.
+        let image_data_found = image_data.len() + bytes_per_pixel * packet_length;
+        if (slice_i + packet_size > slice.len()) || (image_data_found > image_size) {
+            return Err(TgaError::IncompleteImageData(image_data_found, image_size));
+        }
+
+        let packet_data = &slice[(slice_i + 1)..(slice_i + packet_size)];
+        if run_length_packet {
+            for _ in 0..packet_length {
+                image_data.extend_from_slice(packet_data);
+            }
+        } else {
+            image_data.extend_from_slice(packet_data);
+        }
+        // Jump to the next packet.
+        slice_i += packet_size;
+    }
+
+    Ok((image_data, slice_i))
+}
+
+/// Find the cheapest way of splitting a scanline into packets. The function
+/// returns the number of bytes the packets occupy, and for every pixel
+/// position the start of the last packet of the cheapest encoding of the
+/// pixels before it, and whether that packet is a run length packet.
+fn plan_rle_scanline(scanline: &[u8], bytes_per_pixel: usize) -> (usize, Vec<(usize, bool)>) {
+    let pixel_count = scanline.len() / bytes_per_pixel;
+    let pixel = |i: usize| &scanline[(i * bytes_per_pixel)..((i + 1) * bytes_per_pixel)];
+
+    // The number of identical pixels starting at each pixel.
+    let mut run_lengths = vec![1; pixel_count];
+    for i in (0..pixel_count.saturating_sub(1)).rev() {
+        if pixel(i) == pixel(i + 1) {
+            run_lengths[i] = run_lengths[i + 1] + 1;
+        }
+    }
+
+    // The cheapest encoding of the first `i` pixels costs `costs[i]` bytes, and
+    // its last packet starts at pixel `packet_starts[i]`. A run length packet
+    // costs the same however long it is, so only the longest run from each
+    // pixel is considered. A raw packet costs one byte plus its pixels, so the
+    // cheapest raw packet ending at a pixel starts where `costs[j] - j * bpp`
+    // is smallest over the previous 128 pixels, tracked with a monotone queue.
+    let mut costs = vec![usize::MAX; pixel_count + 1];
+    let mut packet_starts = vec![(0, false); pixel_count + 1];
+    let mut run_costs = vec![(usize::MAX, 0); pixel_count + 1];
+    let mut window: VecDeque<(usize, usize)> = VecDeque::new();
+    costs[0] = 0;
+    for end in 0..=pixel_count {
+        if end > 0 {
+            let start = end - 1;
+            let weight = costs[start] + (pixel_count - start) * bytes_per_pixel;
+            while window.back().is_some_and(|&(_, back)| back >= weight) {
+                window.pop_back();
+            }
+            window.push_back((start, weight));
+            while window.front().is_some_and(|&(front, _)| front + RLE_MAX_PACKET_LENGTH < end) {
+                window.pop_front();
+            }
+
+            let (raw_start, _) = window[0];
+            let raw_cost = costs[raw_start] + 1 + (end - raw_start) * bytes_per_pixel;
+            let (run_cost, run_start) = run_costs[end];
+            if run_cost < raw_cost {
+                costs[end] = run_cost;
+                packet_starts[end] = (run_start, true);
+            } else {
+                costs[end] = raw_cost;
+                packet_starts[end] = (raw_start, false);
+            }
+        }
+        if end < pixel_count {
+            let length = usize::min(run_lengths[end], RLE_MAX_PACKET_LENGTH);
+            let cost = costs[end] + 1 + bytes_per_pixel;
+            if (length > 1) && (cost < run_costs[end + length].0) {
+                run_costs[end + length] = (cost, end);
+            }
+        }
+    }
+
+    (costs[pixel_count], packet_starts)
+}
+
+/// The number of bytes `encode_rle_scanline` packs a scanline into, found
+/// without packing it.
+pub(crate) fn rle_scanline_size(scanline: &[u8], bytes_per_pixel: usize) -> usize {
+    plan_rle_scanline(scanline, bytes_per_pixel).0
+}
+
+/// Run length encode a single scanline, appending the resulting packets to `buf`.
+/// Packets never cross scanlines, as recommended by the TGA specification, so
+/// readers can decode the image one row at a time.
+///
+/// The scanline is split into packets optimally: among all the ways of
+/// covering it with run length and raw packets, the encoder picks one with
+/// the fewest bytes. A short run is therefore folded into a surrounding raw
+/// packet whenever a separate run length packet, and the extra raw packet
+/// header it forces, would cost more than storing the run pixel by pixel.
+pub(crate) fn encode_rle_scanline(scanline: &[u8], bytes_per_pixel: usize, buf: &mut Vec<u8>) {
+    let pixel_count = scanline.len() / bytes_per_pixel;
+    let pixel = |i: usize| &scanline[(i * bytes_per_pixel)..((i + 1) * bytes_per_pixel)];
+    let (_, packet_starts) = plan_rle_scanline(scanline, bytes_per_pixel);
+
+    // Walk back from the end of the scanline to recover the packets.
+    let mut packets = Vec::new();
+    let mut end = pixel_count;
+    while end > 0 {
+        let (start, is_run) = packet_starts[end];
+        packets.push((start, end, is_run));
+        end = start;
+    }
+    for &(start, end, is_run) in packets.iter().rev() {
+        // The packet header stores the packet length minus one in its lower
+        // seven bits, and the top bit marks a run length packet.
+        if is_run {
+            buf.push(0x80 | (end - start - 1) as u8);
+            buf.extend_from_slice(pixel(start));
+        } else {
+            buf.push((end - start - 1) as u8);
+            buf.extend_from_slice(&scanline[(start * bytes_per_pixel)..(end * bytes_per_pixel)]);
+        }
+    }
+}
+
+/// A `RunLengthEncodedRgb` image is an unmapped true colour image whose pixel
+/// data is run length encoded (type 10).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RunLengthEncodedRgb {
+    pub(crate) inner: RawTgaImage,
+}
+
+impl RunLengthEncodedRgb {
+    /// Parse an unmapped run length encoded TGA image from a buffer in memory.
+    /// The pixel data is decompressed while it is parsed.
+    pub fn parse_from_buffer(buf: &[u8]) -> Result<RunLengthEncodedRgb, TgaError> {
+        let header = TgaHeader::parse_from_buffer(buf)?;
+
+        // Check that we were passed the correct TGA header.
+        if header.data_type_code != 10 {
+            return Err(TgaError::Not24BitRgb(header.data_type_code as usize));
+        }
+
+        check_true_colour_header(&header)?;
+        let inner = RawTgaImage::parse_from_buffer(buf, header)?;
+
+        Ok(RunLengthEncodedRgb { inner })
+    }
+
+    /// The function `width` returns the width of a TGA image, in pixels.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.inner.width() as u32
+    }
+
+    /// Return the height of a TGA image, in pixels.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.inner.height() as u32
+    }
+
+    /// Return the bit depth per pixel in a TGA Image.
+    #[inline]
+    pub fn bits_per_pixel(&self) -> usize {
+        self.inner.bits_per_pixel()
+    }
+
+    /// Compute the colour map type. The colour map type is either `0` or `1`.
+    /// A `0` indicates that there is no colour map; a `1` indicates that a
+    /// colour map is included.
+    #[inline]
+    pub fn color_map_type(&self) -> usize {
+        self.inner.color_map_type()
+    }
+
+    #[inline]
+    pub fn data_type_code(&self) -> usize {
+        self.inner.data_type_code()
+    }
+
+    /// The function `header` produces a copy of the TGA header.
+    #[inline]
+    pub fn header(&self) -> TgaHeader {
+        self.inner.header()
+    }
+
+    /// The function `pixels` generates an iterator over the pixels of the image.
+    /// It sweeps through the TGA image going from left to right in each row, and
+    /// going from bottom to top. The first pixel returned is the bottom left corner;
+    /// the last pixel returned is the top right corner.
+    #[inline]
+    pub fn pixels(&self) -> PixelIter<'_> {
+        self.inner.pixels()
+    }
+
+    #[inline]
+    pub fn scanlines(&self) -> ScanlineIter<'_> {
+        self.inner.scanlines()
+    }
+
+    /// The function `image_data_length` returns the size of the image,
+    /// in the total number of pixels. This satisfies the following invariant.
+    /// ```text
+    /// self.image_data_length() == self.width() * self.height()
+    /// ```
+    #[inline]
+    pub fn image_data_length(&self) -> usize {
+        self.inner.image_data_length()
+    }
+
+    /// The function `image_data` returns a slice of the uncompressed pixel data,
+    /// stored exactly as the header describes it.
+    #[inline]
+    pub fn image_data(&self) -> &[u8] {
+        self.inner.image_data()
+    }
+
+    /// The function `image_data_length_bytes` computes the size of the
+    /// image data, in the number of bytes. For an unmapped RGB image, this will
+    /// simply be `3 * image_data_length()`, since each RGB pixel is 3 bytes long.
+    #[inline]
+    pub fn image_data_length_bytes(&self) -> usize {
+        self.inner.image_data_length_bytes()
+    }
+}
+
+/// A `RunLengthEncodedColorMapped` image is a colour mapped image whose pixel
+/// data is run length encoded (type 9).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RunLengthEncodedColorMapped {
+    pub(crate) inner: RawTgaImage,
+}
+
+impl RunLengthEncodedColorMapped {
+    /// Parse a colour mapped run length encoded TGA image from a buffer in memory.
+    /// Every pixel must refer to an entry of the colour map.
+    pub fn parse_from_buffer(buf: &[u8]) -> Result<RunLengthEncodedColorMapped, TgaError> {
+        let header = TgaHeader::parse_from_buffer(buf)?;
+
+        // Check that we were passed the correct TGA header.
+        if header.data_type_code != 9 {
+            return Err(TgaError::Not24BitRgb(header.data_type_code as usize));
+        }
+
+        check_colour_mapped_header(&header)?;
+        let inner = RawTgaImage::parse_from_buffer(buf, header)?;
+        inner.validate_colour_map_indices()?;
+
+        Ok(RunLengthEncodedColorMapped { inner })
+    }
+
+    /// The function `width` returns the width of a TGA image, in pixels.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.inner.width() as u32
+    }
+
+    /// Return the height of a TGA image, in pixels.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.inner.height() as u32
+    }
+
+    /// Return the bit depth per pixel in a TGA Image.
+    #[inline]
+    pub fn bits_per_pixel(&self) -> usize {
+        self.inner.bits_per_pixel()
+    }
+
+    /// Compute the colour map type. The colour map type is either `0` or `1`.
+    /// A `0` indicates that there is no colour map; a `1` indicates that a
+    /// colour map is included.
+    #[inline]
+    pub fn color_map_type(&self) -> usize {
+        self.inner.color_map_type()
+    }
+
+    #[inline]
+    pub fn data_type_code(&self) -> usize {
+        self.inner.data_type_code()
+    }
+
+    /// The function `header` produces a copy of the TGA header.
+    #[inline]
+    pub fn header(&self) -> TgaHeader {
+        self.inner.header()
+    }
+
+    /// The function `pixels` generates an iterator over the pixels of the image.
+    /// It sweeps through the TGA image going from left to right in each row, and
+    /// going from bottom to top. The first pixel returned is the bottom left corner;
+    /// the last pixel returned is the top right corner.
+    #[inline]
+    pub fn pixels(&self) -> PixelIter<'_> {
+        self.inner.pixels()
+    }
+
+    #[inline]
+    pub fn scanlines(&self) -> ScanlineIter<'_> {
+        self.inner.scanlines()
+    }
+
+    /// The function `image_data_length` returns the size of the image,
+    /// in the total number of pixels. This satisfies the following invariant.
+    /// ```text
+    /// self.image_data_length() == self.width() * self.height()
+    /// ```
+    #[inline]
+    pub fn image_data_length(&self) -> usize {
+        self.inner.image_data_length()
+    }
+
+    /// The function `image_data` returns a slice of the uncompressed pixel data,
+    /// stored exactly as the header describes it.
+    #[inline]
+    pub fn image_data(&self) -> &[u8] {
+        self.inner.image_data()
+    }
+
+    /// The function `image_data_length_bytes` computes the size of the
+    /// image data, in the number of bytes. For an unmapped RGB image, this will
+    /// simply be `3 * image_data_length()`, since each RGB pixel is 3 bytes long.
+    #[inline]
+    pub fn image_data_length_bytes(&self) -> usize {
+        self.inner.image_data_length_bytes()
+    }
+}
+
+impl TgaImage {
+    /// The function `estimate_rle_size` returns the number of bytes the run
+    /// length encoded packets of the image take, as the encoder writes them,
+    /// without writing them. Comparing it with `image_data_length_bytes`
+    /// shows how much run length encoding would save. Only the pixel data
+    /// is counted, and the pixels are taken as they are stored, so encoder
+    /// options that change the pixels, such as a new pixel depth, are not
+    /// accounted for.
+    pub fn estimate_rle_size(&self) -> usize {
+        let image = self.raw_tga_image();
+        let bytes_per_pixel = image.header.bytes_per_pixel();
+
+        image.rows().map(|scanline| rle_scanline_size(scanline, bytes_per_pixel)).sum()
+    }
+
+    /// The function `rle_compression_ratio` returns the ratio of the size of
+    /// the pixel data of the image to the size of its run length encoded
+    /// packets, as the encoder writes them. A ratio above one means run length
+    /// encoding makes the image smaller. An image without pixels has a ratio
+    /// of one.
+    pub fn rle_compression_ratio(&self) -> f64 {
+        let encoded_length = self.estimate_rle_size();
+        if encoded_length == 0 {
+            return 1.0;
+        }
+
+        self.raw_tga_image().image_data_length_bytes() as f64 / encoded_length as f64
+    }
+}
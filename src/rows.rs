@@ -0,0 +1,251 @@
+//! # Row Decoding
+//!
+//! The `rows` module decodes a TGA file straight from a reader one scanline
+//! at a time, handing each scanline to a callback. It never holds more than a
+//! single scanline of pixel data, which suits devices without the memory for
+//! a whole image, such as a microcontroller pushing rows to a display.
+use std::cmp;
+use std::io;
+use std::ops::ControlFlow;
+use crate::{
+    check_supported_header, swap_red_and_blue, Interleaving, TgaError, TgaHeader, RGB_CHANNEL_ORDER_TAG,
+    TGA_HEADER_LENGTH,
+};
+
+
+/// Read exactly enough bytes to fill `buf`. Running out of input is reported
+/// through `incomplete`, which receives the number of bytes that were read.
+fn read_section<R, E>(reader: &mut R, buf: &mut [u8], incomplete: E) -> Result<(), TgaError>
+where
+    R: io::Read,
+    E: FnOnce(usize) -> TgaError,
+{
+    let mut have = 0;
+    while have < buf.len() {
+        match reader.read(&mut buf[have..]) {
+            Ok(0) => return Err(incomplete(have)),
+            Ok(n) => have += n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(TgaError::Io(Box::new(err))),
+        }
+    }
+
+    Ok(())
+}
+
+/// Read and discard `length` bytes. Running out of input is reported through
+/// `incomplete`, which receives the number of bytes that were skipped.
+fn skip_section<R, E>(reader: &mut R, length: usize, incomplete: E) -> Result<(), TgaError>
+where
+    R: io::Read,
+    E: FnOnce(usize) -> TgaError,
+{
+    let skipped = io::copy(&mut io::Read::take(reader, length as u64), &mut io::sink())
+        .map_err(|err| TgaError::Io(Box::new(err)))? as usize;
+    if skipped < length {
+        return Err(incomplete(skipped));
+    }
+
+    Ok(())
+}
+
+/// The state of run length decoding carried from one scanline to the next,
+/// since packets may cross scanlines.
+#[derive(Copy, Clone, Debug, Default)]
+struct PacketState {
+    run_remaining: usize,
+    run_pixel: [u8; 4],
+    raw_remaining: usize,
+}
+
+impl PacketState {
+    /// The number of pixels left in the current packet.
+    fn pixels_remaining(&self) -> usize {
+        self.run_remaining + self.raw_remaining
+    }
+
+    /// Fill a scanline from the packets of the reader. The argument `decoded`
+    /// is the number of bytes of pixel data decoded before this scanline,
+    /// used to report truncated input.
+    fn fill_scanline<R: io::Read>(
+        &mut self, reader: &mut R, row: &mut [u8], bytes_per_pixel: usize, decoded: usize, image_size: usize
+    ) -> Result<(), TgaError> {
+        let mut filled = 0;
+        while filled < row.len() {
+            let room = (row.len() - filled) / bytes_per_pixel;
+            let incomplete = |_| TgaError::IncompleteImageData(decoded + filled, image_size);
+            if self.run_remaining > 0 {
+                let count = cmp::min(self.run_remaining, room);
+                for pixel in row[filled..(filled + count * bytes_per_pixel)].chunks_mut(bytes_per_pixel) {
+                    pixel.copy_from_slice(&self.run_pixel[0..bytes_per_pixel]);
+                }
+                self.run_remaining -= count;
+                filled += count * bytes_per_pixel;
+            } else if self.raw_remaining > 0 {
+                let count = cmp::min(self.raw_remaining, room);
+                read_section(reader, &mut row[filled..(filled + count * bytes_per_pixel)], incomplete)?;
+                self.raw_remaining -= count;
+                filled += count * bytes_per_pixel;
+            } else {
+                let mut packet_header = [0; 1];
+                read_section(reader, &mut packet_header, incomplete)?;
+                // A run length encoded packet never represents a run of zero.
+                // Hence, we add 1 to get the true run length.
+                let length = (packet_header[0] & 0x7F) as usize + 1;
+                if packet_header[0] & 0x80 != 0 {
+                    read_section(reader, &mut self.run_pixel[0..bytes_per_pixel], incomplete)?;
+                    self.run_remaining = length;
+                } else {
+                    self.raw_remaining = length;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Read the header of a TGA file from a reader, and skip past the image
+/// identification and colour map to the start of the image data. Returns the
+/// header, and whether the channels of the pixels need swapping into blue,
+/// green, red order. A channel order tag is left out of the length of the
+/// image identification the header records, as parsing leaves it out.
+fn read_prelude<R: io::Read>(reader: &mut R) -> Result<(TgaHeader, bool), TgaError> {
+    let mut header_bytes = [0; TGA_HEADER_LENGTH];
+    read_section(reader, &mut header_bytes, |have| TgaError::IncompleteTgaHeader(have, TGA_HEADER_LENGTH))?;
+    let mut header = TgaHeader::parse_from_buffer(&header_bytes)?;
+    check_supported_header(&header)?;
+
+    let mut image_identification = [0; 255];
+    let image_identification = &mut image_identification[0..header.id_length()];
+    read_section(reader, image_identification, |have| TgaError::IncompleteIdString(have, header.id_length()))?;
+    let tagged = image_identification.starts_with(RGB_CHANNEL_ORDER_TAG);
+    let swap_channels = tagged && !header.is_colour_mapped();
+    skip_section(reader, header.colour_map_size(), |have| {
+        TgaError::IncompleteColourMap(have, header.colour_map_size())
+    })?;
+    if tagged {
+        header.id_length -= RGB_CHANNEL_ORDER_TAG.len() as u8;
+    }
+
+    Ok((header, swap_channels))
+}
+
+/// The size of the decompressed image data of an image, in bytes.
+fn image_size(header: &TgaHeader) -> usize {
+    if header.has_image_data() {
+        header.width() * header.height() * header.bytes_per_pixel()
+    } else {
+        0
+    }
+}
+
+/// Decode the scanline stored at `row_index` from the reader into `row`,
+/// leaving its channels in blue, green, red, and alpha order.
+fn decode_row<R: io::Read>(
+    reader: &mut R, packets: &mut PacketState, header: &TgaHeader, swap_channels: bool, row_index: usize,
+    row: &mut [u8]
+) -> Result<(), TgaError> {
+    let decoded = row_index * row.len();
+    let image_size = image_size(header);
+    if header.is_run_length_encoded() {
+        packets.fill_scanline(reader, row, header.bytes_per_pixel(), decoded, image_size)?;
+    } else {
+        read_section(reader, row, |have| TgaError::IncompleteImageData(decoded + have, image_size))?;
+    }
+    if swap_channels {
+        swap_red_and_blue(row, header.bits_per_pixel());
+    }
+
+    Ok(())
+}
+
+/// Check that no packet runs past the last scanline, since such a packet
+/// holds more pixel data than the header reports.
+fn check_packets_finished(packets: &PacketState, header: &TgaHeader) -> Result<(), TgaError> {
+    if packets.pixels_remaining() > 0 {
+        let image_size = image_size(header);
+        return Err(TgaError::IncompleteImageData(
+            image_size + packets.pixels_remaining() * header.bytes_per_pixel(), image_size
+        ));
+    }
+
+    Ok(())
+}
+
+/// The function `decode_rows_with` decodes a TGA file from a reader one
+/// scanline at a time, calling `f` with the index and pixel data of each
+/// scanline in the order they are stored. The pixel data is as `image_data`
+/// returns it: decompressed, with channels in blue, green, red, and alpha
+/// order, and holding colour map indices for colour mapped images. Whether
+/// the first scanline is the top or the bottom row of the image is recorded
+/// in the image descriptor of the header. The scanlines of an interleaved
+/// file are passed in the order they are stored, with the index each one has
+/// once they are put in order, so the indices arrive out of order.
+///
+/// Returning `ControlFlow::Break` from `f` stops decoding without reading the
+/// rest of the file. Only one scanline is ever allocated, and the image
+/// identification and colour map are skipped rather than stored, so the
+/// indices of a colour mapped image are not checked against its colour map.
+/// A file without image data has no scanlines, so `f` is never called.
+pub fn decode_rows_with<R, F>(reader: &mut R, mut f: F) -> Result<(), TgaError>
+where
+    R: io::Read,
+    F: FnMut(usize, &[u8]) -> ControlFlow<()>,
+{
+    let (header, swap_channels) = read_prelude(reader)?;
+    if !header.has_image_data() {
+        return Ok(());
+    }
+
+    let mut row = vec![0; header.width() * header.bytes_per_pixel()];
+    let mut packets = PacketState::default();
+    let interleaving = Interleaving::from_image_descriptor(header.image_descriptor);
+    for row_index in 0..header.height() {
+        decode_row(reader, &mut packets, &header, swap_channels, row_index, &mut row)?;
+        if f(interleaving.row_index(row_index, header.height()), &row).is_break() {
+            return Ok(());
+        }
+    }
+
+    check_packets_finished(&packets, &header)
+}
+
+/// The function `decode_into` decodes the image data of a TGA file from a
+/// reader into a buffer the caller provides, such as memory from a frame
+/// allocator, without allocating. The buffer receives the pixel data as
+/// `image_data` returns it, in the same form `decode_rows_with` passes it,
+/// with the scanlines of an interleaved file put in order. A buffer holding
+/// fewer bytes than the image data fails with `BufferTooSmall` before
+/// anything is decoded; a longer buffer is filled from the start and the
+/// rest is left untouched. To decode a file held in memory, pass a reader
+/// over its bytes, such as `&mut &buf[..]`.
+///
+/// The function returns the header of the file as `TgaImage::header` would
+/// report it: the interleaving bits of the image descriptor are cleared,
+/// since the scanlines are put in order, and a channel order tag is left out
+/// of the length of the image identification. As
+/// with `decode_rows_with`, the indices of a colour mapped image are not
+/// checked against its colour map.
+pub fn decode_into<R: io::Read>(reader: &mut R, out: &mut [u8]) -> Result<TgaHeader, TgaError> {
+    let (mut header, swap_channels) = read_prelude(reader)?;
+    let image_size = image_size(&header);
+    if out.len() < image_size {
+        return Err(TgaError::BufferTooSmall(out.len(), image_size));
+    }
+
+    let row_length = header.width() * header.bytes_per_pixel();
+    let mut packets = PacketState::default();
+    let interleaving = Interleaving::from_image_descriptor(header.image_descriptor);
+    if image_size > 0 {
+        for row_index in 0..header.height() {
+            let y = interleaving.row_index(row_index, header.height());
+            let row = &mut out[(y * row_length)..((y + 1) * row_length)];
+            decode_row(reader, &mut packets, &header, swap_channels, row_index, row)?;
+        }
+    }
+    check_packets_finished(&packets, &header)?;
+    header.image_descriptor &= !0xC0;
+
+    Ok(header)
+}
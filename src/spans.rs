@@ -0,0 +1,76 @@
+//! # Spans
+//!
+//! The `spans` module splits the scanlines of an image into runs of pixels of
+//! a single colour, so consumers that draw or trace flat regions, such as
+//! rasterizers and vectorizers, can handle a whole run at once instead of
+//! visiting every pixel.
+use crate::{PixelDecoder, TgaImage};
+
+
+/// A `Span` is a maximal run of pixels of the same colour within a scanline.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Span {
+    /// The position of the first pixel of the run within the scanline.
+    pub offset: u32,
+    /// The number of pixels in the run.
+    pub length: u32,
+    /// The colour of the run, as blue, green, red, and alpha channels.
+    pub colour: [u8; 4],
+}
+
+/// An iterator over the spans of a scanline, created by `TgaImage::spans`.
+pub struct Spans<'a> {
+    row: &'a [u8],
+    decoder: PixelDecoder<'a>,
+    position: usize,
+}
+
+impl<'a> Iterator for Spans<'a> {
+    type Item = Span;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes_per_pixel = self.decoder.bytes_per_pixel();
+        let pixel_at = |position: usize| &self.row[(position * bytes_per_pixel)..((position + 1) * bytes_per_pixel)];
+        let width = self.row.len() / bytes_per_pixel;
+        if self.position >= width {
+            return None;
+        }
+
+        let start = self.position;
+        let first = pixel_at(start);
+        let colour = self.decoder.decode(first);
+        let mut end = start + 1;
+        // Pixels stored the same way have the same colour, so only pixels
+        // stored differently, such as distinct colour map indices, need to be
+        // decoded to compare them.
+        while (end < width) && ((pixel_at(end) == first) || (self.decoder.decode(pixel_at(end)) == colour)) {
+            end += 1;
+        }
+        self.position = end;
+
+        Some(Span { offset: start as u32, length: (end - start) as u32, colour })
+    }
+}
+
+impl TgaImage {
+    /// The function `spans` returns an iterator over the maximal runs of
+    /// pixels of a single colour in a scanline, from left to right as the
+    /// pixels are stored. Scanlines are numbered in the order they are
+    /// stored, the same order `pixels` yields them. Pixels of the same colour
+    /// belong to the same run even when they are stored differently, such as
+    /// colour map indices of identical colour map entries.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the scanline lies outside of the image.
+    pub fn spans(&self, row: u32) -> Spans<'_> {
+        let image = self.raw_tga_image();
+        assert!((row as usize) < image.height(), "the scanline lies outside of the image");
+
+        Spans {
+            row: image.row(row as usize),
+            decoder: image.pixel_decoder(),
+            position: 0,
+        }
+    }
+}
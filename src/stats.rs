@@ -0,0 +1,91 @@
+//! # Statistics
+//!
+//! The `stats` module summarizes the colours of an image channel by channel,
+//! over the whole image or over the pixels a mask selects, so tests built on
+//! screenshots can check a region of an image without cropping it first.
+use crate::{TgaError, TgaImage};
+
+
+/// The `ChannelStats` of a channel summarize its values over a set of pixels.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct ChannelStats {
+    /// The smallest value of the channel.
+    pub min: u8,
+    /// The largest value of the channel.
+    pub max: u8,
+    /// The mean value of the channel.
+    pub mean: f64,
+    /// The standard deviation of the values of the channel.
+    pub std_dev: f64,
+}
+
+/// The `ImageStats` of an image summarize its colours over a set of pixels.
+/// Every field is zero when the set is empty.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct ImageStats {
+    /// The number of pixels summarized.
+    pub pixel_count: usize,
+    /// The statistics of the blue, green, red, and alpha channels, in the
+    /// order TGA files store them.
+    pub channels: [ChannelStats; 4],
+}
+
+/// Summarize the channels of a sequence of colours.
+fn image_stats<I: Iterator<Item = [u8; 4]>>(colours: I) -> ImageStats {
+    let mut pixel_count = 0;
+    let mut min = [u8::MAX; 4];
+    let mut max = [0; 4];
+    let mut sum = [0.0; 4];
+    let mut sum_of_squares = [0.0; 4];
+    for colour in colours {
+        pixel_count += 1;
+        for channel in 0..4 {
+            let value = colour[channel];
+            min[channel] = u8::min(min[channel], value);
+            max[channel] = u8::max(max[channel], value);
+            sum[channel] += value as f64;
+            sum_of_squares[channel] += (value as f64) * (value as f64);
+        }
+    }
+    if pixel_count == 0 {
+        return ImageStats::default();
+    }
+
+    let mut channels = [ChannelStats::default(); 4];
+    for (channel, stats) in channels.iter_mut().enumerate() {
+        let mean = sum[channel] / pixel_count as f64;
+        let variance = f64::max(0.0, sum_of_squares[channel] / pixel_count as f64 - mean * mean);
+        *stats = ChannelStats { min: min[channel], max: max[channel], mean, std_dev: variance.sqrt() };
+    }
+
+    ImageStats { pixel_count, channels }
+}
+
+impl TgaImage {
+    /// The function `stats` returns the minimum, maximum, mean, and standard
+    /// deviation of each channel of the image over all of its pixels.
+    pub fn stats(&self) -> ImageStats {
+        image_stats(self.raw_tga_image().colours_in_display_order().into_iter())
+    }
+
+    /// The function `stats_masked` returns the statistics of the channels of
+    /// the image over the pixels where the mask is not black: where any of
+    /// the colour channels of the mask is nonzero, whatever its alpha
+    /// channel holds. Pixels are matched by
+    /// their positions on the display, so the image and the mask may be
+    /// stored from different corners. A mask of other dimensions than the
+    /// image is rejected with `DimensionMismatch`, which gives the
+    /// dimensions of the mask.
+    pub fn stats_masked(&self, mask: &TgaImage) -> Result<ImageStats, TgaError> {
+        if (mask.width() != self.width()) || (mask.height() != self.height()) {
+            return Err(TgaError::DimensionMismatch(mask.width(), mask.height()));
+        }
+        let colours = self.raw_tga_image().colours_in_display_order();
+        let mask_colours = mask.raw_tga_image().colours_in_display_order();
+        let selected = colours.into_iter().zip(mask_colours).filter_map(|(colour, mask_colour)| {
+            if mask_colour[0..3] != [0, 0, 0] { Some(colour) } else { None }
+        });
+
+        Ok(image_stats(selected))
+    }
+}
@@ -0,0 +1,178 @@
+//! # Streaming
+//!
+//! The `stream` module writes TGA files one scanline at a time, so an image
+//! can be written out while it is produced, without ever holding the whole
+//! image in memory.
+use std::io;
+use crate::encoder::top_down_header;
+use crate::extension::{write_trailer, DeveloperTag, ExtensionArea, PostageStamp};
+#[cfg(feature = "rle")]
+use crate::rle::encode_rle_scanline;
+use crate::{
+    swap_red_and_blue, EncoderOptions, ChannelOrder, Orientation, PixelDepth, TgaError, TgaFormat, TGA_HEADER_LENGTH,
+};
+
+
+/// Wrap an I/O error in a `TgaError`.
+fn io_error(err: io::Error) -> TgaError {
+    TgaError::Io(Box::new(err))
+}
+
+/// A `ScanlineWriter` writes a true colour TGA file into a writer one scanline
+/// at a time. The header is written when the writer is created, each scanline
+/// is written as soon as it is pushed, and `finish` completes the file.
+///
+/// Scanlines hold pixels at the pixel depth of the encoder options, 24 bits by
+/// default, with their channels in blue, green, red, and alpha order. They are
+/// written in the order they are pushed, and the orientation of the encoder
+/// options records where that order starts. The default orientation is top
+/// left, so the first scanline pushed is the top row of the image.
+#[derive(Debug)]
+pub struct ScanlineWriter<W: io::Write> {
+    writer: W,
+    width: usize,
+    height: usize,
+    bits_per_pixel: usize,
+    rows_written: usize,
+    swap_channels: bool,
+    #[cfg(feature = "rle")]
+    rle: bool,
+    format: TgaFormat,
+    extension_area: Option<ExtensionArea>,
+    developer_tags: Vec<DeveloperTag>,
+    postage_stamp: Option<PostageStamp>,
+    alpha: bool,
+    bytes_written: usize,
+    scratch: Vec<u8>,
+    #[cfg(feature = "rle")]
+    packets: Vec<u8>,
+}
+
+impl<W: io::Write> ScanlineWriter<W> {
+    /// The function `new` writes the header and image identification of an
+    /// image of the given dimensions into the writer, as controlled by the
+    /// encoder options, and returns a writer ready for its scanlines.
+    pub fn new(
+        mut writer: W, width: u32, height: u32, options: &EncoderOptions
+    ) -> Result<ScanlineWriter<W>, TgaError> {
+        let depth = options.pixel_depth.unwrap_or(PixelDepth::Bits24);
+        let mut header = top_down_header(2, width, height, depth.bits() as u8)?;
+        #[cfg(feature = "rle")]
+        let rle = options.rle.unwrap_or(false);
+        #[cfg(feature = "rle")]
+        {
+            if rle {
+                header.data_type_code |= 0x08;
+            }
+        }
+        if let Some((x_origin, y_origin)) = options.origin {
+            header.set_x_origin(x_origin);
+            header.set_y_origin(y_origin);
+        }
+        let orientation = options.orientation.unwrap_or(Orientation::TopLeft);
+        let attribute_bits = options.attribute_bits.unwrap_or_else(|| depth.attribute_bits());
+        header.image_descriptor = orientation.image_descriptor_bits() | (attribute_bits & 0x0F);
+        let image_identification = options.image_identification_in_file(&[]);
+        header.id_length = image_identification.len() as u8;
+
+        writer.write_all(&header.to_bytes()).map_err(io_error)?;
+        writer.write_all(&image_identification).map_err(io_error)?;
+
+        let width = header.width();
+        let height = header.height();
+        let postage_stamp = if options.postage_stamp && (options.format == TgaFormat::New) && (width * height > 0) {
+            Some(PostageStamp::new(width, height, header.bytes_per_pixel()))
+        } else {
+            None
+        };
+
+        Ok(ScanlineWriter {
+            writer,
+            width,
+            height,
+            bits_per_pixel: depth.bits(),
+            rows_written: 0,
+            swap_channels: options.channel_order_in_file == ChannelOrder::Rgb,
+            #[cfg(feature = "rle")]
+            rle,
+            format: options.format,
+            extension_area: options.extension_area.clone(),
+            developer_tags: options.developer_tags.clone(),
+            postage_stamp,
+            alpha: header.image_descriptor & 0x0F != 0,
+            bytes_written: TGA_HEADER_LENGTH + image_identification.len(),
+            scratch: Vec::new(),
+            #[cfg(feature = "rle")]
+            packets: Vec::new(),
+        })
+    }
+
+    /// The function `rows_remaining` returns the number of scanlines still to
+    /// be written before the image is complete.
+    pub fn rows_remaining(&self) -> usize {
+        self.height - self.rows_written
+    }
+
+    /// The function `write_scanline` writes the next scanline of the image.
+    /// The scanline must hold exactly one row of pixels, and no more
+    /// scanlines may be written than the image has rows.
+    pub fn write_scanline(&mut self, scanline: &[u8]) -> Result<(), TgaError> {
+        let bytes_per_pixel = self.bits_per_pixel.div_ceil(8);
+        let row_length = self.width * bytes_per_pixel;
+        if scanline.len() != row_length {
+            return Err(TgaError::IncompleteImageData(scanline.len(), row_length));
+        }
+        if self.rows_written == self.height {
+            return Err(TgaError::UnexpectedScanlineCount(self.rows_written + 1, self.height));
+        }
+
+        let scanline = if self.swap_channels {
+            self.scratch.clear();
+            self.scratch.extend_from_slice(scanline);
+            swap_red_and_blue(&mut self.scratch, self.bits_per_pixel);
+            &self.scratch[..]
+        } else {
+            scanline
+        };
+        if let Some(ref mut postage_stamp) = self.postage_stamp {
+            postage_stamp.push_scanline(self.rows_written, scanline);
+        }
+        #[cfg(feature = "rle")]
+        let scanline = if self.rle {
+            self.packets.clear();
+            encode_rle_scanline(scanline, bytes_per_pixel, &mut self.packets);
+            &self.packets[..]
+        } else {
+            scanline
+        };
+        self.writer.write_all(scanline).map_err(io_error)?;
+        self.bytes_written += scanline.len();
+        self.rows_written += 1;
+
+        Ok(())
+    }
+
+    /// The function `finish` completes the file, writing the developer area,
+    /// postage stamp, extension area, and footer when the encoder options ask
+    /// for TGA 2.0 output, and returns the underlying writer. Every row of
+    /// the image must have been written.
+    pub fn finish(mut self) -> Result<W, TgaError> {
+        if self.rows_written != self.height {
+            return Err(TgaError::UnexpectedScanlineCount(self.rows_written, self.height));
+        }
+        if self.format == TgaFormat::New {
+            write_trailer(
+                &mut self.writer,
+                self.bytes_written,
+                &[],
+                self.extension_area.as_ref(),
+                &self.developer_tags,
+                self.postage_stamp.as_ref(),
+                self.alpha,
+            ).map_err(io_error)?;
+        }
+        self.writer.flush().map_err(io_error)?;
+
+        Ok(self.writer)
+    }
+}
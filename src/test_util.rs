@@ -0,0 +1,346 @@
+//! # Test Utilities
+//!
+//! The `test_util` module contains helpers for exercising the error handling
+//! paths of applications built on top of this library. A `FaultyReader` wraps
+//! any reader and fails after a fixed number of bytes, and `corrupt` damages a
+//! TGA file in memory in the same ways real files tend to get damaged.
+//!
+//! For checking the encoder rather than the error handling, `roundtrip_check`
+//! encodes an image with a set of encoder options, parses the result back, and
+//! reports everything that did not survive the trip.
+//!
+//! For golden image tests, `assert_dimensions`, `assert_pixel`, and
+//! `assert_images_match` check decoded images, and describe where and how an
+//! image differs when a check fails.
+use std::fmt::Write;
+use std::io;
+#[cfg(feature = "encode")]
+use crate::{ChannelOrder, EncoderOptions, TgaError, RGB_CHANNEL_ORDER_TAG};
+use crate::{TgaHeader, TgaImage, TGA_HEADER_LENGTH};
+
+
+/// A `FaultyReader` wraps another reader and returns an I/O error once a fixed
+/// number of bytes has been read from it. Every read before that point is
+/// passed through to the underlying reader unchanged.
+#[derive(Debug)]
+pub struct FaultyReader<R> {
+    inner: R,
+    fail_after: usize,
+    bytes_read: usize,
+    kind: io::ErrorKind,
+}
+
+impl<R: io::Read> FaultyReader<R> {
+    /// Construct a reader that fails with an `io::ErrorKind::Other` error
+    /// after `fail_after` bytes have been read.
+    pub fn new(inner: R, fail_after: usize) -> FaultyReader<R> {
+        Self::with_error_kind(inner, fail_after, io::ErrorKind::Other)
+    }
+
+    /// Construct a reader that fails with an error of the given kind after
+    /// `fail_after` bytes have been read.
+    pub fn with_error_kind(inner: R, fail_after: usize, kind: io::ErrorKind) -> FaultyReader<R> {
+        FaultyReader {
+            inner,
+            fail_after,
+            bytes_read: 0,
+            kind,
+        }
+    }
+
+    /// The function `bytes_read` returns the number of bytes passed through
+    /// the reader before the fault.
+    pub fn bytes_read(&self) -> usize {
+        self.bytes_read
+    }
+}
+
+impl<R: io::Read> io::Read for FaultyReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.bytes_read >= self.fail_after {
+            return Err(io::Error::new(self.kind, "injected fault"));
+        }
+
+        let remaining = self.fail_after - self.bytes_read;
+        let length = if buf.len() < remaining { buf.len() } else { remaining };
+        let bytes_read = self.inner.read(&mut buf[0..length])?;
+        self.bytes_read += bytes_read;
+
+        Ok(bytes_read)
+    }
+}
+
+/// The kinds of damage `corrupt` can do to a TGA file.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CorruptionKind {
+    /// Cut the file off after the given number of bytes.
+    Truncate(usize),
+    /// Invert every bit of the header byte at the given offset. The offset
+    /// must be less than `TGA_HEADER_LENGTH`.
+    FlipHeaderByte(usize),
+    /// Rewrite the final run length encoded packet so the packets claim
+    /// more pixels than the header says the image contains. This only
+    /// applies to run length encoded files; other files are left untouched.
+    BreakRleAccounting,
+}
+
+/// Damage a TGA file in memory. See `CorruptionKind` for the kinds of
+/// corruption available.
+pub fn corrupt(buf: &mut Vec<u8>, kind: CorruptionKind) {
+    match kind {
+        CorruptionKind::Truncate(length) => {
+            buf.truncate(length);
+        }
+        CorruptionKind::FlipHeaderByte(offset) => {
+            assert!(offset < TGA_HEADER_LENGTH);
+            buf[offset] = !buf[offset];
+        }
+        CorruptionKind::BreakRleAccounting => {
+            break_rle_accounting(buf);
+        }
+    }
+}
+
+fn break_rle_accounting(buf: &mut Vec<u8>) {
+    let header = match TgaHeader::parse_from_buffer(buf) {
+        Ok(header) => header,
+        Err(_) => return,
+    };
+
+    if header.data_type_code & 0x08 == 0 {
+        return;
+    }
+
+    let bytes_per_pixel = header.bytes_per_pixel();
+    let pixel_count = header.width() * header.height();
+    let mut offset = TGA_HEADER_LENGTH + header.id_length() + header.colour_map_size();
+    let mut last_packet = None;
+    let mut pixels_found = 0;
+    while (offset < buf.len()) && (pixels_found < pixel_count) {
+        let packet_header = buf[offset];
+        let packet_length = (packet_header & 0x7F) as usize + 1;
+        last_packet = Some(offset);
+        pixels_found += packet_length;
+        if packet_header & 0x80 != 0 {
+            offset += 1 + bytes_per_pixel;
+        } else {
+            offset += 1 + packet_length * bytes_per_pixel;
+        }
+    }
+
+    if let Some(last_packet) = last_packet {
+        if buf[last_packet] & 0x7F < 0x7F {
+            // Claim one more pixel than the image holds.
+            buf[last_packet] += 1;
+        } else {
+            // The final packet is already as long as a packet can be, so cut
+            // the packet stream off in the middle of it instead.
+            buf.truncate(last_packet + 1);
+        }
+    }
+}
+
+/// The number of differing pixels `assert_images_match` lists when it fails.
+const LISTED_MISMATCHES: usize = 10;
+
+/// Assert that an image has the given width and height.
+///
+/// # Panics
+///
+/// This function panics, naming both sets of dimensions, if the image has
+/// other dimensions.
+#[track_caller]
+pub fn assert_dimensions(image: &TgaImage, width: u32, height: u32) {
+    if (image.width() != width) || (image.height() != height) {
+        panic!(
+            "image dimensions differ: expected {}x{}, found {}x{}",
+            width, height, image.width(), image.height()
+        );
+    }
+}
+
+/// Assert that the pixel of an image at column `x` from the left edge and
+/// row `y` from the top edge has the given colour, as blue, green, red, and
+/// alpha channels.
+///
+/// # Panics
+///
+/// This function panics, naming both colours, if the pixel has another
+/// colour, and if the position lies outside the image.
+#[track_caller]
+pub fn assert_pixel(image: &TgaImage, x: u32, y: u32, colour: [u8; 4]) {
+    if (x >= image.width()) || (y >= image.height()) {
+        panic!("pixel ({}, {}) lies outside the {}x{} image", x, y, image.width(), image.height());
+    }
+    let colours = image.raw_tga_image().colours_in_display_order();
+    let found = colours[(y * image.width() + x) as usize];
+    if found != colour {
+        panic!("pixel ({}, {}) differs: expected {:?}, found {:?}", x, y, colour, found);
+    }
+}
+
+/// Assert that two images have the same dimensions, and that the channels of
+/// their pixels differ by at most `tolerance`. Pixels are compared by colour
+/// in display order, so images stored from different corners or with
+/// different compression match when they look the same.
+///
+/// # Panics
+///
+/// This function panics if the images differ, naming the number of pixels
+/// that differ, the largest difference of any channel, and the positions and
+/// colours of the first few pixels that differ.
+#[track_caller]
+pub fn assert_images_match(left: &TgaImage, right: &TgaImage, tolerance: u8) {
+    if (left.width() != right.width()) || (left.height() != right.height()) {
+        panic!(
+            "image dimensions differ: left is {}x{}, right is {}x{}",
+            left.width(), left.height(), right.width(), right.height()
+        );
+    }
+
+    let width = left.width() as usize;
+    let left_colours = left.raw_tga_image().colours_in_display_order();
+    let right_colours = right.raw_tga_image().colours_in_display_order();
+    let mut mismatched_pixels = 0;
+    let mut largest_difference = 0;
+    let mut listing = String::new();
+    for (position, (left_colour, right_colour)) in left_colours.iter().zip(right_colours.iter()).enumerate() {
+        let difference = left_colour.iter().zip(right_colour.iter())
+            .map(|(&left_channel, &right_channel)| left_channel.abs_diff(right_channel))
+            .max()
+            .unwrap_or(0);
+        if difference <= tolerance {
+            continue;
+        }
+        if mismatched_pixels < LISTED_MISMATCHES {
+            let _ = writeln!(
+                listing, "  ({}, {}): left {:?}, right {:?}",
+                position % width, position / width, left_colour, right_colour
+            );
+        }
+        mismatched_pixels += 1;
+        largest_difference = u8::max(largest_difference, difference);
+    }
+    if mismatched_pixels > 0 {
+        panic!(
+            "{} of {} pixels differ by more than {}, by up to {}; the first differences are:\n{}",
+            mismatched_pixels, left_colours.len(), tolerance, largest_difference, listing
+        );
+    }
+}
+
+/// A `RoundTripReport` records what changed when an image was encoded and
+/// parsed back by `roundtrip_check`. Pixels are compared by colour in display
+/// order, so changes of orientation, pixel layout, or compression that keep
+/// the picture intact do not count as differences.
+#[cfg(feature = "encode")]
+#[derive(Debug)]
+pub struct RoundTripReport {
+    /// The error the parser rejected the encoded file with, if any. The
+    /// remaining fields only describe the decoded image when this is `None`.
+    pub decode_error: Option<TgaError>,
+    /// Whether the decoded image has the width and height of the original.
+    pub dimensions_match: bool,
+    /// The number of pixels whose colour differs from the original.
+    pub mismatched_pixels: usize,
+    /// The position of the first pixel whose colour differs, counted in
+    /// columns from the left edge and rows from the top edge of the image.
+    pub first_mismatch: Option<(u32, u32)>,
+    /// The names of the metadata fields of the decoded image that differ
+    /// from what the encoder options asked for: `"image identification"`,
+    /// `"origin"`, and `"attribute bits"`.
+    pub metadata_mismatches: Vec<&'static str>,
+    /// The size of the encoded file, in bytes.
+    pub encoded_size: usize,
+    /// The size of the file produced by encoding the decoded image again with
+    /// the same options. A stable encoder produces a file of the same size.
+    pub reencoded_size: usize,
+}
+
+#[cfg(feature = "encode")]
+impl RoundTripReport {
+    /// The function `is_lossless` determines whether the image survived the
+    /// round trip unchanged: it decoded, its pixels and metadata match, and
+    /// encoding it again gives a file of the same size.
+    pub fn is_lossless(&self) -> bool {
+        self.decode_error.is_none()
+            && self.dimensions_match
+            && (self.mismatched_pixels == 0)
+            && self.metadata_mismatches.is_empty()
+            && (self.encoded_size == self.reencoded_size)
+    }
+}
+
+/// Encode an image with the given encoder options, parse the encoded file
+/// back, and report how the decoded image differs from the original. The
+/// metadata is compared with what the options ask for, so an option that
+/// sets the origin, for instance, is not reported as a difference. Options
+/// that lose information, such as reducing pixels to 16 bits, show up as
+/// mismatched pixels.
+#[cfg(feature = "encode")]
+pub fn roundtrip_check(image: &TgaImage, options: &EncoderOptions) -> RoundTripReport {
+    let original = image.raw_tga_image();
+    let encoded = original.encode_with_options(options);
+    let mut report = RoundTripReport {
+        decode_error: None,
+        dimensions_match: false,
+        mismatched_pixels: 0,
+        first_mismatch: None,
+        metadata_mismatches: Vec::new(),
+        encoded_size: encoded.len(),
+        reencoded_size: 0,
+    };
+    let decoded = match TgaImage::parse_from_buffer(&encoded) {
+        Ok(decoded) => decoded,
+        Err(err) => {
+            report.decode_error = Some(err);
+            return report;
+        }
+    };
+    let decoded = decoded.raw_tga_image();
+
+    report.dimensions_match = (decoded.width() == original.width()) && (decoded.height() == original.height());
+    if report.dimensions_match {
+        let width = original.width();
+        let expected = original.colours_in_display_order();
+        let result = decoded.colours_in_display_order();
+        for (position, (expected, result)) in expected.iter().zip(result.iter()).enumerate() {
+            if expected != result {
+                report.mismatched_pixels += 1;
+                if report.first_mismatch.is_none() {
+                    report.first_mismatch = Some(((position % width) as u32, (position / width) as u32));
+                }
+            }
+        }
+    }
+
+    // The field holds at most 255 bytes, including the channel order tag.
+    let mut image_identification = options.image_identification.clone()
+        .unwrap_or_else(|| original.image_identification.to_vec());
+    let room = match options.channel_order_in_file {
+        ChannelOrder::Rgb => 255 - RGB_CHANNEL_ORDER_TAG.len(),
+        ChannelOrder::Bgr => 255,
+    };
+    image_identification.truncate(room);
+    if decoded.image_identification[..] != image_identification[..] {
+        report.metadata_mismatches.push("image identification");
+    }
+    let origin = options.origin.unwrap_or((original.header.x_origin(), original.header.y_origin()));
+    if (decoded.header.x_origin(), decoded.header.y_origin()) != origin {
+        report.metadata_mismatches.push("origin");
+    }
+    let attribute_bits = match (options.attribute_bits, options.pixel_depth) {
+        (Some(attribute_bits), _) => attribute_bits & 0x0F,
+        (None, Some(depth)) if original.header.has_image_data()
+            && (original.header.is_colour_mapped() || (depth.bits() != original.header.bits_per_pixel())) => {
+            depth.attribute_bits()
+        }
+        (None, _) => original.header.image_descriptor & 0x0F,
+    };
+    if decoded.header.image_descriptor & 0x0F != attribute_bits {
+        report.metadata_mismatches.push("attribute bits");
+    }
+    report.reencoded_size = decoded.encode_with_options(options).len();
+
+    report
+}
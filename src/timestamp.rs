@@ -0,0 +1,109 @@
+//! # Timestamps
+//!
+//! The `timestamp` module contains the `Timestamp` type, the date and time an
+//! image was saved as the extension area of a TGA 2.0 file records it: six
+//! 16 bit fields for the month, day, year, hour, minute, and second. With the
+//! `time` feature, timestamps convert to and from the date and time types of
+//! the `time` crate.
+#[cfg(feature = "time")]
+use std::convert::TryFrom;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+/// A date and time, as recorded in an extension area.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Timestamp {
+    /// The year, such as 2024.
+    pub year: u16,
+    /// The month, from 1 to 12.
+    pub month: u16,
+    /// The day of the month, from 1 to 31.
+    pub day: u16,
+    /// The hour, from 0 to 23.
+    pub hour: u16,
+    /// The minute, from 0 to 59.
+    pub minute: u16,
+    /// The second, from 0 to 59.
+    pub second: u16,
+}
+
+impl Timestamp {
+    /// The function `from_system_time` converts a system time into a
+    /// timestamp in UTC. Times before 1970 are recorded as the start of 1970.
+    pub fn from_system_time(time: SystemTime) -> Timestamp {
+        let seconds = time.duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+        let days = (seconds / 86400) as i64;
+        let seconds_of_day = seconds % 86400;
+
+        // Convert the number of days since 1970 into a civil date, counting
+        // years from March so leap days fall at the end of each year.
+        let days = days + 719468;
+        let era = days / 146097;
+        let day_of_era = days - era * 146097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Timestamp {
+            year: year as u16,
+            month: month as u16,
+            day: day as u16,
+            hour: (seconds_of_day / 3600) as u16,
+            minute: (seconds_of_day / 60 % 60) as u16,
+            second: (seconds_of_day % 60) as u16,
+        }
+    }
+
+    /// The function `now` returns the current time in UTC.
+    pub fn now() -> Timestamp {
+        Timestamp::from_system_time(SystemTime::now())
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<Timestamp> for time::PrimitiveDateTime {
+    type Error = time::error::ComponentRange;
+
+    /// Convert a timestamp into a date and time, failing when a field is out
+    /// of range, such as a month of 13 or the 30th of February.
+    fn try_from(timestamp: Timestamp) -> Result<time::PrimitiveDateTime, Self::Error> {
+        // Fields too large for the `time` crate saturate, so they are still
+        // rejected as out of range.
+        let narrow = |field: u16| u16::min(field, u8::MAX as u16) as u8;
+        let month = time::Month::try_from(narrow(timestamp.month))?;
+        let date = time::Date::from_calendar_date(timestamp.year as i32, month, narrow(timestamp.day))?;
+        let time = time::Time::from_hms(narrow(timestamp.hour), narrow(timestamp.minute), narrow(timestamp.second))?;
+
+        Ok(time::PrimitiveDateTime::new(date, time))
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::PrimitiveDateTime> for Timestamp {
+    /// Convert a date and time into a timestamp, dropping the fraction of a
+    /// second. Years outside the 16 bit field of a timestamp are clamped.
+    fn from(date_time: time::PrimitiveDateTime) -> Timestamp {
+        Timestamp {
+            year: date_time.year().clamp(0, u16::MAX as i32) as u16,
+            month: u8::from(date_time.month()) as u16,
+            day: date_time.day() as u16,
+            hour: date_time.hour() as u16,
+            minute: date_time.minute() as u16,
+            second: date_time.second() as u16,
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for Timestamp {
+    /// Convert a date and time into a timestamp in UTC, the time zone
+    /// `Timestamp::now` records.
+    fn from(date_time: time::OffsetDateTime) -> Timestamp {
+        let date_time = date_time.to_offset(time::UtcOffset::UTC);
+
+        Timestamp::from(time::PrimitiveDateTime::new(date_time.date(), date_time.time()))
+    }
+}
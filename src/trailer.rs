@@ -0,0 +1,372 @@
+//! # Trailing Areas
+//!
+//! The `trailer` module finds the TGA 2.0 extension area and developer area
+//! among the bytes a parsed image keeps from after its pixel data. The footer
+//! at the end of those bytes records where each area starts as an offset
+//! into the source file, so the position the bytes had in the file is kept
+//! alongside them to resolve the offsets.
+//!
+//! The trailing bytes are classified into the extension area, the developer
+//! area, and the unknown bytes before them, each of which can be read on its
+//! own. The module also reads the fields of the extension area that describe
+//! how every image is displayed, the key colour and the pixel aspect ratio.
+#[cfg(feature = "encode")]
+use crate::DeveloperTag;
+#[cfg(feature = "metadata")]
+use crate::{TgaError, TgaImage};
+use crate::{RawTgaImage, EXTENSION_AREA_LENGTH, TGA_FOOTER};
+
+
+/// The positions of the key colour and the pixel aspect ratio within the
+/// extension area.
+#[cfg(any(feature = "metadata", feature = "encode"))]
+const KEY_COLOUR_OFFSET: usize = 470;
+#[cfg(any(feature = "metadata", feature = "encode"))]
+const PIXEL_ASPECT_RATIO_OFFSET: usize = 474;
+
+/// The positions of the offsets of the colour correction table, the postage
+/// stamp, and the scan line table within the extension area.
+#[cfg(feature = "metadata")]
+const COLOUR_CORRECTION_OFFSET: usize = 482;
+#[cfg(any(feature = "metadata", feature = "encode"))]
+const POSTAGE_STAMP_OFFSET: usize = 486;
+#[cfg(feature = "metadata")]
+const SCAN_LINE_TABLE_OFFSET: usize = 490;
+
+/// The length of a colour correction table: 256 entries of four 16 bit
+/// channels.
+#[cfg(feature = "metadata")]
+const COLOUR_CORRECTION_LENGTH: usize = 2048;
+
+/// The length of a developer directory entry: a tag, an offset, and a size.
+#[cfg(any(feature = "metadata", feature = "encode"))]
+const DEVELOPER_ENTRY_LENGTH: usize = 10;
+
+/// Read a little endian 32 bit value.
+fn read_u32(buf: &[u8], position: usize) -> usize {
+    u32::from_le_bytes([buf[position], buf[position + 1], buf[position + 2], buf[position + 3]]) as usize
+}
+
+impl RawTgaImage {
+    /// Resolve a file offset into a position within the trailing bytes of the
+    /// image, checking that `length` bytes starting there lie before the
+    /// footer. Offsets of zero mean an area is absent.
+    fn trailing_range(&self, offset: usize, length: usize) -> Option<usize> {
+        let trailing = &self.extended_image_identification;
+        let position = offset.checked_sub(self.trailing_position?)?;
+        let end = position.checked_add(length)?;
+        if (offset == 0) || (end > trailing.len() - TGA_FOOTER.len()) {
+            return None;
+        }
+
+        Some(position)
+    }
+
+    /// The offset recorded at `field` in the footer of the file the image was
+    /// parsed from: 0 for the extension area, and 4 for the developer
+    /// directory.
+    fn footer_offset(&self, field: usize) -> Option<usize> {
+        let trailing = &self.extended_image_identification;
+        if (trailing.len() < TGA_FOOTER.len()) || !trailing.ends_with(&TGA_FOOTER[8..]) {
+            return None;
+        }
+
+        Some(read_u32(trailing, trailing.len() - TGA_FOOTER.len() + field))
+    }
+
+    /// The position of the extension area within the trailing bytes of the
+    /// image. Areas too short to be a TGA 2.0 extension area are ignored.
+    pub(crate) fn extension_area_position(&self) -> Option<usize> {
+        let position = self.trailing_range(self.footer_offset(0)?, EXTENSION_AREA_LENGTH)?;
+        let trailing = &self.extended_image_identification;
+        let size = u16::from_le_bytes([trailing[position], trailing[position + 1]]) as usize;
+        if size < EXTENSION_AREA_LENGTH {
+            return None;
+        }
+
+        Some(position)
+    }
+
+    /// Read the ratio stored as a numerator and denominator at `field` in
+    /// the extension area of the image. Ratios with a zero numerator or
+    /// denominator are unspecified.
+    pub(crate) fn extension_area_ratio(&self, field: usize) -> Option<(u16, u16)> {
+        let position = self.extension_area_position()? + field;
+        let bytes = &self.extended_image_identification[position..(position + 4)];
+        let numerator = u16::from_le_bytes([bytes[0], bytes[1]]);
+        let denominator = u16::from_le_bytes([bytes[2], bytes[3]]);
+        if (numerator == 0) || (denominator == 0) {
+            return None;
+        }
+
+        Some((numerator, denominator))
+    }
+
+    /// The key colour recorded in the extension area of the image.
+    #[cfg(any(feature = "metadata", feature = "encode"))]
+    pub(crate) fn key_colour(&self) -> Option<[u8; 4]> {
+        let position = self.extension_area_position()? + KEY_COLOUR_OFFSET;
+        let bytes = &self.extended_image_identification[position..(position + 4)];
+        let colour = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if colour == [0; 4] {
+            return None;
+        }
+
+        Some(colour)
+    }
+
+    /// Check that `length` bytes starting at the file offset `offset` lie
+    /// between the pixel data and the footer of the file the image was
+    /// parsed from, naming the area they belong to when they do not.
+    #[cfg(feature = "metadata")]
+    fn check_area(&self, area: &'static str, offset: usize, length: usize) -> Result<(), TgaError> {
+        let trailing_position = self.trailing_position.unwrap_or(0);
+        let footer_position = trailing_position + self.extended_image_identification.len() - TGA_FOOTER.len();
+        if offset < trailing_position {
+            return Err(TgaError::InvalidAreaOffset(area, offset, "overlaps the data before the trailing bytes"));
+        }
+        if offset >= footer_position + TGA_FOOTER.len() {
+            return Err(TgaError::InvalidAreaOffset(area, offset, "points past the end of the file"));
+        }
+        if offset.checked_add(length).map_or(true, |end| end > footer_position) {
+            return Err(TgaError::InvalidAreaOffset(area, offset, "runs into the footer or past the end of the file"));
+        }
+
+        Ok(())
+    }
+
+    /// Check the offsets of the footer, the extension area, and the
+    /// developer directory of the image.
+    #[cfg(feature = "metadata")]
+    pub(crate) fn validate_trailing_areas(&self) -> Result<(), TgaError> {
+        let trailing_position = match self.trailing_position {
+            Some(trailing_position) => trailing_position,
+            None => return Ok(()),
+        };
+        let trailing = &self.extended_image_identification;
+        let read_u16 = |offset: usize| {
+            let position = offset - trailing_position;
+            u16::from_le_bytes([trailing[position], trailing[position + 1]]) as usize
+        };
+
+        let extension_area = self.footer_offset(0).unwrap_or(0);
+        if extension_area != 0 {
+            self.check_area("extension area", extension_area, 2)?;
+            let size = read_u16(extension_area);
+            if size < EXTENSION_AREA_LENGTH {
+                return Err(TgaError::InvalidAreaOffset("extension area", extension_area, "is too short"));
+            }
+            self.check_area("extension area", extension_area, size)?;
+
+            let field = |offset: usize| read_u32(trailing, extension_area - trailing_position + offset);
+            let colour_correction = field(COLOUR_CORRECTION_OFFSET);
+            if colour_correction != 0 {
+                self.check_area("colour correction table", colour_correction, COLOUR_CORRECTION_LENGTH)?;
+            }
+            let postage_stamp = field(POSTAGE_STAMP_OFFSET);
+            if postage_stamp != 0 {
+                self.check_area("postage stamp", postage_stamp, 2)?;
+                let position = postage_stamp - trailing_position;
+                let pixels = trailing[position] as usize * trailing[position + 1] as usize;
+                self.check_area("postage stamp", postage_stamp, 2 + pixels * self.header.bytes_per_pixel())?;
+            }
+            let scan_line_table = field(SCAN_LINE_TABLE_OFFSET);
+            if scan_line_table != 0 {
+                self.check_area("scan line table", scan_line_table, 4 * self.height())?;
+            }
+        }
+
+        let directory = self.footer_offset(4).unwrap_or(0);
+        if directory != 0 {
+            self.check_area("developer directory", directory, 2)?;
+            let entries = read_u16(directory);
+            self.check_area("developer directory", directory, 2 + entries * DEVELOPER_ENTRY_LENGTH)?;
+            for entry in 0..entries {
+                let position = directory - trailing_position + 2 + entry * DEVELOPER_ENTRY_LENGTH;
+                let size = read_u32(trailing, position + 6);
+                if size > 0 {
+                    self.check_area("developer tag", read_u32(trailing, position + 2), size)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The extension area of the image, as many bytes long as its size
+    /// field says.
+    #[cfg(feature = "metadata")]
+    fn extension_area(&self) -> Option<&[u8]> {
+        let position = self.extension_area_position()?;
+        let trailing = &self.extended_image_identification;
+        let size = u16::from_le_bytes([trailing[position], trailing[position + 1]]) as usize;
+        let length = self.trailing_range(self.footer_offset(0)?, size).map_or(EXTENSION_AREA_LENGTH, |_| size);
+
+        Some(&trailing[position..(position + length)])
+    }
+
+    /// The tag numbers and data of the developer tags of the image.
+    #[cfg(any(feature = "metadata", feature = "encode"))]
+    fn developer_area(&self) -> Vec<(u16, &[u8])> {
+        self.developer_entries().into_iter().map(|(tag, data, size)| {
+            (tag, &self.extended_image_identification[data..(data + size)])
+        }).collect()
+    }
+
+    /// The pixel aspect ratio recorded in the extension area of the image.
+    #[cfg(any(feature = "metadata", feature = "encode"))]
+    pub(crate) fn pixel_aspect_ratio(&self) -> Option<(u16, u16)> {
+        self.extension_area_ratio(PIXEL_ASPECT_RATIO_OFFSET)
+    }
+
+    /// The tag numbers, positions within the trailing bytes, and sizes of the
+    /// developer tags listed in the developer directory of the file the image
+    /// was parsed from, in the order they are listed. Entries whose data lies
+    /// outside the trailing bytes are skipped.
+    #[cfg(any(feature = "metadata", feature = "encode"))]
+    fn developer_entries(&self) -> Vec<(u16, usize, usize)> {
+        let trailing = &self.extended_image_identification;
+        let offset = match self.footer_offset(4) {
+            Some(offset) => offset,
+            None => return Vec::new(),
+        };
+        let directory = match self.trailing_range(offset, 2) {
+            Some(directory) => directory,
+            None => return Vec::new(),
+        };
+        let entries = u16::from_le_bytes([trailing[directory], trailing[directory + 1]]) as usize;
+        if self.trailing_range(offset, 2 + entries * DEVELOPER_ENTRY_LENGTH).is_none() {
+            return Vec::new();
+        }
+
+        (0..entries).filter_map(|entry| {
+            let position = directory + 2 + entry * DEVELOPER_ENTRY_LENGTH;
+            let tag = u16::from_le_bytes([trailing[position], trailing[position + 1]]);
+            let size = read_u32(trailing, position + 6);
+            let data = self.trailing_range(read_u32(trailing, position + 2), size)?;
+
+            Some((tag, data, size))
+        }).collect()
+    }
+
+    /// The data of the first developer tag with the given tag number, as
+    /// listed in the developer directory of the file the image was parsed
+    /// from.
+    #[cfg(any(feature = "metadata", feature = "encode"))]
+    pub(crate) fn developer_tag(&self, tag: u16) -> Option<&[u8]> {
+        let (_, data, size) = self.developer_entries().into_iter().find(|&(entry_tag, _, _)| entry_tag == tag)?;
+
+        Some(&self.extended_image_identification[data..(data + size)])
+    }
+
+    /// The developer tags listed in the developer directory of the file the
+    /// image was parsed from, in the order they are listed.
+    #[cfg(feature = "encode")]
+    pub(crate) fn developer_tags(&self) -> Vec<DeveloperTag> {
+        self.developer_area().into_iter().map(|(tag, data)| DeveloperTag { tag, data: data.to_vec() }).collect()
+    }
+
+    /// The length of the trailing bytes of the image that come before the
+    /// TGA 2.0 areas of the file it was parsed from: the developer area, the
+    /// extension area and its postage stamp, and the footer. The encoder
+    /// writes areas of its own, so only the bytes before these are kept.
+    #[cfg(any(feature = "metadata", feature = "encode"))]
+    pub(crate) fn trailing_data_length(&self) -> usize {
+        let trailing = &self.extended_image_identification;
+        if (trailing.len() < TGA_FOOTER.len()) || !trailing.ends_with(&TGA_FOOTER[8..]) {
+            return trailing.len();
+        }
+
+        let mut length = trailing.len() - TGA_FOOTER.len();
+        for (_, data, size) in self.developer_entries() {
+            if size > 0 {
+                length = usize::min(length, data);
+            }
+        }
+        if let Some(directory) = self.footer_offset(4).and_then(|offset| self.trailing_range(offset, 2)) {
+            length = usize::min(length, directory);
+        }
+        if let Some(extension_area) = self.extension_area_position() {
+            length = usize::min(length, extension_area);
+            let postage_stamp = read_u32(trailing, extension_area + POSTAGE_STAMP_OFFSET);
+            if let Some(postage_stamp) = self.trailing_range(postage_stamp, 2) {
+                length = usize::min(length, postage_stamp);
+            }
+        }
+
+        length
+    }
+}
+
+#[cfg(feature = "metadata")]
+impl TgaImage {
+    /// The function `key_colour` returns the key colour recorded in the
+    /// extension area of the file the image was read from: the background
+    /// colour of the areas of the display the image does not cover, which
+    /// compositors use as the matte. The colour is given as blue, green, red,
+    /// and alpha channels. It returns `None` when the file has no extension
+    /// area, or records a key colour of zero, which the TGA specification
+    /// uses for an unspecified one. The key colour is kept when the image is
+    /// encoded again.
+    pub fn key_colour(&self) -> Option<[u8; 4]> {
+        self.raw_tga_image().key_colour()
+    }
+
+    /// The function `pixel_aspect_ratio` returns the ratio of the width of a
+    /// pixel to its height, as recorded in the extension area of the file the
+    /// image was read from, so anamorphic frames can be displayed at their
+    /// intended shape. Square pixels are `(1, 1)`. It returns `None` when the
+    /// file has no extension area or leaves the ratio unspecified. The ratio
+    /// is kept when the image is encoded again.
+    pub fn pixel_aspect_ratio(&self) -> Option<(u16, u16)> {
+        self.raw_tga_image().pixel_aspect_ratio()
+    }
+
+    /// The function `extension_area` returns the bytes of the TGA 2.0
+    /// extension area of the file the image was read from, starting with its
+    /// two byte size field, when the footer of the file points at one at
+    /// least 495 bytes long. The fields the library knows are read by
+    /// accessors of their own, such as `key_colour`.
+    pub fn extension_area(&self) -> Option<&[u8]> {
+        self.raw_tga_image().extension_area()
+    }
+
+    /// The function `developer_area` returns the tag numbers and data of the
+    /// developer tags listed in the developer directory of the file the image
+    /// was read from, in the order they are listed. Entries whose data lies
+    /// outside the file are skipped.
+    pub fn developer_area(&self) -> Vec<(u16, &[u8])> {
+        self.raw_tga_image().developer_area()
+    }
+
+    /// The function `developer_tag` returns the data of the first developer
+    /// tag with the given tag number in the developer area of the file the
+    /// image was read from.
+    pub fn developer_tag(&self, tag: u16) -> Option<&[u8]> {
+        self.raw_tga_image().developer_tag(tag)
+    }
+
+    /// The function `unknown_trailing_bytes` returns the bytes of the file the
+    /// image was read from that follow the pixel data and come before the
+    /// developer area, the extension area, and the footer: data of neither
+    /// TGA version, such as an application's own trailer. These bytes are
+    /// kept when the image is encoded again.
+    pub fn unknown_trailing_bytes(&self) -> &[u8] {
+        let image = self.raw_tga_image();
+
+        &image.extended_image_identification[..image.trailing_data_length()]
+    }
+
+    /// The function `validate_trailing_areas` checks the offsets the footer
+    /// of the file the image was read from records, and the offsets the
+    /// extension area and the developer directory record in turn. Parsing
+    /// never fails over these offsets: areas they do not find are left out,
+    /// and their bytes count as unknown trailing bytes. This function reports
+    /// the first offset that points into the header or pixel data, into the
+    /// footer, or past the end of the file, with `InvalidAreaOffset`, naming
+    /// the area and the offset. Files without a TGA 2.0 footer, and images
+    /// that were not read from a file, always pass.
+    pub fn validate_trailing_areas(&self) -> Result<(), TgaError> {
+        self.raw_tga_image().validate_trailing_areas()
+    }
+}
@@ -0,0 +1,198 @@
+//! # Transcoding
+//!
+//! The `transcode` module switches TGA files between uncompressed and run
+//! length encoded pixel data without decoding their pixels into colours. The
+//! header, image identification, colour map, and everything stored after the
+//! pixel data are carried over byte for byte, apart from the file offsets that
+//! must move with the pixel data.
+use crate::lowlevel::{parse_packet, Packet};
+use crate::rle::encode_rle_scanline;
+use crate::{TgaError, TgaHeader, TGA_HEADER_LENGTH};
+
+
+/// The signature that ends a TGA 2.0 file footer.
+const TGA_SIGNATURE: &[u8; 18] = b"TRUEVISION-XFILE.\0";
+
+/// The length of a TGA 2.0 file footer.
+const FOOTER_LENGTH: usize = 26;
+
+/// The length of a TGA 2.0 extension area.
+const EXTENSION_AREA_LENGTH: usize = 495;
+
+/// The positions of the colour correction table, postage stamp, and scan line
+/// table offsets within an extension area.
+const COLOUR_CORRECTION_OFFSET: usize = 482;
+const POSTAGE_STAMP_OFFSET: usize = 486;
+const SCAN_LINE_TABLE_OFFSET: usize = 490;
+
+/// The length of a developer directory entry: a tag, an offset, and a size.
+const DEVELOPER_ENTRY_LENGTH: usize = 10;
+
+/// Read a little endian 32 bit value.
+fn read_u32(buf: &[u8], position: usize) -> u32 {
+    u32::from_le_bytes([buf[position], buf[position + 1], buf[position + 2], buf[position + 3]])
+}
+
+/// Write a little endian 32 bit value.
+fn write_u32(buf: &mut [u8], position: usize, value: u32) {
+    buf[position..(position + 4)].copy_from_slice(&value.to_le_bytes());
+}
+
+/// The bytes stored after the pixel data of a file, together with the file
+/// offsets they contain. Offsets pointing past the pixel data are moved when
+/// the pixel data changes size.
+struct TrailingAreas {
+    bytes: Vec<u8>,
+    old_start: usize,
+    new_start: usize,
+}
+
+impl TrailingAreas {
+    /// Move the offset stored at `position` in the trailing bytes, if it points
+    /// into them. Offsets of zero mean the area is absent and are left alone.
+    fn shift_offset(&mut self, position: usize) -> Option<usize> {
+        if position + 4 > self.bytes.len() {
+            return None;
+        }
+        let offset = read_u32(&self.bytes, position) as usize;
+        if (offset == 0) || (offset < self.old_start) {
+            return None;
+        }
+        write_u32(&mut self.bytes, position, (offset - self.old_start + self.new_start) as u32);
+
+        Some(offset - self.old_start)
+    }
+
+    /// Move every offset in a TGA 2.0 footer, extension area, and developer
+    /// directory. The scan line table of the extension area records where each
+    /// scanline of the pixel data starts, which transcoding changes, so it is
+    /// dropped by clearing its offset.
+    fn shift_offsets(&mut self) {
+        let length = self.bytes.len();
+        if (length < FOOTER_LENGTH) || !self.bytes.ends_with(TGA_SIGNATURE) {
+            return;
+        }
+        let footer = length - FOOTER_LENGTH;
+        if let Some(extension_area) = self.shift_offset(footer) {
+            if extension_area + EXTENSION_AREA_LENGTH <= length {
+                self.shift_offset(extension_area + COLOUR_CORRECTION_OFFSET);
+                self.shift_offset(extension_area + POSTAGE_STAMP_OFFSET);
+                write_u32(&mut self.bytes, extension_area + SCAN_LINE_TABLE_OFFSET, 0);
+            }
+        }
+        if let Some(directory) = self.shift_offset(footer + 4) {
+            if directory + 2 <= length {
+                let entries = u16::from_le_bytes([self.bytes[directory], self.bytes[directory + 1]]) as usize;
+                for entry in 0..entries {
+                    self.shift_offset(directory + 2 + entry * DEVELOPER_ENTRY_LENGTH + 2);
+                }
+            }
+        }
+    }
+}
+
+/// Decompress run length encoded pixel data one scanline at a time, passing
+/// each scanline to `emit`. Packets may cross scanlines. The function returns
+/// the number of bytes the packets occupied.
+fn for_each_decoded_scanline<F: FnMut(&[u8])>(
+    buf: &[u8], header: &TgaHeader, mut emit: F
+) -> Result<usize, TgaError> {
+    let bytes_per_pixel = header.bytes_per_pixel();
+    let row_length = header.width() * bytes_per_pixel;
+    let image_size = row_length * header.height();
+    let mut row = Vec::with_capacity(row_length + 128 * bytes_per_pixel);
+    let mut decoded = 0;
+    let mut offset = 0;
+    while decoded < image_size {
+        let (packet, packet_size) = parse_packet(&buf[offset..], bytes_per_pixel)
+            .map_err(|_| TgaError::IncompleteImageData(decoded, image_size))?;
+        match packet {
+            Packet::Run { length, pixel } => {
+                for _ in 0..length {
+                    row.extend_from_slice(pixel);
+                }
+            }
+            Packet::Raw { pixels } => row.extend_from_slice(pixels),
+        }
+        decoded += packet.pixel_count(bytes_per_pixel) * bytes_per_pixel;
+        offset += packet_size;
+        if decoded > image_size {
+            return Err(TgaError::IncompleteImageData(decoded, image_size));
+        }
+
+        // The loop only runs for images with pixels, so rows are never empty.
+        let complete_rows = row.len() / row_length;
+        for scanline in row.chunks(row_length).take(complete_rows) {
+            emit(scanline);
+        }
+        row.drain(0..(complete_rows * row_length));
+    }
+
+    Ok(offset)
+}
+
+/// The function `transcode` converts a TGA file between uncompressed and run
+/// length encoded pixel data: type 1 and type 9 files for colour mapped
+/// images, type 2 and type 10 files for true colour images, and type 3 and
+/// type 11 files for grayscale images. With `rle` set the file is run length
+/// encoded, and otherwise it is uncompressed. A file already in the requested
+/// form is returned unchanged.
+///
+/// The pixels are never decoded into colours: run length encoding works on
+/// the stored scanlines, and decompression expands packets straight into
+/// scanlines. The header, image identification, colour map, and the areas
+/// following the pixel data are copied unchanged, except that the offsets in
+/// a TGA 2.0 footer, extension area, and developer directory are moved along
+/// with the areas they point to. The scan line table offset of an extension
+/// area is cleared, since the scanlines no longer start where it says.
+pub fn transcode(buf: &[u8], rle: bool) -> Result<Vec<u8>, TgaError> {
+    let header = TgaHeader::parse_from_buffer(buf)?;
+    match header.data_type_code & !0x08 {
+        1..=3 => {}
+        _ => return Err(TgaError::Not24BitRgb(header.data_type_code as usize)),
+    }
+    if header.is_run_length_encoded() == rle {
+        return Ok(buf.to_vec());
+    }
+
+    let data_start = TGA_HEADER_LENGTH + header.id_length() + header.colour_map_size();
+    if buf.len() < data_start {
+        return Err(TgaError::IncompleteColourMap(
+            buf.len().saturating_sub(TGA_HEADER_LENGTH + header.id_length()), header.colour_map_size()
+        ));
+    }
+    let mut target = header;
+    target.data_type_code ^= 0x08;
+    let mut transcoded = Vec::with_capacity(buf.len());
+    transcoded.extend_from_slice(&target.to_bytes());
+    transcoded.extend_from_slice(&buf[TGA_HEADER_LENGTH..data_start]);
+
+    let data = &buf[data_start..];
+    let data_length = if header.is_run_length_encoded() {
+        for_each_decoded_scanline(data, &header, |scanline| transcoded.extend_from_slice(scanline))?
+    } else {
+        let bytes_per_pixel = header.bytes_per_pixel();
+        let row_length = header.width() * bytes_per_pixel;
+        let image_size = row_length * header.height();
+        if data.len() < image_size {
+            return Err(TgaError::IncompleteImageData(data.len(), image_size));
+        }
+        if row_length > 0 {
+            for scanline in data[0..image_size].chunks(row_length) {
+                encode_rle_scanline(scanline, bytes_per_pixel, &mut transcoded);
+            }
+        }
+
+        image_size
+    };
+
+    let mut trailing = TrailingAreas {
+        bytes: data[data_length..].to_vec(),
+        old_start: data_start + data_length,
+        new_start: transcoded.len(),
+    };
+    trailing.shift_offsets();
+    transcoded.extend_from_slice(&trailing.bytes);
+
+    Ok(transcoded)
+}
@@ -0,0 +1,150 @@
+//! # Pixel Transforms
+//!
+//! The `transforms` module contains operations that change the colours of a
+//! decoded image. Every transform works on the colours of the image, so for a
+//! colour mapped image it changes the colour map rather than the indices.
+//!
+//! The TGA format has no notion of colour spaces, so the library never
+//! converts between them itself. Applications that track the colour space of
+//! their images elsewhere supply the conversion as a `ColourTransform`, which
+//! the library applies wherever it converts colours.
+use std::fmt;
+use std::sync::Arc;
+use crate::{decode_pixel, encode_pixel, RawTgaImage, TgaImage};
+
+
+/// A `Brightness` adjustment shifts every colour channel by a fraction of full
+/// intensity. A brightness of `0.0` leaves the image unchanged, `1.0` turns it
+/// white, and `-1.0` turns it black.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Brightness(pub f32);
+
+/// A `Contrast` adjustment scales every colour channel about mid grey. A
+/// contrast of `1.0` leaves the image unchanged, larger values increase the
+/// contrast, and values between `0.0` and `1.0` reduce it.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Contrast(pub f32);
+
+/// A `Gamma` adjustment raises every colour channel to the power `1 / gamma`.
+/// A gamma of `1.0` leaves the image unchanged, and larger values brighten
+/// the midtones. The gamma must be positive.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Gamma(pub f32);
+
+/// A `ColourTransform` converts colours from one colour space to another,
+/// such as from a wide gamut space into sRGB. Colours are given and returned
+/// as blue, green, red, and alpha channels. Any function or closure from one
+/// colour to another is a colour transform.
+pub trait ColourTransform {
+    /// Convert a single colour.
+    fn transform(&self, colour: [u8; 4]) -> [u8; 4];
+}
+
+impl<F> ColourTransform for F where F: Fn([u8; 4]) -> [u8; 4] {
+    fn transform(&self, colour: [u8; 4]) -> [u8; 4] {
+        self(colour)
+    }
+}
+
+impl fmt::Debug for dyn ColourTransform {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("ColourTransform")
+    }
+}
+
+/// Convert an sRGB encoded channel value in the range `[0, 1]` to linear light.
+#[inline]
+pub(crate) fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert a linear light channel value in the range `[0, 1]` to sRGB encoding.
+#[inline]
+pub(crate) fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Build a lookup table mapping every eight bit channel value to its adjusted
+/// value. When `linear_light` is set, brightness and contrast are applied to
+/// linear light values instead of the encoded ones.
+pub(crate) fn adjustment_table(
+    brightness: Brightness, contrast: Contrast, gamma: Gamma, linear_light: bool
+) -> [u8; 256] {
+    assert!(gamma.0 > 0.0, "gamma must be positive, got {}", gamma.0);
+
+    let mut table = [0; 256];
+    for (value, entry) in table.iter_mut().enumerate() {
+        let mut channel = value as f32 / 255.0;
+        if linear_light {
+            channel = srgb_to_linear(channel);
+        }
+        channel = (channel - 0.5) * contrast.0 + 0.5 + brightness.0;
+        channel = channel.clamp(0.0, 1.0);
+        if linear_light {
+            channel = linear_to_srgb(channel);
+        }
+        channel = channel.powf(1.0 / gamma.0);
+        *entry = (channel.clamp(0.0, 1.0) * 255.0).round() as u8;
+    }
+
+    table
+}
+
+impl RawTgaImage {
+    /// Apply a function to the colour of every pixel in the image. The colour
+    /// is given as blue, green, red, and alpha channels. For a colour mapped
+    /// image, the function is applied to each colour map entry instead.
+    pub(crate) fn map_colours<F>(&mut self, f: F) where F: Fn([u8; 4]) -> [u8; 4] {
+        let (data, bits) = if self.header.is_colour_mapped() {
+            (Arc::make_mut(&mut self.colour_map_data).as_mut_slice(), self.header.colour_map_depth())
+        } else {
+            let bits = self.header.bits_per_pixel();
+            (self.image_data_mut(), bits)
+        };
+
+        let bytes_per_pixel = bits.div_ceil(8);
+        for bytes in data.chunks_mut(bytes_per_pixel) {
+            encode_pixel(f(decode_pixel(bytes, bits)), bits, bytes);
+        }
+    }
+}
+
+impl TgaImage {
+    /// The function `adjust` applies a brightness, contrast, and gamma
+    /// adjustment to every pixel of the image, working directly on the stored
+    /// channel values. Brightness and contrast are applied first, then gamma.
+    /// The alpha channel is left unchanged.
+    pub fn adjust(&mut self, brightness: Brightness, contrast: Contrast, gamma: Gamma) {
+        let table = adjustment_table(brightness, contrast, gamma, false);
+        self.raw_tga_image_mut().map_colours(|colour| {
+            [table[colour[0] as usize], table[colour[1] as usize], table[colour[2] as usize], colour[3]]
+        });
+    }
+
+    /// The function `adjust_linear` applies the same adjustment as `adjust`,
+    /// except that brightness and contrast are applied in linear light. The
+    /// stored channel values are taken to be sRGB encoded, so this gives
+    /// perceptually correct results at the cost of a conversion per channel.
+    /// Gamma is still applied to the encoded values.
+    pub fn adjust_linear(&mut self, brightness: Brightness, contrast: Contrast, gamma: Gamma) {
+        let table = adjustment_table(brightness, contrast, gamma, true);
+        self.raw_tga_image_mut().map_colours(|colour| {
+            [table[colour[0] as usize], table[colour[1] as usize], table[colour[2] as usize], colour[3]]
+        });
+    }
+
+    /// The function `apply_colour_transform` converts the colour of every
+    /// pixel of the image with a colour transform. For a colour mapped image,
+    /// the colour map entries are converted instead.
+    pub fn apply_colour_transform<T: ColourTransform + ?Sized>(&mut self, transform: &T) {
+        self.raw_tga_image_mut().map_colours(|colour| transform.transform(colour));
+    }
+}
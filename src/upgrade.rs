@@ -0,0 +1,38 @@
+//! # Upgrading Files
+//!
+//! The `upgrade` module turns TGA 1.0 files into TGA 2.0 files by appending
+//! the footer, and an extension area for it to point at, after everything
+//! the file already holds. Nothing in the file is moved or rewritten, so the
+//! upgrade works on any file whose pixels this library cannot decode.
+use crate::extension::write_trailer;
+use crate::{ExtensionArea, TgaHeader, Timestamp, TGA_FOOTER};
+
+
+/// The function `upgrade_to_v2` returns a copy of a TGA 1.0 file with an
+/// extension area and a TGA 2.0 footer appended. The extension area records
+/// the current time and this library as the software that saved the file,
+/// and whether the pixels carry alpha, going by the attribute bits of the
+/// header. Files that already end with a TGA 2.0 footer, and buffers too
+/// short to hold a header, are returned unchanged, as are files too large
+/// for the 32 bit offsets of the footer.
+pub fn upgrade_to_v2(buf: &[u8]) -> Vec<u8> {
+    let header = match TgaHeader::parse_from_buffer(buf) {
+        Ok(header) => header,
+        Err(_) => return buf.to_vec(),
+    };
+    if buf.ends_with(&TGA_FOOTER[8..]) {
+        return buf.to_vec();
+    }
+
+    let extension_area = ExtensionArea {
+        timestamp: Some(Timestamp::now()),
+        software_id: format!("tga {}", env!("CARGO_PKG_VERSION")),
+        ..ExtensionArea::default()
+    };
+    let alpha = header.image_descriptor & 0x0F != 0;
+    let mut upgraded = buf.to_vec();
+    match write_trailer(&mut upgraded, buf.len(), &[], Some(&extension_area), &[], None, alpha) {
+        Ok(()) => upgraded,
+        Err(_) => buf.to_vec(),
+    }
+}
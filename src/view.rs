@@ -0,0 +1,137 @@
+//! # Views
+//!
+//! The `view` module creates images that share their pixels with the image
+//! they were taken from. A view borrows nothing: it holds its own reference to
+//! the shared buffers, and only copies them when it is modified.
+use crate::{RawTgaImage, TgaError, TgaImage};
+use std::cell::OnceCell;
+
+
+impl RawTgaImage {
+    /// Create a view of a rectangular region of the image. The region must lie
+    /// within the image.
+    fn view(&self, x: usize, y: usize, width: usize, height: usize) -> RawTgaImage {
+        let mut header = self.header;
+        header.width = (width as u16).to_le_bytes();
+        header.height = (height as u16).to_le_bytes();
+
+        RawTgaImage {
+            header,
+            image_identification: self.image_identification.clone(),
+            colour_map_data: self.colour_map_data.clone(),
+            image_data: self.image_data.clone(),
+            extended_image_identification: self.extended_image_identification.clone(),
+            trailing_position: self.trailing_position,
+            interleaving: self.interleaving,
+            data_offset: self.data_offset + y * self.data_stride + x * self.header.bytes_per_pixel(),
+            data_stride: self.data_stride,
+            contiguous_data: OnceCell::new(),
+        }
+    }
+}
+
+impl TgaImage {
+    /// Wrap raw image data in the same type of image as this one.
+    pub(crate) fn with_raw_tga_image(&self, inner: RawTgaImage) -> TgaImage {
+        match *self {
+            TgaImage::Empty(_) => TgaImage::Empty(crate::EmptyImage { inner }),
+            TgaImage::Type01(_) => TgaImage::Type01(crate::UncompressedColorMapped { inner }),
+            TgaImage::Type02(_) => TgaImage::Type02(crate::UncompressedRgb { inner }),
+            #[cfg(feature = "rle")]
+            TgaImage::Type09(_) => TgaImage::Type09(crate::RunLengthEncodedColorMapped { inner }),
+            #[cfg(feature = "rle")]
+            TgaImage::Type10(_) => TgaImage::Type10(crate::RunLengthEncodedRgb { inner }),
+        }
+    }
+
+    /// The function `crop` returns a view of a rectangular region of the image.
+    /// The view shares its pixels with the image, so cropping never copies
+    /// pixel data; the view gets its own copy of the pixels only when it is
+    /// modified. Coordinates follow the order the pixels are stored in, the
+    /// same order `pixels` yields them, so `(0, 0)` is the first stored pixel.
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Result<TgaImage, TgaError> {
+        let fits = |start: u32, length: u32, limit: u32| {
+            start.checked_add(length).is_some_and(|end| end <= limit)
+        };
+        if !fits(x, width, self.width()) || !fits(y, height, self.height()) {
+            return Err(TgaError::RegionOutOfBounds(x, y, width, height));
+        }
+
+        let inner = self.raw_tga_image().view(x as usize, y as usize, width as usize, height as usize);
+
+        Ok(self.with_raw_tga_image(inner))
+    }
+
+    /// The function `tiles` returns an iterator that splits the image into
+    /// views of `tile_width` by `tile_height` pixels, together with the
+    /// coordinates of their first pixels. Tiles are produced row by row, and
+    /// the tiles along the right and last edges are smaller when the image
+    /// dimensions are not multiples of the tile dimensions.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if either tile dimension is zero.
+    pub fn tiles(&self, tile_width: u32, tile_height: u32) -> Tiles<'_> {
+        assert!((tile_width > 0) && (tile_height > 0), "tile dimensions must be nonzero");
+
+        Tiles {
+            image: self,
+            tile_width,
+            tile_height,
+            x: 0,
+            y: 0,
+        }
+    }
+}
+
+/// An iterator over views of the tiles of an image, created by `TgaImage::tiles`.
+pub struct Tiles<'a> {
+    image: &'a TgaImage,
+    tile_width: u32,
+    tile_height: u32,
+    x: u32,
+    y: u32,
+}
+
+impl<'a> Iterator for Tiles<'a> {
+    type Item = (u32, u32, TgaImage);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let width = self.image.width();
+        let height = self.image.height();
+        if (self.y >= height) || (width == 0) {
+            return None;
+        }
+
+        let (x, y) = (self.x, self.y);
+        let tile_width = u32::min(self.tile_width, width - x);
+        let tile_height = u32::min(self.tile_height, height - y);
+        let inner = self.image.raw_tga_image().view(
+            x as usize, y as usize, tile_width as usize, tile_height as usize
+        );
+        self.x = self.x.saturating_add(self.tile_width);
+        if self.x >= width {
+            self.x = 0;
+            self.y = self.y.saturating_add(self.tile_height);
+        }
+
+        Some((x, y, self.image.with_raw_tga_image(inner)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let width = self.image.width() as usize;
+        let height = self.image.height() as usize;
+        if (self.y as usize >= height) || (width == 0) {
+            return (0, Some(0));
+        }
+
+        let columns = width.div_ceil(self.tile_width as usize);
+        let rows = height.div_ceil(self.tile_height as usize);
+        let produced = (self.y / self.tile_height) as usize * columns + (self.x / self.tile_width) as usize;
+        let remaining = columns * rows - produced;
+
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for Tiles<'a> {}
@@ -0,0 +1,170 @@
+//! # Pixel Windows
+//!
+//! The `windows` module walks an image one neighbourhood of pixels at a
+//! time, for simple convolution and filter passes such as blurs, edge
+//! detection, and median filters. A window is centred on each pixel in turn,
+//! and a `BorderPolicy` decides what the parts of a window that hang over the
+//! edges of the image hold.
+use std::ops::Deref;
+use crate::{RawTgaImage, TgaImage};
+
+
+/// A `BorderPolicy` decides what the pixels of a window outside the image
+/// hold.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BorderPolicy {
+    /// Repeat the nearest edge pixel of the image.
+    Extend,
+    /// Repeat the pixels from the opposite edge of the image.
+    Wrap,
+    /// Use a fixed colour, given as blue, green, and red channels.
+    Constant([u8; 3]),
+    /// Leave out the pixels whose windows do not lie within the image, so
+    /// every window holds only pixels of the image.
+    Skip,
+}
+
+/// A `Window` is the neighbourhood of a pixel: the decoded pixels of a
+/// rectangle centred on it, row by row. It dereferences to a slice of its
+/// pixels.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Window {
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Window {
+    /// The function `width` returns the width of the window, in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The function `height` returns the height of the window, in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The function `pixel` returns the pixel at a column and row of the
+    /// window, with `(0, 0)` its first pixel. The pixel the window is centred
+    /// on is at `(width / 2, height / 2)`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the position lies outside the window.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        assert!((x < self.width) && (y < self.height), "({}, {}) is outside the window", x, y);
+
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
+
+impl Deref for Window {
+    type Target = [[u8; 3]];
+
+    fn deref(&self) -> &[[u8; 3]] {
+        &self.pixels
+    }
+}
+
+/// The position along an image dimension of length `length` that a window
+/// position `offset` pixels from the first pixel of the image stands for, or
+/// `None` for a position that takes the constant colour.
+fn source_index(offset: isize, length: usize, border: BorderPolicy) -> Option<usize> {
+    if (offset >= 0) && ((offset as usize) < length) {
+        return Some(offset as usize);
+    }
+
+    match border {
+        BorderPolicy::Extend | BorderPolicy::Skip => Some(offset.clamp(0, length as isize - 1) as usize),
+        BorderPolicy::Wrap => Some(offset.rem_euclid(length as isize) as usize),
+        BorderPolicy::Constant(_) => None,
+    }
+}
+
+/// A `Windows` is an iterator over the neighbourhoods of the pixels of an
+/// image, created by `TgaImage::windows`.
+pub struct Windows<'a> {
+    image: &'a RawTgaImage,
+    window_width: u32,
+    window_height: u32,
+    border: BorderPolicy,
+    /// The columns and rows of the pixels the windows are centred on.
+    columns: (u32, u32),
+    rows: (u32, u32),
+    x: u32,
+    y: u32,
+}
+
+impl<'a> Iterator for Windows<'a> {
+    type Item = (u32, u32, Window);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if (self.y >= self.rows.1) || (self.columns.0 >= self.columns.1) {
+            return None;
+        }
+
+        let (x, y) = (self.x, self.y);
+        let (width, height) = (self.image.width(), self.image.height());
+        let left = x as isize - (self.window_width / 2) as isize;
+        let top = y as isize - (self.window_height / 2) as isize;
+        let mut pixels = Vec::with_capacity((self.window_width * self.window_height) as usize);
+        for row in top..(top + self.window_height as isize) {
+            for column in left..(left + self.window_width as isize) {
+                let source_row = source_index(row, height, self.border);
+                let source_column = source_index(column, width, self.border);
+                let pixel = match (source_column, source_row, self.border) {
+                    (Some(column), Some(row), _) => self.image.pixel(row * width + column),
+                    (_, _, BorderPolicy::Constant(colour)) => colour,
+                    _ => unreachable!("only constant borders leave positions without a pixel"),
+                };
+                pixels.push(pixel);
+            }
+        }
+        self.x += 1;
+        if self.x >= self.columns.1 {
+            self.x = self.columns.0;
+            self.y += 1;
+        }
+
+        Some((x, y, Window { width: self.window_width, height: self.window_height, pixels }))
+    }
+}
+
+impl TgaImage {
+    /// The function `windows` returns an iterator over the neighbourhoods of
+    /// the pixels of the image, as windows of `window_width` by
+    /// `window_height` pixels centred on each pixel in turn, together with
+    /// the coordinates of the pixel. Coordinates follow the order the pixels
+    /// are stored in, the same order `pixels` yields them, and windows are
+    /// produced row by row. A window of even width or height has one more
+    /// pixel before its centre than after it. The parts of a window outside
+    /// the image are filled as `border` says, and with `BorderPolicy::Skip`
+    /// only the pixels whose windows lie within the image are visited.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if either window dimension is zero.
+    pub fn windows(&self, window_width: u32, window_height: u32, border: BorderPolicy) -> Windows<'_> {
+        assert!((window_width > 0) && (window_height > 0), "window dimensions must be nonzero");
+
+        let (width, height) = (self.width(), self.height());
+        let inner = |length: u32, window: u32| match border {
+            BorderPolicy::Skip => (window / 2, (length + window / 2 + 1).saturating_sub(window)),
+            _ => (0, length),
+        };
+        let columns = inner(width, window_width);
+        let rows = inner(height, window_height);
+
+        Windows {
+            image: self.raw_tga_image(),
+            window_width,
+            window_height,
+            border,
+            columns,
+            rows,
+            x: columns.0,
+            y: rows.0,
+        }
+    }
+}
@@ -1,45 +1,65 @@
+#![allow(dead_code)]
+
 pub const LENA_TGA: &str = "sample/lena.tga";
-pub const LENA_TGA_WIDTH: usize = 512;
-pub const LENA_TGA_HEIGHT: usize = 512;
+pub const LENA_TGA_WIDTH: u32 = 512;
+pub const LENA_TGA_HEIGHT: u32 = 512;
 pub const LENA_TGA_BITS_PER_PIXEL: usize = 24;
 pub const LENA_TGA_COLOR_MAP_TYPE: usize = 0;
 pub const LENA_TGA_DATA_TYPE_CODE: usize = 2;
 
 pub const COLOR_TGA: &str = "sample/color.tga";
-pub const COLOR_TGA_WIDTH: usize = 640;
-pub const COLOR_TGA_HEIGHT: usize = 480;
+pub const COLOR_TGA_WIDTH: u32 = 640;
+pub const COLOR_TGA_HEIGHT: u32 = 480;
 pub const COLOR_TGA_BITS_PER_PIXEL: usize = 24;
 pub const COLOR_TGA_COLOR_MAP_TYPE: usize = 0;
 pub const COLOR_TGA_DATA_TYPE_CODE: usize = 2;
 
 pub const ONE_TGA: &str = "sample/one.tga";
-pub const ONE_TGA_WIDTH: usize = 1;
-pub const ONE_TGA_HEIGHT: usize = 1;
+pub const ONE_TGA_WIDTH: u32 = 1;
+pub const ONE_TGA_HEIGHT: u32 = 1;
 pub const ONE_TGA_BITS_PER_PIXEL: usize = 24;
 pub const ONE_TGA_COLOR_MAP_TYPE: usize = 0;
 pub const ONE_TGA_DATA_TYPE_CODE: usize = 2;
 
 pub const LENA_RLE_TGA: &str = "sample/lena_rle.tga";
-pub const LENA_RLE_TGA_WIDTH: usize = 512;
-pub const LENA_RLE_TGA_HEIGHT: usize = 512;
+pub const LENA_RLE_TGA_WIDTH: u32 = 512;
+pub const LENA_RLE_TGA_HEIGHT: u32 = 512;
 pub const LENA_RLE_TGA_BITS_PER_PIXEL: usize = 24;
 pub const LENA_RLE_TGA_COLOR_MAP_TYPE: usize = 0;
 pub const LENA_RLE_TGA_DATA_TYPE_CODE: usize = 10;
 
 pub const COLOR_RLE_TGA: &str = "sample/color_rle.tga";
-pub const COLOR_RLE_TGA_WIDTH: usize = 640;
-pub const COLOR_RLE_TGA_HEIGHT: usize = 480;
+pub const COLOR_RLE_TGA_WIDTH: u32 = 640;
+pub const COLOR_RLE_TGA_HEIGHT: u32 = 480;
 pub const COLOR_RLE_TGA_BITS_PER_PIXEL: usize = 24;
 pub const COLOR_RLE_TGA_COLOR_MAP_TYPE: usize = 0;
 pub const COLOR_RLE_TGA_DATA_TYPE_CODE: usize = 10;
 
 pub const ONE_RLE_TGA: &str = "sample/one_rle.tga";
-pub const ONE_RLE_TGA_WIDTH: usize = 1;
-pub const ONE_RLE_TGA_HEIGHT: usize = 1;
+pub const ONE_RLE_TGA_WIDTH: u32 = 1;
+pub const ONE_RLE_TGA_HEIGHT: u32 = 1;
 pub const ONE_RLE_TGA_BITS_PER_PIXEL: usize = 24;
 pub const ONE_RLE_TGA_COLOR_MAP_TYPE: usize = 0;
 pub const ONE_RLE_TGA_DATA_TYPE_CODE: usize = 10;
 
+pub const PALETTE_TGA: &str = "sample/palette.tga";
+pub const PALETTE_16_TGA: &str = "sample/palette_16.tga";
+pub const PALETTE_32_TGA: &str = "sample/palette_32.tga";
+pub const PALETTE_MAPPED_16_TGA: &str = "sample/palette_mapped_16.tga";
+pub const PALETTE_MAPPED_24_TGA: &str = "sample/palette_mapped_24.tga";
+pub const PALETTE_MAPPED_32_TGA: &str = "sample/palette_mapped_32.tga";
+pub const PALETTE_MAPPED_ORIGIN_TGA: &str = "sample/palette_mapped_origin.tga";
+pub const PALETTE_MAPPED_RLE_TGA: &str = "sample/palette_mapped_rle.tga";
+pub const PALETTE_TGA_WIDTH: u32 = 32;
+pub const PALETTE_TGA_HEIGHT: u32 = 16;
+pub const PALETTE_TGA_COLOR_MAP_LENGTH: usize = 16;
+
+pub const INTERLEAVED_TWO_WAY_TGA: &str = "sample/interleaved_two_way.tga";
+pub const INTERLEAVED_TWO_WAY_RLE_TGA: &str = "sample/interleaved_two_way_rle.tga";
+pub const INTERLEAVED_FOUR_WAY_RLE_TGA: &str = "sample/interleaved_four_way_rle.tga";
+pub const INTERLEAVED_TGA_WIDTH: u32 = 32;
+pub const INTERLEAVED_TGA_HEIGHT: u32 = 16;
+
 #[cfg(test)]
 mod tests {
     use std::fs::File;
@@ -53,6 +73,12 @@ mod tests {
             data: vec![
                 super::LENA_TGA,     super::COLOR_TGA,     super::ONE_TGA, 
                 super::LENA_RLE_TGA, super::COLOR_RLE_TGA, super::ONE_RLE_TGA,
+                super::PALETTE_TGA, super::PALETTE_16_TGA, super::PALETTE_32_TGA,
+                super::PALETTE_MAPPED_16_TGA, super::PALETTE_MAPPED_24_TGA,
+                super::PALETTE_MAPPED_32_TGA, super::PALETTE_MAPPED_ORIGIN_TGA,
+                super::PALETTE_MAPPED_RLE_TGA,
+                super::INTERLEAVED_TWO_WAY_TGA, super::INTERLEAVED_TWO_WAY_RLE_TGA,
+                super::INTERLEAVED_FOUR_WAY_RLE_TGA,
             ]
         }
     }
@@ -0,0 +1,88 @@
+#![cfg(all(feature = "encode", feature = "metadata"))]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_attributes_type {
+    use tga::{AttributesType, EncoderOptions, ExtensionArea, TgaImage};
+    use std::fs::File;
+    use super::sample;
+
+
+    fn open(filename: &str) -> TgaImage {
+        let mut file = File::open(filename).unwrap();
+        TgaImage::parse_from_file(&mut file).unwrap()
+    }
+
+    fn reencode(image: &TgaImage, extension_area: Option<ExtensionArea>) -> TgaImage {
+        let options = EncoderOptions { extension_area, ..EncoderOptions::default() };
+        TgaImage::parse_from_buffer(&image.encode_to_vec_with_options(&options)).unwrap()
+    }
+
+    /// The attributes type should follow the alpha channel of the image,
+    /// unless the extension area gives one.
+    #[test]
+    fn test_attributes_type_should_be_read_back() {
+        let with_alpha = open(sample::PALETTE_32_TGA);
+        let without_alpha = open(sample::LENA_TGA);
+        let premultiplied = ExtensionArea {
+            attributes_type: Some(AttributesType::PremultipliedAlpha),
+            ..ExtensionArea::default()
+        };
+
+        assert_eq!(with_alpha.attributes_type(), None);
+        assert_eq!(reencode(&with_alpha, Some(ExtensionArea::default())).attributes_type(), Some(AttributesType::Alpha));
+        assert_eq!(reencode(&without_alpha, Some(ExtensionArea::default())).attributes_type(), Some(AttributesType::NoAlpha));
+        assert_eq!(reencode(&with_alpha, Some(premultiplied)).attributes_type(), Some(AttributesType::PremultipliedAlpha));
+    }
+
+    /// Premultiplying should scale the colours by their alpha and be
+    /// recorded, and survive re-encoding, and unpremultiplying should undo
+    /// it.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_premultiply_alpha_should_scale_colours_and_be_recorded() {
+        let mut image = TgaImage::from_fn(1, 1, |_, _| [200, 100, 50]).unwrap();
+        let options = EncoderOptions {
+            pixel_depth: Some(tga::PixelDepth::Bits32),
+            attribute_bits: Some(8),
+            extension_area: Some(ExtensionArea::default()),
+            ..EncoderOptions::default()
+        };
+        image = TgaImage::parse_from_buffer(&image.encode_to_vec_with_options(&options)).unwrap();
+        image.image_data_mut()[3] = 128;
+        image.premultiply_alpha().unwrap();
+        let premultiplied = image.image_data().to_vec();
+        let kept = reencode(&image, None);
+        image.unpremultiply_alpha().unwrap();
+
+        assert_eq!(premultiplied, vec![100, 50, 25, 128]);
+        assert_eq!(kept.attributes_type(), Some(AttributesType::PremultipliedAlpha));
+        assert_eq!(image.image_data(), &[199, 100, 50, 128]);
+        assert_eq!(image.attributes_type(), Some(AttributesType::Alpha));
+    }
+
+    /// Converting the alpha of an image without eight alpha bits should fail.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_premultiply_alpha_should_reject_other_depths() {
+        let mut image = open(sample::LENA_TGA);
+
+        assert!(matches!(image.premultiply_alpha(), Err(tga::TgaError::UnsupportedBitDepth(24))));
+    }
+
+    /// The alphas of a 32 bit image should be its attribute bytes, and an
+    /// image without alpha should be opaque.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_alphas_should_yield_the_attribute_bytes() {
+        let image = TgaImage::from_bgra_with_stride(3, 1, 12, &[1, 2, 3, 0, 4, 5, 6, 128, 7, 8, 9, 255]).unwrap();
+        let opaque = open(sample::LENA_TGA);
+
+        assert_eq!(image.alphas().collect::<Vec<_>>(), [0, 128, 255]);
+        assert_eq!(opaque.alphas().len(), opaque.image_data_length());
+        assert!(opaque.alphas().all(|alpha| alpha == 255));
+    }
+}
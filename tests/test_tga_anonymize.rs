@@ -0,0 +1,118 @@
+#![cfg(all(feature = "metadata", feature = "encode"))]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_anonymize {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use tga::{ChannelOrder, EncoderOptions, ExtensionArea, TgaImage, RGB_CHANNEL_ORDER_TAG};
+    use super::sample;
+
+
+    /// Write a file for a test to anonymize.
+    fn setup(name: &str, buf: &[u8]) -> PathBuf {
+        let mut path = env::temp_dir();
+        path.push(name);
+        fs::write(&path, buf).unwrap();
+
+        path
+    }
+
+    fn encode(options: &EncoderOptions) -> Vec<u8> {
+        let buf = fs::read(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_buffer(&buf).unwrap();
+
+        image.encode_to_vec_with_options(options)
+    }
+
+    /// The image identification and the identifying fields of the extension
+    /// area should be zeroed, and every other byte left alone.
+    #[test]
+    fn test_anonymize_should_zero_identifying_fields() {
+        let extension_area = ExtensionArea {
+            author_name: "Ada".to_string(),
+            author_comments: "internal build".to_string(),
+            job_name: "project-x".to_string(),
+            software_id: "tga".to_string(),
+            ..ExtensionArea::default()
+        };
+        let options = EncoderOptions {
+            image_identification: Some(b"secret".to_vec()),
+            extension_area: Some(extension_area),
+            ..EncoderOptions::default()
+        };
+        let original = encode(&options);
+        let path = setup("tga_anonymize_extension_area.tga", &original);
+        tga::anonymize(&path).unwrap();
+        let result = fs::read(&path).unwrap();
+        let footer = result.len() - 26;
+        let offset = u32::from_le_bytes([result[footer], result[footer + 1], result[footer + 2], result[footer + 3]]);
+        let offset = offset as usize;
+        let changed = (0..result.len()).filter(|&i| result[i] != original[i]).collect::<Vec<usize>>();
+
+        assert_eq!(result.len(), original.len());
+        assert_eq!(&result[18..24], &[0; 6]);
+        assert!(result[(offset + 2)..(offset + 426)].iter().all(|&byte| byte == 0));
+        assert_eq!(&result[(offset + 426)..(offset + 430)], b"tga\0");
+        assert!(changed.iter().all(|&i| (i < 24) || ((i >= offset + 2) && (i < offset + 426))));
+        assert_eq!(
+            TgaImage::parse_from_buffer(&result).unwrap().image_data(),
+            TgaImage::parse_from_buffer(&original).unwrap().image_data()
+        );
+    }
+
+    /// Files without an extension area should only lose their image
+    /// identification.
+    #[test]
+    fn test_anonymize_should_only_zero_the_image_identification_without_an_extension_area() {
+        let options = EncoderOptions { image_identification: Some(b"secret".to_vec()), ..EncoderOptions::default() };
+        let original = encode(&options);
+        let path = setup("tga_anonymize_no_extension_area.tga", &original);
+        tga::anonymize(&path).unwrap();
+        let result = fs::read(&path).unwrap();
+
+        assert_eq!(&result[0..18], &original[0..18]);
+        assert_eq!(&result[18..24], &[0; 6]);
+        assert_eq!(&result[24..], &original[24..]);
+    }
+
+    /// Files with their channels stored in red, green, blue order should
+    /// keep the channel order tag, so their pixels decode the same way.
+    #[test]
+    fn test_anonymize_should_keep_the_channel_order_tag() {
+        let options = EncoderOptions {
+            image_identification: Some(b"secret".to_vec()),
+            channel_order_in_file: ChannelOrder::Rgb,
+            ..EncoderOptions::default()
+        };
+        let original = encode(&options);
+        let path = setup("tga_anonymize_channel_order_tag.tga", &original);
+        tga::anonymize(&path).unwrap();
+        let result = fs::read(&path).unwrap();
+        let tag_end = 18 + RGB_CHANNEL_ORDER_TAG.len();
+
+        assert_eq!(&result[18..tag_end], RGB_CHANNEL_ORDER_TAG);
+        assert_eq!(&result[tag_end..(tag_end + 6)], &[0; 6]);
+        assert_eq!(
+            TgaImage::parse_from_buffer(&result).unwrap().image_data(),
+            TgaImage::parse_from_buffer(&original).unwrap().image_data()
+        );
+    }
+
+    /// A file cut short in its image identification should not grow.
+    #[test]
+    fn test_anonymize_should_not_extend_a_truncated_file() {
+        let options = EncoderOptions { image_identification: Some(b"secret".to_vec()), ..EncoderOptions::default() };
+        let original = encode(&options);
+        let path = setup("tga_anonymize_truncated.tga", &original[..21]);
+        tga::anonymize(&path).unwrap();
+        let result = fs::read(&path).unwrap();
+
+        assert_eq!(result.len(), 21);
+        assert_eq!(&result[18..21], &[0; 3]);
+    }
+}
@@ -0,0 +1,70 @@
+#![cfg(all(feature = "encode", feature = "transforms"))]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_atlas {
+    use tga::{PadMode, TgaError, TgaImage};
+    use std::fs::File;
+    use super::sample;
+
+
+    /// A 2 by 2 image whose pixels are numbered row by row.
+    fn numbered() -> TgaImage {
+        TgaImage::from_fn(2, 2, |x, y| [(1 + x + 2 * y) as u8; 3]).unwrap()
+    }
+
+    /// The first channel of every pixel of an image, row by row.
+    fn numbers(image: &TgaImage) -> Vec<u8> {
+        image.pixels().map(|pixel| pixel[0]).collect()
+    }
+
+    /// Extending should repeat the nearest edge pixel into the padding.
+    #[test]
+    fn test_extend_should_repeat_edge_pixels() {
+        let tile = numbered().prepare_atlas_tile(1, PadMode::Extend).unwrap();
+
+        assert_eq!((tile.width(), tile.height()), (4, 4));
+        assert_eq!(numbers(&tile), [1, 1, 2, 2, 1, 1, 2, 2, 3, 3, 4, 4, 3, 3, 4, 4]);
+    }
+
+    /// Wrapping should repeat the pixels of the opposite edge into the
+    /// padding.
+    #[test]
+    fn test_wrap_should_repeat_opposite_edge_pixels() {
+        let tile = numbered().prepare_atlas_tile(1, PadMode::Wrap).unwrap();
+
+        assert_eq!(numbers(&tile), [4, 3, 4, 3, 2, 1, 2, 1, 4, 3, 4, 3, 2, 1, 2, 1]);
+    }
+
+    /// Images with alpha should be premultiplied before they are padded.
+    #[test]
+    fn test_images_with_alpha_should_be_premultiplied() {
+        let image = TgaImage::from_bgra_with_stride(1, 1, 4, &[200, 100, 50, 128]).unwrap();
+        let tile = image.prepare_atlas_tile(1, PadMode::Extend).unwrap();
+
+        assert_eq!(tile.image_data(), &[100, 50, 25, 128][..].repeat(9)[..]);
+    }
+
+    /// Colour mapped images should be padded with colour map indices.
+    #[test]
+    fn test_colour_mapped_images_should_pad_indices() {
+        let mut file = File::open(sample::PALETTE_MAPPED_ORIGIN_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let tile = image.prepare_atlas_tile(2, PadMode::Wrap).unwrap();
+
+        assert_eq!(tile.width(), image.width() + 4);
+        assert_eq!(tile.crop(2, 2, image.width(), image.height()).unwrap(), image);
+    }
+
+    /// Padding that does not fit in a TGA header should be rejected.
+    #[test]
+    fn test_oversized_padding_should_be_rejected() {
+        match numbered().prepare_atlas_tile(40000, PadMode::Extend) {
+            Err(TgaError::InvalidDimensions(80002, 80002)) => {}
+            other => panic!("expected InvalidDimensions, got {:?}", other),
+        }
+    }
+}
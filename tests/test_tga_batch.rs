@@ -0,0 +1,94 @@
+#![cfg(all(feature = "metadata", feature = "rle"))]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_rewrite_headers {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use tga::{TgaError, TgaHeaderBuilder, TgaImage};
+    use tga::batch;
+    use super::sample;
+
+
+    /// Copy some sample files into a fresh directory for a test to modify.
+    fn setup(name: &str, filenames: &[&str]) -> PathBuf {
+        let mut dir = env::temp_dir();
+        dir.push(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for filename in filenames.iter() {
+            let path = PathBuf::from(filename);
+            fs::copy(&path, dir.join(path.file_name().unwrap())).unwrap();
+        }
+
+        dir
+    }
+
+    /// Rewriting the headers should change only the header of each file, and
+    /// leave the rest of the file untouched.
+    #[test]
+    fn test_rewrite_headers_should_patch_only_the_header() {
+        let filenames = [sample::LENA_TGA, sample::LENA_RLE_TGA, sample::PALETTE_MAPPED_24_TGA];
+        let dir = setup("tga_test_batch_patch", &filenames);
+        fs::write(dir.join("notes.txt"), b"not an image").unwrap();
+        let rewritten = batch::rewrite_headers(&dir, |mut header| {
+            header.set_image_descriptor(header.image_descriptor() | 0x20);
+            header.set_y_origin(7);
+            Some(header)
+        }).unwrap();
+
+        assert_eq!(rewritten, filenames.len());
+        for filename in filenames.iter() {
+            let original = fs::read(filename).unwrap();
+            let path = dir.join(PathBuf::from(filename).file_name().unwrap());
+            let patched = fs::read(&path).unwrap();
+            let image = TgaImage::open(&path).unwrap();
+
+            assert_eq!(image.header().image_descriptor() & 0x20, 0x20);
+            assert_eq!(image.header().y_origin(), 7);
+            assert_eq!(patched[18..], original[18..]);
+        }
+        assert_eq!(fs::read(dir.join("notes.txt")).unwrap(), b"not an image");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Files for which the function returns no new header should be left alone.
+    #[test]
+    fn test_rewrite_headers_should_skip_unchanged_files() {
+        let dir = setup("tga_test_batch_skip", &[sample::LENA_TGA, sample::ONE_TGA]);
+        let rewritten = batch::rewrite_headers(&dir, |_| None).unwrap();
+
+        assert_eq!(rewritten, 0);
+        assert_eq!(fs::read(dir.join("lena.tga")).unwrap(), fs::read(sample::LENA_TGA).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// A new header that changes the layout of the file should be rejected,
+    /// and the file left alone.
+    #[test]
+    fn test_rewrite_headers_should_reject_a_layout_change() {
+        let dir = setup("tga_test_batch_layout", &[sample::LENA_TGA]);
+        let new_header = TgaHeaderBuilder::new(2).width(1).height(1).bits_per_pixel(24).build().unwrap();
+        let err = batch::rewrite_headers(&dir, |_| Some(new_header)).unwrap_err();
+
+        assert!(matches!(err, TgaError::Named(_, ref cause) if matches!(**cause, TgaError::LayoutMismatch)));
+        assert_eq!(fs::read(dir.join("lena.tga")).unwrap(), fs::read(sample::LENA_TGA).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// A file that fails to parse should be named in the error.
+    #[test]
+    fn test_rewrite_headers_should_name_the_failing_file() {
+        let dir = setup("tga_test_batch_named", &[sample::ONE_TGA]);
+        fs::write(dir.join("short.tga"), b"short").unwrap();
+        let err = batch::rewrite_headers(&dir, |_| None).unwrap_err();
+
+        assert_eq!(err.source_label(), Some(dir.join("short.tga").display().to_string().as_str()));
+        assert!(format!("{}", err).contains("short.tga"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "in-place")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_tga_image_ref {
+    use std::fs;
+    use tga::{TgaError, TgaImage, TgaImageRef};
+    use super::sample;
+
+
+    /// A borrowed image should hold the same sections as a parsed image,
+    /// borrowed from the buffer.
+    #[test]
+    fn test_borrowed_image_should_match_a_parsed_image() {
+        for filename in [sample::LENA_TGA, sample::PALETTE_MAPPED_ORIGIN_TGA].iter() {
+            let buf = fs::read(filename).unwrap();
+            let borrowed = TgaImageRef::parse_from_buffer(&buf).unwrap();
+            let image = TgaImage::parse_from_buffer(&buf).unwrap();
+            let buf_range = buf.as_ptr_range();
+
+            assert_eq!(borrowed.header(), image.header());
+            assert_eq!((borrowed.width(), borrowed.height()), (image.width(), image.height()));
+            assert_eq!(borrowed.image_data(), image.image_data());
+            assert!(buf_range.contains(&borrowed.image_data().as_ptr()));
+            assert!(borrowed.rows().eq(image.rows()));
+            assert_eq!(borrowed.to_image().unwrap(), image);
+        }
+    }
+
+    /// The rows of an interleaved file should be put in order.
+    #[test]
+    fn test_borrowed_interleaved_image_should_put_rows_in_order() {
+        let buf = fs::read(sample::INTERLEAVED_TWO_WAY_TGA).unwrap();
+        let borrowed = TgaImageRef::parse_from_buffer(&buf).unwrap();
+        let image = TgaImage::parse_from_buffer(&buf).unwrap();
+
+        assert_ne!(borrowed.image_data(), image.image_data());
+        assert_eq!(borrowed.rows().len(), image.height() as usize);
+        assert!(borrowed.rows().eq(image.rows()));
+        assert_eq!(borrowed.row(image.height()), None);
+    }
+
+    /// Run length encoded and truncated files should be rejected.
+    #[test]
+    fn test_borrowed_image_should_reject_compressed_and_truncated_files() {
+        let compressed = fs::read(sample::LENA_RLE_TGA).unwrap();
+        let buf = fs::read(sample::LENA_TGA).unwrap();
+
+        assert!(matches!(TgaImageRef::parse_from_buffer(&compressed), Err(TgaError::Not24BitRgb(10))));
+        assert!(matches!(TgaImageRef::parse_from_buffer(&buf[..1000]), Err(TgaError::IncompleteImageData(982, _))));
+    }
+
+    /// A type 0 file has no rows, whatever dimensions its header claims, so
+    /// it should agree with the parsed image.
+    #[test]
+    fn test_borrowed_type_0_file_should_have_no_rows() {
+        let mut buf = vec![0; tga::TGA_HEADER_LENGTH];
+        buf[12] = 4;
+        buf[14] = 2;
+        buf[16] = 24;
+        let borrowed = TgaImageRef::parse_from_buffer(&buf).unwrap();
+        let image = TgaImage::parse_from_buffer(&buf).unwrap();
+
+        assert_eq!((borrowed.width(), borrowed.height()), (image.width(), image.height()));
+        assert_eq!((borrowed.width(), borrowed.height()), (0, 0));
+        assert_eq!(borrowed.row(0), None);
+        assert_eq!(borrowed.rows().count(), 0);
+    }
+}
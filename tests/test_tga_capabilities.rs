@@ -0,0 +1,38 @@
+#![cfg(feature = "decode24")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_capabilities {
+    use std::fs;
+    use tga::TgaImage;
+    use super::sample;
+
+
+    /// Every sample file whose type code the build reports as readable
+    /// should parse.
+    #[test]
+    fn test_reported_type_codes_should_parse() {
+        let capabilities = tga::capabilities();
+        let filenames = [sample::LENA_TGA, sample::LENA_RLE_TGA, sample::PALETTE_MAPPED_24_TGA, sample::PALETTE_16_TGA];
+        for filename in filenames.iter() {
+            let buf = fs::read(filename).unwrap();
+            let result = TgaImage::parse_from_buffer(&buf);
+
+            assert_eq!(capabilities.read_type_codes.contains(&buf[2]), result.is_ok(), "{}", filename);
+        }
+    }
+
+    /// Run length encoding should be reported exactly when the `rle` feature
+    /// is enabled.
+    #[test]
+    fn test_run_length_encoding_should_follow_the_feature() {
+        let capabilities = tga::capabilities();
+
+        assert_eq!(capabilities.run_length_encoding, cfg!(feature = "rle"));
+        assert_eq!(capabilities.read_type_codes.contains(&10), cfg!(feature = "rle"));
+        assert_eq!(capabilities.write_type_codes.is_empty(), !cfg!(feature = "encode"));
+    }
+}
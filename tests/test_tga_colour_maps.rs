@@ -0,0 +1,137 @@
+#![cfg(any(feature = "metadata", feature = "encode"))]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(all(test, feature = "metadata"))]
+mod tests_colour_map_cache {
+    use std::collections::HashSet;
+    use std::fs;
+    use tga::{ColourMapCache, TgaImage};
+    use super::sample;
+
+
+    /// The cache should hold one colour map for each distinct colour map
+    /// parsed through it, and leave the images unchanged.
+    #[test]
+    fn test_parse_interned_should_keep_one_copy_of_each_colour_map() {
+        let filenames = [
+            sample::PALETTE_MAPPED_24_TGA, sample::PALETTE_MAPPED_16_TGA,
+            sample::PALETTE_MAPPED_24_TGA, sample::PALETTE_MAPPED_32_TGA, sample::LENA_TGA,
+        ];
+        let mut cache = ColourMapCache::new();
+        let mut distinct = HashSet::new();
+        for filename in filenames.iter() {
+            let buf = fs::read(filename).unwrap();
+            let image = TgaImage::parse_interned(&buf, &mut cache).unwrap();
+            let colour_map_length = u16::from_le_bytes([buf[5], buf[6]]) as usize * (buf[7] as usize).div_ceil(8);
+            if colour_map_length > 0 {
+                distinct.insert(buf[18..(18 + colour_map_length)].to_vec());
+            }
+
+            assert_eq!(image, TgaImage::parse_from_buffer(&buf).unwrap());
+        }
+
+        assert_eq!(cache.len(), distinct.len());
+        assert_eq!(cache.len(), 3);
+    }
+}
+
+#[cfg(all(test, feature = "encode"))]
+mod tests_external_palette {
+    use std::fs;
+    use tga::{Palette, TgaError, TgaImage};
+    use super::sample;
+
+
+    /// Remove the colour map of a colour mapped file, returning the file and
+    /// the colours of the colour map as an opaque palette.
+    fn without_colour_map(filename: &str) -> (Vec<u8>, Palette) {
+        let buf = fs::read(filename).unwrap();
+        let length = u16::from_le_bytes([buf[5], buf[6]]) as usize;
+        let colours = buf[18..(18 + 3 * length)].chunks(3).map(|c| [c[0], c[1], c[2]]).collect::<Vec<_>>();
+        let mut stripped = buf[0..18].to_vec();
+        stripped[1] = 0;
+        stripped[5] = 0;
+        stripped[6] = 0;
+        stripped[7] = 0;
+        stripped.extend_from_slice(&buf[(18 + 3 * length)..]);
+
+        (stripped, Palette::from_bgr(&colours))
+    }
+
+    /// A file without its colour map should parse with the palette supplied
+    /// in its place.
+    #[test]
+    fn test_parse_with_palette_should_supply_the_missing_colour_map() {
+        let (stripped, palette) = without_colour_map(sample::PALETTE_MAPPED_24_TGA);
+        let image = TgaImage::parse_with_palette(&stripped, &palette).unwrap();
+        let expected = TgaImage::open(sample::PALETTE_MAPPED_24_TGA).unwrap();
+
+        assert!(image.pixels().eq(expected.pixels()));
+        assert!(matches!(TgaImage::parse_from_buffer(&stripped), Err(TgaError::CorruptTgaHeader)));
+    }
+
+    /// A palette too small for the indices of the file should be rejected.
+    #[test]
+    fn test_parse_with_palette_should_reject_a_palette_missing_colours() {
+        let (stripped, _) = without_colour_map(sample::PALETTE_MAPPED_24_TGA);
+        let palette = Palette::from_bgr(&[[0, 0, 0]]);
+        let result = TgaImage::parse_with_palette(&stripped, &palette);
+
+        assert!(matches!(result, Err(TgaError::InvalidColourMapIndex(_))));
+    }
+}
+
+#[cfg(all(test, feature = "metadata"))]
+mod tests_palette {
+    use tga::{lowlevel, TgaError, TgaImage};
+    use super::sample;
+
+
+    /// Looking each pixel up in the palette by its index should give the
+    /// colour of the pixel, including for a colour map that does not start at
+    /// index zero.
+    #[test]
+    fn test_palette_entries_should_be_indexed_from_the_origin() {
+        for filename in [sample::PALETTE_MAPPED_16_TGA, sample::PALETTE_MAPPED_ORIGIN_TGA].iter() {
+            let image = TgaImage::open(filename).unwrap();
+            let header = lowlevel::write_header(image.header());
+            let palette = image.palette().collect::<Vec<_>>();
+            let origin = u16::from_le_bytes([header[3], header[4]]) as usize;
+
+            assert_eq!(palette.len(), u16::from_le_bytes([header[5], header[6]]) as usize);
+            assert_eq!(palette[0].0, origin);
+            for (&index, pixel) in image.image_data().iter().zip(image.pixels()) {
+                let colour = palette[index as usize - origin].1;
+                assert_eq!([colour[0], colour[1], colour[2]], pixel);
+            }
+        }
+    }
+
+    /// An image without a colour map should have an empty palette.
+    #[test]
+    fn test_palette_of_a_true_colour_image_should_be_empty() {
+        assert_eq!(TgaImage::open(sample::LENA_TGA).unwrap().palette().len(), 0);
+    }
+
+    /// A colour map whose entries have a depth the palette cannot decode
+    /// should be rejected when the file is parsed, whatever the type of the
+    /// file.
+    #[test]
+    fn test_colour_map_of_an_unsupported_depth_should_be_rejected() {
+        let mut type_codes = vec![0, 2];
+        if cfg!(feature = "rle") {
+            type_codes.push(10);
+        }
+        for &type_code in type_codes.iter() {
+            let mut buf = vec![0, 1, type_code, 0, 0, 141, 0, 17, 0, 0, 0, 0, 1, 0, 1, 0, 24, 0x20];
+            buf.extend_from_slice(&[0; 141 * 3]);
+            buf.extend_from_slice(&[0, 1, 2, 3]);
+            let result = TgaImage::parse_from_buffer(&buf);
+
+            assert!(matches!(result, Err(TgaError::UnsupportedBitDepth(17))), "{}", type_code);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+#![cfg(feature = "encode")]
+extern crate tga;
+
+
+#[cfg(test)]
+mod tests_from_fn {
+    use tga::{EncoderOptions, Orientation, TgaError, TgaImage};
+
+
+    /// The pixels of the image should be the colours the closure returns,
+    /// from the top left corner of the image one row at a time.
+    #[test]
+    fn test_from_fn_should_fill_the_image_from_the_closure() {
+        let gradient = |x: u32, y: u32| [(x * 16) as u8, (y * 32) as u8, 128];
+        let image = TgaImage::from_fn(16, 8, gradient).unwrap();
+        let expected = (0..8).flat_map(|y| (0..16).map(move |x| gradient(x, y))).collect::<Vec<_>>();
+
+        assert_eq!((image.width(), image.height()), (16, 8));
+        assert_eq!(image.bits_per_pixel(), 24);
+        assert_eq!(image.pixels().collect::<Vec<_>>(), expected);
+        assert_eq!(TgaImage::parse_from_buffer(&image.encode_to_vec()).unwrap(), image);
+    }
+
+    /// Dimensions too large for a TGA header should be rejected before the
+    /// closure is called.
+    #[test]
+    fn test_from_fn_should_reject_dimensions_too_large_for_a_header() {
+        let result = TgaImage::from_fn(70000, 1, |_, _| panic!("the closure should not be called"));
+
+        assert!(matches!(result, Err(TgaError::InvalidDimensions(70000, 1))));
+    }
+
+    /// Enumerating the pixels should give back the coordinates each pixel
+    /// was built at, in whichever corner the pixels are stored from.
+    #[test]
+    fn test_enumerate_pixels_should_give_display_coordinates() {
+        let gradient = |x: u32, y: u32| [x as u8, y as u8, 0];
+        let image = TgaImage::from_fn(5, 3, gradient).unwrap();
+        for &orientation in [Orientation::TopLeft, Orientation::BottomLeft, Orientation::BottomRight].iter() {
+            let options = EncoderOptions { orientation: Some(orientation), ..EncoderOptions::default() };
+            let stored = TgaImage::parse_from_buffer(&image.encode_to_vec_with_options(&options)).unwrap();
+
+            assert_eq!(stored.enumerate_pixels().count(), 15);
+            assert!(stored.enumerate_pixels().all(|(x, y, pixel)| pixel == gradient(x, y)));
+        }
+    }
+
+    /// Pixels addressed on the display should be found, and overwritten,
+    /// wherever they are stored.
+    #[test]
+    fn test_pixel_at_should_use_display_coordinates() {
+        let gradient = |x: u32, y: u32| [x as u8, y as u8, 0];
+        let image = TgaImage::from_fn(5, 3, gradient).unwrap();
+        for &orientation in [Orientation::TopLeft, Orientation::BottomLeft, Orientation::TopRight].iter() {
+            let options = EncoderOptions { orientation: Some(orientation), ..EncoderOptions::default() };
+            let mut stored = TgaImage::parse_from_buffer(&image.encode_to_vec_with_options(&options)).unwrap();
+            stored.set_pixel_at(4, 0, &[9, 9, 9]).unwrap();
+
+            assert_eq!(stored.pixel_at(1, 2), Some(gradient(1, 2)));
+            assert_eq!(stored.pixel_at(4, 0), Some([9, 9, 9]));
+            assert_eq!(stored.enumerate_pixels().find(|&(x, y, _)| (x, y) == (4, 0)).unwrap().2, [9, 9, 9]);
+            assert_eq!(stored.pixel_at(5, 0), None);
+            assert!(matches!(stored.set_pixel_at(0, 3, &[0; 3]), Err(TgaError::RegionOutOfBounds(0, 3, 1, 1))));
+        }
+    }
+
+    /// The scanlines from the top down should be in display order, whichever
+    /// corner the pixels are stored from.
+    #[test]
+    fn test_scanlines_top_down_should_be_in_display_order() {
+        let gradient = |x: u32, y: u32| [x as u8, y as u8, 0];
+        let image = TgaImage::from_fn(5, 3, gradient).unwrap();
+        let expected = image.scanlines().collect::<Vec<_>>();
+        for &orientation in [Orientation::BottomLeft, Orientation::TopRight, Orientation::BottomRight].iter() {
+            let options = EncoderOptions { orientation: Some(orientation), ..EncoderOptions::default() };
+            let stored = TgaImage::parse_from_buffer(&image.encode_to_vec_with_options(&options)).unwrap();
+
+            assert_ne!(stored.scanlines().collect::<Vec<_>>(), expected);
+            assert_eq!(stored.scanlines_top_down().collect::<Vec<_>>(), expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_new {
+    use tga::{TgaError, TgaImage};
+
+
+    /// An image built from pixels should hold those pixels, top row first,
+    /// and match the image built from the same pixels by a closure.
+    #[test]
+    fn test_new_should_hold_the_given_pixels() {
+        let pixels = (0..12).map(|i| [i as u8, (2 * i) as u8, (3 * i) as u8]).collect::<Vec<_>>();
+        let image = TgaImage::new(4, 3, pixels.clone()).unwrap();
+        let expected = TgaImage::from_fn(4, 3, |x, y| pixels[(4 * y + x) as usize]).unwrap();
+
+        assert_eq!(image.pixels().collect::<Vec<_>>(), pixels);
+        assert_eq!(image, expected);
+    }
+
+    /// A pixel count that does not match the dimensions should be rejected.
+    #[test]
+    fn test_new_should_reject_the_wrong_number_of_pixels() {
+        let result = TgaImage::new(4, 3, vec![[0, 0, 0]; 11]);
+
+        assert!(matches!(result, Err(TgaError::IncompleteImageData(11, 12))));
+    }
+}
+
+#[cfg(test)]
+mod tests_tga_image_builder {
+    use tga::{PixelDepth, TgaError, TgaImage, TgaImageBuilder};
+
+
+    /// The header of a built image should record every property given to the
+    /// builder, and survive encoding.
+    #[test]
+    fn test_build_should_record_every_property_in_the_header() {
+        let builder = TgaImageBuilder::new()
+            .width(2)
+            .height(2)
+            .pixel_format(PixelDepth::Bits32)
+            .origin(5, 6)
+            .id_string("builder");
+        #[cfg(feature = "rle")]
+        let builder = builder.rle(true);
+        let pixels = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+        let image = builder.build(&pixels).unwrap();
+        let header = image.header();
+
+        assert_eq!((image.width(), image.height(), image.bits_per_pixel()), (2, 2, 32));
+        assert_eq!((header.x_origin(), header.y_origin()), (5, 6));
+        assert_eq!(header.image_descriptor(), 0x28);
+        assert_eq!(image.data_type_code(), if cfg!(feature = "rle") { 10 } else { 2 });
+        assert_eq!(image.image_data(), &pixels[..]);
+        assert_eq!(TgaImage::parse_from_buffer(&image.encode_to_vec()).unwrap().image_data(), &pixels[..]);
+    }
+
+    /// Pixel buffers of the wrong length and overlong identifications should
+    /// be rejected.
+    #[test]
+    fn test_build_should_reject_inconsistent_properties() {
+        let builder = TgaImageBuilder::new().width(2).height(2).pixel_format(PixelDepth::Bits16);
+        let long_id = "x".repeat(256);
+
+        assert!(matches!(builder.build(&[0; 6]), Err(TgaError::IncompleteImageData(6, 8))));
+        assert!(matches!(
+            builder.clone().id_string(&long_id).build(&[0; 8]), Err(TgaError::ImageIdentificationTooLong(256))
+        ));
+        assert!(matches!(builder.width(70000).build(&[]), Err(TgaError::InvalidDimensions(70000, 2))));
+    }
+}
+
+#[cfg(test)]
+mod tests_from_bgra_with_stride {
+    use tga::{TgaError, TgaImage};
+
+
+    /// A 3 by 2 capture with rows padded to 16 bytes, top row first, with
+    /// the last row unpadded.
+    fn capture() -> Vec<u8> {
+        let mut pixels = Vec::new();
+        for y in 0..2u8 {
+            for x in 0..3u8 {
+                pixels.extend_from_slice(&[x, y, 100, 255 - x]);
+            }
+            if y == 0 {
+                pixels.extend_from_slice(&[0xEE; 4]);
+            }
+        }
+
+        pixels
+    }
+
+    /// Padding should be skipped, and rows kept from the top of the image to
+    /// the bottom.
+    #[test]
+    fn test_from_bgra_with_stride_should_skip_padding() {
+        let image = TgaImage::from_bgra_with_stride(3, 2, 16, &capture()).unwrap();
+        let expected = [
+            0, 0, 100, 255, 1, 0, 100, 254, 2, 0, 100, 253,
+            0, 1, 100, 255, 1, 1, 100, 254, 2, 1, 100, 253,
+        ];
+
+        assert_eq!((image.width(), image.height(), image.bits_per_pixel()), (3, 2, 32));
+        assert_eq!(image.image_data(), &expected[..]);
+        assert_eq!(TgaImage::parse_from_buffer(&image.encode_to_vec()).unwrap(), image);
+    }
+
+    /// A negative stride should read the rows from the bottom of the image
+    /// to the top.
+    #[test]
+    fn test_from_bgra_with_stride_should_flip_negative_strides() {
+        let top_down = TgaImage::from_bgra_with_stride(3, 2, 16, &capture()).unwrap();
+        let bottom_up = TgaImage::from_bgra_with_stride(3, 2, -16, &capture()).unwrap();
+
+        assert_eq!(&bottom_up.image_data()[0..12], &top_down.image_data()[12..24]);
+        assert_eq!(&bottom_up.image_data()[12..24], &top_down.image_data()[0..12]);
+    }
+
+    /// Strides shorter than a row and buffers shorter than the rows should
+    /// be rejected.
+    #[test]
+    fn test_from_bgra_with_stride_should_reject_short_strides_and_buffers() {
+        let short_stride = TgaImage::from_bgra_with_stride(3, 2, -8, &capture());
+        let short_buffer = TgaImage::from_bgra_with_stride(3, 2, 16, &capture()[..27]);
+
+        assert!(matches!(short_stride, Err(TgaError::InvalidStride(-8))));
+        assert!(matches!(short_buffer, Err(TgaError::IncompleteImageData(27, 28))));
+    }
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "metadata")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_digest {
+    use tga::{Digest, TgaImage};
+    use std::fs::File;
+    use super::sample;
+
+
+    fn digest(filename: &str) -> (TgaImage, Digest) {
+        let mut file = File::open(filename).unwrap();
+        TgaImage::parse_from_file_with_digest(&mut file).unwrap()
+    }
+
+    /// The checksum of the file should be the CRC-32 zlib computes for its bytes.
+    #[test]
+    fn test_file_crc32_should_match_zlib() {
+        assert_eq!(digest(sample::LENA_TGA).1.file_crc32, 0xF089_B3EA);
+        assert_eq!(digest(sample::ONE_TGA).1.file_crc32, 0xB14E_4CCF);
+    }
+
+    /// Parsing with a digest should decode the same image as parsing without one.
+    #[cfg(feature = "rle")]
+    #[test]
+    fn test_parse_from_file_with_digest_should_decode_the_same_image() {
+        let mut file = File::open(sample::LENA_RLE_TGA).unwrap();
+        let expected = TgaImage::parse_from_file(&mut file).unwrap();
+        let (image, _) = digest(sample::LENA_RLE_TGA);
+
+        assert_eq!(image.pixels().collect::<Vec<[u8; 3]>>(), expected.pixels().collect::<Vec<[u8; 3]>>());
+    }
+
+    /// The hash of the content should depend on the colours of the image, not
+    /// on how the file stores them.
+    #[cfg(feature = "rle")]
+    #[test]
+    fn test_content_hash_should_not_depend_on_the_encoding() {
+        let (_, uncompressed) = digest(sample::LENA_TGA);
+        let (_, compressed) = digest(sample::LENA_RLE_TGA);
+        let (_, other) = digest(sample::COLOR_TGA);
+
+        assert_ne!(uncompressed.file_crc32, compressed.file_crc32);
+        assert_eq!(uncompressed.content_hash, compressed.content_hash);
+        assert_ne!(uncompressed.content_hash, other.content_hash);
+    }
+}
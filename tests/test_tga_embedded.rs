@@ -0,0 +1,42 @@
+#![cfg(all(feature = "macros", feature = "rle"))]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_tga_image {
+    use tga::{EmbeddedTga, TgaImage};
+    use super::sample;
+
+
+    const LENA: EmbeddedTga = tga::tga_image!("../sample/lena.tga");
+
+    const _: () = assert!(LENA.width() == 512);
+
+    /// The embedded image should know its dimensions at compile time.
+    #[test]
+    fn test_tga_image_should_record_the_dimensions() {
+        assert_eq!(LENA.width(), sample::LENA_TGA_WIDTH);
+        assert_eq!(LENA.height(), sample::LENA_TGA_HEIGHT);
+        assert_eq!(LENA.data_type_code(), sample::LENA_TGA_DATA_TYPE_CODE);
+        assert_eq!(LENA.bits_per_pixel(), sample::LENA_TGA_BITS_PER_PIXEL);
+    }
+
+    /// The embedded image should decode to the same image as the file.
+    #[test]
+    fn test_tga_image_should_decode_like_the_file() {
+        let image = tga::tga_image!("../sample/palette_mapped_rle.tga");
+        let expected = TgaImage::open(sample::PALETTE_MAPPED_RLE_TGA).unwrap();
+
+        assert_eq!(image.decode().unwrap(), expected);
+    }
+
+    /// A truncated file should be rejected.
+    #[test]
+    #[should_panic(expected = "the pixel data is incomplete")]
+    fn test_from_bytes_should_reject_a_truncated_file() {
+        static BYTES: &[u8] = include_bytes!("../sample/lena.tga");
+        EmbeddedTga::from_bytes(&BYTES[..1000]);
+    }
+}
@@ -0,0 +1,1219 @@
+#![cfg(all(feature = "encode", feature = "rle"))]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_rle_encoder {
+    use std::fs::File;
+    use tga::TgaImage;
+    use super::sample;
+
+
+    /// A run length encoded image should decode to exactly the same pixels
+    /// as the image it was encoded from.
+    #[test]
+    fn test_rle_encoded_image_should_decode_to_the_same_pixels() {
+        for filename in [sample::LENA_TGA, sample::COLOR_TGA, sample::ONE_TGA].iter() {
+            let mut file = File::open(filename).unwrap();
+            let image = TgaImage::parse_from_file(&mut file).unwrap();
+            let encoded = image.encode_rle();
+            let decoded = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+            assert_eq!(decoded.data_type_code(), 10);
+            assert_eq!(decoded.width(), image.width());
+            assert_eq!(decoded.height(), image.height());
+            assert_eq!(decoded.image_data(), image.image_data());
+        }
+    }
+
+    /// Run length encoding an image consisting of a single colour should
+    /// produce a file much smaller than the uncompressed image data.
+    #[test]
+    fn test_rle_encoding_a_single_colour_image_should_compress_it() {
+        let mut file = File::open(sample::COLOR_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let encoded = image.encode_rle();
+
+        assert!(encoded.len() < image.image_data_length_bytes() / 10);
+    }
+
+    /// Re-encoding an image that was already run length encoded should
+    /// reproduce the same pixels.
+    #[test]
+    fn test_rle_encoding_a_decoded_rle_image_should_preserve_its_pixels() {
+        let mut file = File::open(sample::LENA_RLE_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let encoded = image.encode_rle();
+        let decoded = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert_eq!(decoded, image);
+    }
+
+    /// The compression ratio should compare the pixel data with the packets
+    /// the encoder writes for it.
+    #[test]
+    fn test_rle_compression_ratio_should_match_the_encoded_packets() {
+        for filename in [sample::COLOR_TGA, sample::LENA_TGA].iter() {
+            let mut file = File::open(filename).unwrap();
+            let image = TgaImage::parse_from_file(&mut file).unwrap();
+            let packets_length = image.encode_rle().len() - 18 - 26;
+            let expected = image.image_data_length_bytes() as f64 / packets_length as f64;
+
+            assert_eq!(image.rle_compression_ratio(), expected);
+        }
+    }
+    /// The estimated size should be the size of the packets the encoder
+    /// writes, for true colour and colour mapped images.
+    #[test]
+    fn test_estimate_rle_size_should_match_the_encoded_packets() {
+        for filename in [sample::COLOR_TGA, sample::LENA_TGA, sample::PALETTE_MAPPED_24_TGA].iter() {
+            let mut file = File::open(filename).unwrap();
+            let image = TgaImage::parse_from_file(&mut file).unwrap();
+            let uncompressed = image.encode_to_vec();
+            let compressed = image.encode_rle();
+            let packets_length = compressed.len() - (uncompressed.len() - image.image_data_length_bytes());
+
+            assert_eq!(image.estimate_rle_size(), packets_length, "{}", filename);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_encode_to_vec {
+    use std::fs::File;
+    use std::io::Read;
+    use tga::TgaImage;
+    use super::sample;
+
+
+    /// Encoding an uncompressed image should reproduce the file it was
+    /// parsed from byte for byte.
+    #[test]
+    fn test_encode_to_vec_should_match_raw_unmapped_rgb_file() {
+        for filename in [sample::LENA_TGA, sample::COLOR_TGA, sample::ONE_TGA].iter() {
+            let mut file = File::open(filename).unwrap();
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf).unwrap();
+            let image = TgaImage::parse_from_buffer(&buf).unwrap();
+
+            assert_eq!(image.encode_to_vec(), buf);
+        }
+    }
+
+    /// Encoding a run length encoded image should produce a file that parses
+    /// back into the same image.
+    #[test]
+    fn test_encode_to_vec_should_round_trip_rle_images() {
+        for filename in [sample::LENA_RLE_TGA, sample::COLOR_RLE_TGA, sample::ONE_RLE_TGA].iter() {
+            let mut file = File::open(filename).unwrap();
+            let image = TgaImage::parse_from_file(&mut file).unwrap();
+            let encoded = image.encode_to_vec();
+            let decoded = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+            assert_eq!(decoded, image);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_encode_to_writer {
+    use std::fs::File;
+    use std::io;
+    use tga::{TgaError, TgaImage};
+    use super::sample;
+
+
+    /// A writer that accepts a fixed number of bytes and then fails.
+    struct FailingWriter {
+        capacity: usize,
+    }
+
+    impl io::Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.capacity == 0 {
+                return Err(io::Error::new(io::ErrorKind::Other, "out of space"));
+            }
+            let length = usize::min(buf.len(), self.capacity);
+            self.capacity -= length;
+
+            Ok(length)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Writing an image into a writer should produce the same bytes as
+    /// encoding it into a vector.
+    #[test]
+    fn test_encode_to_writer_should_match_encode_to_vec() {
+        let filenames = [
+            sample::LENA_TGA, sample::LENA_RLE_TGA, sample::ONE_RLE_TGA, sample::PALETTE_MAPPED_RLE_TGA
+        ];
+        for filename in filenames.iter() {
+            let mut file = File::open(filename).unwrap();
+            let image = TgaImage::parse_from_file(&mut file).unwrap();
+            let mut buf = Vec::new();
+            image.encode_to_writer(&mut buf).unwrap();
+
+            assert_eq!(buf, image.encode_to_vec());
+        }
+    }
+
+    /// A failure in the underlying writer should be reported as an I/O error.
+    #[test]
+    fn test_encode_to_writer_should_report_io_errors() {
+        let mut file = File::open(sample::LENA_RLE_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let mut writer = FailingWriter { capacity: 1000 };
+
+        match image.encode_to_writer(&mut writer) {
+            Err(TgaError::Io(_)) => {}
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_save {
+    use std::env;
+    use std::fs;
+    use std::fs::File;
+    use tga::TgaImage;
+    use super::sample;
+
+
+    /// Saving an image and reading the file back should produce the same bytes
+    /// as encoding the image in memory.
+    #[test]
+    fn test_save_should_write_the_encoded_image() {
+        for filename in [sample::LENA_TGA, sample::LENA_RLE_TGA].iter() {
+            let mut file = File::open(filename).unwrap();
+            let image = TgaImage::parse_from_file(&mut file).unwrap();
+            let mut path = env::temp_dir();
+            path.push(format!("tga_test_save_{}.tga", image.data_type_code()));
+            image.save(&path).unwrap();
+            let saved = fs::read(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(saved, image.encode_to_vec());
+        }
+    }
+
+    /// Saving into a directory that does not exist should report an error.
+    #[test]
+    fn test_save_to_a_missing_directory_should_fail() {
+        let mut file = File::open(sample::ONE_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let mut path = env::temp_dir();
+        path.push("tga_test_missing_directory");
+        path.push("one.tga");
+
+        assert!(image.save(&path).is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests_channel_order {
+    use std::fs::File;
+    use tga::{ChannelOrder, EncoderOptions, TgaImage, RGB_CHANNEL_ORDER_TAG};
+    use super::sample;
+
+
+    fn rgb_options() -> EncoderOptions {
+        EncoderOptions { channel_order_in_file: ChannelOrder::Rgb, ..EncoderOptions::default() }
+    }
+
+    /// Writing an image in red, green, blue order should tag its image
+    /// identification and swap the red and blue channels of every pixel.
+    #[test]
+    fn test_rgb_channel_order_should_tag_and_swap_pixels() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let encoded = image.encode_to_vec_with_options(&rgb_options());
+        let id_length = encoded[0] as usize;
+        let image_identification = &encoded[18..(18 + id_length)];
+        let image_data = &encoded[(18 + id_length)..(18 + id_length + image.image_data_length_bytes())];
+
+        assert!(image_identification.starts_with(RGB_CHANNEL_ORDER_TAG));
+        for (written, original) in image_data.chunks(3).zip(image.image_data().chunks(3)) {
+            assert_eq!(written, &[original[2], original[1], original[0]]);
+        }
+    }
+
+    /// Parsing a file written in red, green, blue order should restore the
+    /// original image, including its image identification.
+    #[test]
+    fn test_rgb_channel_order_should_round_trip() {
+        let filenames = [sample::LENA_TGA, sample::LENA_RLE_TGA, sample::PALETTE_MAPPED_24_TGA];
+        for filename in filenames.iter() {
+            let mut file = File::open(filename).unwrap();
+            let image = TgaImage::parse_from_file(&mut file).unwrap();
+            let encoded = image.encode_to_vec_with_options(&rgb_options());
+            let decoded = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+            assert_eq!(decoded, image);
+        }
+    }
+
+    /// The default options should write the standard blue, green, red order.
+    #[test]
+    fn test_default_options_should_match_encode_to_vec() {
+        let mut file = File::open(sample::PALETTE_MAPPED_32_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+
+        assert_eq!(image.encode_to_vec_with_options(&EncoderOptions::new()), image.encode_to_vec());
+    }
+}
+
+#[cfg(test)]
+mod tests_encoder_options {
+    use std::fs::File;
+    use std::time::{Duration, UNIX_EPOCH};
+    use tga::{DeveloperTag, EncoderOptions, ExtensionArea, Orientation, TgaFormat, TgaImage, Timestamp};
+    use super::sample;
+
+
+    fn open(filename: &str) -> TgaImage {
+        let mut file = File::open(filename).unwrap();
+        TgaImage::parse_from_file(&mut file).unwrap()
+    }
+
+    /// Turning run length encoding off should write an uncompressed file with
+    /// the same pixels.
+    #[test]
+    fn test_rle_off_should_write_an_uncompressed_file() {
+        for (filename, expected_type) in [(sample::LENA_RLE_TGA, 2), (sample::PALETTE_MAPPED_RLE_TGA, 1)].iter() {
+            let image = open(filename);
+            let options = EncoderOptions { rle: Some(false), ..EncoderOptions::default() };
+            let decoded = TgaImage::parse_from_buffer(&image.encode_to_vec_with_options(&options)).unwrap();
+
+            assert_eq!(decoded.data_type_code(), *expected_type);
+            assert_eq!(decoded.image_data(), image.image_data());
+        }
+    }
+
+    /// The original format should write a TGA 1.0 file, without a footer,
+    /// that still parses.
+    #[test]
+    fn test_original_format_should_omit_the_footer() {
+        let image = open(sample::LENA_TGA);
+        let options = EncoderOptions { format: TgaFormat::Original, ..EncoderOptions::default() };
+        let encoded = image.encode_to_vec_with_options(&options);
+        let decoded = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert_eq!(encoded.len() + 26, image.encode_to_vec().len());
+        assert!(!encoded.ends_with(b"TRUEVISION-XFILE.\0"));
+        assert_eq!(decoded.image_data(), image.image_data());
+    }
+
+    /// A file whose footer points into the data after its pixel data.
+    fn file_with_trailing_data() -> Vec<u8> {
+        let mut buf = std::fs::read(sample::ONE_TGA).unwrap();
+        buf.truncate(buf.len() - 26);
+        buf.extend_from_slice(b"trailing");
+        buf.extend_from_slice(&21_u32.to_le_bytes());
+        buf.extend_from_slice(&[0; 4]);
+        buf.extend_from_slice(b"TRUEVISION-XFILE.\0");
+
+        buf
+    }
+
+    /// The original format should drop everything after the pixel data.
+    #[test]
+    fn test_original_format_should_omit_the_trailing_data() {
+        let image = TgaImage::parse_from_buffer(&file_with_trailing_data()).unwrap();
+        let options = EncoderOptions { format: TgaFormat::Original, ..EncoderOptions::default() };
+        let encoded = image.encode_to_vec_with_options(&options);
+
+        assert_eq!(encoded.len(), 18 + 3);
+    }
+
+    /// The new format should replace a footer carried over from the source
+    /// file instead of writing a second one.
+    #[test]
+    fn test_new_format_should_write_a_single_footer() {
+        let image = TgaImage::parse_from_buffer(&file_with_trailing_data()).unwrap();
+        let encoded = image.encode_to_vec();
+        let mut expected = b"trailing".to_vec();
+        expected.extend_from_slice(&[0; 8]);
+        expected.extend_from_slice(b"TRUEVISION-XFILE.\0");
+
+        assert_eq!(&encoded[(18 + 3)..], &expected[..]);
+    }
+
+    /// An extension area should be written after the pixel data, with the
+    /// footer pointing at it.
+    #[test]
+    fn test_extension_area_should_be_written_with_its_offset() {
+        let image = open(sample::PALETTE_32_TGA);
+        let extension_area = ExtensionArea {
+            author_name: "Ada".to_string(),
+            author_comments: "first line\nsecond line".to_string(),
+            timestamp: Some(Timestamp { year: 2024, month: 5, day: 17, hour: 9, minute: 30, second: 5 }),
+            software_id: "tga".to_string(),
+            software_version: Some((220, b'b')),
+            ..ExtensionArea::default()
+        };
+        let options = EncoderOptions { extension_area: Some(extension_area), ..EncoderOptions::default() };
+        let encoded = image.encode_to_vec_with_options(&options);
+        let footer = encoded.len() - 26;
+        let offset = u32::from_le_bytes([encoded[footer], encoded[footer + 1], encoded[footer + 2], encoded[footer + 3]]);
+        let offset = offset as usize;
+        let decoded = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert_eq!(offset, 18 + image.image_data().len());
+        assert_eq!(offset + 495, footer);
+        assert_eq!(&encoded[offset..(offset + 2)], &495u16.to_le_bytes());
+        assert_eq!(&encoded[(offset + 2)..(offset + 6)], b"Ada\0");
+        assert_eq!(&encoded[(offset + 124)..(offset + 136)], b"second line\0");
+        assert_eq!(&encoded[(offset + 367)..(offset + 373)], &[5, 0, 17, 0, 0xE8, 0x07]);
+        assert_eq!(&encoded[(offset + 426)..(offset + 430)], b"tga\0");
+        assert_eq!(&encoded[(offset + 467)..(offset + 470)], &[220, 0, b'b']);
+        assert_eq!(encoded[offset + 494], 3);
+        assert_eq!(decoded.image_data(), image.image_data());
+    }
+
+    /// The pixel aspect ratio of an extension area should be read back from
+    /// the encoded file.
+    #[test]
+    fn test_pixel_aspect_ratio_should_be_read_back() {
+        let image = open(sample::LENA_TGA);
+        let extension_area = ExtensionArea { pixel_aspect_ratio: Some((4, 3)), ..ExtensionArea::default() };
+        let options = EncoderOptions { extension_area: Some(extension_area), ..EncoderOptions::default() };
+        let encoded = image.encode_to_vec_with_options(&options);
+        let offset = read_u32(&encoded, encoded.len() - 26);
+        let result = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert_eq!(&encoded[(offset + 474)..(offset + 478)], &[4, 0, 3, 0]);
+        assert_eq!(image.pixel_aspect_ratio(), None);
+        assert_eq!(result.pixel_aspect_ratio(), Some((4, 3)));
+    }
+
+    /// Re-encoding an image should keep its pixel aspect ratio, unless the
+    /// options give another one.
+    #[test]
+    fn test_pixel_aspect_ratio_should_be_kept_when_re_encoding() {
+        let image = open(sample::LENA_TGA);
+        let extension_area = ExtensionArea { pixel_aspect_ratio: Some((4, 3)), ..ExtensionArea::default() };
+        let options = EncoderOptions { extension_area: Some(extension_area), ..EncoderOptions::default() };
+        let anamorphic = TgaImage::parse_from_buffer(&image.encode_to_vec_with_options(&options)).unwrap();
+        let kept = TgaImage::parse_from_buffer(&anamorphic.encode_to_vec()).unwrap();
+        let with_author = EncoderOptions {
+            extension_area: Some(ExtensionArea { author_name: "Ada".to_string(), ..ExtensionArea::default() }),
+            ..EncoderOptions::default()
+        };
+        let kept_with_author = TgaImage::parse_from_buffer(&anamorphic.encode_to_vec_with_options(&with_author)).unwrap();
+        let square = EncoderOptions {
+            extension_area: Some(ExtensionArea { pixel_aspect_ratio: Some((1, 1)), ..ExtensionArea::default() }),
+            ..EncoderOptions::default()
+        };
+        let replaced = TgaImage::parse_from_buffer(&anamorphic.encode_to_vec_with_options(&square)).unwrap();
+
+        assert_eq!(kept.pixel_aspect_ratio(), Some((4, 3)));
+        assert_eq!(kept_with_author.pixel_aspect_ratio(), Some((4, 3)));
+        assert_eq!(replaced.pixel_aspect_ratio(), Some((1, 1)));
+    }
+
+    /// The key colour of an extension area should be written in blue,
+    /// green, red, and alpha order, read back, and kept when re-encoding.
+    #[test]
+    fn test_key_colour_should_be_read_back_and_kept() {
+        let image = open(sample::LENA_TGA);
+        let extension_area = ExtensionArea { key_colour: Some([0x10, 0x20, 0x30, 0xFF]), ..ExtensionArea::default() };
+        let options = EncoderOptions { extension_area: Some(extension_area), ..EncoderOptions::default() };
+        let encoded = image.encode_to_vec_with_options(&options);
+        let offset = read_u32(&encoded, encoded.len() - 26);
+        let result = TgaImage::parse_from_buffer(&encoded).unwrap();
+        let kept = TgaImage::parse_from_buffer(&result.encode_to_vec()).unwrap();
+
+        assert_eq!(&encoded[(offset + 470)..(offset + 474)], &[0x10, 0x20, 0x30, 0xFF]);
+        assert_eq!(image.key_colour(), None);
+        assert_eq!(result.key_colour(), Some([0x10, 0x20, 0x30, 0xFF]));
+        assert_eq!(kept.key_colour(), Some([0x10, 0x20, 0x30, 0xFF]));
+    }
+
+    /// Read a little endian 32 bit value.
+    fn read_u32(buf: &[u8], position: usize) -> usize {
+        u32::from_le_bytes([buf[position], buf[position + 1], buf[position + 2], buf[position + 3]]) as usize
+    }
+
+    /// Developer tags should be written with a developer directory that the
+    /// footer points to, alongside an extension area.
+    #[test]
+    fn test_developer_tags_should_be_listed_in_the_developer_directory() {
+        let image = open(sample::ONE_TGA);
+        let options = EncoderOptions {
+            extension_area: Some(ExtensionArea::default()),
+            developer_tags: vec![
+                DeveloperTag { tag: 7, data: b"seven".to_vec() },
+                DeveloperTag { tag: 42, data: b"forty two".to_vec() },
+            ],
+            ..EncoderOptions::default()
+        };
+        let encoded = image.encode_to_vec_with_options(&options);
+        let footer = encoded.len() - 26;
+        let extension_area = read_u32(&encoded, footer);
+        let directory = read_u32(&encoded, footer + 4);
+        let entry = |index: usize| {
+            let position = directory + 2 + 10 * index;
+            let tag = u16::from_le_bytes([encoded[position], encoded[position + 1]]);
+            let offset = read_u32(&encoded, position + 2);
+            let size = read_u32(&encoded, position + 6);
+
+            (tag, &encoded[offset..(offset + size)])
+        };
+
+        assert_eq!(&encoded[directory..(directory + 2)], &[2, 0]);
+        assert_eq!(entry(0), (7, &b"seven"[..]));
+        assert_eq!(entry(1), (42, &b"forty two"[..]));
+        assert_eq!(extension_area, directory + 2 + 20);
+        assert_eq!(&encoded[extension_area..(extension_area + 2)], &495u16.to_le_bytes());
+        assert!(TgaImage::parse_from_buffer(&encoded).is_ok());
+    }
+
+    /// Re-encoding an image should keep the developer tags of the file it was
+    /// read from, moved to offsets of their own, unless the options replace
+    /// them, and drop the areas of the file that are written anew.
+    #[test]
+    fn test_developer_tags_should_be_kept_when_re_encoding() {
+        let image = open(sample::ONE_TGA);
+        let tags = |tags: &[(u16, &[u8])]| {
+            tags.iter().map(|&(tag, data)| DeveloperTag { tag, data: data.to_vec() }).collect::<Vec<_>>()
+        };
+        let original = EncoderOptions {
+            extension_area: Some(ExtensionArea::default()),
+            developer_tags: tags(&[(7, b"seven"), (42, b"forty two")]),
+            postage_stamp: true,
+            ..EncoderOptions::default()
+        };
+        let replacement = EncoderOptions {
+            developer_tags: tags(&[(42, b"replaced")]),
+            ..EncoderOptions::default()
+        };
+        let expected = EncoderOptions {
+            developer_tags: tags(&[(7, b"seven"), (42, b"replaced")]),
+            ..EncoderOptions::default()
+        };
+        let kept = EncoderOptions { developer_tags: original.developer_tags.clone(), ..EncoderOptions::default() };
+        let decoded = TgaImage::parse_from_buffer(&image.encode_to_vec_with_options(&original)).unwrap();
+
+        assert_eq!(decoded.encode_to_vec_with_options(&replacement), image.encode_to_vec_with_options(&expected));
+        assert_eq!(decoded.encode_to_vec(), image.encode_to_vec_with_options(&kept));
+    }
+
+    /// Read the postage stamp a file records in its extension area.
+    fn postage_stamp(encoded: &[u8]) -> &[u8] {
+        let extension_area = read_u32(encoded, encoded.len() - 26);
+        let position = read_u32(encoded, extension_area + 486);
+
+        &encoded[position..extension_area]
+    }
+
+    /// A postage stamp should be a nearest pixel thumbnail of at most 64 by
+    /// 64 pixels, recorded in an extension area even when none is given.
+    #[test]
+    fn test_postage_stamp_should_scale_large_images_down() {
+        let image = open(sample::LENA_TGA);
+        let options = EncoderOptions { postage_stamp: true, ..EncoderOptions::default() };
+        let encoded = image.encode_to_vec_with_options(&options);
+        let stamp = postage_stamp(&encoded);
+        let row_length = 512 * 3;
+        let mut expected = vec![64, 64];
+        for row in 0..64 {
+            for column in 0..64 {
+                let position = 8 * row * row_length + 8 * column * 3;
+                expected.extend_from_slice(&image.image_data()[position..(position + 3)]);
+            }
+        }
+
+        assert_eq!(stamp, &expected[..]);
+        assert_eq!(TgaImage::parse_from_buffer(&encoded).unwrap().image_data(), image.image_data());
+    }
+
+    /// Images no larger than a postage stamp should be their own postage
+    /// stamp, with colour mapped images keeping their colour map indices.
+    #[test]
+    fn test_postage_stamp_should_keep_small_images_whole() {
+        let image = open(sample::PALETTE_MAPPED_24_TGA);
+        let options = EncoderOptions { postage_stamp: true, ..EncoderOptions::default() };
+        let encoded = image.encode_to_vec_with_options(&options);
+        let stamp = postage_stamp(&encoded);
+
+        assert_eq!(&stamp[0..2], &[32, 16]);
+        assert_eq!(&stamp[2..], image.image_data());
+    }
+
+    /// A system time should convert into the date and time in UTC.
+    #[test]
+    fn test_timestamp_from_system_time_should_give_the_utc_date() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        let timestamp = Timestamp::from_system_time(time);
+
+        assert_eq!(timestamp, Timestamp { year: 2024, month: 2, day: 29, hour: 12, minute: 34, second: 56 });
+    }
+
+    /// Changing the orientation should reorder the pixels so the image still
+    /// displays the same way.
+    #[test]
+    fn test_orientation_should_reorder_the_pixels() {
+        let image = open(sample::PALETTE_TGA);
+        let options = EncoderOptions { orientation: Some(Orientation::TopRight), ..EncoderOptions::default() };
+        let decoded = TgaImage::parse_from_buffer(&image.encode_to_vec_with_options(&options)).unwrap();
+        let mut expected = image.pixels().collect::<Vec<[u8; 3]>>();
+        expected.reverse();
+
+        assert_eq!(decoded.header().image_descriptor() & 0x30, 0x30);
+        assert_eq!(decoded.pixels().collect::<Vec<[u8; 3]>>(), expected);
+    }
+
+    /// The origin, attribute bits, and image identification should be written
+    /// into the file as given.
+    #[cfg(feature = "metadata")]
+    #[test]
+    fn test_header_fields_should_be_written_as_given() {
+        let image = open(sample::PALETTE_32_TGA);
+        let options = EncoderOptions {
+            origin: Some((3, 4)),
+            attribute_bits: Some(8),
+            image_identification: Some(b"palette".to_vec()),
+            ..EncoderOptions::default()
+        };
+        let decoded = TgaImage::parse_from_buffer(&image.encode_to_vec_with_options(&options)).unwrap();
+
+        assert_eq!((decoded.header().x_origin(), decoded.header().y_origin()), (3, 4));
+        assert_eq!(decoded.header().image_descriptor() & 0x0F, 8);
+        assert_eq!(decoded.image_identification(), b"palette");
+        assert_eq!(decoded.image_data(), image.image_data());
+    }
+}
+
+#[cfg(all(test, feature = "metadata"))]
+mod tests_image_identification {
+    use tga::{TgaError, TgaImage};
+    use super::sample;
+
+
+    /// An image identification set on an image should be written into files
+    /// encoded from it.
+    #[test]
+    fn test_set_image_identification_should_be_encoded() {
+        let mut image = TgaImage::open(sample::LENA_TGA).unwrap();
+        image.set_image_identification(b"build 1234").unwrap();
+        let encoded = image.encode_to_vec();
+        let decoded = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert_eq!(encoded[0], 10);
+        assert_eq!(decoded.image_identification(), b"build 1234");
+        assert_eq!(decoded.image_data(), image.image_data());
+    }
+
+    /// An image identification longer than its field should be rejected, and
+    /// leave the image alone.
+    #[test]
+    fn test_set_image_identification_should_reject_long_identifications() {
+        let mut image = TgaImage::open(sample::LENA_TGA).unwrap();
+        let result = image.set_image_identification(&[b'x'; 256]);
+
+        assert!(matches!(result, Err(TgaError::ImageIdentificationTooLong(256))));
+        assert!(image.image_identification().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests_pixel_depth {
+    use std::fs;
+    use tga::{EncoderOptions, Palette, PixelDepth, TgaImage};
+    use super::sample;
+
+
+    fn rgba_options() -> EncoderOptions {
+        EncoderOptions { pixel_depth: Some(PixelDepth::Bits32), ..EncoderOptions::default() }
+    }
+
+    /// Writing a 24 bit image or a colour mapped image as a 32 bit image should
+    /// produce an opaque 32 bit file with eight alpha attribute bits.
+    #[test]
+    fn test_32_bit_output_should_match_a_32_bit_file() {
+        let expected = fs::read(sample::PALETTE_32_TGA).unwrap();
+        for filename in [sample::PALETTE_TGA, sample::PALETTE_MAPPED_24_TGA, sample::PALETTE_MAPPED_16_TGA].iter() {
+            let image = TgaImage::open(filename).unwrap();
+
+            assert_eq!(image.encode_to_vec_with_options(&rgba_options()), expected);
+        }
+    }
+
+    /// Writing a run length encoded colour mapped image as a 32 bit image should
+    /// produce a run length encoded true colour image.
+    #[test]
+    fn test_32_bit_output_of_an_rle_image_should_stay_run_length_encoded() {
+        let expected = TgaImage::open(sample::PALETTE_32_TGA).unwrap();
+        let image = TgaImage::open(sample::PALETTE_MAPPED_RLE_TGA).unwrap();
+        let decoded = TgaImage::parse_from_buffer(&image.encode_to_vec_with_options(&rgba_options())).unwrap();
+
+        assert_eq!(decoded.data_type_code(), 10);
+        assert_eq!(decoded.header().image_descriptor() & 0x0F, 8);
+        assert_eq!(decoded.image_data(), expected.image_data());
+    }
+
+    /// Writing a 32 bit image as a 16 bit image and back should keep the
+    /// alpha channel as the attribute bit.
+    #[test]
+    fn test_16_bit_output_should_keep_the_attribute_bit() {
+        let image = TgaImage::open(sample::PALETTE_32_TGA).unwrap();
+        let options = EncoderOptions { pixel_depth: Some(PixelDepth::Bits16), ..EncoderOptions::default() };
+
+        assert_eq!(image.encode_to_vec_with_options(&options), fs::read(sample::PALETTE_16_TGA).unwrap());
+    }
+
+    /// Count the pixels of a 16 bit file whose blue channel is at the given level.
+    fn count_blue_level(encoded: &[u8], pixels: usize, level: u8) -> usize {
+        encoded[18..(18 + 2 * pixels)].chunks(2).filter(|pixel| pixel[0] & 0x1F == level).count()
+    }
+
+    /// A flat colour halfway between two 5 bit levels should be truncated to the
+    /// lower level without dithering, and spread evenly over both levels with it.
+    #[test]
+    fn test_16_bit_output_with_dithering_should_spread_colours_between_levels() {
+        let palette = Palette::from_bgr(&[[4, 4, 4]]);
+        let encoded = tga::encode_colour_mapped(4, 4, &[0; 16], &palette, &EncoderOptions::default()).unwrap();
+        let image = TgaImage::parse_from_buffer(&encoded).unwrap();
+        let options = EncoderOptions { pixel_depth: Some(PixelDepth::Bits16), ..EncoderOptions::default() };
+        let plain = image.encode_to_vec_with_options(&options);
+        let dithered = image.encode_to_vec_with_options(&EncoderOptions { dither: true, ..options });
+
+        assert_eq!(count_blue_level(&plain, 16, 0), 16);
+        assert_eq!(count_blue_level(&dithered, 16, 0), 8);
+        assert_eq!(count_blue_level(&dithered, 16, 1), 8);
+    }
+}
+
+#[cfg(test)]
+mod tests_grayscale {
+    use tga::{lowlevel, EncoderOptions, PixelDepth, TgaError, TgaFormat, TgaImage};
+
+
+    const WIDTH: u32 = 4;
+    const HEIGHT: u32 = 3;
+    const DATA: [u8; 12] = [0, 0, 0, 0, 64, 64, 128, 128, 255, 255, 255, 255];
+
+    /// A grayscale buffer should be written as a type 3 file with eight bit
+    /// pixels, stored top to bottom.
+    #[test]
+    fn test_encode_grayscale_should_write_a_type_3_file() {
+        let encoded = tga::encode_grayscale(WIDTH, HEIGHT, &DATA, &EncoderOptions::default()).unwrap();
+        let header = lowlevel::parse_header(&encoded).unwrap();
+
+        assert_eq!(encoded[2], 3);
+        assert_eq!(encoded[16], 8);
+        assert_eq!(header.image_descriptor(), 0x20);
+        assert_eq!(&encoded[18..30], &DATA);
+        assert!(encoded.ends_with(b"TRUEVISION-XFILE.\0"));
+    }
+
+    /// A run length encoded grayscale file should be type 11 and decode back
+    /// to the same grey levels.
+    #[test]
+    fn test_encode_grayscale_with_rle_should_write_a_type_11_file() {
+        let options = EncoderOptions { rle: Some(true), format: TgaFormat::Original, ..EncoderOptions::default() };
+        let encoded = tga::encode_grayscale(WIDTH, HEIGHT, &DATA, &options).unwrap();
+        let (decoded, length) = lowlevel::decode_rle(&encoded[18..], DATA.len(), 1).unwrap();
+
+        assert_eq!(encoded[2], 11);
+        assert_eq!(decoded, DATA);
+        assert_eq!(18 + length, encoded.len());
+    }
+
+    /// Converting a grayscale buffer to 24 bit pixels should give a true
+    /// colour image with equal colour channels.
+    #[test]
+    fn test_encode_grayscale_as_true_colour_should_repeat_the_grey_level() {
+        let options = EncoderOptions { pixel_depth: Some(PixelDepth::Bits24), ..EncoderOptions::default() };
+        let encoded = tga::encode_grayscale(WIDTH, HEIGHT, &DATA, &options).unwrap();
+        let image = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert!(image.pixels().zip(DATA.iter()).all(|(pixel, &grey)| pixel == [grey, grey, grey]));
+    }
+
+    /// Grayscale files are write only, so reading one back should fail with
+    /// the image type, whether or not it is run length encoded.
+    #[test]
+    fn test_encoded_grayscale_files_should_not_be_readable() {
+        for &(rle, data_type_code) in [(false, 3), (true, 11)].iter() {
+            let options = EncoderOptions { rle: Some(rle), ..EncoderOptions::default() };
+            let encoded = tga::encode_grayscale(WIDTH, HEIGHT, &DATA, &options).unwrap();
+
+            match TgaImage::parse_from_buffer(&encoded) {
+                Err(TgaError::Not24BitRgb(found)) => assert_eq!(found, data_type_code),
+                other => panic!("expected grayscale files to be unreadable, got {:?}", other),
+            }
+        }
+    }
+
+    /// A buffer of the wrong length should be rejected.
+    #[test]
+    fn test_encode_grayscale_should_reject_the_wrong_buffer_length() {
+        match tga::encode_grayscale(WIDTH, HEIGHT + 1, &DATA, &EncoderOptions::default()) {
+            Err(TgaError::IncompleteImageData(12, 16)) => {}
+            other => panic!("expected an incomplete image error, got {:?}", other),
+        }
+    }
+
+    /// Dimensions that do not fit in the 16 bit fields of the header should be
+    /// rejected instead of being truncated.
+    #[test]
+    fn test_encode_grayscale_should_reject_dimensions_out_of_range() {
+        match tga::encode_grayscale(65536, 1, &[0; 65536], &EncoderOptions::default()) {
+            Err(TgaError::InvalidDimensions(65536, 1)) => {}
+            other => panic!("expected an invalid dimensions error, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_colour_mapped {
+    use std::fs;
+    use tga::{EncoderOptions, Orientation, Palette, PixelDepth, TgaError, TgaImage};
+    use super::sample;
+
+
+    /// Read the palette and the top to bottom indices of a 24 bit colour mapped sample.
+    fn palette_and_indices() -> (Palette, Vec<u8>) {
+        let buf = fs::read(sample::PALETTE_MAPPED_24_TGA).unwrap();
+        let colours = buf[18..(18 + 3 * 16)].chunks(3)
+            .map(|entry| [entry[0], entry[1], entry[2]])
+            .collect::<Vec<[u8; 3]>>();
+        let start = 18 + 3 * 16;
+        let width = sample::PALETTE_TGA_WIDTH as usize;
+        let indices = buf[start..(start + width * sample::PALETTE_TGA_HEIGHT as usize)]
+            .chunks(width)
+            .rev()
+            .flat_map(|row| row.iter().cloned())
+            .collect::<Vec<u8>>();
+
+        (Palette::from_bgr(&colours), indices)
+    }
+
+    /// Writing indices and a palette should reproduce the colour mapped file
+    /// they were taken from.
+    #[test]
+    fn test_encode_colour_mapped_should_reproduce_a_colour_mapped_file() {
+        let (palette, indices) = palette_and_indices();
+        let options = EncoderOptions { orientation: Some(Orientation::BottomLeft), ..EncoderOptions::default() };
+        let encoded = tga::encode_colour_mapped(
+            sample::PALETTE_TGA_WIDTH, sample::PALETTE_TGA_HEIGHT, &indices, &palette, &options
+        ).unwrap();
+
+        assert_eq!(encoded, fs::read(sample::PALETTE_MAPPED_24_TGA).unwrap());
+    }
+
+    /// The colour map origin, length, and depth should be recorded in the header.
+    #[test]
+    fn test_encode_colour_mapped_should_record_the_colour_map_specification() {
+        let (palette, indices) = palette_and_indices();
+        let palette = Palette::new(palette.colours().to_vec(), PixelDepth::Bits16).with_origin(16);
+        let indices = indices.iter().map(|index| index + 16).collect::<Vec<u8>>();
+        let encoded = tga::encode_colour_mapped(
+            sample::PALETTE_TGA_WIDTH, sample::PALETTE_TGA_HEIGHT, &indices, &palette, &EncoderOptions::default()
+        ).unwrap();
+        let image = TgaImage::parse_from_buffer(&encoded).unwrap();
+        let expected = TgaImage::open(sample::PALETTE_MAPPED_16_TGA).unwrap();
+        assert_eq!(&encoded[1..8], &[1, 1, 16, 0, 16, 0, 16]);
+        let mut scanlines = image.scanlines().collect::<Vec<_>>();
+        scanlines.reverse();
+
+        assert_eq!(scanlines, expected.scanlines().collect::<Vec<_>>());
+    }
+
+    /// An index outside of the palette should be rejected.
+    #[test]
+    fn test_encode_colour_mapped_should_reject_indices_outside_the_palette() {
+        let palette = Palette::from_bgr(&[[0, 0, 0], [255, 255, 255]]);
+        let result = tga::encode_colour_mapped(2, 1, &[1, 2], &palette, &EncoderOptions::default());
+
+        match result {
+            Err(TgaError::InvalidColourMapIndex(2)) => {}
+            other => panic!("expected an invalid colour map index, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_quantize {
+    use std::fs;
+    use tga::{EncoderOptions, Quantizer, TgaError, TgaImage};
+    use super::sample;
+
+
+    /// An image with few colours should be written with an exact palette
+    /// and decode to the same pixels.
+    #[test]
+    fn test_encode_indexed_should_preserve_an_image_with_few_colours() {
+        let image = TgaImage::open(sample::PALETTE_TGA).unwrap();
+        let encoded = image.encode_indexed(Quantizer::Exact, &EncoderOptions::default()).unwrap();
+        let result = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert_eq!(result.data_type_code(), 1);
+        assert!(encoded[5] as usize <= sample::PALETTE_TGA_COLOR_MAP_LENGTH);
+        assert!(result.pixels().eq(image.pixels()));
+    }
+
+    /// Transparent colours should produce a 32 bit palette.
+    #[test]
+    fn test_encode_indexed_should_keep_transparent_colours() {
+        let mut buf = fs::read(sample::PALETTE_32_TGA).unwrap();
+        buf[18 + 3] = 0;
+        let image = TgaImage::parse_from_buffer(&buf).unwrap();
+        let encoded = image.encode_indexed(Quantizer::Exact, &EncoderOptions::default()).unwrap();
+        let result = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert_eq!(encoded[7], 32);
+        assert_eq!(encoded[18 + 3], 0);
+        assert!(result.pixels().eq(image.pixels()));
+    }
+
+    /// The exact quantizer should reject an image with more than 256 colours.
+    #[test]
+    fn test_encode_indexed_exact_should_reject_too_many_colours() {
+        let image = TgaImage::open(sample::LENA_TGA).unwrap();
+        let result = image.encode_indexed(Quantizer::Exact, &EncoderOptions::default());
+
+        match result {
+            Err(TgaError::TooManyColours(colours)) => assert!(colours > 256),
+            other => panic!("expected too many colours, got {:?}", other),
+        }
+    }
+
+    /// The median cut quantizer should reduce a run length encoded image to a
+    /// full palette and write a type 9 file.
+    #[test]
+    fn test_encode_indexed_median_cut_should_write_run_length_encoded_output() {
+        let image = TgaImage::open(sample::LENA_RLE_TGA).unwrap();
+        let encoded = image.encode_indexed(Quantizer::MedianCut, &EncoderOptions::default()).unwrap();
+        let result = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert_eq!(result.data_type_code(), 9);
+        assert_eq!(&encoded[5..7], &[0, 1]);
+        assert_eq!(result.width(), image.width());
+        assert_eq!(result.height(), image.height());
+    }
+
+    /// An exact palette should be reported as having no error.
+    #[test]
+    fn test_encode_indexed_with_report_should_report_no_error_for_an_exact_palette() {
+        let image = TgaImage::open(sample::PALETTE_TGA).unwrap();
+        let (encoded, report) = image.encode_indexed_with_report(Quantizer::Exact, &EncoderOptions::default()).unwrap();
+
+        assert_eq!(report.max_error, 0.0);
+        assert_eq!(report.mean_error, 0.0);
+        assert_eq!(report.colours_used, encoded[5] as usize);
+    }
+
+    /// A reduced palette should report its error, with the mean error no
+    /// larger than the largest error.
+    #[test]
+    fn test_encode_indexed_with_report_should_measure_the_error_of_a_reduced_palette() {
+        let image = TgaImage::open(sample::LENA_TGA).unwrap();
+        let (_, report) = image.encode_indexed_with_report(Quantizer::MedianCut, &EncoderOptions::default()).unwrap();
+
+        assert_eq!(report.colours_used, 256);
+        assert!(report.max_error > 0.0);
+        assert!((report.mean_error > 0.0) && (report.mean_error <= report.max_error));
+    }
+}
+
+#[cfg(test)]
+mod tests_scanline_writer {
+    use tga::{EncoderOptions, Orientation, ScanlineWriter, TgaError, TgaImage};
+    use super::sample;
+
+
+    /// Stream the stored rows of an image through a scanline writer.
+    fn stream(image: &TgaImage, options: &EncoderOptions) -> Vec<u8> {
+        let row_length = 3 * image.width() as usize;
+        let mut writer = ScanlineWriter::new(Vec::new(), image.width(), image.height(), options).unwrap();
+        for row in image.image_data().chunks(row_length) {
+            writer.write_scanline(row).unwrap();
+        }
+
+        writer.finish().unwrap()
+    }
+
+    /// Streaming the rows of an image should produce the same file as
+    /// encoding the whole image at once.
+    #[test]
+    fn test_scanline_writer_should_match_the_encoder() {
+        let image = TgaImage::open(sample::LENA_TGA).unwrap();
+        let options = EncoderOptions { orientation: Some(Orientation::BottomLeft), ..EncoderOptions::default() };
+
+        assert_eq!(stream(&image, &options), image.encode_to_vec());
+    }
+
+    /// Streaming with run length encoding should produce the same file as
+    /// encoding the whole image with run length encoding.
+    #[test]
+    fn test_scanline_writer_should_match_the_run_length_encoder() {
+        let image = TgaImage::open(sample::LENA_RLE_TGA).unwrap();
+        let options = EncoderOptions {
+            orientation: Some(Orientation::BottomLeft), rle: Some(true), ..EncoderOptions::default()
+        };
+
+        assert_eq!(stream(&image, &options), image.encode_to_vec());
+    }
+
+    /// Finishing an image before every row has been written should be an error.
+    #[test]
+    fn test_scanline_writer_should_reject_missing_rows() {
+        let mut writer = ScanlineWriter::new(Vec::new(), 2, 2, &EncoderOptions::default()).unwrap();
+        writer.write_scanline(&[0; 6]).unwrap();
+
+        match writer.finish() {
+            Err(TgaError::UnexpectedScanlineCount(1, 2)) => {}
+            other => panic!("expected a scanline count error, got {:?}", other),
+        }
+    }
+
+    /// A scanline of the wrong length should be rejected.
+    #[test]
+    fn test_scanline_writer_should_reject_the_wrong_row_length() {
+        let mut writer = ScanlineWriter::new(Vec::new(), 2, 2, &EncoderOptions::default()).unwrap();
+
+        match writer.write_scanline(&[0; 3]) {
+            Err(TgaError::IncompleteImageData(3, 6)) => {}
+            other => panic!("expected an incomplete image error, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_canonical_output {
+    use std::fs;
+    use tga::{DeveloperTag, EncoderOptions, ExtensionArea, TgaImage, TgaImageBuilder, Timestamp};
+    use super::sample;
+
+
+    /// Encoding the same image with the same options should always give the
+    /// same bytes, whatever the options ask for.
+    #[test]
+    fn test_encoding_should_be_deterministic() {
+        let options = [
+            EncoderOptions::default(),
+            EncoderOptions {
+                rle: Some(cfg!(feature = "rle")),
+                extension_area: Some(ExtensionArea {
+                    author_name: "author".to_string(),
+                    timestamp: Some(Timestamp { year: 2024, month: 1, day: 2, hour: 3, minute: 4, second: 5 }),
+                    ..ExtensionArea::default()
+                }),
+                developer_tags: vec![DeveloperTag { tag: 1, data: b"data".to_vec() }],
+                postage_stamp: true,
+                ..EncoderOptions::default()
+            },
+        ];
+        for filename in [sample::LENA_TGA, sample::PALETTE_MAPPED_RLE_TGA].iter() {
+            let buf = fs::read(filename).unwrap();
+            for options in options.iter() {
+                let first = TgaImage::parse_from_buffer(&buf).unwrap().encode_to_vec_with_options(options);
+                let second = TgaImage::parse_from_buffer(&buf).unwrap().encode_to_vec_with_options(options);
+
+                assert_eq!(first, second, "{}", filename);
+            }
+        }
+    }
+
+    /// Images built in memory from the same pixels should encode to the same
+    /// bytes, starting from the canonical header fields.
+    #[test]
+    fn test_built_images_should_use_the_canonical_header_fields() {
+        let colour = |x: u32, y: u32| [x as u8, y as u8, 7];
+        let pixels = (0..2).flat_map(|y| (0..3).map(move |x| colour(x, y))).collect::<Vec<_>>();
+        let bytes = pixels.iter().flat_map(|pixel| pixel.iter().cloned()).collect::<Vec<_>>();
+        let from_fn = TgaImage::from_fn(3, 2, colour).unwrap().encode_to_vec();
+        let new = TgaImage::new(3, 2, pixels).unwrap().encode_to_vec();
+        let built = TgaImageBuilder::new().width(3).height(2).build(&bytes).unwrap().encode_to_vec();
+
+        assert_eq!(&from_fn[0..18], &[0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0, 2, 0, 24, 0x20]);
+        assert_eq!(&from_fn[(18 + 18)..(18 + 26)], &[0; 8]);
+        assert_eq!(&from_fn[(18 + 26)..], b"TRUEVISION-XFILE.\0");
+        assert_eq!(new, from_fn);
+        assert_eq!(built, from_fn);
+    }
+}
+
+#[cfg(all(test, feature = "metadata"))]
+mod tests_extension_area_metadata {
+    use tga::{EncoderOptions, ExtensionArea, TgaImage, Timestamp};
+    use super::sample;
+
+
+    fn encode(image: &TgaImage, extension_area: ExtensionArea) -> TgaImage {
+        let options = EncoderOptions { extension_area: Some(extension_area), ..EncoderOptions::default() };
+        TgaImage::parse_from_buffer(&image.encode_to_vec_with_options(&options)).unwrap()
+    }
+
+    /// The lines of author comments should be read back as they were set,
+    /// with blank lines kept in place and line breaks within a line replaced.
+    #[test]
+    fn test_author_comments_should_be_read_back() {
+        let image = TgaImage::open(sample::LENA_TGA).unwrap();
+        let mut extension_area = ExtensionArea::default();
+        extension_area.set_author_comment_lines(&["", "second", "third\nline", &"x".repeat(100)]);
+        let result = encode(&image, extension_area);
+        let expected = ["".to_string(), "second".to_string(), "third line".to_string(), "x".repeat(80)];
+
+        assert_eq!(image.author_comments(), None);
+        assert_eq!(result.author_comments(), Some(expected));
+    }
+
+    /// Author comments that are not UTF-8 should be read with the invalid
+    /// bytes replaced.
+    #[test]
+    fn test_author_comments_should_be_read_lossily() {
+        let image = TgaImage::open(sample::LENA_TGA).unwrap();
+        let extension_area = ExtensionArea { author_comments: "caf\u{e9}".to_string(), ..ExtensionArea::default() };
+        let mut encoded = image.encode_to_vec_with_options(
+            &EncoderOptions { extension_area: Some(extension_area), ..EncoderOptions::default() }
+        );
+        let footer = encoded.len() - 26;
+        let offset = u32::from_le_bytes([encoded[footer], encoded[footer + 1], encoded[footer + 2], encoded[footer + 3]]);
+        encoded[offset as usize + 43 + 4] = 0xFF;
+        let result = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert_eq!(result.author_comments().unwrap()[0], "caf\u{fffd}\u{fffd}");
+    }
+
+    /// The software identification and version should be read back as the
+    /// encoder stamped them.
+    #[test]
+    fn test_software_id_and_version_should_be_read_back() {
+        let image = TgaImage::open(sample::LENA_TGA).unwrap();
+        let extension_area = ExtensionArea {
+            software_id: "mytool".to_string(),
+            software_version: Some((140, b'b')),
+            ..ExtensionArea::default()
+        };
+        let result = encode(&image, extension_area);
+        let unstamped = encode(&image, ExtensionArea::default());
+
+        assert_eq!(image.software_id(), None);
+        assert_eq!(result.software_id(), Some("mytool".to_string()));
+        assert_eq!(result.software_version(), Some((140, b'b')));
+        assert_eq!(unstamped.software_id(), None);
+        assert_eq!(unstamped.software_version(), None);
+    }
+
+    /// The timestamp should be read back as the encoder stamped it, and be
+    /// missing when none was stamped.
+    #[test]
+    fn test_timestamp_should_be_read_back() {
+        let image = TgaImage::open(sample::LENA_TGA).unwrap();
+        let timestamp = Timestamp { year: 2024, month: 2, day: 29, hour: 23, minute: 59, second: 58 };
+        let result = encode(&image, ExtensionArea { timestamp: Some(timestamp), ..ExtensionArea::default() });
+        let unstamped = encode(&image, ExtensionArea::default());
+
+        assert_eq!(image.timestamp(), None);
+        assert_eq!(result.timestamp(), Some(timestamp));
+        assert_eq!(unstamped.timestamp(), None);
+    }
+
+    /// Timestamps should convert to and from the date and times of the
+    /// `time` crate, rejecting dates that do not exist.
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_timestamp_should_convert_to_and_from_the_time_crate() {
+        use std::convert::TryFrom;
+        use time::{Date, Month, PrimitiveDateTime, Time};
+
+        let timestamp = Timestamp { year: 2024, month: 2, day: 29, hour: 23, minute: 59, second: 58 };
+        let date_time = PrimitiveDateTime::new(
+            Date::from_calendar_date(2024, Month::February, 29).unwrap(),
+            Time::from_hms(23, 59, 58).unwrap(),
+        );
+
+        assert_eq!(PrimitiveDateTime::try_from(timestamp).unwrap(), date_time);
+        assert_eq!(Timestamp::from(date_time), timestamp);
+        assert_eq!(Timestamp::from(date_time.assume_utc()), timestamp);
+        assert!(PrimitiveDateTime::try_from(Timestamp { year: 2023, ..timestamp }).is_err());
+        assert!(PrimitiveDateTime::try_from(Timestamp { month: 300, ..timestamp }).is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests_trailing_areas {
+    use tga::{DeveloperTag, EncoderOptions, ExtensionArea, TgaError, TgaImage};
+    use super::sample;
+
+
+    /// The trailing bytes of a file should be classified into the unknown
+    /// bytes, the developer area, and the extension area.
+    #[test]
+    fn test_trailing_areas_should_be_classified() {
+        let mut buf = TgaImage::open(sample::ONE_TGA).unwrap().encode_to_vec();
+        buf.truncate(buf.len() - 26);
+        buf.extend_from_slice(b"unknown");
+        let options = EncoderOptions {
+            extension_area: Some(ExtensionArea { author_name: "Ada".to_string(), ..ExtensionArea::default() }),
+            developer_tags: vec![
+                DeveloperTag { tag: 7, data: b"seven".to_vec() },
+                DeveloperTag { tag: 42, data: b"forty two".to_vec() },
+            ],
+            ..EncoderOptions::default()
+        };
+        let encoded = TgaImage::parse_from_buffer(&buf).unwrap().encode_to_vec_with_options(&options);
+        let result = TgaImage::parse_from_buffer(&encoded).unwrap();
+        let extension_area = result.extension_area().unwrap();
+
+        assert_eq!(result.unknown_trailing_bytes(), b"unknown");
+        assert_eq!(result.developer_area(), vec![(7, &b"seven"[..]), (42, &b"forty two"[..])]);
+        assert_eq!(result.developer_tag(42), Some(&b"forty two"[..]));
+        assert_eq!(result.developer_tag(8), None);
+        assert_eq!(extension_area.len(), 495);
+        assert_eq!(&extension_area[2..6], b"Ada\0");
+    }
+
+    /// Files without TGA 2.0 areas should have no extension area and an
+    /// empty developer area.
+    #[test]
+    fn test_files_without_areas_should_have_none() {
+        let image = TgaImage::open(sample::LENA_TGA).unwrap();
+
+        assert_eq!(image.extension_area(), None);
+        assert!(image.developer_area().is_empty());
+        assert!(image.unknown_trailing_bytes().is_empty());
+    }
+
+    /// Offsets that point at the areas they should should pass, and offsets
+    /// that point into the pixel data or past the end of the file should be
+    /// reported, naming the area.
+    #[test]
+    fn test_validate_trailing_areas_should_report_bad_offsets() {
+        let image = TgaImage::open(sample::ONE_TGA).unwrap();
+        let options = EncoderOptions {
+            extension_area: Some(ExtensionArea::default()),
+            developer_tags: vec![DeveloperTag { tag: 7, data: b"seven".to_vec() }],
+            postage_stamp: true,
+            ..EncoderOptions::default()
+        };
+        let encoded = image.encode_to_vec_with_options(&options);
+        let footer = encoded.len() - 26;
+        let with_offset = |field: usize, offset: u32| {
+            let mut buf = encoded.clone();
+            buf[(footer + field)..(footer + field + 4)].copy_from_slice(&offset.to_le_bytes());
+            TgaImage::parse_from_buffer(&buf).unwrap().validate_trailing_areas()
+        };
+
+        assert!(TgaImage::parse_from_buffer(&encoded).unwrap().validate_trailing_areas().is_ok());
+        assert!(image.validate_trailing_areas().is_ok());
+        assert!(matches!(
+            with_offset(0, 18),
+            Err(TgaError::InvalidAreaOffset("extension area", 18, "overlaps the data before the trailing bytes"))
+        ));
+        assert!(matches!(
+            with_offset(4, 100000),
+            Err(TgaError::InvalidAreaOffset("developer directory", 100000, "points past the end of the file"))
+        ));
+    }
+}
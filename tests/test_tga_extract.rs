@@ -0,0 +1,78 @@
+#![cfg(feature = "in-place")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_extract {
+    use std::env;
+    use std::path::PathBuf;
+    #[cfg(feature = "transforms")]
+    use tga::TgaImage;
+    use tga::{Region, TgaError};
+    use super::sample;
+
+
+    /// The path of a file for a test to extract into.
+    fn output(name: &str) -> PathBuf {
+        let mut path = env::temp_dir();
+        path.push(name);
+
+        path
+    }
+
+    #[cfg(feature = "transforms")]
+    fn pixels(image: &TgaImage) -> Vec<[u8; 3]> {
+        image.pixels().collect()
+    }
+
+    /// The extracted file should hold the pixels of the region, as cropping
+    /// the decoded image gives them.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_extract_region_to_file_should_match_crop() {
+        let region = Region { x: 100, y: 200, width: 57, height: 31 };
+        let path = output("tga_extract_lena.tga");
+        tga::extract_region_to_file(sample::LENA_TGA, region, &path).unwrap();
+        let expected = TgaImage::open(sample::LENA_TGA).unwrap().crop(100, 200, 57, 31).unwrap();
+        let result = TgaImage::open(&path).unwrap();
+
+        assert_eq!(result.width(), 57);
+        assert_eq!(result.height(), 31);
+        assert_eq!(pixels(&result), pixels(&expected));
+    }
+
+    /// Regions of colour mapped and interleaved files should be extracted with
+    /// their colour maps, and with their scanlines in order.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_extract_region_to_file_should_handle_colour_maps_and_interleaving() {
+        for &filename in [sample::PALETTE_MAPPED_24_TGA, sample::INTERLEAVED_TWO_WAY_TGA].iter() {
+            let path = output("tga_extract_mapped.tga");
+            tga::extract_region_to_file(filename, Region { x: 3, y: 5, width: 20, height: 9 }, &path).unwrap();
+            let expected = TgaImage::open(filename).unwrap().crop(3, 5, 20, 9).unwrap();
+            let result = TgaImage::open(&path).unwrap();
+
+            assert_eq!(pixels(&result), pixels(&expected), "{}", filename);
+        }
+    }
+
+    /// Regions outside the image and run length encoded files should be
+    /// rejected, labelled with the path of the source.
+    #[test]
+    fn test_extract_region_to_file_should_reject_unsupported_requests() {
+        let path = output("tga_extract_rejected.tga");
+        let outside = tga::extract_region_to_file(sample::ONE_TGA, Region { x: 0, y: 0, width: 2, height: 1 }, &path);
+        let compressed = tga::extract_region_to_file(sample::ONE_RLE_TGA, Region { x: 0, y: 0, width: 1, height: 1 }, &path);
+
+        assert_eq!(
+            outside.unwrap_err().to_string(),
+            TgaError::RegionOutOfBounds(0, 0, 2, 1).named(sample::ONE_TGA).to_string()
+        );
+        assert_eq!(
+            compressed.unwrap_err().to_string(),
+            TgaError::Not24BitRgb(10).named(sample::ONE_RLE_TGA).to_string()
+        );
+    }
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "transforms")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_flat_samples {
+    use tga::{SampleFormat, TgaImage};
+    use super::sample;
+
+
+    /// The flat samples of an image should describe its rows, stored from
+    /// the bottom up in blue, green, red order.
+    #[test]
+    fn test_flat_samples_should_describe_the_image_data() {
+        let image = TgaImage::open(sample::LENA_TGA).unwrap();
+        let flat = image.as_flat_samples().unwrap();
+
+        assert_eq!(flat.samples, image.image_data());
+        assert_eq!((flat.width, flat.height, flat.row_stride), (512, 512, 512 * 3));
+        assert_eq!(flat.format, SampleFormat::Bgr8);
+        assert_eq!(flat.format.channel_count(), 3);
+        assert!(!flat.top_to_bottom && !flat.right_to_left);
+    }
+
+    /// The flat samples of a view should borrow the rows of the image it was
+    /// cut from, with the stride of that image.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_flat_samples_of_a_view_should_keep_the_stride() {
+        let image = TgaImage::open(sample::LENA_TGA).unwrap();
+        let cropped = image.crop(5, 7, 11, 3).unwrap();
+        let flat = cropped.as_flat_samples().unwrap();
+        let rows = (0..3).map(|y| &flat.samples[(y * flat.row_stride)..(y * flat.row_stride + 33)]);
+
+        assert_eq!(flat.row_stride, 512 * 3);
+        assert_eq!(flat.samples.len(), 2 * 512 * 3 + 33);
+        assert!(rows.eq(cropped.rows()));
+    }
+
+    /// Colour mapped images should be described as colour map indices.
+    #[test]
+    fn test_flat_samples_of_colour_mapped_images_should_be_indices() {
+        let image = TgaImage::open(sample::PALETTE_MAPPED_24_TGA).unwrap();
+        let flat = image.as_flat_samples().unwrap();
+
+        assert_eq!((flat.format, flat.format.bytes_per_pixel()), (SampleFormat::Index8, 1));
+        assert_eq!(flat.samples.len(), image.image_data_length());
+    }
+
+    /// Sixteen bit images should be described as packed colours with an
+    /// alpha bit.
+    #[test]
+    fn test_flat_samples_of_16_bit_images_should_be_packed() {
+        let image = TgaImage::open(sample::PALETTE_16_TGA).unwrap();
+        let flat = image.as_flat_samples().unwrap();
+
+        assert_eq!((flat.format, flat.format.bytes_per_pixel()), (SampleFormat::Bgra5551, 2));
+        assert_eq!(flat.format.channel_count(), 4);
+    }
+}
@@ -0,0 +1,93 @@
+#![cfg(all(feature = "encode", feature = "rle"))]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_gamma {
+    use tga::{EncoderOptions, ExtensionArea, TgaImage};
+    use std::fs::File;
+    use super::sample;
+
+
+    fn open(filename: &str) -> TgaImage {
+        let mut file = File::open(filename).unwrap();
+        TgaImage::parse_from_file(&mut file).unwrap()
+    }
+
+    /// Encode an image with an extension area recording the given gamma.
+    fn encode_with_gamma(image: &TgaImage, rle: bool, numerator: u16, denominator: u16) -> Vec<u8> {
+        let options = EncoderOptions {
+            rle: Some(rle),
+            extension_area: Some(ExtensionArea::default()),
+            ..EncoderOptions::default()
+        };
+        let mut encoded = image.encode_to_vec_with_options(&options);
+        let footer = encoded.len() - 26;
+        let offset = u32::from_le_bytes([encoded[footer], encoded[footer + 1], encoded[footer + 2], encoded[footer + 3]]);
+        let gamma = offset as usize + 478;
+        encoded[gamma..(gamma + 2)].copy_from_slice(&numerator.to_le_bytes());
+        encoded[(gamma + 2)..(gamma + 4)].copy_from_slice(&denominator.to_le_bytes());
+
+        encoded
+    }
+
+    /// The gamma should be read from the extension area the footer points
+    /// at, whether or not the pixels are run length encoded.
+    #[test]
+    fn test_gamma_should_be_read_from_the_extension_area() {
+        let image = open(sample::LENA_TGA);
+        let mut rle = vec![false];
+        if cfg!(feature = "rle") {
+            rle.push(true);
+        }
+        for &rle in rle.iter() {
+            let encoded = encode_with_gamma(&image, rle, 22, 10);
+            let result = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+            assert_eq!(result.gamma(), Some((22, 10)));
+        }
+    }
+
+    /// Files without an extension area, or with an unspecified gamma,
+    /// should report no gamma.
+    #[test]
+    fn test_gamma_should_be_none_when_unspecified() {
+        let image = open(sample::LENA_TGA);
+        let encoded = encode_with_gamma(&image, false, 22, 0);
+        let result = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert_eq!(image.gamma(), None);
+        assert_eq!(result.gamma(), None);
+    }
+
+    /// Applying the gamma should raise every colour channel to its power,
+    /// and record a gamma of 1.0 afterwards.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_apply_gamma_should_convert_to_linear_light() {
+        let image = TgaImage::from_fn(3, 1, |x, _| [[0, 128, 255][x as usize]; 3]).unwrap();
+        let encoded = encode_with_gamma(&image, false, 2, 1);
+        let mut result = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert!(result.apply_gamma());
+        assert_eq!(result.pixels().collect::<Vec<[u8; 3]>>(), vec![[0; 3], [64; 3], [255; 3]]);
+        assert_eq!(result.gamma(), Some((1, 1)));
+    }
+
+    /// The linear floats should use the recorded gamma, and the sRGB curve
+    /// when there is none.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_to_linear_f32_should_use_the_recorded_gamma() {
+        let image = TgaImage::from_fn(1, 1, |_, _| [0, 0, 128]).unwrap();
+        let encoded = encode_with_gamma(&image, false, 2, 1);
+        let result = TgaImage::parse_from_buffer(&encoded).unwrap().to_linear_f32();
+        let srgb = image.to_linear_f32();
+
+        assert!((result[0] - 0.2520).abs() < 0.001);
+        assert!((srgb[0] - 0.2158).abs() < 0.001);
+        assert_eq!(&result[1..], &[0.0, 0.0, 1.0]);
+    }
+}
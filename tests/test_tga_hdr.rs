@@ -0,0 +1,47 @@
+#![cfg(all(feature = "half", feature = "encode"))]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_to_f16_vec {
+    use tga::TgaImage;
+    use super::sample;
+
+
+    /// Every pixel should become four half floats in linear light, with the
+    /// colour channels reordered to red, green, blue, and alpha.
+    #[test]
+    fn test_to_f16_vec_should_convert_pixels_to_linear_light() {
+        let image = TgaImage::from_fn(4, 1, |x, _| [0, 255, [0, 128, 188, 255][x as usize]]).unwrap();
+        let result = image.to_f16_vec().iter().map(|value| value.to_f32()).collect::<Vec<_>>();
+        let red = [result[0], result[4], result[8], result[12]];
+
+        assert_eq!(result.len(), 16);
+        assert_eq!(&result[0..4], &[0.0, 1.0, 0.0, 1.0]);
+        assert!((red[1] - 0.2158).abs() < 0.001);
+        assert!((red[2] - 0.5029).abs() < 0.001);
+        assert_eq!(red[3], 1.0);
+    }
+
+    /// The converted pixels should follow the order `pixels` yields them in.
+    #[test]
+    fn test_to_f16_vec_should_follow_the_stored_pixel_order() {
+        let image = TgaImage::open(sample::PALETTE_MAPPED_24_TGA).unwrap();
+        let result = image.to_f16_vec();
+        let expected = image.to_planar();
+
+        assert_eq!(result.len(), 4 * expected.r.len());
+        for (i, pixel) in result.chunks(4).enumerate() {
+            assert_eq!(pixel[0], half::f16::from_f32(srgb_to_linear(expected.r[i])));
+            assert_eq!(pixel[2], half::f16::from_f32(srgb_to_linear(expected.b[i])));
+        }
+    }
+
+    /// The sRGB transfer function, applied to an eight bit channel.
+    fn srgb_to_linear(value: u8) -> f32 {
+        let value = value as f32 / 255.0;
+        if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+    }
+}
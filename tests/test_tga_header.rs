@@ -0,0 +1,43 @@
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_tga_header_builder {
+    use std::fs;
+    use tga::{lowlevel, TgaError, TgaHeaderBuilder};
+    use super::sample;
+
+
+    /// Built headers should match the headers of files with the same fields.
+    #[test]
+    fn test_build_should_match_the_headers_of_sample_files() {
+        let true_colour = TgaHeaderBuilder::new(2).width(512).height(512).bits_per_pixel(24).build().unwrap();
+        let colour_mapped = TgaHeaderBuilder::new(1)
+            .colour_map(16, 16, 32)
+            .width(32)
+            .height(16)
+            .bits_per_pixel(8)
+            .build()
+            .unwrap();
+
+        assert_eq!(&lowlevel::write_header(true_colour)[..], &fs::read(sample::LENA_TGA).unwrap()[0..18]);
+        assert_eq!(
+            &lowlevel::write_header(colour_mapped)[..], &fs::read(sample::PALETTE_MAPPED_ORIGIN_TGA).unwrap()[0..18]
+        );
+    }
+
+    /// Headers whose fields contradict each other should be rejected.
+    #[test]
+    fn test_build_should_reject_inconsistent_fields() {
+        let true_colour = TgaHeaderBuilder::new(10).width(4).height(4).bits_per_pixel(24);
+
+        assert!(matches!(true_colour.colour_map(0, 16, 24).build(), Err(TgaError::InconsistentHeader(_))));
+        assert!(matches!(TgaHeaderBuilder::new(1).bits_per_pixel(8).build(), Err(TgaError::InconsistentHeader(_))));
+        assert!(matches!(true_colour.bits_per_pixel(8).build(), Err(TgaError::UnsupportedBitDepth(8))));
+        assert!(matches!(true_colour.image_descriptor(0x28).build(), Err(TgaError::InconsistentHeader(_))));
+        assert!(matches!(TgaHeaderBuilder::new(4).build(), Err(TgaError::InconsistentHeader(_))));
+        assert!(true_colour.bits_per_pixel(32).image_descriptor(0x28).build().is_ok());
+    }
+}
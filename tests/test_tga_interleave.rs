@@ -0,0 +1,72 @@
+#![cfg(feature = "decode24")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_interleave {
+    use std::fs;
+    #[cfg(feature = "in-place")]
+    use std::ops::ControlFlow;
+    use tga::{Interleaving, TgaImage};
+    use super::sample;
+
+
+    /// The pixel data of the region of the lena sample the interleaved
+    /// samples were made from.
+    fn expected() -> Vec<u8> {
+        let buf = fs::read(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_buffer(&buf).unwrap();
+        let row_length = sample::INTERLEAVED_TGA_WIDTH as usize * 3;
+
+        image.rows()
+            .take(sample::INTERLEAVED_TGA_HEIGHT as usize)
+            .flat_map(|row| row[..row_length].iter().cloned())
+            .collect()
+    }
+
+    fn samples() -> Vec<(&'static str, Interleaving)> {
+        let mut samples = vec![(sample::INTERLEAVED_TWO_WAY_TGA, Interleaving::TwoWay)];
+        if cfg!(feature = "rle") {
+            samples.push((sample::INTERLEAVED_TWO_WAY_RLE_TGA, Interleaving::TwoWay));
+            samples.push((sample::INTERLEAVED_FOUR_WAY_RLE_TGA, Interleaving::FourWay));
+        }
+
+        samples
+    }
+
+    /// Interleaved scanlines should be put in order, including when run
+    /// length encoded packets cross them, and the original interleaving
+    /// should be reported.
+    #[test]
+    fn test_interleaved_scanlines_should_be_put_in_order() {
+        let expected = expected();
+        for &(filename, interleaving) in samples().iter() {
+            let buf = fs::read(filename).unwrap();
+            let image = TgaImage::parse_from_buffer(&buf).unwrap();
+
+            assert_eq!(image.interleaving(), interleaving, "{}", filename);
+            assert_eq!(image.header().image_descriptor() & 0xC0, 0, "{}", filename);
+            assert_eq!(image.image_data(), &expected[..], "{}", filename);
+        }
+    }
+
+    /// Decoding row by row should give every scanline the index it has once
+    /// the scanlines are put in order.
+    #[cfg(feature = "in-place")]
+    #[test]
+    fn test_decode_rows_with_should_report_deinterleaved_indices() {
+        let expected = expected();
+        for &(filename, _) in samples().iter() {
+            let buf = fs::read(filename).unwrap();
+            let mut result = vec![0; expected.len()];
+            tga::decode_rows_with(&mut &buf[..], |row_index, row| {
+                result[(row_index * row.len())..((row_index + 1) * row.len())].copy_from_slice(row);
+                ControlFlow::Continue(())
+            }).unwrap();
+
+            assert_eq!(result, expected, "{}", filename);
+        }
+    }
+}
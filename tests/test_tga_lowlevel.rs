@@ -0,0 +1,163 @@
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_header {
+    use std::fs;
+    use tga::lowlevel;
+    use super::sample;
+
+
+    /// Writing a parsed header should reproduce the bytes it was parsed from.
+    #[test]
+    fn test_write_header_should_invert_parse_header() {
+        for filename in [sample::LENA_TGA, sample::PALETTE_MAPPED_ORIGIN_TGA].iter() {
+            let buf = fs::read(filename).unwrap();
+            let header = lowlevel::parse_header(&buf).unwrap();
+
+            assert_eq!(&lowlevel::write_header(header)[..], &buf[0..18]);
+        }
+    }
+
+    /// Parsing a buffer shorter than a header should fail.
+    #[test]
+    fn test_parse_header_should_reject_short_buffers() {
+        assert!(lowlevel::parse_header(&[0; 17]).is_err());
+    }
+}
+
+#[cfg(all(test, feature = "rle"))]
+mod tests_packets {
+    use tga::{lowlevel, TgaError};
+    use tga::lowlevel::Packet;
+
+
+    /// Encoding a scanline and parsing its packets back should give the
+    /// original pixels.
+    #[test]
+    fn test_parsed_packets_should_reproduce_an_encoded_scanline() {
+        let scanline = [1, 1, 1, 1, 2, 3, 4, 4];
+        let mut buf = Vec::new();
+        lowlevel::encode_rle_scanline(&scanline, 2, &mut buf);
+        let mut decoded = Vec::new();
+        let mut offset = 0;
+        while offset < buf.len() {
+            let (packet, size) = lowlevel::parse_packet(&buf[offset..], 2).unwrap();
+            match packet {
+                Packet::Run { length, pixel } => {
+                    for _ in 0..length {
+                        decoded.extend_from_slice(pixel);
+                    }
+                }
+                Packet::Raw { pixels } => decoded.extend_from_slice(pixels),
+            }
+            offset += size;
+        }
+
+        assert_eq!(decoded, scanline);
+        assert_eq!(lowlevel::decode_rle(&buf, scanline.len(), 2).unwrap(), (scanline.to_vec(), buf.len()));
+    }
+
+    /// A packet cut short by the end of the buffer should be reported as
+    /// incomplete.
+    #[test]
+    fn test_parse_packet_should_reject_truncated_packets() {
+        assert!(lowlevel::parse_packet(&[0x02, 1, 2], 1).is_err());
+        assert!(lowlevel::parse_packet(&[], 1).is_err());
+        assert_eq!(
+            lowlevel::parse_packet(&[0x83, 7, 9], 1).unwrap(),
+            (Packet::Run { length: 4, pixel: &[7][..] }, 2)
+        );
+    }
+
+    /// Decoding should report how much pixel data the packets hold when
+    /// they end early, are cut short, or overrun the image.
+    #[test]
+    fn test_decode_rle_should_report_the_pixel_data_found() {
+        let incomplete = |result| match result {
+            Err(TgaError::IncompleteImageData(found, expected)) => (found, expected),
+            other => panic!("expected IncompleteImageData, got {:?}", other),
+        };
+
+        assert_eq!(incomplete(lowlevel::decode_rle(&[0x81, 7], 4, 1)), (2, 4));
+        assert_eq!(incomplete(lowlevel::decode_rle(&[0x81, 7, 0x02, 1], 4, 1)), (5, 4));
+        assert_eq!(incomplete(lowlevel::decode_rle(&[0x81, 7, 0x01, 1], 4, 1)), (4, 4));
+        assert_eq!(incomplete(lowlevel::decode_rle(&[0x84, 7, 0x00, 1], 4, 1)), (5, 4));
+        assert_eq!(lowlevel::decode_rle(&[0x81, 7, 0x01, 1, 2, 9], 4, 1).unwrap(), (vec![7, 7, 1, 2], 5));
+    }
+
+    /// A short run between other pixels should be folded into a raw packet
+    /// when a separate run length packet would take more bytes.
+    #[test]
+    fn test_encoded_scanline_should_fold_short_runs_into_raw_packets() {
+        let mut buf = Vec::new();
+        lowlevel::encode_rle_scanline(&[1, 2, 2, 3], 1, &mut buf);
+
+        assert_eq!(buf, [3, 1, 2, 2, 3]);
+    }
+
+    /// Long runs should still be written as run length packets.
+    #[test]
+    fn test_encoded_scanline_should_keep_long_runs() {
+        let mut buf = Vec::new();
+        lowlevel::encode_rle_scanline(&[1, 2, 2, 2, 2, 3], 1, &mut buf);
+
+        assert_eq!(buf, [0, 1, 0x83, 2, 0, 3]);
+    }
+}
+
+#[cfg(all(test, feature = "rle"))]
+mod tests_validate_rle_stream {
+    use tga::lowlevel;
+    use tga::lowlevel::{PacketIssue, PacketProblem};
+    use tga::{TgaHeader, TgaHeaderBuilder};
+
+
+    /// A 3 by 2 run length encoded greyscale header.
+    fn header() -> TgaHeader {
+        TgaHeaderBuilder::new(11).width(3).height(2).bits_per_pixel(8).build().unwrap()
+    }
+
+    /// A stream with every packet inside a scanline should have no issues.
+    #[test]
+    fn test_well_formed_stream_should_have_no_issues() {
+        let mut buf = Vec::new();
+        lowlevel::encode_rle_scanline(&[1, 1, 1], 1, &mut buf);
+        lowlevel::encode_rle_scanline(&[1, 2, 3], 1, &mut buf);
+
+        assert!(lowlevel::validate_rle_stream(&buf, header()).is_empty());
+    }
+
+    /// Packets crossing scanlines and running past the image should be
+    /// reported with their offsets, along with the bytes after the image.
+    #[test]
+    fn test_issues_should_locate_offending_packets() {
+        let buf = [0x83, 7, 0x82, 8, 0, 9];
+        let expected = vec![
+            PacketIssue { offset: 0, pixel: 0, problem: PacketProblem::OverrunsScanline { row: 0, overrun: 1 } },
+            PacketIssue { offset: 2, pixel: 4, problem: PacketProblem::ExceedsImage { excess: 1 } },
+            PacketIssue { offset: 4, pixel: 6, problem: PacketProblem::TrailingBytes { length: 2 } },
+        ];
+
+        assert_eq!(lowlevel::validate_rle_stream(&buf, header()), expected);
+    }
+
+    /// A stream ending inside a packet or before the image is complete should
+    /// be reported where it ends.
+    #[test]
+    fn test_short_streams_should_be_reported() {
+        let truncated = PacketProblem::Truncated { needed: 4, found: 2 };
+        let missing = PacketProblem::MissingPixels { missing: 4 };
+
+        assert_eq!(
+            lowlevel::validate_rle_stream(&[0x81, 7, 0x02, 1], header()),
+            vec![PacketIssue { offset: 2, pixel: 2, problem: truncated }]
+        );
+        assert_eq!(
+            lowlevel::validate_rle_stream(&[0x81, 7], header()),
+            vec![PacketIssue { offset: 2, pixel: 2, problem: missing }]
+        );
+    }
+}
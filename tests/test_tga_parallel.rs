@@ -0,0 +1,43 @@
+#![cfg(feature = "rayon")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_par_map_pixels_in_place {
+    use tga::TgaImage;
+    use std::fs::File;
+    use super::sample;
+
+
+    /// Every pixel of a true colour image should be transformed, whichever
+    /// thread its scanline lands on.
+    #[test]
+    fn test_par_map_pixels_in_place_should_transform_every_pixel() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let mut image = TgaImage::parse_from_file(&mut file).unwrap();
+        let expected = image.pixels()
+            .map(|pixel| [255 - pixel[0], 255 - pixel[1], 255 - pixel[2]])
+            .collect::<Vec<[u8; 3]>>();
+        image.par_map_pixels_in_place(|colour| [255 - colour[0], 255 - colour[1], 255 - colour[2], colour[3]]);
+        let result = image.pixels().collect::<Vec<[u8; 3]>>();
+
+        assert_eq!(result, expected);
+    }
+
+    /// A colour mapped image should have its colour map transformed, leaving
+    /// its indices alone.
+    #[test]
+    fn test_par_map_pixels_in_place_should_transform_the_colour_map() {
+        let mut file = File::open(sample::PALETTE_MAPPED_24_TGA).unwrap();
+        let mut image = TgaImage::parse_from_file(&mut file).unwrap();
+        let indices = image.image_data().to_vec();
+        let expected = image.pixels().map(|pixel| [pixel[2], pixel[1], pixel[0]]).collect::<Vec<[u8; 3]>>();
+        image.par_map_pixels_in_place(|colour| [colour[2], colour[1], colour[0], colour[3]]);
+        let result = image.pixels().collect::<Vec<[u8; 3]>>();
+
+        assert_eq!(result, expected);
+        assert_eq!(image.image_data(), &indices[..]);
+    }
+}
@@ -1,3 +1,4 @@
+#![cfg(feature = "decode24")]
 extern crate tga;
 
 use std::fs::File;
@@ -10,8 +11,8 @@ mod sample;
 #[derive(Copy, Clone, Debug)]
 struct TestCaseConfig<'a> {
     filename: &'a str,
-    width: usize,
-    height: usize,
+    width: u32,
+    height: u32,
     bits_per_pixel: usize,
     color_map_type: usize,
     data_type_code: usize,
@@ -20,8 +21,8 @@ struct TestCaseConfig<'a> {
 #[derive(Clone, Debug)]
 struct TestCase<'a> {
     filename: &'a str,
-    width: usize,
-    height: usize,
+    width: u32,
+    height: u32,
     bits_per_pixel: usize,
     color_map_type: usize,
     data_type_code: usize,
@@ -41,7 +42,7 @@ impl<'a> TestCase<'a> {
             bits_per_pixel: config.bits_per_pixel,
             color_map_type: config.color_map_type,
             data_type_code: config.data_type_code,
-            image: image,
+            image,
         }
     }
 
@@ -57,7 +58,7 @@ struct Test<'a> {
 }
 
 impl<'a> Test<'a> {
-    fn iter(&self) -> TestIter {
+    fn iter(&self) -> TestIter<'_> {
         TestIter {
             inner: self.tests.iter(),
         }
@@ -107,6 +108,7 @@ fn test_cases<'a>() -> Test<'a> {
     }
 }
 
+#[cfg(feature = "rle")]
 fn test_cases_rle<'a>() -> Test<'a> {
     Test {
         tests: vec![
@@ -141,7 +143,7 @@ fn test_cases_rle<'a>() -> Test<'a> {
 #[cfg(test)]
 mod tests_unmapped_rgb {
     use std::fs::File;
-    use tga::TgaImage;
+    use tga::{TgaError, TgaImage};
     use super::sample;
 
 
@@ -152,7 +154,7 @@ mod tests_unmapped_rgb {
         for test_case in super::test_cases().iter() {
             let image = TgaImage::parse_from_file(&mut test_case.as_slice());
         
-            assert!(image.is_ok());
+            assert!(image.is_ok(), "{}", test_case.filename);
         }
     }
 
@@ -197,7 +199,7 @@ mod tests_unmapped_rgb {
         for test_case in super::test_cases().iter() {
             let image = TgaImage::parse_from_file(&mut test_case.as_slice()).unwrap();
 
-            assert_eq!(image.image_data_length(), image.width() * image.height());
+            assert_eq!(image.image_data_length(), (image.width() * image.height()) as usize);
         }
     }
 
@@ -241,13 +243,166 @@ mod tests_unmapped_rgb {
     
         let scanlines = image.scanlines();
         let slice = image.image_data();
-        let scanlines_from_pixels = slice.chunks(3 * image.width());
+        let scanlines_from_pixels = slice.chunks(3 * image.width() as usize);
 
         assert!(scanlines.zip(scanlines_from_pixels).all(
             |(scanline, scanline_from_pixels)| { scanline == *scanline_from_pixels }
         ));
     }
 
+    /// The rows of an image should match its scanlines, and be borrowed from
+    /// its image data, including for a view whose rows are not adjacent.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_tga_image_rows_should_borrow_the_image_data() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let cropped = image.crop(5, 7, 11, 3).unwrap();
+        let row_length = 3 * image.width() as usize;
+        let last_row = &image.image_data()[(image.image_data().len() - row_length)..];
+
+        assert_eq!(image.rows().len(), image.height() as usize);
+        assert!(image.rows().zip(image.scanlines()).all(|(row, scanline)| scanline == *row));
+        assert_eq!(image.rows().next_back().unwrap().as_ptr(), last_row.as_ptr());
+        assert_eq!(cropped.rows().nth(1).unwrap(), &image.image_data()[(8 * row_length + 15)..(8 * row_length + 48)]);
+    }
+
+    /// The pixel iterator should know its length, and iterating it from the
+    /// back should give the pixels in reverse, including for a view.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_tga_image_pixel_iterator_should_be_exact_size_and_reversible() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        for image in [image.clone(), image.crop(5, 7, 11, 3).unwrap()].iter() {
+            let forward = image.pixels().collect::<Vec<_>>();
+            let mut reversed = image.pixels().rev().collect::<Vec<_>>();
+            reversed.reverse();
+            let mut pixels = image.pixels();
+            let first = pixels.next();
+            let last = pixels.next_back();
+
+            assert_eq!(image.pixels().len(), image.image_data_length());
+            assert_eq!(reversed, forward);
+            assert_eq!((first, last), (forward.first().cloned(), forward.last().cloned()));
+            assert_eq!(pixels.len(), forward.len() - 2);
+            assert_eq!(image.pixels().nth(12), Some(forward[12]));
+        }
+    }
+
+    /// Consuming an image into its pixels should give the same pixels as
+    /// borrowing them, from either end.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_tga_image_into_pixels_should_match_pixels() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let cropped = image.crop(5, 7, 11, 3).unwrap();
+        let expected = cropped.pixels().collect::<Vec<_>>();
+
+        assert_eq!(image.clone().into_pixels().len(), image.image_data_length());
+        assert_eq!(cropped.clone().into_pixels().rev().last(), expected.first().cloned());
+        assert_eq!(cropped.into_iter().collect::<Vec<_>>(), expected);
+    }
+
+    /// Iterating a reference to an image should give its pixels.
+    #[test]
+    fn test_tga_image_reference_should_iterate_over_pixels() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let mut pixels = Vec::new();
+        for pixel in &image {
+            pixels.push(pixel);
+        }
+
+        assert_eq!(pixels, image.pixels().collect::<Vec<_>>());
+    }
+
+    /// A scanline should work like a slice of the pixels in its row.
+    #[test]
+    fn test_tga_image_scanline_should_behave_like_a_slice() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let width = image.width() as usize;
+        let expected = image.pixels().take(width).collect::<Vec<_>>();
+        let scanline = image.scanlines().next().unwrap();
+
+        assert_eq!(scanline.len(), width);
+        assert_eq!(scanline[1], expected[1]);
+        assert_eq!(&scanline[2..4], &expected[2..4]);
+        assert_eq!((&scanline).into_iter().count(), width);
+        assert_eq!(scanline.into_iter().collect::<Vec<_>>(), expected);
+    }
+
+    /// References to the pixels of a 24 bit image should match the decoded
+    /// pixels, including for a view.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_tga_image_pixel_refs_should_match_pixels() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        for image in [image.clone(), image.crop(5, 7, 11, 3).unwrap()].iter() {
+            let pixel_refs = image.pixel_refs().unwrap();
+
+            assert_eq!(pixel_refs.len(), image.image_data_length());
+            assert!(pixel_refs.cloned().eq(image.pixels()));
+        }
+    }
+
+    /// The pixels of an image should be viewable as a slice of arrays of the
+    /// depth of the image, and of no other depth.
+    #[test]
+    fn test_tga_image_pixel_slices_should_match_the_image_data() {
+        let image = TgaImage::parse_from_file(&mut File::open(sample::LENA_TGA).unwrap()).unwrap();
+        let with_alpha = TgaImage::parse_from_file(&mut File::open(sample::PALETTE_32_TGA).unwrap()).unwrap();
+        let bgr = image.as_bgr_pixels().unwrap();
+        let bgra = with_alpha.as_bgra_pixels().unwrap();
+
+        assert_eq!(bgr.len(), image.image_data_length());
+        assert_eq!(bgr.concat(), image.image_data());
+        assert_eq!(bgra.concat(), with_alpha.image_data());
+        assert!(matches!(image.as_bgra_pixels(), Err(TgaError::UnsupportedBitDepth(24))));
+        assert!(matches!(with_alpha.as_bgr_pixels(), Err(TgaError::UnsupportedBitDepth(32))));
+    }
+
+    /// Images whose stored pixels are not 24 bit colours should have no
+    /// pixel references.
+    #[test]
+    fn test_tga_image_pixel_refs_should_reject_other_pixel_formats() {
+        let open = |filename| TgaImage::parse_from_file(&mut File::open(filename).unwrap()).unwrap();
+
+        assert!(matches!(open(sample::PALETTE_32_TGA).pixel_refs(), Err(TgaError::UnsupportedBitDepth(32))));
+        assert!(matches!(open(sample::PALETTE_MAPPED_24_TGA).pixel_refs(), Err(TgaError::Not24BitRgb(1))));
+    }
+
+    /// The scanline iterator should know how many rows it has left.
+    #[test]
+    fn test_tga_image_scanline_iterator_should_be_exact_size() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let mut scanlines = image.scanlines_top_down();
+        scanlines.next();
+
+        assert_eq!(image.scanlines().len(), image.height() as usize);
+        assert_eq!(scanlines.len(), image.height() as usize - 1);
+        assert_eq!(scanlines.count(), image.height() as usize - 1);
+    }
+
+    /// A single row should be the same row the row iterator yields.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_tga_image_row_should_match_rows() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let cropped = image.crop(5, 7, 11, 3).unwrap();
+        let mut blitted = vec![0; 33];
+        blitted.copy_from_slice(cropped.row(2).unwrap());
+
+        assert_eq!(image.row(3), image.rows().nth(3));
+        assert_eq!(blitted, cropped.rows().last().unwrap());
+        assert_eq!(cropped.row(3), None);
+    }
+
     /// The TGA image pixel iterator should return every pixel in the image.
     #[test]
     fn test_tga_image_iterator_should_return_every_pixel_in_image() {
@@ -274,10 +429,10 @@ mod tests_unmapped_rgb {
 
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "rle"))]
 mod tests_rle_rgb {
     use std::fs::File;
-    use tga::TgaImage;
+    use tga::{TgaError, TgaImage};
     use super::sample;
 
     /// The TGA image parser should be able to take a valid existing TGA
@@ -287,7 +442,7 @@ mod tests_rle_rgb {
         for test_case in super::test_cases_rle().iter() {
             let image = TgaImage::parse_from_file(&mut test_case.as_slice());
         
-            assert!(image.is_ok());
+            assert!(image.is_ok(), "{}", test_case.filename);
         }
     }
 
@@ -332,7 +487,7 @@ mod tests_rle_rgb {
         for test_case in super::test_cases_rle().iter() {
             let image = TgaImage::parse_from_buffer(test_case.as_slice()).unwrap();
 
-            assert_eq!(image.image_data_length(), image.width() * image.height());
+            assert_eq!(image.image_data_length(), (image.width() * image.height()) as usize);
         }
     }
 
@@ -405,9 +560,25 @@ mod tests_rle_rgb {
 
         assert_eq!(image_rle.image_data(), image.image_data());
     }
+
+    /// A tiny file whose header claims huge dimensions should fail to parse
+    /// because its packets run out, without first reserving memory for the
+    /// whole image the header claims.
+    #[test]
+    fn test_tiny_rle_file_claiming_huge_dimensions_should_fail_to_parse() {
+        let mut file = vec![0, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 24, 0x20];
+        for _ in 0..20 {
+            file.extend_from_slice(&[0xFF, 1, 2, 3]);
+        }
+        let result = TgaImage::parse_from_buffer(&file);
+
+        assert!(matches!(
+            result, Err(TgaError::IncompleteImageData(have, need)) if (have, need) == (20 * 128 * 3, 0xFFFF * 0xFFFF * 3)
+        ));
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "encode"))]
 mod tests_tga_reader {
     use tga::TgaImage;
     use tga::TgaReader;
@@ -419,9 +590,503 @@ mod tests_tga_reader {
             let image = TgaImage::parse_from_buffer(test_case.as_slice()).unwrap();
             let mut reader = TgaReader::new(&image);
             let mut buf = vec![0; test_case.as_slice().len()];
-            reader.read(&mut buf).unwrap();
+            reader.read_exact(&mut buf).unwrap();
 
             assert_eq!(buf.as_slice(), test_case.as_slice());
         }
     }
 }
+
+#[cfg(all(test, feature = "rle", feature = "encode"))]
+mod tests_tga_reader_rle {
+    use std::fs::File;
+    use std::io::Read;
+    use tga::{DeveloperTag, EncoderOptions, ExtensionArea, TgaImage, TgaReader};
+    use super::sample;
+
+
+    fn open(filename: &str) -> TgaImage {
+        let mut file = File::open(filename).unwrap();
+        TgaImage::parse_from_file(&mut file).unwrap()
+    }
+
+    /// Reading a run length encoded image should give a valid uncompressed
+    /// file with the same pixels, even when read in one go.
+    #[test]
+    fn test_tga_reader_should_write_rle_images_as_uncompressed_files() {
+        for filename in [sample::LENA_RLE_TGA, sample::PALETTE_MAPPED_RLE_TGA].iter() {
+            let image = open(filename);
+            let mut buf = Vec::new();
+            TgaReader::new(&image).read_to_end(&mut buf).unwrap();
+            let decoded = TgaImage::parse_from_buffer(&buf).unwrap();
+
+            assert_eq!(decoded.data_type_code(), image.data_type_code() & !0x08);
+            assert!(decoded.pixels().eq(image.pixels()));
+        }
+    }
+
+    /// The compressed mode should produce the same file as the run length encoder.
+    #[test]
+    fn test_tga_reader_compressed_should_match_the_run_length_encoder() {
+        for filename in [sample::LENA_RLE_TGA, sample::LENA_TGA].iter() {
+            let image = open(filename);
+            let mut buf = Vec::new();
+            TgaReader::compressed(&image).read_to_end(&mut buf).unwrap();
+
+            assert_eq!(buf, image.encode_rle());
+        }
+    }
+
+    /// An image read from a file with a developer area and an extension area
+    /// should be written with a single footer whose offsets point at the
+    /// areas in the new file, whichever way the pixels are stored.
+    #[cfg(feature = "metadata")]
+    #[test]
+    fn test_tga_reader_should_rebuild_the_footer_of_the_source_file() {
+        let options = EncoderOptions {
+            rle: Some(true),
+            developer_tags: vec![DeveloperTag { tag: 7, data: b"seven".to_vec() }],
+            extension_area: Some(ExtensionArea { key_colour: Some([1, 2, 3, 4]), ..ExtensionArea::default() }),
+            ..EncoderOptions::default()
+        };
+        let image = TgaImage::parse_from_buffer(&open(sample::LENA_TGA).encode_to_vec_with_options(&options)).unwrap();
+        let mut uncompressed = Vec::new();
+        TgaReader::new(&image).read_to_end(&mut uncompressed).unwrap();
+        let mut compressed = Vec::new();
+        TgaReader::compressed(&image).read_to_end(&mut compressed).unwrap();
+
+        for buf in [uncompressed, compressed].iter() {
+            let footers = buf.windows(18).filter(|window| *window == b"TRUEVISION-XFILE.\0").count();
+            let decoded = TgaImage::parse_from_buffer(buf).unwrap();
+
+            assert_eq!(footers, 1);
+            assert_eq!(decoded.developer_tag(7), Some(&b"seven"[..]));
+            assert_eq!(decoded.key_colour(), Some([1, 2, 3, 4]));
+            assert!(decoded.pixels().eq(image.pixels()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_empty_image {
+    #[cfg(feature = "in-place")]
+    use std::ops::ControlFlow;
+    use tga::TgaImage;
+
+
+    /// A type 0 file claiming a 4 by 2 image, with an image identification
+    /// and some data after it.
+    fn stub() -> Vec<u8> {
+        let mut buf = vec![0; tga::TGA_HEADER_LENGTH];
+        buf[0] = 4;
+        buf[12] = 4;
+        buf[14] = 2;
+        buf[16] = 24;
+        buf.extend_from_slice(b"stub");
+        buf.extend_from_slice(b"notes");
+
+        buf
+    }
+
+    /// A file without image data should parse into an image without pixels
+    /// that keeps its header and metadata.
+    #[test]
+    fn test_type_0_file_should_parse_into_an_empty_image() {
+        let image = TgaImage::parse_from_buffer(&stub()).unwrap();
+
+        assert!(matches!(image, TgaImage::Empty(_)));
+        assert_eq!(image.data_type_code(), 0);
+        assert_eq!((image.width(), image.height()), (0, 0));
+        assert_eq!(image.pixels().count(), 0);
+        assert!(image.image_data().is_empty());
+        assert_eq!(image.header(), tga::lowlevel::parse_header(&stub()).unwrap());
+    }
+
+    /// Encoding an image without image data should reproduce the file, so
+    /// placeholders survive a round trip.
+    #[cfg(all(feature = "encode", feature = "metadata"))]
+    #[test]
+    fn test_empty_image_should_round_trip() {
+        let image = TgaImage::parse_from_buffer(&stub()).unwrap();
+        let encoded = image.encode_to_vec();
+
+        assert_eq!(image.image_identification(), b"stub");
+        assert_eq!(image.extended_image_identification(), b"notes");
+        assert_eq!(&encoded[..(encoded.len() - 26)], &stub()[..]);
+    }
+
+    /// Decoding the rows of a file without image data should produce no rows.
+    #[cfg(feature = "in-place")]
+    #[test]
+    fn test_type_0_file_should_have_no_rows() {
+        let mut rows = 0;
+        tga::decode_rows_with(&mut &stub()[..], |_, _| {
+            rows += 1;
+            ControlFlow::Continue(())
+        }).unwrap();
+
+        assert_eq!(rows, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests_tga_writer {
+    use std::fs::File;
+    use std::io;
+    use std::io::Write;
+    use tga::{TgaImage, TgaWriter};
+    use super::sample;
+
+
+    /// Copying a file into a writer should produce the image parsed from it.
+    #[test]
+    fn test_tga_writer_should_assemble_the_copied_image() {
+        for filename in [sample::LENA_TGA, sample::PALETTE_MAPPED_24_TGA].iter() {
+            let mut writer = TgaWriter::new();
+            io::copy(&mut File::open(filename).unwrap(), &mut writer).unwrap();
+            let image = writer.finish().unwrap();
+            let expected = TgaImage::open(filename).unwrap();
+
+            assert_eq!(image, expected);
+        }
+    }
+
+    /// A header of an unsupported image type should fail the write that
+    /// completes it.
+    #[test]
+    fn test_tga_writer_should_reject_unsupported_headers_early() {
+        let mut header = [0; tga::TGA_HEADER_LENGTH];
+        header[2] = 3;
+        let mut writer = TgaWriter::new();
+        writer.write_all(&header[0..10]).unwrap();
+        let result = writer.write_all(&header[10..]);
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    /// A packet holding more pixels than the image has left should fail the
+    /// write that completes it, and leave the writer as it was, so the right
+    /// bytes can still be written.
+    #[cfg(feature = "rle")]
+    #[test]
+    fn test_tga_writer_should_reject_packets_as_they_arrive() {
+        let header = [0, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0, 1, 0, 24, 0x20];
+        let mut writer = TgaWriter::new();
+        writer.write_all(&header).unwrap();
+        writer.write_all(&[0x81, 1, 2]).unwrap();
+        let result = writer.write_all(&[3, 0x81, 4, 5, 6]);
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(writer.bytes_written(), header.len() + 3);
+
+        writer.write_all(&[3, 0x00, 4, 5, 6]).unwrap();
+        let image = writer.finish().unwrap();
+
+        assert_eq!(image.pixels().collect::<Vec<_>>(), vec![[1, 2, 3], [1, 2, 3], [4, 5, 6]]);
+    }
+
+    /// Finishing a tiny file whose header claims huge dimensions should fail
+    /// without first reserving memory for the whole image the header claims.
+    #[cfg(feature = "rle")]
+    #[test]
+    fn test_tga_writer_should_reject_tiny_files_claiming_huge_dimensions() {
+        let header = [0, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 24, 0x20];
+        let mut writer = TgaWriter::new();
+        writer.write_all(&header).unwrap();
+        writer.write_all(&[0xFF, 1, 2, 3]).unwrap();
+
+        assert!(writer.finish().is_err());
+    }
+
+    /// A colour map index outside the colour map should be rejected as soon
+    /// as it arrives.
+    #[test]
+    fn test_tga_writer_should_reject_colour_map_indices_as_they_arrive() {
+        let header = [0, 1, 1, 0, 0, 2, 0, 24, 0, 0, 0, 0, 2, 0, 1, 0, 8, 0x20];
+        let colour_map = [1, 2, 3, 4, 5, 6];
+        let mut writer = TgaWriter::new();
+        writer.write_all(&header).unwrap();
+        writer.write_all(&colour_map).unwrap();
+        writer.write_all(&[1]).unwrap();
+        let result = writer.write_all(&[5]);
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        writer.write_all(&[0]).unwrap();
+        let image = writer.finish().unwrap();
+
+        assert_eq!(image.pixels().collect::<Vec<_>>(), vec![[4, 5, 6], [1, 2, 3]]);
+    }
+}
+
+#[cfg(all(test, feature = "rle", feature = "encode"))]
+mod tests_color_mapped {
+    use std::fs::File;
+    use std::io::Read;
+    use tga::{TgaError, TgaImage};
+    use super::sample;
+
+
+    fn read_file(filename: &str) -> Vec<u8> {
+        let mut file = File::open(filename).unwrap();
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf).unwrap();
+
+        buf
+    }
+
+    fn mapped_test_cases() -> Vec<&'static str> {
+        vec![
+            sample::PALETTE_MAPPED_16_TGA,
+            sample::PALETTE_MAPPED_24_TGA,
+            sample::PALETTE_MAPPED_32_TGA,
+            sample::PALETTE_MAPPED_ORIGIN_TGA,
+            sample::PALETTE_MAPPED_RLE_TGA,
+        ]
+    }
+
+    /// The parser should accept colour mapped images with 8 bit indices
+    /// regardless of the bit depth of the colour map entries.
+    #[test]
+    fn test_parse_color_mapped_images_succeeds() {
+        for filename in mapped_test_cases() {
+            let image = TgaImage::parse_from_buffer(&read_file(filename)).unwrap();
+
+            assert_eq!(image.width(), sample::PALETTE_TGA_WIDTH);
+            assert_eq!(image.height(), sample::PALETTE_TGA_HEIGHT);
+            assert_eq!(image.bits_per_pixel(), 8);
+            assert_eq!(image.color_map_type(), 1);
+            assert_eq!(image.image_data_length(), (image.width() * image.height()) as usize);
+        }
+    }
+
+    /// Looking pixels up in the colour map should produce the same colours as
+    /// the equivalent true colour image, whatever the depth of the colour map
+    /// entries and wherever the colour map starts.
+    #[test]
+    fn test_color_mapped_pixels_should_match_true_color_pixels() {
+        let expected = TgaImage::parse_from_buffer(&read_file(sample::PALETTE_TGA)).unwrap();
+        for filename in mapped_test_cases() {
+            let image = TgaImage::parse_from_buffer(&read_file(filename)).unwrap();
+
+            assert!(image.pixels().eq(expected.pixels()), "{}", filename);
+        }
+    }
+
+    /// Sixteen and thirty two bit true colour pixels should decode through the
+    /// same path as colour map entries of those depths.
+    #[test]
+    fn test_16_and_32_bit_true_color_pixels_should_match_24_bit_pixels() {
+        let expected = TgaImage::parse_from_buffer(&read_file(sample::PALETTE_TGA)).unwrap();
+        for filename in [sample::PALETTE_16_TGA, sample::PALETTE_32_TGA].iter() {
+            let image = TgaImage::parse_from_buffer(&read_file(filename)).unwrap();
+
+            assert!(image.pixels().eq(expected.pixels()), "{}", filename);
+        }
+    }
+
+    /// Encoding a colour mapped image should write the colour map out at its
+    /// own depth, independently of the depth of the indices.
+    #[test]
+    fn test_encode_color_mapped_image_should_preserve_color_map_depth() {
+        for filename in mapped_test_cases() {
+            let buf = read_file(filename);
+            let image = TgaImage::parse_from_buffer(&buf).unwrap();
+            let encoded = image.encode_to_vec();
+            let decoded = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+            assert_eq!(encoded[7], buf[7]);
+            assert_eq!(decoded, image);
+        }
+    }
+
+    /// A pixel referring to an entry outside of the colour map should be
+    /// rejected.
+    #[test]
+    fn test_color_map_index_out_of_range_should_fail_to_parse() {
+        let mut buf = read_file(sample::PALETTE_MAPPED_24_TGA);
+        let last_pixel = buf.len() - 27;
+        buf[last_pixel] = sample::PALETTE_TGA_COLOR_MAP_LENGTH as u8;
+
+        match TgaImage::parse_from_buffer(&buf) {
+            Err(TgaError::InvalidColourMapIndex(index)) => {
+                assert_eq!(index, sample::PALETTE_TGA_COLOR_MAP_LENGTH);
+            }
+            other => panic!("expected an invalid colour map index, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "rle"))]
+mod tests_open {
+    use std::fs::File;
+    use tga::{TgaError, TgaImage};
+    use super::sample;
+
+
+    /// Opening a file by path should give the same image as parsing it from
+    /// an open file.
+    #[test]
+    fn test_open_should_match_parse_from_file() {
+        for filename in [sample::LENA_TGA, sample::LENA_RLE_TGA, sample::PALETTE_MAPPED_24_TGA].iter() {
+            let mut file = File::open(filename).unwrap();
+            let expected = TgaImage::parse_from_file(&mut file).unwrap();
+            let image = TgaImage::open(filename).unwrap();
+
+            assert_eq!(image, expected);
+        }
+    }
+
+    /// Parsing a named buffer should label errors with the name.
+    #[test]
+    fn test_parse_named_should_label_errors() {
+        let buf = std::fs::read(sample::ONE_TGA).unwrap();
+        let err = TgaImage::parse_named(&buf[0..20], "textures/hero.tga").unwrap_err();
+
+        assert_eq!(err.source_label(), Some("textures/hero.tga"));
+        assert_eq!(
+            format!("{}", err),
+            "Named(source=textures/hero.tga, error=IncompleteImageData(have=2, need=3))"
+        );
+    }
+
+    /// Opening a file that does not exist should report an I/O error.
+    #[test]
+    fn test_open_missing_file_should_report_io_error() {
+        match TgaImage::open("sample/does_not_exist.tga") {
+            Err(TgaError::Io(_)) => {}
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "rle")))]
+mod tests_without_rle {
+    use std::fs::File;
+    use tga::{TgaError, TgaImage};
+    use super::sample;
+
+
+    /// Without the `rle` feature, run length encoded images should be rejected
+    /// as unsupported.
+    #[test]
+    fn test_rle_image_should_be_unsupported_without_rle_feature() {
+        let mut file = File::open(sample::LENA_RLE_TGA).unwrap();
+
+        match TgaImage::parse_from_file(&mut file) {
+            Err(TgaError::Not24BitRgb(10)) => {}
+            other => panic!("expected an unsupported image type, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "rle", feature = "encode"))]
+mod tests_deterministic_decode {
+    use std::fs;
+    use std::thread;
+    use tga::TgaImage;
+    use super::sample;
+
+
+    /// Decode a buffer, returning either the re-encoded image or the error message.
+    fn decode(buf: &[u8]) -> Result<Vec<u8>, String> {
+        TgaImage::parse_from_buffer(buf)
+            .map(|image| image.encode_to_vec())
+            .map_err(|err| format!("{}", err))
+    }
+
+    /// Decode a buffer on several threads at once.
+    fn decode_on_threads(buf: &[u8]) -> Vec<Result<Vec<u8>, String>> {
+        thread::scope(|scope| {
+            let handles = (0..4).map(|_| scope.spawn(|| decode(buf))).collect::<Vec<_>>();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        })
+    }
+
+    /// Decoding on several threads should produce output bit identical to
+    /// decoding on a single thread.
+    #[test]
+    fn test_decoding_on_threads_should_match_decoding_on_one_thread() {
+        for path in [sample::LENA_TGA, sample::LENA_RLE_TGA, sample::PALETTE_MAPPED_RLE_TGA] {
+            let buf = fs::read(path).unwrap();
+            let expected = decode(&buf);
+            assert!(expected.is_ok());
+
+            for result in decode_on_threads(&buf) {
+                assert_eq!(result, expected);
+            }
+        }
+    }
+
+    /// A truncated file should fail with the same error on every thread.
+    #[test]
+    fn test_decoding_on_threads_should_report_the_same_error() {
+        let buf = fs::read(sample::LENA_RLE_TGA).unwrap();
+        let buf = &buf[..(buf.len() / 2)];
+        let expected = decode(buf);
+        assert!(expected.is_err());
+
+        for result in decode_on_threads(buf) {
+            assert_eq!(result, expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_error_codes {
+    use std::io;
+    use tga::{TgaError, TgaImage};
+
+
+    fn io_error() -> Box<io::Error> {
+        Box::new(io::Error::new(io::ErrorKind::Other, "io"))
+    }
+
+    /// Every kind of error should keep the code and string it was assigned.
+    #[test]
+    fn test_error_codes_should_be_stable() {
+        let errors = vec![
+            TgaError::CorruptTgaHeader,
+            TgaError::Not24BitRgb(3),
+            TgaError::CorruptIdString(io_error()),
+            TgaError::CorruptColourMap(io_error()),
+            TgaError::CorruptImageData(io_error()),
+            TgaError::IncompleteTgaHeader(0, 18),
+            TgaError::IncompleteIdString(0, 1),
+            TgaError::IncompleteColourMap(0, 1),
+            TgaError::IncompleteImageData(0, 1),
+            TgaError::Io(io_error()),
+            TgaError::UnsupportedBitDepth(7),
+            TgaError::InvalidColourMapIndex(9),
+            TgaError::InvalidDimensions(0, 0),
+            TgaError::RegionOutOfBounds(0, 0, 1, 1),
+            TgaError::PaletteTooLarge(300),
+            TgaError::TooManyColours(300),
+            TgaError::UnexpectedScanlineCount(1, 2),
+            TgaError::ImageIdentificationTooLong(256),
+            TgaError::LayoutMismatch,
+            TgaError::InconsistentHeader("reason"),
+            TgaError::InvalidProvenance("reason"),
+            TgaError::InvalidStride(-1),
+            TgaError::InvalidAreaOffset("extension area", 0, "reason"),
+            TgaError::DimensionMismatch(1, 1),
+            TgaError::BufferTooSmall(0, 1),
+        ];
+        let codes = errors.iter().map(TgaError::error_code).collect::<Vec<_>>();
+
+        assert_eq!(codes, (1..=25).collect::<Vec<u32>>());
+        assert_eq!(errors[0].error_id(), "corrupt_tga_header");
+        assert_eq!(errors[8].error_id(), "incomplete_image_data");
+        assert_eq!(errors[21].error_id(), "invalid_stride");
+    }
+
+    /// A named error should have the code and string of the error it names.
+    #[test]
+    fn test_named_errors_should_have_the_code_of_their_cause() {
+        let err = TgaImage::parse_named(&[0; 4], "short.tga").unwrap_err();
+
+        assert_eq!(err.source_label(), Some("short.tga"));
+        assert_eq!(err.error_code(), 6);
+        assert_eq!(err.error_id(), "incomplete_tga_header");
+    }
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "in-place")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_patch {
+    use std::fs;
+    use tga::{DirtyRect, TgaError, TgaImage};
+    use super::sample;
+
+
+    /// Paint a rectangle of an image white, working on the stored bytes.
+    fn paint(image: &mut TgaImage, rect: DirtyRect) {
+        let row_length = image.width() as usize * 3;
+        let pixels = image.image_data_mut();
+        for y in rect.y..(rect.y + rect.height) {
+            let start = y as usize * row_length + rect.x as usize * 3;
+            let end = start + rect.width as usize * 3;
+            pixels[start..end].iter_mut().for_each(|byte| *byte = 0xFF);
+        }
+    }
+
+    /// Patching a file should give the file of the edited image, changing
+    /// only the bytes of the pixels inside the dirty rectangles.
+    #[test]
+    fn test_encode_patched_should_rewrite_only_the_dirty_pixels() {
+        let original = fs::read(sample::LENA_TGA).unwrap();
+        let mut image = TgaImage::parse_from_buffer(&original).unwrap();
+        let rects = [
+            DirtyRect { x: 5, y: 10, width: 3, height: 2 },
+            DirtyRect { x: 500, y: 0, width: 12, height: 1 },
+        ];
+        for &rect in rects.iter() {
+            paint(&mut image, rect);
+        }
+        let patched = image.encode_patched(&original, &rects).unwrap();
+        let changed = original.iter().zip(patched.iter()).filter(|&(a, b)| a != b).count();
+
+        assert_eq!(patched.len(), original.len());
+        assert_eq!(TgaImage::parse_from_buffer(&patched).unwrap().image_data(), image.image_data());
+        assert!(changed <= 3 * (3 * 2 + 12));
+    }
+
+    /// Files that do not store their pixels the way the image does, and
+    /// rectangles outside of the image, should be rejected.
+    #[test]
+    fn test_patch_in_place_should_reject_inconsistent_arguments() {
+        let mut original = fs::read(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_buffer(&original).unwrap();
+        let mut other = fs::read(sample::COLOR_TGA).unwrap();
+        let rect = DirtyRect { x: 510, y: 0, width: 3, height: 1 };
+
+        assert!(matches!(image.patch_in_place(&mut other, &[]), Err(TgaError::LayoutMismatch)));
+        assert!(matches!(image.patch_in_place(&mut original, &[rect]), Err(TgaError::RegionOutOfBounds(510, 0, 3, 1))));
+        if cfg!(feature = "rle") {
+            let mut compressed = fs::read(sample::LENA_RLE_TGA).unwrap();
+            assert!(matches!(image.patch_in_place(&mut compressed, &[]), Err(TgaError::LayoutMismatch)));
+        }
+    }
+}
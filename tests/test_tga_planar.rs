@@ -0,0 +1,62 @@
+#![cfg(feature = "transforms")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_planar {
+    use tga::{Channel, TgaImage};
+    use super::sample;
+
+
+    /// The planes of an image should hold the channels of its pixels.
+    #[test]
+    fn test_to_planar_should_split_the_channels_of_every_pixel() {
+        for filename in [sample::LENA_TGA, sample::PALETTE_MAPPED_24_TGA, sample::PALETTE_16_TGA].iter() {
+            let image = TgaImage::open(filename).unwrap();
+            let planes = image.to_planar();
+            let pixels = image.pixels().collect::<Vec<_>>();
+
+            assert_eq!(planes.b, pixels.iter().map(|pixel| pixel[0]).collect::<Vec<_>>());
+            assert_eq!(planes.g, pixels.iter().map(|pixel| pixel[1]).collect::<Vec<_>>());
+            assert_eq!(planes.r, pixels.iter().map(|pixel| pixel[2]).collect::<Vec<_>>());
+            assert_eq!(planes.a.is_some(), image.header().image_descriptor() & 0x0F != 0);
+        }
+    }
+
+    /// Each channel of an image should match its plane, including for a view.
+    #[test]
+    fn test_channel_should_match_the_planes() {
+        for filename in [sample::PALETTE_MAPPED_24_TGA, sample::PALETTE_32_TGA].iter() {
+            let image = TgaImage::open(filename).unwrap().crop(3, 2, 10, 5).unwrap();
+            let planes = image.to_planar();
+            let alpha = image.channel(Channel::Alpha).collect::<Vec<_>>();
+            let reversed_blue = planes.b.iter().rev().cloned().collect::<Vec<_>>();
+
+            assert_eq!(image.channel(Channel::Red).len(), 50);
+            assert_eq!(image.channel(Channel::Red).collect::<Vec<_>>(), planes.r);
+            assert_eq!(image.channel(Channel::Green).collect::<Vec<_>>(), planes.g);
+            assert_eq!(image.channel(Channel::Blue).rev().collect::<Vec<_>>(), reversed_blue);
+            assert_eq!(planes.a.unwrap_or_else(|| vec![255; 50]), alpha);
+        }
+    }
+
+    /// Interleaving the planes of an image should give back its pixels, and
+    /// planes of the wrong length should be rejected.
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_from_planar_should_invert_to_planar() {
+        let image = TgaImage::from_fn(5, 3, |x, y| [x as u8, y as u8, (x * y) as u8]).unwrap();
+        let mut planes = image.to_planar();
+        let result = TgaImage::from_planar(5, 3, &planes).unwrap();
+        planes.a = Some(vec![128; 15]);
+        let with_alpha = TgaImage::from_planar(5, 3, &planes).unwrap();
+        planes.r.pop();
+
+        assert_eq!(result, image);
+        assert_eq!(with_alpha.bits_per_pixel(), 32);
+        assert_eq!(with_alpha.to_planar().a, Some(vec![128; 15]));
+        assert!(TgaImage::from_planar(5, 3, &planes).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "transforms")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_present_into {
+    use tga::TgaImage;
+    use super::sample;
+
+
+    const WIDTH: usize = sample::PALETTE_TGA_WIDTH as usize;
+    const HEIGHT: usize = sample::PALETTE_TGA_HEIGHT as usize;
+
+    /// The pixels of an image as displayed, top row first, as `0xAARRGGBB` values.
+    fn displayed_pixels(image: &TgaImage) -> Vec<Vec<u32>> {
+        let pixels = image.pixels()
+            .map(|[blue, green, red]| u32::from_be_bytes([0xFF, red, green, blue]))
+            .collect::<Vec<u32>>();
+
+        pixels.chunks(WIDTH).rev().map(|row| row.to_vec()).collect()
+    }
+
+    /// An image presented into a framebuffer of its own size should fill the
+    /// framebuffer upright.
+    #[test]
+    fn test_present_into_should_copy_the_image_upright() {
+        for filename in [sample::PALETTE_TGA, sample::PALETTE_16_TGA, sample::PALETTE_MAPPED_24_TGA].iter() {
+            let image = TgaImage::open(filename).unwrap();
+            let mut framebuffer = vec![0; WIDTH * HEIGHT];
+            image.present_into(&mut framebuffer, WIDTH as u32, HEIGHT as u32, (0, 0));
+            let expected = displayed_pixels(&image).concat();
+
+            assert_eq!(framebuffer, expected);
+        }
+    }
+
+    /// The parts of an image outside of the framebuffer should be clipped,
+    /// and the rest of the framebuffer left alone.
+    #[test]
+    fn test_present_into_should_clip_the_image() {
+        let image = TgaImage::open(sample::PALETTE_TGA).unwrap();
+        let expected = displayed_pixels(&image);
+        let mut framebuffer = vec![7; 8 * 8];
+        image.present_into(&mut framebuffer, 8, 8, (-28, -12));
+
+        for y in 0..8 {
+            for x in 0..8 {
+                let pixel = if (x < 4) && (y < 4) { expected[y + 12][x + 28] } else { 7 };
+                assert_eq!(framebuffer[y * 8 + x], pixel, "pixel ({}, {})", x, y);
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "encode")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_provenance {
+    use tga::{DeveloperTag, EncoderOptions, Orientation, ProvenanceInfo, TgaError, TgaImage, Timestamp};
+    use std::fs::File;
+    use super::sample;
+
+
+    fn open(filename: &str) -> TgaImage {
+        let mut file = File::open(filename).unwrap();
+        TgaImage::parse_from_file(&mut file).unwrap()
+    }
+
+    fn info() -> ProvenanceInfo {
+        ProvenanceInfo {
+            tool: "asset-baker".to_string(),
+            commit_hash: "3f2a9c1d".to_string(),
+            timestamp: Timestamp { year: 2024, month: 5, day: 17, hour: 9, minute: 30, second: 5 },
+        }
+    }
+
+    /// A provenance record should be read back from the file it was
+    /// embedded in, alongside other developer tags, even when the file
+    /// stores the pixels in another order.
+    #[test]
+    fn test_read_provenance_should_return_the_embedded_record() {
+        let image = open(sample::LENA_TGA);
+        let mut options = EncoderOptions {
+            developer_tags: vec![DeveloperTag { tag: 7, data: vec![1, 2, 3] }],
+            orientation: Some(Orientation::TopRight),
+            ..EncoderOptions::default()
+        };
+        options.embed_provenance(&image, info());
+        let encoded = image.encode_to_vec_with_options(&options);
+        let result = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert_eq!(result.read_provenance().unwrap(), Some(info()));
+    }
+
+    /// Images without a provenance record should report none.
+    #[test]
+    fn test_read_provenance_should_be_none_without_a_record() {
+        let image = open(sample::LENA_TGA);
+        let encoded = image.encode_to_vec();
+        let result = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        assert_eq!(image.read_provenance().unwrap(), None);
+        assert_eq!(result.read_provenance().unwrap(), None);
+    }
+
+    /// Changing a pixel after the record was made should fail verification.
+    #[test]
+    fn test_read_provenance_should_detect_modified_pixels() {
+        let image = open(sample::LENA_TGA);
+        let mut options = EncoderOptions::default();
+        options.embed_provenance(&image, info());
+        let mut encoded = image.encode_to_vec_with_options(&options);
+        encoded[18] ^= 0xFF;
+        let result = TgaImage::parse_from_buffer(&encoded).unwrap();
+
+        match result.read_provenance() {
+            Err(TgaError::InvalidProvenance(_)) => {}
+            other => panic!("expected InvalidProvenance, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+#![cfg(all(feature = "encode", feature = "transforms"))]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_pyramid {
+    use std::env;
+    use std::fs;
+    use tga::TgaImage;
+    use super::sample;
+
+
+    /// Each level should halve the one before it until a single pixel is
+    /// left, with every level split into tiles of at most the tile size.
+    #[test]
+    fn test_build_pyramid_should_halve_each_level() {
+        let image = TgaImage::open(sample::COLOR_TGA).unwrap();
+        let pyramid = image.build_pyramid(20, 256);
+        let dimensions = pyramid.levels.iter().map(|level| (level.width, level.height)).collect::<Vec<_>>();
+        let tile_counts = pyramid.levels.iter().map(|level| level.tiles.len()).collect::<Vec<_>>();
+        let last = &pyramid.levels[1].tiles[1];
+
+        assert_eq!(dimensions, vec![
+            (640, 480), (320, 240), (160, 120), (80, 60), (40, 30), (20, 15), (10, 8), (5, 4), (3, 2), (2, 1), (1, 1)
+        ]);
+        assert_eq!(tile_counts, vec![6, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1]);
+        assert_eq!((last.column, last.row, last.x, last.y), (1, 0, 256, 0));
+        assert_eq!((last.image.width(), last.image.height()), (64, 240));
+        assert_eq!(image.build_pyramid(2, 256).levels.len(), 2);
+    }
+
+    /// The pixels of a level should average the blocks of pixels they
+    /// replace, repeating the last row and column along odd edges.
+    #[test]
+    fn test_build_pyramid_should_average_blocks_of_pixels() {
+        let image = TgaImage::from_fn(3, 3, |x, y| [(40 * x) as u8, (40 * y) as u8, 200]).unwrap();
+        let pyramid = image.build_pyramid(2, 16);
+        let pixels = pyramid.levels[1].tiles[0].image.pixels().collect::<Vec<_>>();
+
+        assert_eq!(pixels, vec![[20, 20, 200], [80, 20, 200], [20, 80, 200], [80, 80, 200]]);
+    }
+
+    /// Saving a pyramid should write every tile and a manifest listing them.
+    #[test]
+    fn test_save_should_write_the_tiles_and_manifest() {
+        let image = TgaImage::open(sample::PALETTE_MAPPED_24_TGA).unwrap();
+        let pyramid = image.build_pyramid(2, 16);
+        let mut dir = env::temp_dir();
+        dir.push("tga_test_pyramid");
+        pyramid.save(&dir).unwrap();
+        let manifest = fs::read_to_string(dir.join("manifest.json")).unwrap();
+        let tile = TgaImage::open(dir.join("0").join("1_0.tga")).unwrap();
+        let level_one = TgaImage::open(dir.join("1").join("0_0.tga")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(manifest, pyramid.manifest());
+        assert!(manifest.starts_with("{\"tile_size\":16,\"levels\":[{\"level\":0,\"width\":32,\"height\":16,"));
+        assert!(manifest.contains("{\"column\":1,\"row\":0,\"x\":16,\"y\":0,\"width\":16,\"height\":16,\"path\":\"0/1_0.tga\"}"));
+        assert!(tile.pixels().eq(pyramid.levels[0].tiles[1].image.pixels()));
+        assert_eq!((level_one.width(), level_one.height()), (16, 8));
+    }
+}
@@ -0,0 +1,169 @@
+#![cfg(feature = "in-place")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_decode_rows_with {
+    use std::fs::File;
+    use std::io::Read;
+    use std::ops::ControlFlow;
+    use tga::{TgaError, TgaImage};
+    use super::sample;
+
+
+    /// Collect the scanlines decoded from a buffer.
+    fn decoded_rows(buf: &[u8]) -> Result<Vec<Vec<u8>>, TgaError> {
+        let mut rows = Vec::new();
+        tga::decode_rows_with(&mut &buf[..], |row_index, row| {
+            assert_eq!(row_index, rows.len());
+            rows.push(row.to_vec());
+            ControlFlow::Continue(())
+        })?;
+
+        Ok(rows)
+    }
+
+    /// The scanlines decoded from a file should match the pixel data of the
+    /// image parsed from it, for uncompressed and run length encoded files.
+    #[test]
+    fn test_decoded_rows_should_match_the_parsed_image_data() {
+        let mut filenames = vec![sample::LENA_TGA, sample::PALETTE_16_TGA, sample::PALETTE_MAPPED_24_TGA];
+        if cfg!(feature = "rle") {
+            filenames.extend_from_slice(&[sample::LENA_RLE_TGA, sample::COLOR_RLE_TGA, sample::ONE_RLE_TGA]);
+        }
+        for filename in filenames {
+            let mut buf = Vec::new();
+            File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+            let image = TgaImage::parse_from_buffer(&buf).unwrap();
+            let row_length = image.image_data().len() / image.height() as usize;
+            let expected = image.image_data().chunks(row_length).map(|row| row.to_vec()).collect::<Vec<_>>();
+            let result = decoded_rows(&buf).unwrap();
+
+            assert_eq!(result, expected, "{}", filename);
+        }
+    }
+
+    /// Breaking out of the callback should stop decoding at that scanline.
+    #[test]
+    fn test_decode_rows_with_should_stop_on_break() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let mut calls = 0;
+        tga::decode_rows_with(&mut file, |row_index, _| {
+            calls += 1;
+            if row_index == 9 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        }).unwrap();
+
+        assert_eq!(calls, 10);
+    }
+
+    /// A file cut short in its pixel data should report the pixel data found.
+    #[test]
+    fn test_decode_rows_with_should_reject_truncated_image_data() {
+        let mut buf = Vec::new();
+        File::open(sample::ONE_TGA).unwrap().read_to_end(&mut buf).unwrap();
+        buf.truncate(tga::TGA_HEADER_LENGTH + 2);
+        let result = decoded_rows(&buf);
+
+        assert!(matches!(result, Err(TgaError::IncompleteImageData(2, 3))));
+    }
+}
+
+#[cfg(test)]
+mod tests_decode_into {
+    use std::fs::File;
+    use std::io::Read;
+    #[cfg(feature = "encode")]
+    use tga::{ChannelOrder, EncoderOptions};
+    use tga::{TgaError, TgaImage};
+    use super::sample;
+
+
+    /// The pixel data decoded into a buffer should match the pixel data of
+    /// the image parsed from the same file.
+    #[test]
+    fn test_decode_into_should_match_the_parsed_image_data() {
+        let mut filenames = vec![sample::LENA_TGA, sample::PALETTE_16_TGA, sample::PALETTE_MAPPED_24_TGA];
+        if cfg!(feature = "rle") {
+            filenames.extend_from_slice(&[sample::LENA_RLE_TGA, sample::COLOR_RLE_TGA, sample::ONE_RLE_TGA]);
+        }
+        for filename in filenames {
+            let mut buf = Vec::new();
+            File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+            let image = TgaImage::parse_from_buffer(&buf).unwrap();
+            let mut out = vec![0; image.image_data().len()];
+            let header = tga::decode_into(&mut &buf[..], &mut out).unwrap();
+
+            assert_eq!(out, image.image_data(), "{}", filename);
+            assert_eq!(header, image.header(), "{}", filename);
+        }
+    }
+
+    /// The scanlines of an interleaved file should be decoded into the buffer
+    /// in order.
+    #[test]
+    fn test_decode_into_should_put_interleaved_scanlines_in_order() {
+        let mut filenames = vec![sample::INTERLEAVED_TWO_WAY_TGA];
+        if cfg!(feature = "rle") {
+            filenames.extend_from_slice(&[sample::INTERLEAVED_TWO_WAY_RLE_TGA, sample::INTERLEAVED_FOUR_WAY_RLE_TGA]);
+        }
+        for filename in filenames {
+            let mut buf = Vec::new();
+            File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+            let image = TgaImage::parse_from_buffer(&buf).unwrap();
+            let mut out = vec![0; image.image_data().len()];
+            tga::decode_into(&mut &buf[..], &mut out).unwrap();
+
+            assert_eq!(out, image.image_data(), "{}", filename);
+        }
+    }
+
+    /// A file storing its channels in red, green, blue order should decode
+    /// to the pixels and header of the image parsed from it, without the
+    /// channel order tag.
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_decode_into_should_leave_out_the_channel_order_tag() {
+        let image = TgaImage::parse_from_file(&mut File::open(sample::LENA_TGA).unwrap()).unwrap();
+        let options = EncoderOptions {
+            channel_order_in_file: ChannelOrder::Rgb,
+            image_identification: Some(b"lena".to_vec()),
+            ..EncoderOptions::default()
+        };
+        let buf = image.encode_to_vec_with_options(&options);
+        let expected = TgaImage::parse_from_buffer(&buf).unwrap();
+        let mut out = vec![0; expected.image_data().len()];
+        let header = tga::decode_into(&mut &buf[..], &mut out).unwrap();
+
+        assert_eq!(out, expected.image_data());
+        assert_eq!(header, expected.header());
+        assert_eq!(tga::lowlevel::write_header(header)[0], 4);
+    }
+
+    /// A buffer too small for the pixel data should be rejected before
+    /// anything is decoded.
+    #[test]
+    fn test_decode_into_should_reject_a_buffer_that_is_too_small() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let mut out = vec![0; 16];
+        let result = tga::decode_into(&mut file, &mut out);
+
+        assert!(matches!(result, Err(TgaError::BufferTooSmall(16, 786432))));
+        assert!(out.iter().all(|&byte| byte == 0));
+    }
+
+    /// A buffer larger than the pixel data should be filled from the start,
+    /// leaving the rest untouched.
+    #[test]
+    fn test_decode_into_should_leave_the_rest_of_a_larger_buffer_untouched() {
+        let mut file = File::open(sample::LENA_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let length = image.image_data().len();
+        let mut out = vec![0xAA; length + 7];
+        tga::decode_into(&mut File::open(sample::LENA_TGA).unwrap(), &mut out).unwrap();
+
+        assert_eq!(&out[..length], image.image_data());
+        assert!(out[length..].iter().all(|&byte| byte == 0xAA));
+    }
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "transforms")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_spans {
+    use tga::TgaImage;
+    use super::sample;
+
+
+    /// Expanding the spans of every scanline should reproduce the pixels of
+    /// the image, and neighbouring spans should differ in colour.
+    #[test]
+    fn test_spans_should_cover_each_scanline_with_maximal_runs() {
+        for filename in [sample::PALETTE_TGA, sample::PALETTE_16_TGA, sample::PALETTE_MAPPED_24_TGA].iter() {
+            let image = TgaImage::open(filename).unwrap();
+            let pixels = image.pixels().collect::<Vec<_>>();
+            for (row, expected) in pixels.chunks(image.width() as usize).enumerate() {
+                let spans = image.spans(row as u32).collect::<Vec<_>>();
+                let mut expanded = Vec::new();
+                for span in spans.iter() {
+                    assert_eq!(span.offset as usize, expanded.len());
+                    let [blue, green, red, _] = span.colour;
+                    expanded.extend((0..span.length).map(|_| [blue, green, red]));
+                }
+
+                assert_eq!(expanded, expected);
+                assert!(spans.windows(2).all(|pair| pair[0].colour != pair[1].colour));
+            }
+        }
+    }
+
+    /// A scanline of a cropped view should be split only within the view.
+    #[test]
+    fn test_spans_should_follow_a_cropped_view() {
+        let image = TgaImage::open(sample::PALETTE_TGA).unwrap();
+        let view = image.crop(3, 2, 5, 4).unwrap();
+        let length = view.spans(1).map(|span| span.length).sum::<u32>();
+
+        assert_eq!(length, 5);
+    }
+
+    /// Asking for a scanline outside of the image should panic.
+    #[test]
+    #[should_panic]
+    fn test_spans_should_panic_outside_of_the_image() {
+        let image = TgaImage::open(sample::PALETTE_TGA).unwrap();
+        image.spans(image.height());
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg(all(feature = "encode", feature = "transforms"))]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_stats {
+    use tga::{EncoderOptions, Orientation, TgaError, TgaImage};
+    use super::sample;
+
+
+    /// An image whose left half is dark and whose right half is bright.
+    fn halves() -> TgaImage {
+        TgaImage::from_fn(4, 2, |x, y| if x < 2 { [10, 20, 30] } else { [200 + y as u8, 100, 50] }).unwrap()
+    }
+
+    /// The statistics of the whole image should cover every pixel.
+    #[test]
+    fn test_stats_should_summarize_every_pixel() {
+        let stats = halves().stats();
+
+        assert_eq!(stats.pixel_count, 8);
+        assert_eq!((stats.channels[0].min, stats.channels[0].max), (10, 201));
+        assert_eq!(stats.channels[1].mean, 60.0);
+        assert_eq!(stats.channels[2].std_dev, 10.0);
+        assert_eq!(stats.channels[3].mean, 255.0);
+    }
+
+    /// The statistics of a masked image should only cover the pixels where
+    /// the mask is not black, matched by their positions on the display.
+    #[test]
+    fn test_stats_masked_should_only_summarize_selected_pixels() {
+        let mask = TgaImage::from_fn(4, 2, |x, _| if x >= 2 { [0, 0, 1] } else { [0, 0, 0] }).unwrap();
+        let options = EncoderOptions { orientation: Some(Orientation::BottomRight), ..EncoderOptions::default() };
+        let flipped_mask = TgaImage::parse_from_buffer(&mask.encode_to_vec_with_options(&options)).unwrap();
+        let stats = halves().stats_masked(&mask).unwrap();
+
+        assert_eq!(stats.pixel_count, 4);
+        assert_eq!((stats.channels[0].min, stats.channels[0].max), (200, 201));
+        assert_eq!(stats.channels[0].mean, 200.5);
+        assert_eq!(stats.channels[1].std_dev, 0.0);
+        assert_eq!(halves().stats_masked(&flipped_mask).unwrap(), stats);
+    }
+
+    /// An empty mask should give empty statistics, and a mask of other
+    /// dimensions should be rejected.
+    #[test]
+    fn test_stats_masked_should_handle_empty_and_mismatched_masks() {
+        let empty = TgaImage::from_fn(4, 2, |_, _| [0, 0, 0]).unwrap();
+        let lena = TgaImage::open(sample::LENA_TGA).unwrap();
+
+        assert_eq!(halves().stats_masked(&empty).unwrap().pixel_count, 0);
+        assert!(matches!(halves().stats_masked(&lena), Err(TgaError::DimensionMismatch(512, 512))));
+    }
+}
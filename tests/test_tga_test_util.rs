@@ -0,0 +1,174 @@
+#![cfg(all(feature = "test-util", feature = "rle"))]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_fault_injection {
+    use std::fs::File;
+    use std::io::Read;
+    use tga::{TgaError, TgaImage, TGA_HEADER_LENGTH};
+    use tga::test_util::{corrupt, CorruptionKind, FaultyReader};
+    use super::sample;
+
+
+    fn read_file(filename: &str) -> Vec<u8> {
+        let mut file = File::open(filename).unwrap();
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf).unwrap();
+
+        buf
+    }
+
+    /// An I/O error while reading a file should be reported as an error
+    /// rather than a panic.
+    #[test]
+    fn test_parse_from_file_should_report_io_errors() {
+        let file = File::open(sample::LENA_TGA).unwrap();
+        let mut reader = FaultyReader::new(file, 1000);
+        let image = TgaImage::parse_from_file(&mut reader);
+
+        assert_eq!(reader.bytes_read(), 1000);
+        match image {
+            Err(TgaError::Io(_)) => {}
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+    }
+
+    /// A file truncated anywhere inside the header or the image data should
+    /// be rejected by the parser.
+    #[test]
+    fn test_truncated_files_should_fail_to_parse() {
+        for filename in [sample::LENA_TGA, sample::LENA_RLE_TGA, sample::ONE_TGA].iter() {
+            let original = read_file(filename);
+            let lengths = [0, 1, TGA_HEADER_LENGTH - 1, TGA_HEADER_LENGTH, TGA_HEADER_LENGTH + 2];
+            for &length in lengths.iter() {
+                let mut buf = original.clone();
+                corrupt(&mut buf, CorruptionKind::Truncate(length));
+
+                assert!(TgaImage::parse_from_buffer(&buf).is_err());
+            }
+        }
+    }
+
+    /// Flipping the data type code in the header should make the parser
+    /// reject the file.
+    #[test]
+    fn test_flipping_the_data_type_code_should_fail_to_parse() {
+        let mut buf = read_file(sample::LENA_TGA);
+        corrupt(&mut buf, CorruptionKind::FlipHeaderByte(2));
+
+        assert!(TgaImage::parse_from_buffer(&buf).is_err());
+    }
+
+    /// A packet stream claiming more pixels than the image holds should be
+    /// reported as incomplete image data.
+    #[test]
+    fn test_broken_rle_accounting_should_fail_to_parse() {
+        for filename in [sample::LENA_RLE_TGA, sample::COLOR_RLE_TGA, sample::ONE_RLE_TGA].iter() {
+            let mut buf = read_file(filename);
+            corrupt(&mut buf, CorruptionKind::BreakRleAccounting);
+
+            match TgaImage::parse_from_buffer(&buf) {
+                Err(TgaError::IncompleteImageData(_, _)) => {}
+                other => panic!("expected incomplete image data, got {:?}", other),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "encode"))]
+mod tests_roundtrip_check {
+    use tga::{ChannelOrder, EncoderOptions, Orientation, PixelDepth, TgaImage};
+    use tga::test_util::roundtrip_check;
+    use super::sample;
+
+
+    /// Options that keep every pixel should survive the round trip for every
+    /// kind of sample image.
+    #[test]
+    fn test_roundtrip_check_should_find_lossless_options_lossless() {
+        let options = [
+            EncoderOptions::default(),
+            EncoderOptions { rle: Some(true), ..EncoderOptions::default() },
+            EncoderOptions { orientation: Some(Orientation::TopRight), ..EncoderOptions::default() },
+            EncoderOptions { channel_order_in_file: ChannelOrder::Rgb, ..EncoderOptions::default() },
+            EncoderOptions { origin: Some((3, 4)), image_identification: Some(b"id".to_vec()), ..EncoderOptions::default() },
+        ];
+        let filenames = [
+            sample::LENA_TGA, sample::LENA_RLE_TGA, sample::PALETTE_MAPPED_24_TGA, sample::PALETTE_MAPPED_RLE_TGA,
+        ];
+        for filename in filenames.iter() {
+            let image = TgaImage::open(filename).unwrap();
+            for options in options.iter() {
+                let report = roundtrip_check(&image, options);
+
+                assert!(report.is_lossless(), "{}: {:?}", filename, report);
+            }
+        }
+    }
+
+    /// Reducing pixels to 16 bits should be reported as mismatched pixels.
+    #[test]
+    fn test_roundtrip_check_should_report_lost_colours() {
+        let image = TgaImage::open(sample::LENA_TGA).unwrap();
+        let options = EncoderOptions { pixel_depth: Some(PixelDepth::Bits16), ..EncoderOptions::default() };
+        let report = roundtrip_check(&image, &options);
+
+        assert!(report.decode_error.is_none());
+        assert!(report.dimensions_match);
+        assert!(report.metadata_mismatches.is_empty());
+        assert!(report.mismatched_pixels > 0);
+        assert!(report.first_mismatch.is_some());
+        assert!(!report.is_lossless());
+    }
+}
+
+#[cfg(all(test, feature = "encode"))]
+mod tests_assertions {
+    use tga::TgaImage;
+    use tga::test_util::{assert_dimensions, assert_images_match, assert_pixel};
+    use super::sample;
+
+
+    fn gradient(offset: u8) -> TgaImage {
+        TgaImage::from_fn(4, 3, |x, y| [x as u8 * 10 + offset, y as u8 * 10, 200]).unwrap()
+    }
+
+    /// Matching images, dimensions, and pixels should pass, whatever the
+    /// files the images came from compress.
+    #[test]
+    fn test_assertions_should_pass_for_matching_images() {
+        let image = TgaImage::open(sample::LENA_TGA).unwrap();
+        let compressed = TgaImage::open(sample::LENA_RLE_TGA).unwrap();
+
+        assert_dimensions(&image, sample::LENA_TGA_WIDTH, sample::LENA_TGA_HEIGHT);
+        assert_pixel(&gradient(0), 3, 2, [30, 20, 200, 255]);
+        assert_images_match(&image, &compressed, 0);
+        assert_images_match(&gradient(0), &gradient(2), 2);
+    }
+
+    /// Images whose pixels differ by more than the tolerance should fail,
+    /// listing where they differ.
+    #[test]
+    #[should_panic(expected = "12 of 12 pixels differ by more than 1, by up to 2; \
+                               the first differences are:\n  (0, 0): left [0, 0, 200, 255], right [2, 0, 200, 255]")]
+    fn test_assert_images_match_should_describe_differences() {
+        assert_images_match(&gradient(0), &gradient(2), 1);
+    }
+
+    /// Pixels of another colour should fail, naming both colours.
+    #[test]
+    #[should_panic(expected = "pixel (1, 2) differs: expected [0, 0, 0, 255], found [10, 20, 200, 255]")]
+    fn test_assert_pixel_should_name_both_colours() {
+        assert_pixel(&gradient(0), 1, 2, [0, 0, 0, 255]);
+    }
+
+    /// Images of other dimensions should fail, naming both.
+    #[test]
+    #[should_panic(expected = "image dimensions differ: expected 2x3, found 4x3")]
+    fn test_assert_dimensions_should_name_both_dimensions() {
+        assert_dimensions(&gradient(0), 2, 3);
+    }
+}
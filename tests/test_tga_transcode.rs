@@ -0,0 +1,80 @@
+#![cfg(feature = "rle")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_transcode {
+    use std::fs;
+    use tga::TgaImage;
+    use super::sample;
+
+
+    /// Read a little endian 32 bit value.
+    fn read_u32(buf: &[u8], position: usize) -> usize {
+        u32::from_le_bytes([buf[position], buf[position + 1], buf[position + 2], buf[position + 3]]) as usize
+    }
+
+    /// Transcoding an uncompressed file to run length encoding and back should
+    /// reproduce the original file exactly.
+    #[test]
+    fn test_transcode_should_round_trip_an_uncompressed_file() {
+        for filename in [sample::LENA_TGA, sample::PALETTE_MAPPED_24_TGA].iter() {
+            let buf = fs::read(filename).unwrap();
+            let compressed = tga::transcode(&buf, true).unwrap();
+            let image = TgaImage::parse_from_buffer(&compressed).unwrap();
+            let expected = TgaImage::parse_from_buffer(&buf).unwrap();
+
+            assert_eq!(compressed[2], buf[2] | 0x08);
+            assert!(image.pixels().eq(expected.pixels()));
+            assert_eq!(tga::transcode(&compressed, false).unwrap(), buf);
+        }
+    }
+
+    /// Decompressing a run length encoded file should give an uncompressed
+    /// file with the same pixels.
+    #[test]
+    fn test_transcode_should_decompress_a_run_length_encoded_file() {
+        for filename in [sample::LENA_RLE_TGA, sample::PALETTE_MAPPED_RLE_TGA].iter() {
+            let buf = fs::read(filename).unwrap();
+            let uncompressed = tga::transcode(&buf, false).unwrap();
+            let image = TgaImage::parse_from_buffer(&uncompressed).unwrap();
+            let expected = TgaImage::parse_from_buffer(&buf).unwrap();
+
+            assert_eq!(uncompressed[2], buf[2] & !0x08);
+            assert!(image.pixels().eq(expected.pixels()));
+        }
+    }
+
+    /// The offsets of a TGA 2.0 footer and extension area should move with
+    /// the areas they point to, and the scan line table should be dropped.
+    #[test]
+    fn test_transcode_should_move_the_offsets_of_trailing_areas() {
+        let buf = fs::read(sample::PALETTE_TGA).unwrap();
+        let data_end = 18 + 3 * (sample::PALETTE_TGA_WIDTH * sample::PALETTE_TGA_HEIGHT) as usize;
+        let mut extension_area = vec![0; 495];
+        extension_area[0..2].copy_from_slice(&495u16.to_le_bytes());
+        extension_area[486..490].copy_from_slice(&((data_end + 495) as u32).to_le_bytes());
+        extension_area[490..494].copy_from_slice(&((data_end + 495) as u32).to_le_bytes());
+        let postage_stamp = [1, 1, 0x11, 0x22, 0x33];
+        let mut file = buf[..data_end].to_vec();
+        file.extend_from_slice(&extension_area);
+        file.extend_from_slice(&postage_stamp);
+        file.extend_from_slice(&(data_end as u32).to_le_bytes());
+        file.extend_from_slice(&[0; 4]);
+        file.extend_from_slice(b"TRUEVISION-XFILE.\0");
+
+        let compressed = tga::transcode(&file, true).unwrap();
+        let trailing_length = file.len() - data_end;
+        let new_data_end = compressed.len() - trailing_length;
+        let footer = compressed.len() - 26;
+
+        assert!(new_data_end < data_end);
+        assert_eq!(read_u32(&compressed, footer), new_data_end);
+        assert_eq!(read_u32(&compressed, footer + 4), 0);
+        assert_eq!(read_u32(&compressed, new_data_end + 486), new_data_end + 495);
+        assert_eq!(read_u32(&compressed, new_data_end + 490), 0);
+        assert_eq!(&compressed[(new_data_end + 495)..footer], &postage_stamp);
+    }
+}
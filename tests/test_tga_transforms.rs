@@ -0,0 +1,181 @@
+#![cfg(feature = "transforms")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_adjust {
+    use std::fs::File;
+    use tga::{Brightness, Contrast, Gamma, TgaImage};
+    use super::sample;
+
+
+    fn open(filename: &str) -> TgaImage {
+        let mut file = File::open(filename).unwrap();
+        TgaImage::parse_from_file(&mut file).unwrap()
+    }
+
+    /// An adjustment with neutral brightness, contrast, and gamma should leave
+    /// the image unchanged.
+    #[test]
+    fn test_neutral_adjustment_should_not_change_the_image() {
+        for filename in [sample::LENA_TGA, sample::PALETTE_16_TGA, sample::PALETTE_MAPPED_32_TGA].iter() {
+            let expected = open(filename);
+            let mut image = open(filename);
+            image.adjust(Brightness(0.0), Contrast(1.0), Gamma(1.0));
+
+            assert_eq!(image, expected);
+
+            image.adjust_linear(Brightness(0.0), Contrast(1.0), Gamma(1.0));
+
+            assert_eq!(image, expected);
+        }
+    }
+
+    /// Full brightness should turn every pixel white.
+    #[test]
+    fn test_full_brightness_should_turn_the_image_white() {
+        let mut image = open(sample::LENA_TGA);
+        image.adjust(Brightness(1.0), Contrast(1.0), Gamma(1.0));
+
+        assert!(image.pixels().all(|pixel| pixel == [255, 255, 255]));
+    }
+
+    /// Adjusting a colour mapped image should change its colours in the same
+    /// way as adjusting the equivalent true colour image.
+    #[test]
+    fn test_adjusting_a_color_mapped_image_should_match_a_true_color_image() {
+        let mut expected = open(sample::PALETTE_TGA);
+        expected.adjust(Brightness(0.1), Contrast(1.2), Gamma(2.2));
+        let mut image = open(sample::PALETTE_MAPPED_24_TGA);
+        image.adjust(Brightness(0.1), Contrast(1.2), Gamma(2.2));
+
+        assert!(image.pixels().eq(expected.pixels()));
+    }
+
+    /// Raising the contrast in linear light should give different results
+    /// than raising it on the encoded values.
+    #[test]
+    fn test_linear_light_adjustment_should_differ_from_encoded_adjustment() {
+        let mut encoded = open(sample::LENA_TGA);
+        encoded.adjust(Brightness(0.0), Contrast(1.5), Gamma(1.0));
+        let mut linear = open(sample::LENA_TGA);
+        linear.adjust_linear(Brightness(0.0), Contrast(1.5), Gamma(1.0));
+
+        assert_ne!(encoded.image_data(), linear.image_data());
+    }
+}
+
+#[cfg(test)]
+mod tests_pipeline {
+    use std::fs::File;
+    use tga::{Brightness, ChannelOrder, ColourTransform, Contrast, Gamma, TgaError, TgaImage};
+    use super::sample;
+
+
+    fn open(filename: &str) -> TgaImage {
+        let mut file = File::open(filename).unwrap();
+        TgaImage::parse_from_file(&mut file).unwrap()
+    }
+
+    /// Flipping and swizzling twice should give back the original image.
+    #[test]
+    fn test_pipeline_applied_twice_should_restore_the_image() {
+        for filename in [sample::LENA_TGA, sample::PALETTE_16_TGA, sample::PALETTE_MAPPED_24_TGA].iter() {
+            let image = open(filename);
+            let result = image.pipeline()
+                .flip_vertical().flip_horizontal().swizzle(ChannelOrder::Rgb)
+                .flip_horizontal().swizzle(ChannelOrder::Rgb).flip_vertical()
+                .collect()
+                .unwrap();
+
+            assert_eq!(result, image);
+        }
+    }
+
+    /// A fused pipeline should give the same pixels as applying its operations
+    /// one after another.
+    #[test]
+    fn test_pipeline_should_match_the_individual_operations() {
+        for filename in [sample::PALETTE_TGA, sample::PALETTE_MAPPED_24_TGA].iter() {
+            let image = open(filename);
+            let result = image.pipeline()
+                .flip_vertical()
+                .adjust(Brightness(0.1), Contrast(1.2), Gamma(1.0))
+                .swizzle(ChannelOrder::Rgb)
+                .collect()
+                .unwrap();
+            let mut expected = image.clone();
+            expected.adjust(Brightness(0.1), Contrast(1.2), Gamma(1.0));
+            let expected_pixels = expected.pixels().map(|[b, g, r]| [r, g, b]).collect::<Vec<_>>();
+            let mut expected_rows = expected_pixels.chunks(image.width() as usize).collect::<Vec<_>>();
+            expected_rows.reverse();
+
+            assert_eq!(result.data_type_code(), image.data_type_code());
+            assert_eq!(result.pixels().collect::<Vec<_>>(), expected_rows.concat());
+        }
+    }
+
+    /// Resizing to twice the size should repeat every pixel twice along each axis.
+    #[test]
+    fn test_pipeline_resize_should_sample_the_nearest_pixel() {
+        let image = open(sample::PALETTE_TGA);
+        let width = sample::PALETTE_TGA_WIDTH as usize;
+        let pixels = image.pixels().collect::<Vec<_>>();
+        let result = image.pipeline().resize(2 * width as u32, 2 * sample::PALETTE_TGA_HEIGHT).collect().unwrap();
+        let expected = (0..(2 * sample::PALETTE_TGA_HEIGHT as usize))
+            .flat_map(|y| (0..(2 * width)).map(move |x| (x, y)))
+            .map(|(x, y)| pixels[(y / 2) * width + x / 2])
+            .collect::<Vec<_>>();
+
+        assert_eq!(result.pixels().collect::<Vec<_>>(), expected);
+    }
+
+    /// Output dimensions too large for a TGA header should be rejected.
+    #[test]
+    fn test_pipeline_resize_should_reject_dimensions_out_of_range() {
+        let image = open(sample::ONE_TGA);
+
+        match image.pipeline().resize(70000, 1).collect() {
+            Err(TgaError::InvalidDimensions(70000, 1)) => {}
+            other => panic!("expected an invalid dimensions error, got {:?}", other),
+        }
+    }
+
+    /// A colour transform that inverts the colour channels.
+    struct Invert;
+
+    impl ColourTransform for Invert {
+        fn transform(&self, colour: [u8; 4]) -> [u8; 4] {
+            [255 - colour[0], 255 - colour[1], 255 - colour[2], colour[3]]
+        }
+    }
+
+    /// A colour transform should convert colours in a pipeline the same way
+    /// it converts them in place, for true colour and colour mapped images.
+    #[test]
+    fn test_colour_transform_should_convert_every_colour() {
+        for filename in [sample::LENA_TGA, sample::PALETTE_MAPPED_24_TGA].iter() {
+            let image = open(filename);
+            let result = image.pipeline().colour_transform(Invert).collect().unwrap();
+            let mut expected = image.clone();
+            expected.apply_colour_transform(&Invert);
+            let inverted = image.pixels().map(|pixel| [255 - pixel[0], 255 - pixel[1], 255 - pixel[2]]);
+
+            assert_eq!(result, expected);
+            assert!(result.pixels().eq(inverted));
+        }
+    }
+
+    /// Closures should serve as colour transforms.
+    #[test]
+    fn test_closure_colour_transform_should_match_swizzle() {
+        let image = open(sample::LENA_TGA);
+        let swap = |colour: [u8; 4]| [colour[2], colour[1], colour[0], colour[3]];
+        let result = image.pipeline().colour_transform(swap).collect().unwrap();
+        let expected = image.pipeline().swizzle(ChannelOrder::Rgb).collect().unwrap();
+
+        assert_eq!(result, expected);
+    }
+}
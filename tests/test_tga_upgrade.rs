@@ -0,0 +1,49 @@
+#![cfg(feature = "encode")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(test)]
+mod tests_upgrade {
+    use std::fs;
+    use tga::TgaImage;
+    use super::sample;
+
+
+    fn read_u32(buf: &[u8], position: usize) -> usize {
+        u32::from_le_bytes([buf[position], buf[position + 1], buf[position + 2], buf[position + 3]]) as usize
+    }
+
+    /// Upgrading a TGA 1.0 file should append an extension area and a footer
+    /// pointing at it, leaving the file before them alone.
+    #[test]
+    fn test_upgrade_to_v2_should_append_an_extension_area_and_footer() {
+        let mut buf = fs::read(sample::PALETTE_32_TGA).unwrap();
+        buf.truncate(buf.len() - 26);
+        let upgraded = tga::upgrade_to_v2(&buf);
+        let footer = upgraded.len() - 26;
+        let offset = read_u32(&upgraded, footer);
+        let image = TgaImage::parse_from_buffer(&buf).unwrap();
+        let result = TgaImage::parse_from_buffer(&upgraded).unwrap();
+
+        assert_eq!(&upgraded[..buf.len()], &buf[..]);
+        assert_eq!(offset, buf.len());
+        assert_eq!(offset + 495, footer);
+        assert_eq!(&upgraded[(footer + 8)..], b"TRUEVISION-XFILE.\0");
+        assert!(upgraded[(offset + 426)..].starts_with(b"tga "));
+        assert_ne!(&upgraded[(offset + 367)..(offset + 379)], &[0; 12]);
+        assert_eq!(upgraded[offset + 494], 3);
+        assert_eq!(result.pixels().collect::<Vec<_>>(), image.pixels().collect::<Vec<_>>());
+    }
+
+    /// Files that are already TGA 2.0 files, and buffers that are not TGA
+    /// files at all, should be returned unchanged.
+    #[test]
+    fn test_upgrade_to_v2_should_leave_other_buffers_alone() {
+        let buf = fs::read(sample::LENA_TGA).unwrap();
+
+        assert_eq!(tga::upgrade_to_v2(&buf), buf);
+        assert_eq!(tga::upgrade_to_v2(&buf[..10]), &buf[..10]);
+    }
+}
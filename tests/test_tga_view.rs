@@ -0,0 +1,275 @@
+#![cfg(feature = "decode24")]
+extern crate tga;
+
+mod sample;
+
+
+#[cfg(all(test, feature = "transforms"))]
+mod tests_crop {
+    use std::fs::File;
+    use tga::{TgaError, TgaImage};
+    use super::sample;
+
+
+    fn open(filename: &str) -> TgaImage {
+        let mut file = File::open(filename).unwrap();
+        TgaImage::parse_from_file(&mut file).unwrap()
+    }
+
+    /// The pixels of a cropped image should be the pixels of the region it
+    /// was cropped from.
+    #[test]
+    fn test_crop_should_contain_the_pixels_of_the_region() {
+        for filename in [sample::PALETTE_TGA, sample::PALETTE_16_TGA, sample::PALETTE_MAPPED_24_TGA].iter() {
+            let image = open(filename);
+            let pixels = image.pixels().collect::<Vec<[u8; 3]>>();
+            let cropped = image.crop(5, 3, 10, 7).unwrap();
+            let expected = (3..10)
+                .flat_map(|y| (5..15).map(move |x| (x, y)))
+                .map(|(x, y)| pixels[y * image.width() as usize + x])
+                .collect::<Vec<[u8; 3]>>();
+
+            assert_eq!((cropped.width(), cropped.height()), (10, 7));
+            assert_eq!(cropped.pixels().collect::<Vec<[u8; 3]>>(), expected);
+            assert_eq!(cropped.scanlines().count(), 7);
+            assert_eq!(cropped.image_data().len(), cropped.image_data_length_bytes());
+        }
+    }
+
+    /// Cropping a cropped image should give the same image as cropping the
+    /// original image directly.
+    #[test]
+    fn test_crop_of_a_crop_should_match_a_direct_crop() {
+        let image = open(sample::LENA_TGA);
+        let cropped = image.crop(100, 50, 200, 200).unwrap().crop(10, 20, 30, 40).unwrap();
+
+        assert_eq!(cropped, image.crop(110, 70, 30, 40).unwrap());
+    }
+
+    /// A region extending past the edge of the image should be rejected.
+    #[test]
+    fn test_crop_outside_the_image_should_fail() {
+        let image = open(sample::PALETTE_TGA);
+
+        match image.crop(30, 0, 3, 1) {
+            Err(TgaError::RegionOutOfBounds(30, 0, 3, 1)) => {}
+            other => panic!("expected an out of bounds region, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "transforms"))]
+mod tests_tiles {
+    use std::fs::File;
+    use tga::TgaImage;
+    use super::sample;
+
+
+    /// The tiles of an image should cover every pixel exactly once, with
+    /// smaller tiles along the edges.
+    #[test]
+    fn test_tiles_should_cover_the_image() {
+        let mut file = File::open(sample::PALETTE_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let tiles = image.tiles(10, 6).collect::<Vec<(u32, u32, TgaImage)>>();
+
+        assert_eq!(tiles.len(), 4 * 3);
+        assert_eq!(tiles.iter().map(|(_, _, tile)| tile.image_data_length()).sum::<usize>(), 32 * 16);
+        let (x, y, ref last) = tiles[tiles.len() - 1];
+        assert_eq!((x, y, last.width(), last.height()), (30, 12, 2, 4));
+        for (x, y, tile) in tiles.iter() {
+            assert_eq!(*tile, image.crop(*x, *y, tile.width(), tile.height()).unwrap());
+        }
+    }
+    /// The tile iterator should know how many tiles it has left.
+    #[test]
+    fn test_tiles_should_be_exact_size() {
+        let mut file = File::open(sample::PALETTE_TGA).unwrap();
+        let image = TgaImage::parse_from_file(&mut file).unwrap();
+        let mut tiles = image.tiles(10, 6);
+        tiles.nth(4);
+
+        assert_eq!(image.tiles(10, 6).len(), 4 * 3);
+        assert_eq!(tiles.len(), 4 * 3 - 5);
+        assert_eq!(tiles.count(), 4 * 3 - 5);
+        assert_eq!(image.tiles(100, 100).len(), 1);
+    }
+}
+
+#[cfg(all(test, feature = "transforms", feature = "encode"))]
+mod tests_copy_on_write {
+    use std::fs::File;
+    use tga::{Brightness, Contrast, Gamma, TgaImage};
+    use super::sample;
+
+
+    fn open(filename: &str) -> TgaImage {
+        let mut file = File::open(filename).unwrap();
+        TgaImage::parse_from_file(&mut file).unwrap()
+    }
+
+    /// Modifying a clone or a view should leave the original image unchanged.
+    #[test]
+    fn test_modifying_a_view_should_not_change_the_original() {
+        let image = open(sample::LENA_TGA);
+        let original = image.image_data().to_vec();
+        let mut clone = image.clone();
+        clone.adjust(Brightness(0.5), Contrast(1.0), Gamma(1.0));
+        let mut cropped = image.crop(10, 10, 20, 20).unwrap();
+        let expected = image.crop(10, 10, 20, 20).unwrap();
+        cropped.adjust(Brightness(-0.5), Contrast(1.0), Gamma(1.0));
+
+        assert_eq!(image.image_data(), &original[..]);
+        assert_ne!(clone, image);
+        assert_ne!(cropped, expected);
+        assert_eq!(cropped.width(), 20);
+    }
+
+    /// Encoding a view should write a file holding just the pixels of the view.
+    #[test]
+    fn test_encoded_view_should_round_trip() {
+        for filename in [sample::LENA_RLE_TGA, sample::PALETTE_MAPPED_RLE_TGA].iter() {
+            let cropped = open(filename).crop(3, 2, 17, 9).unwrap();
+            let decoded = TgaImage::parse_from_buffer(&cropped.encode_to_vec()).unwrap();
+
+            assert_eq!(decoded, cropped);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_set_pixel {
+    use std::fs::File;
+    use tga::{TgaError, TgaImage};
+    use super::sample;
+
+
+    fn open(filename: &str) -> TgaImage {
+        let mut file = File::open(filename).unwrap();
+        TgaImage::parse_from_file(&mut file).unwrap()
+    }
+
+    /// Setting a pixel of a view should change that pixel of the view and
+    /// leave the image it was cut from unchanged.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_set_pixel_should_only_change_the_edited_image() {
+        let image = open(sample::LENA_TGA);
+        let mut cropped = image.crop(10, 10, 4, 4).unwrap();
+        cropped.set_pixel(1, 2, &[1, 2, 3]).unwrap();
+
+        assert_eq!(cropped.pixels().nth(2 * 4 + 1), Some([1, 2, 3]));
+        assert_eq!(&cropped.image_data()[0..3], image.crop(10, 10, 1, 1).unwrap().image_data());
+        assert_ne!(image.crop(10, 10, 4, 4).unwrap(), cropped);
+    }
+
+    /// Editing the image data in place should change the pixels it yields.
+    #[test]
+    fn test_image_data_mut_should_edit_in_place() {
+        let mut image = open(sample::LENA_TGA);
+        image.image_data_mut()[0..3].copy_from_slice(&[9, 8, 7]);
+
+        assert_eq!(image.pixels().next(), Some([9, 8, 7]));
+    }
+
+    /// Indexing an image should read and write the stored bytes of a pixel,
+    /// leaving images sharing the pixels unchanged.
+    #[cfg(feature = "transforms")]
+    #[test]
+    fn test_index_should_read_and_write_pixels() {
+        let image = open(sample::LENA_TGA);
+        let mut cropped = image.crop(10, 10, 4, 4).unwrap();
+        let original = cropped[(1, 2)].to_vec();
+        cropped[(1, 2)].copy_from_slice(&[1, 2, 3]);
+
+        assert_eq!(&image[(11, 12)], &original[..]);
+        assert_eq!(&cropped[(1, 2)], &[1, 2, 3]);
+        assert_eq!(cropped.pixels().nth(2 * 4 + 1), Some([1, 2, 3]));
+    }
+
+    /// Indexing a pixel outside the image should panic.
+    #[test]
+    #[should_panic]
+    fn test_index_outside_the_image_should_panic() {
+        let image = open(sample::LENA_TGA);
+        let _ = &image[(image.width() as usize, 0)];
+    }
+
+    /// Swapping channels through the mutable pixel iterator should change
+    /// every pixel the image yields.
+    #[test]
+    fn test_pixels_mut_should_visit_every_pixel() {
+        let image = open(sample::LENA_TGA);
+        let mut swapped = image.clone();
+        for pixel in swapped.pixels_mut() {
+            pixel.swap(0, 2);
+        }
+        let expected = image.pixels().map(|[b, g, r]| [r, g, b]);
+
+        assert_eq!(swapped.pixels_mut().len(), image.image_data_length());
+        assert!(swapped.pixels().eq(expected));
+    }
+
+    /// Pixels outside the image, or of the wrong length, should be rejected.
+    #[test]
+    fn test_set_pixel_should_reject_invalid_pixels() {
+        let mut image = open(sample::LENA_TGA);
+        let width = image.width();
+
+        assert!(matches!(image.set_pixel(width, 0, &[0; 3]), Err(TgaError::RegionOutOfBounds(_, 0, 1, 1))));
+        assert!(matches!(image.set_pixel(0, 0, &[0; 4]), Err(TgaError::IncompleteImageData(4, 3))));
+    }
+}
+
+#[cfg(all(test, feature = "encode"))]
+mod tests_set_pixel_colour_mapped {
+    use tga::{EncoderOptions, Palette, TgaError, TgaImage};
+
+
+    /// Build a two by one colour mapped image whose colour map starts at
+    /// index 10.
+    fn colour_mapped_image() -> TgaImage {
+        let palette = Palette::from_bgr(&[[1, 2, 3], [4, 5, 6]]).with_origin(10);
+        let encoded = tga::encode_colour_mapped(2, 1, &[10, 11], &palette, &EncoderOptions::default()).unwrap();
+
+        TgaImage::parse_from_buffer(&encoded).unwrap()
+    }
+
+    /// Colour map indices outside the colour map, including those below its
+    /// origin, should be rejected and leave the image readable.
+    #[test]
+    fn test_set_pixel_should_reject_indices_outside_the_colour_map() {
+        let mut image = colour_mapped_image();
+
+        assert!(matches!(image.set_pixel(0, 0, &[9]), Err(TgaError::InvalidColourMapIndex(9))));
+        assert!(matches!(image.set_pixel(0, 0, &[12]), Err(TgaError::InvalidColourMapIndex(12))));
+        assert_eq!(image.pixels().collect::<Vec<_>>(), vec![[1, 2, 3], [4, 5, 6]]);
+
+        image.set_pixel(0, 0, &[11]).unwrap();
+
+        assert_eq!(image.pixels().collect::<Vec<_>>(), vec![[4, 5, 6], [4, 5, 6]]);
+    }
+
+    /// Colour map indices outside the colour map written through indexing,
+    /// including those below its origin, should read as transparent black
+    /// rather than panic or wrap around to another entry.
+    #[test]
+    fn test_reading_an_index_outside_the_colour_map_should_give_transparent_black() {
+        let mut image = colour_mapped_image();
+        image[(0, 0)][0] = 9;
+        image[(1, 0)][0] = 12;
+
+        assert_eq!(image.pixels().collect::<Vec<_>>(), vec![[0, 0, 0], [0, 0, 0]]);
+        assert_eq!(&*image.scanlines().next().unwrap(), &[[0, 0, 0], [0, 0, 0]]);
+    }
+
+    /// Colour map indices outside the colour map written through
+    /// `image_data_mut` should read as transparent black too.
+    #[test]
+    fn test_image_data_mut_indices_outside_the_colour_map_should_give_transparent_black() {
+        let mut image = colour_mapped_image();
+        image.image_data_mut().copy_from_slice(&[11, 255]);
+
+        assert_eq!(image.pixels().collect::<Vec<_>>(), vec![[4, 5, 6], [0, 0, 0]]);
+    }
+}
@@ -0,0 +1,58 @@
+#![cfg(all(feature = "encode", feature = "transforms"))]
+extern crate tga;
+
+
+#[cfg(test)]
+mod tests_windows {
+    use tga::{BorderPolicy, TgaImage};
+
+
+    /// A 3 by 2 image whose pixels are numbered row by row.
+    fn numbered() -> TgaImage {
+        TgaImage::from_fn(3, 2, |x, y| [(1 + x + 3 * y) as u8; 3]).unwrap()
+    }
+
+    /// The first channel of every pixel of a window, row by row.
+    fn numbers(window: &[[u8; 3]]) -> Vec<u8> {
+        window.iter().map(|pixel| pixel[0]).collect()
+    }
+
+    /// Every pixel should get a window centred on it, extended past the
+    /// edges of the image.
+    #[test]
+    fn test_windows_should_be_centred_on_every_pixel() {
+        let image = numbered();
+        let windows = image.windows(3, 3, BorderPolicy::Extend).collect::<Vec<_>>();
+        let (x, y, ref window) = windows[1];
+
+        assert_eq!(windows.len(), 6);
+        assert_eq!((x, y, window.width(), window.height()), (1, 0, 3, 3));
+        assert_eq!(numbers(window), [1, 2, 3, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(window.pixel(1, 1), [2; 3]);
+        assert!(windows.iter().zip(image.pixels()).all(|((_, _, window), pixel)| window.pixel(1, 1) == pixel));
+    }
+
+    /// Wrapping and constant borders should fill the parts of a window
+    /// outside the image from the opposite edge and with the colour.
+    #[test]
+    fn test_border_policies_should_fill_the_edges() {
+        let image = numbered();
+        let wrapped = image.windows(3, 3, BorderPolicy::Wrap).next().unwrap().2;
+        let constant = image.windows(3, 3, BorderPolicy::Constant([0; 3])).next().unwrap().2;
+
+        assert_eq!(numbers(&wrapped), [6, 4, 5, 3, 1, 2, 6, 4, 5]);
+        assert_eq!(numbers(&constant), [0, 0, 0, 0, 1, 2, 0, 4, 5]);
+    }
+
+    /// Skipping should only visit the pixels whose windows lie within the
+    /// image.
+    #[test]
+    fn test_skip_should_only_visit_windows_inside_the_image() {
+        let image = numbered();
+        let inside = image.windows(3, 1, BorderPolicy::Skip).collect::<Vec<_>>();
+
+        assert_eq!(inside.iter().map(|&(x, y, _)| (x, y)).collect::<Vec<_>>(), [(1, 0), (1, 1)]);
+        assert_eq!(numbers(&inside[1].2), [4, 5, 6]);
+        assert_eq!(image.windows(3, 3, BorderPolicy::Skip).count(), 0);
+    }
+}